@@ -9,6 +9,10 @@ const LEARNING_RATE: f64 = 0.15;
 const MEMORY_DECAY: f64 = 0.95;
 const PATTERN_THRESHOLD: f64 = 0.70;
 const COORDINATION_DELAY_TICKS: u64 = 3;
+const FAMILIARITY_DECAY: f64 = 0.85;
+const FAMILIARITY_SCALE: f64 = 5.0;
+const BASE_BLOCK_PROBABILITY: f64 = 0.10;
+const MAX_BLOCK_PROBABILITY: f64 = 0.95;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BypassTactic {
@@ -182,6 +186,35 @@ impl MultiRegionCoordinator {
     }
 }
 
+/// Цензор, который «привыкает» к тактикам Федерации: чем чаще тактика
+/// повторяется, тем выше шанс её блокировки. Неиспользуемые тактики забываются.
+#[derive(Debug, Clone, Default)]
+pub struct AdaptiveCensor {
+    familiarity: HashMap<BypassTactic, f64>,
+    pub observations: u64,
+}
+
+impl AdaptiveCensor {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn observe(&mut self, tactic: BypassTactic) {
+        for (t, fam) in self.familiarity.iter_mut() {
+            if *t != tactic { *fam *= FAMILIARITY_DECAY; }
+        }
+        *self.familiarity.entry(tactic).or_insert(0.0) += 1.0;
+        self.observations += 1;
+    }
+
+    pub fn familiarity(&self, tactic: &BypassTactic) -> f64 {
+        self.familiarity.get(tactic).copied().unwrap_or(0.0)
+    }
+
+    pub fn block_probability(&self, tactic: &BypassTactic) -> f64 {
+        let learned = 1.0 - (-self.familiarity(tactic) / FAMILIARITY_SCALE).exp();
+        BASE_BLOCK_PROBABILITY + (MAX_BLOCK_PROBABILITY - BASE_BLOCK_PROBABILITY) * learned
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,4 +271,22 @@ mod tests {
             _ => panic!("Стратегия не адаптировалась"),
         }
     }
+
+    #[test]
+    fn test_adaptive_censor_rewards_diversity() {
+        let mut spammed = AdaptiveCensor::new();
+        for _ in 0..20 { spammed.observe(BypassTactic::Mirage); }
+
+        let rotation = [BypassTactic::Mirage, BypassTactic::Onion, BypassTactic::Mesh,
+                        BypassTactic::Satellite, BypassTactic::Mutation, BypassTactic::Strike];
+        let mut rotated = AdaptiveCensor::new();
+        for i in 0..20 { rotated.observe(rotation[i % rotation.len()].clone()); }
+
+        let spam_p = spammed.block_probability(&BypassTactic::Mirage);
+        assert!(spam_p > 0.8, "spam block p = {}", spam_p);
+        for t in &rotation {
+            assert!(rotated.block_probability(t) < 0.5);
+        }
+        assert!(spammed.block_probability(&BypassTactic::Onion) < 0.2);
+    }
 }
@@ -14,13 +14,22 @@
 //   FederationCipher — высокоуровневый API для Федерации
 // =============================================================================
 
+use crate::error::FederationError;
+use crate::noise::hmac;
+use crate::rng::{RngCore, XorShift64};
 use std::collections::HashMap;
+use std::io::Read;
 
 pub const KEY_SIZE: usize    = 32; // 256 бит
 pub const NONCE_SIZE: usize  = 12; // 96 бит
 pub const TAG_SIZE: usize    = 16; // 128 бит MAC
 pub const BLOCK_SIZE: usize  = 64; // ChaCha20 блок
 
+// Лимиты сессии: после любого — повторный handshake
+pub const SESSION_MAX_AGE_SECS: u64  = 24 * 3600;
+pub const SESSION_MAX_MESSAGES: u64  = 1 << 20;
+pub const SESSION_MAX_BYTES: u64     = 1 << 30;
+
 // -----------------------------------------------------------------------------
 // Утилиты
 // -----------------------------------------------------------------------------
@@ -168,93 +177,76 @@ impl Poly1305 {
         Poly1305 { r, s, h: [0u64; 5] }
     }
 
-    fn process_block(&mut self, block: &[u8], last: bool) {
-        let shift = block.len() * 8;
-        let pad = if last || shift >= 128 { 0u128 } else { 1u128 << shift };
+    /// Ключ с нулевым r (после clamp) даёт tag = s для любого сообщения —
+    /// подделка тривиальна. Такой ключ отвергаем
+    pub fn is_weak_key(key: &[u8; 32]) -> bool {
+        u128::from_le_bytes(key[..16].try_into().unwrap()) & 0x0ffffffc0ffffffc0ffffffc0fffffff == 0
+    }
+
+    // Блок до 16 байт: n = block || 0x01 (2^(8·len)), h = (h + n) · r mod 2^130-5
+    fn process_block(&mut self, block: &[u8]) {
         let mut n = [0u8; 17];
         n[..block.len()].copy_from_slice(block);
-        if !last { n[block.len()] = 1; }
-        let n = u128::from_le_bytes(n[..16].try_into().unwrap()) | pad;
+        n[block.len()] = 1;
+        let lo = u128::from_le_bytes(n[..16].try_into().unwrap());
 
         // Добавляем к аккумулятору
-        let n0 = (n & 0x3ffffff) as u64;
-        let n1 = ((n >> 26) & 0x3ffffff) as u64;
-        let n2 = ((n >> 52) & 0x3ffffff) as u64;
-        let n3 = ((n >> 78) & 0x3ffffff) as u64;
-        let n4 = ((n >> 104) & 0x3ffffff) as u64;
-
-        let mut h = [0u64; 5];
-        h[0] = self.h[0].wrapping_add(n0);
-        h[1] = self.h[1].wrapping_add(n1);
-        h[2] = self.h[2].wrapping_add(n2);
-        h[3] = self.h[3].wrapping_add(n3);
-        h[4] = self.h[4].wrapping_add(n4);
-
-        // Умножение h * r (упрощённое для pure Rust)
+        let mut h = self.h;
+        h[0] += (lo & 0x3ffffff) as u64;
+        h[1] += ((lo >> 26) & 0x3ffffff) as u64;
+        h[2] += ((lo >> 52) & 0x3ffffff) as u64;
+        h[3] += ((lo >> 78) & 0x3ffffff) as u64;
+        h[4] += ((lo >> 104) as u64) | ((n[16] as u64) << 24);
+
+        // Умножение h * r: старшие разряды заворачиваются с множителем 5
         let r = &self.r;
-        let mut t = [0u128; 5];
-        t[0] = h[0] as u128 * r[0] as u128
-             + h[1] as u128 * (r[4]*5) as u128
-             + h[2] as u128 * (r[3]*5) as u128
-             + h[3] as u128 * (r[2]*5) as u128
-             + h[4] as u128 * (r[1]*5) as u128;
-        t[1] = h[0] as u128 * r[1] as u128
-             + h[1] as u128 * r[0] as u128
-             + h[2] as u128 * (r[4]*5) as u128
-             + h[3] as u128 * (r[3]*5) as u128
-             + h[4] as u128 * (r[2]*5) as u128;
-        t[2] = h[0] as u128 * r[2] as u128
-             + h[1] as u128 * r[1] as u128
-             + h[2] as u128 * r[0] as u128
-             + h[3] as u128 * (r[4]*5) as u128
-             + h[4] as u128 * (r[3]*5) as u128;
-        t[3] = h[0] as u128 * r[3] as u128
-             + h[1] as u128 * r[2] as u128
-             + h[2] as u128 * r[1] as u128
-             + h[3] as u128 * r[0] as u128
-             + h[4] as u128 * (r[4]*5) as u128;
-        t[4] = h[0] as u128 * r[4] as u128
-             + h[1] as u128 * r[3] as u128
-             + h[2] as u128 * r[2] as u128
-             + h[3] as u128 * r[1] as u128
-             + h[4] as u128 * r[0] as u128;
-
-        // Редукция по 2^130-5
-        let mut h2 = [0u64; 5];
+        let m = |a: u64, b: u64| a as u128 * b as u128;
+        let t = [
+            m(h[0], r[0]) + m(h[1], r[4]*5) + m(h[2], r[3]*5) + m(h[3], r[2]*5) + m(h[4], r[1]*5),
+            m(h[0], r[1]) + m(h[1], r[0])   + m(h[2], r[4]*5) + m(h[3], r[3]*5) + m(h[4], r[2]*5),
+            m(h[0], r[2]) + m(h[1], r[1])   + m(h[2], r[0])   + m(h[3], r[4]*5) + m(h[4], r[3]*5),
+            m(h[0], r[3]) + m(h[1], r[2])   + m(h[2], r[1])   + m(h[3], r[0])   + m(h[4], r[4]*5),
+            m(h[0], r[4]) + m(h[1], r[3])   + m(h[2], r[2])   + m(h[3], r[1])   + m(h[4], r[0]),
+        ];
+
+        // Частичная редукция по 2^130-5
         let mut c = 0u128;
         for i in 0..5 {
             let v = t[i] + c;
-            h2[i] = (v & 0x3ffffff) as u64;
+            h[i] = (v & 0x3ffffff) as u64;
             c = v >> 26;
         }
-        h2[0] = h2[0].wrapping_add((c * 5) as u64);
-        c = (h2[0] >> 26) as u128;
-        h2[0] &= 0x3ffffff;
-        h2[1] = h2[1].wrapping_add(c as u64);
+        h[0] += (c * 5) as u64;
+        let c = h[0] >> 26;
+        h[0] &= 0x3ffffff;
+        h[1] += c;
 
-        self.h = h2;
+        self.h = h;
     }
 
     pub fn mac(&mut self, msg: &[u8]) -> [u8; TAG_SIZE] {
-        // Обрабатываем блоки по 16 байт
-        let mut i = 0;
-        while i + 16 <= msg.len() {
-            let block = &msg[i..i+16];
-            self.process_block(block, false);
-            i += 16;
-        }
-        if i < msg.len() {
-            self.process_block(&msg[i..], true);
+        // Обрабатываем блоки по 16 байт, хвост — короче
+        for block in msg.chunks(16) {
+            self.process_block(block);
         }
 
-        // Финализация: h + s
+        // Полная редукция: переносы, затем вычитаем p, если h >= p
         let mut h = self.h;
-        // Полная редукция
-        let mut c = h[1] >> 26; h[1] &= 0x3ffffff; h[2] = h[2].wrapping_add(c);
-        c = h[2] >> 26; h[2] &= 0x3ffffff; h[3] = h[3].wrapping_add(c);
-        c = h[3] >> 26; h[3] &= 0x3ffffff; h[4] = h[4].wrapping_add(c);
-        c = h[4] >> 26; h[4] &= 0x3ffffff; h[0] = h[0].wrapping_add(c * 5);
-        c = h[0] >> 26; h[0] &= 0x3ffffff; h[1] = h[1].wrapping_add(c);
+        let mut c = h[1] >> 26; h[1] &= 0x3ffffff; h[2] += c;
+        c = h[2] >> 26; h[2] &= 0x3ffffff; h[3] += c;
+        c = h[3] >> 26; h[3] &= 0x3ffffff; h[4] += c;
+        c = h[4] >> 26; h[4] &= 0x3ffffff; h[0] += c * 5;
+        c = h[0] >> 26; h[0] &= 0x3ffffff; h[1] += c;
+
+        let mut g = [0u64; 5];
+        c = 5;
+        for i in 0..5 {
+            g[i] = h[i] + c;
+            c = g[i] >> 26;
+            g[i] &= 0x3ffffff;
+        }
+        // c == 1 ⇔ h + 5 >= 2^130 ⇔ h >= p
+        if c == 1 { h = g; }
 
         // Сборка в 128 бит
         let tag_val = (h[0] as u128)
@@ -307,11 +299,14 @@ impl ChaCha20Poly1305 {
         ChaCha20Poly1305 { key }
     }
 
-    // Генерация one-time ключа Poly1305
+    // Генерация one-time ключа Poly1305. Нулевой r из блока ChaCha20
+    // означает сломанный шифр (вероятность ~2^-106) — падаем громко
     fn poly1305_key(&self, nonce: &[u8; NONCE_SIZE]) -> [u8; 32] {
         let mut cipher = ChaCha20::new(&self.key, nonce, 0);
         let ks = cipher.keystream(64);
-        ks[..32].try_into().unwrap()
+        let key: [u8; 32] = ks[..32].try_into().unwrap();
+        assert!(!Poly1305::is_weak_key(&key), "poly1305 one-time key has zero r");
+        key
     }
 
     // Паддинг до кратного 16
@@ -320,6 +315,20 @@ impl ChaCha20Poly1305 {
         if rem == 0 { vec![] } else { vec![0u8; 16 - rem] }
     }
 
+    // Вход MAC по RFC 8439 §2.8: aad || pad || ct || pad || len(aad) || len(ct).
+    // Блок длин привязывает границу AAD/ct: перенос байт между ними
+    // при той же сумме меняет тег
+    fn mac_data(aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        let mut mac_input = Vec::with_capacity(aad.len() + ciphertext.len() + 48);
+        mac_input.extend_from_slice(aad);
+        mac_input.extend_from_slice(&Self::pad16(aad.len()));
+        mac_input.extend_from_slice(ciphertext);
+        mac_input.extend_from_slice(&Self::pad16(ciphertext.len()));
+        mac_input.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+        mac_input.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+        mac_input
+    }
+
     pub fn seal(&self, plaintext: &[u8], aad: &[u8],
                 nonce: &[u8; NONCE_SIZE]) -> AeadCiphertext {
         // Шифруем с counter=1
@@ -328,31 +337,18 @@ impl ChaCha20Poly1305 {
 
         // MAC: poly1305(aad || pad || ct || pad || len_aad || len_ct)
         let poly_key = self.poly1305_key(nonce);
-        let mut mac_input = Vec::new();
-        mac_input.extend_from_slice(aad);
-        mac_input.extend_from_slice(&Self::pad16(aad.len()));
-        mac_input.extend_from_slice(&ciphertext);
-        mac_input.extend_from_slice(&Self::pad16(ciphertext.len()));
-        mac_input.extend_from_slice(&(aad.len() as u64).to_le_bytes());
-        mac_input.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
-
-        let tag = Poly1305::new(&poly_key).mac(&mac_input);
+        let tag = Poly1305::new(&poly_key).mac(&Self::mac_data(aad, &ciphertext));
 
         AeadCiphertext { nonce: *nonce, ciphertext, tag, aad_len: aad.len() }
     }
 
     pub fn open(&self, ct: &AeadCiphertext, aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+        // Заявленная длина AAD должна совпасть с фактической
+        if ct.aad_len != aad.len() { return Err("aad length mismatch"); }
+
         // Верифицируем MAC
         let poly_key = self.poly1305_key(&ct.nonce);
-        let mut mac_input = Vec::new();
-        mac_input.extend_from_slice(aad);
-        mac_input.extend_from_slice(&Self::pad16(aad.len()));
-        mac_input.extend_from_slice(&ct.ciphertext);
-        mac_input.extend_from_slice(&Self::pad16(ct.ciphertext.len()));
-        mac_input.extend_from_slice(&(aad.len() as u64).to_le_bytes());
-        mac_input.extend_from_slice(&(ct.ciphertext.len() as u64).to_le_bytes());
-
-        let expected_tag = Poly1305::new(&poly_key).mac(&mac_input);
+        let expected_tag = Poly1305::new(&poly_key).mac(&Self::mac_data(aad, &ct.ciphertext));
 
         // Constant-time сравнение
         let tag_ok = ct.tag.iter().zip(expected_tag.iter())
@@ -438,9 +434,47 @@ impl X25519 {
 // FederationCipher — высокоуровневый API
 // -----------------------------------------------------------------------------
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionLimits {
+    pub max_age_secs: u64,
+    pub max_messages: u64,
+    pub max_bytes: u64,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        SessionLimits { max_age_secs: SESSION_MAX_AGE_SECS,
+            max_messages: SESSION_MAX_MESSAGES, max_bytes: SESSION_MAX_BYTES }
+    }
+}
+
+// Ключи направлений сессии: у каждой стороны свой ключ отправки,
+// поэтому одинаковые nonce в двух направлениях не встречаются под одним ключом
+const SESSION_LABEL_I2R: &[u8] = b"federation-session-initiator->responder";
+const SESSION_LABEL_R2I: &[u8] = b"federation-session-responder->initiator";
+
+struct Session {
+    send: ChaCha20Poly1305,
+    recv: ChaCha20Poly1305,
+    created_at: u64,
+    messages: u64,
+    bytes: u64,
+}
+
+impl Session {
+    fn expired(&self, limits: &SessionLimits, now: u64) -> bool {
+        now.saturating_sub(self.created_at) >= limits.max_age_secs
+            || self.messages >= limits.max_messages
+            || self.bytes >= limits.max_bytes
+    }
+}
+
 pub struct FederationCipher {
-    sessions: HashMap<String, ChaCha20Poly1305>,
-    rng: u64,
+    sessions: HashMap<String, Session>,
+    pub limits: SessionLimits,
+    clock_secs: u64,
+    stream_ids: HashMap<String, u32>,
+    rng: XorShift64,
     pub encrypt_count: u64,
     pub decrypt_count: u64,
     pub bytes_encrypted: u64,
@@ -451,73 +485,148 @@ impl FederationCipher {
     pub fn new() -> Self {
         FederationCipher {
             sessions: HashMap::new(),
-            rng: 0xFEDC_1A50_C0DE_0000,
+            limits: SessionLimits::default(),
+            clock_secs: 0,
+            stream_ids: HashMap::new(),
+            rng: XorShift64::from_time(),
             encrypt_count: 0, decrypt_count: 0,
             bytes_encrypted: 0, auth_failures: 0,
         }
     }
 
-    fn next_rng(&mut self) -> u64 {
-        self.rng ^= self.rng << 13;
-        self.rng ^= self.rng >> 7;
-        self.rng ^= self.rng << 17;
-        self.rng
+    pub fn with_limits(limits: SessionLimits) -> Self {
+        FederationCipher { limits, ..Self::new() }
+    }
+
+    /// Текущее время (сек) для проверки возраста сессий
+    pub fn set_clock(&mut self, now_secs: u64) {
+        self.clock_secs = now_secs;
+    }
+
+    /// Сессия есть, но исчерпала лимиты — нужен новый handshake
+    pub fn session_expired(&self, peer_id: &str) -> bool {
+        self.sessions.get(peer_id)
+            .map(|s| s.expired(&self.limits, self.clock_secs))
+            .unwrap_or(false)
     }
 
     pub fn random_nonce(&mut self) -> [u8; NONCE_SIZE] {
         let mut n = [0u8; NONCE_SIZE];
         for i in 0..3 {
-            let r = self.next_rng().to_le_bytes();
+            let r = self.rng.next_u64().to_le_bytes();
             n[i*4..i*4+4].copy_from_slice(&r[..4]);
         }
         n
     }
 
-    pub fn establish_session(&mut self, peer_id: &str, shared_secret: &[u8]) {
-        let mut key = [0u8; KEY_SIZE];
-        for i in 0..KEY_SIZE {
-            key[i] = shared_secret[i % shared_secret.len()];
-        }
-        // HKDF-подобный mix
-        for i in 0..KEY_SIZE {
-            let r = self.next_rng();
-            key[i] ^= (r >> (i*3 % 57)) as u8;
-        }
-        // Восстанавливаем детерминированно из shared_secret
-        let mut key2 = [0u8; KEY_SIZE];
-        let mut state = 0x4845_4453_4554u64; // "HEDSET"
-        for i in 0..KEY_SIZE {
-            state ^= shared_secret[i % shared_secret.len()] as u64;
-            state = state.wrapping_mul(6364136223846793005);
-            state = state.wrapping_add(1442695040888963407);
-            key2[i] = (state >> 33) as u8;
-        }
-        self.sessions.insert(peer_id.to_string(), ChaCha20Poly1305::new(key2));
+    /// Сессия с пиром из общего секрета handshake. Обе стороны выводят
+    /// одну пару ключей направлений; `initiator` выбирает, какой из них —
+    /// ключ отправки. Нумерация потоков стартует со случайного stream_id
+    pub fn establish_session(&mut self, peer_id: &str, shared_secret: &[u8], initiator: bool) {
+        let i2r = ChaCha20Poly1305::new(hmac(shared_secret, SESSION_LABEL_I2R));
+        let r2i = ChaCha20Poly1305::new(hmac(shared_secret, SESSION_LABEL_R2I));
+        let (send, recv) = if initiator { (i2r, r2i) } else { (r2i, i2r) };
+        self.sessions.insert(peer_id.to_string(), Session {
+            send, recv, created_at: self.clock_secs, messages: 0, bytes: 0 });
+        self.stream_ids.insert(peer_id.to_string(), self.rng.next_u64() as u32);
     }
 
+    /// None — нет сессии или она истекла (см. session_expired)
     pub fn encrypt_pulse(&mut self, peer_id: &str, pulse: &[u8],
                           aad: &[u8]) -> Option<AeadCiphertext> {
+        if self.session_expired(peer_id) { return None; }
         let nonce = self.random_nonce();
-        let cipher = self.sessions.get(peer_id)?;
-        let ct = cipher.seal(pulse, aad, &nonce);
+        let session = self.sessions.get_mut(peer_id)?;
+        let ct = session.send.seal(pulse, aad, &nonce);
+        session.messages += 1;
+        session.bytes += pulse.len() as u64;
         self.encrypt_count += 1;
         self.bytes_encrypted += pulse.len() as u64;
         Some(ct)
     }
 
     pub fn decrypt_pulse(&mut self, peer_id: &str, ct: &AeadCiphertext,
-                          aad: &[u8]) -> Result<Vec<u8>, &'static str> {
-        let cipher = self.sessions.get(peer_id)
-            .ok_or("no session")?;
-        match cipher.open(ct, aad) {
+                          aad: &[u8]) -> Result<Vec<u8>, FederationError> {
+        let session = self.sessions.get(peer_id)
+            .ok_or(FederationError::Cipher("no session"))?;
+        match session.recv.open(ct, aad) {
             Ok(pt) => { self.decrypt_count += 1; Ok(pt) }
-            Err(e) => { self.auth_failures += 1; Err(e) }
+            Err(e) => { self.auth_failures += 1; Err(FederationError::Cipher(e)) }
+        }
+    }
+
+    // Nonce чанка: stream_id || индекс || флаг последнего. Подмена
+    // индекса или флага меняет nonce — тег не сойдётся
+    fn stream_nonce(stream_id: u32, index: u32, last: bool) -> [u8; NONCE_SIZE] {
+        let mut n = [0u8; NONCE_SIZE];
+        n[..4].copy_from_slice(&stream_id.to_le_bytes());
+        n[4..8].copy_from_slice(&index.to_le_bytes());
+        n[8] = last as u8;
+        n[9..].copy_from_slice(b"STR");
+        n
+    }
+
+    /// Шифрует поток (например сериализованный NeuralState) чанками по
+    /// chunk_size байт. Каждый поток сессии получает свой stream_id,
+    /// последний чанк помечен — decrypt_stream заметит обрезку
+    pub fn encrypt_stream(&mut self, peer_id: &str, mut reader: impl Read,
+                          chunk_size: usize) -> Result<Vec<AeadCiphertext>, &'static str> {
+        if chunk_size == 0 { return Err("chunk_size must be positive"); }
+        if !self.sessions.contains_key(peer_id) { return Err("no session"); }
+        if self.session_expired(peer_id) { return Err("session expired"); }
+        let read_chunk = |reader: &mut dyn Read| -> Result<Vec<u8>, &'static str> {
+            let mut buf = Vec::with_capacity(chunk_size);
+            reader.take(chunk_size as u64).read_to_end(&mut buf)
+                .map_err(|_| "stream read failed")?;
+            Ok(buf)
+        };
+
+        let counter = self.stream_ids.entry(peer_id.to_string()).or_insert(0);
+        let stream_id = *counter;
+        *counter = counter.wrapping_add(1);
+        let session = self.sessions.get_mut(peer_id).ok_or("no session")?;
+
+        let mut chunks = Vec::new();
+        let mut current = read_chunk(&mut reader)?;
+        loop {
+            let next = read_chunk(&mut reader)?;
+            let last = next.is_empty();
+            let index = u32::try_from(chunks.len()).map_err(|_| "stream too long")?;
+            let nonce = Self::stream_nonce(stream_id, index, last);
+            chunks.push(session.send.seal(&current, &[], &nonce));
+            session.messages += 1;
+            session.bytes += current.len() as u64;
+            self.encrypt_count += 1;
+            self.bytes_encrypted += current.len() as u64;
+            if last { return Ok(chunks); }
+            current = next;
+        }
+    }
+
+    /// Собирает поток обратно: чанки одного stream_id, индексы подряд
+    /// с нуля, последний — с флагом конца и только он
+    pub fn decrypt_stream(&mut self, peer_id: &str,
+                          chunks: &[AeadCiphertext]) -> Result<Vec<u8>, FederationError> {
+        let first = chunks.first().ok_or(FederationError::Cipher("empty stream"))?;
+        let stream_id = u32::from_le_bytes(first.nonce[..4].try_into().unwrap());
+        let mut out = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let last = i + 1 == chunks.len();
+            if chunk.nonce != Self::stream_nonce(stream_id, i as u32, chunk.nonce[8] == 1) {
+                return Err(FederationError::Cipher("stream chunk out of order"));
+            }
+            if chunk.nonce[8] == 1 && !last { return Err(FederationError::Cipher("data after final chunk")); }
+            out.extend(self.decrypt_pulse(peer_id, chunk, &[])?);
+            if last && chunk.nonce[8] != 1 { return Err(FederationError::Cipher("stream truncated")); }
         }
+        Ok(out)
     }
 
     pub fn stats(&self) -> CipherStats {
         CipherStats {
             sessions: self.sessions.len(),
+            expired_sessions: self.sessions.values()
+                .filter(|s| s.expired(&self.limits, self.clock_secs)).count(),
             encrypt_count: self.encrypt_count,
             decrypt_count: self.decrypt_count,
             bytes_encrypted: self.bytes_encrypted,
@@ -531,8 +640,210 @@ impl Default for FederationCipher { fn default() -> Self { Self::new() } }
 #[derive(Debug)]
 pub struct CipherStats {
     pub sessions: usize,
+    pub expired_sessions: usize,
     pub encrypt_count: u64,
     pub decrypt_count: u64,
     pub bytes_encrypted: u64,
     pub auth_failures: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        let s: String = s.split_whitespace().collect();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn stream_pair() -> (FederationCipher, FederationCipher) {
+        let (mut alice, mut bob) = (FederationCipher::new(), FederationCipher::new());
+        alice.establish_session("bob", b"shared-secret-for-streams", true);
+        bob.establish_session("alice", b"shared-secret-for-streams", false);
+        (alice, bob)
+    }
+
+    #[test]
+    fn test_stream_roundtrip_multi_chunk() {
+        let (mut alice, mut bob) = stream_pair();
+        let blob: Vec<u8> = (0..10_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let chunks = alice.encrypt_stream("bob", &blob[..], 4096).unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(bob.decrypt_stream("alice", &chunks).unwrap(), blob);
+
+        // Второй поток той же сессии — другие nonce
+        let again = alice.encrypt_stream("bob", &blob[..], 4096).unwrap();
+        assert_ne!(again[0].nonce, chunks[0].nonce);
+        let mut swapped = chunks.clone();
+        swapped.swap(0, 1);
+        assert_eq!(bob.decrypt_stream("alice", &swapped), Err(FederationError::Cipher("stream chunk out of order")));
+    }
+
+    #[test]
+    fn test_session_directions_use_separate_keys() {
+        let (mut alice, mut bob) = stream_pair();
+        let nonce = [7u8; NONCE_SIZE];
+        let a2b = alice.sessions["bob"].send.seal(b"pulse", &[], &nonce);
+        let b2a = bob.sessions["alice"].send.seal(b"pulse", &[], &nonce);
+        // Тот же nonce в обе стороны — разные ключевые потоки
+        assert_ne!(a2b.ciphertext, b2a.ciphertext);
+        assert_eq!(bob.decrypt_pulse("alice", &a2b, &[]).unwrap(), b"pulse");
+        assert!(alice.decrypt_pulse("bob", &a2b, &[]).is_err());
+
+        // Новый экземпляр на том же секрете не повторяет stream_id
+        let first = alice.encrypt_stream("bob", &b"blob"[..], 4).unwrap();
+        let mut restarted = FederationCipher::new();
+        restarted.establish_session("bob", b"shared-secret-for-streams", true);
+        let again = restarted.encrypt_stream("bob", &b"blob"[..], 4).unwrap();
+        assert_ne!(first[0].nonce[..4], again[0].nonce[..4]);
+    }
+
+    #[test]
+    fn test_stream_detects_dropped_final_chunk() {
+        let (mut alice, mut bob) = stream_pair();
+        let blob = vec![7u8; 9000];
+        let mut chunks = alice.encrypt_stream("bob", &blob[..], 3000).unwrap();
+        assert_eq!(chunks.len(), 3);
+        chunks.pop();
+        assert_eq!(bob.decrypt_stream("alice", &chunks), Err(FederationError::Cipher("stream truncated")));
+
+        // Подделать флаг конца нельзя: nonce под MAC
+        let mut forged = chunks.clone();
+        forged[1].nonce[8] = 1;
+        assert!(bob.decrypt_stream("alice", &forged).is_err());
+    }
+
+    #[test]
+    fn test_session_expires_after_message_limit() {
+        let mut alice = FederationCipher::with_limits(SessionLimits {
+            max_messages: 3, ..SessionLimits::default() });
+        alice.establish_session("bob", b"secret", true);
+        for _ in 0..3 { assert!(alice.encrypt_pulse("bob", b"pulse", b"").is_some()); }
+        assert!(alice.session_expired("bob"));
+        assert!(alice.encrypt_pulse("bob", b"pulse", b"").is_none());
+        assert_eq!(alice.encrypt_stream("bob", &b"blob"[..], 2).unwrap_err(), "session expired");
+        assert_eq!(alice.stats().expired_sessions, 1);
+
+        alice.establish_session("bob", b"fresh-secret", true);
+        assert!(alice.encrypt_pulse("bob", b"pulse", b"").is_some());
+        assert_eq!(alice.stats().expired_sessions, 0);
+    }
+
+    #[test]
+    fn test_session_expires_by_age() {
+        let mut alice = FederationCipher::new();
+        alice.set_clock(1000);
+        alice.establish_session("bob", b"secret", true);
+        alice.set_clock(1000 + SESSION_MAX_AGE_SECS - 1);
+        assert!(alice.encrypt_pulse("bob", b"pulse", b"").is_some());
+        alice.set_clock(1000 + SESSION_MAX_AGE_SECS);
+        assert!(alice.encrypt_pulse("bob", b"pulse", b"").is_none());
+    }
+
+    // RFC 8439 §2.1.1
+    #[test]
+    fn test_quarter_round_rfc8439_vector() {
+        let (mut a, mut b, mut c, mut d) = (0x11111111u32, 0x01020304u32, 0x9b8d6f43u32, 0x01234567u32);
+        quarter_round(&mut a, &mut b, &mut c, &mut d);
+        assert_eq!((a, b, c, d), (0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb));
+    }
+
+    // RFC 8439 §2.3.2
+    #[test]
+    fn test_chacha20_block_rfc8439_vector() {
+        let key: [u8; KEY_SIZE] = core::array::from_fn(|i| i as u8);
+        let nonce: [u8; NONCE_SIZE] = unhex("000000090000004a00000000").try_into().unwrap();
+        let mut cipher = ChaCha20::new(&key, &nonce, 1);
+        assert_eq!(cipher.keystream(BLOCK_SIZE), unhex("
+            10f1e7e4d13b5915500fdd1fa32071c4 c7d1f4c733c068030422aa9ac3d46c4e
+            d2826446079faa0914c2d705d98b02a2 b5129cd1de164eb9cbd083e8a2503c4e"),
+            "блок ChaCha20 не совпал с RFC 8439 §2.3.2");
+        // Счётчик продвинулся на один блок
+        assert_eq!(cipher.state[12], 2);
+    }
+
+    // RFC 8439 §2.4.2 — sunscreen, counter=1, два блока с хвостом
+    #[test]
+    fn test_chacha20_encrypt_rfc8439_vector() {
+        let key: [u8; KEY_SIZE] = core::array::from_fn(|i| i as u8);
+        let nonce: [u8; NONCE_SIZE] = unhex("000000000000004a00000000").try_into().unwrap();
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+                          only one tip for the future, sunscreen would be it.";
+        let ct = ChaCha20::new(&key, &nonce, 1).encrypt(plaintext);
+        assert_eq!(ct, unhex("
+            6e2e359a2568f98041ba0728dd0d6981 e97e7aec1d4360c20a27afccfd9fae0b
+            f91b65c5524733ab8f593dabcd62b357 1639d624e65152ab8f530c359f0861d8
+            07ca0dbf500d6a6156a38e088a22b65e 52bc514d16ccf806818ce91ab7793736
+            5af90bbf74a35be6b40b8eedf2785e42 874d"), "шифртекст не совпал с RFC 8439 §2.4.2");
+        assert_eq!(ChaCha20::new(&key, &nonce, 1).decrypt(&ct), plaintext.to_vec());
+    }
+
+    // RFC 8439 §2.5.2
+    #[test]
+    fn test_poly1305_rfc8439_vector() {
+        let key: [u8; 32] = unhex("85d6be7857556d337f4452fe42d506a8\
+                                   0103808afb0db2fd4abff6af4149f51b").try_into().unwrap();
+        let tag = Poly1305::new(&key).mac(b"Cryptographic Forum Research Group");
+        assert_eq!(tag.to_vec(), unhex("a8061dc1305136c6c22b8baf0c0127a9"),
+            "Poly1305 не совпал с RFC 8439 §2.5.2");
+    }
+
+    // RFC 8439 §2.8.2
+    #[test]
+    fn test_aead_rfc8439_vector() {
+        let key: [u8; KEY_SIZE] = core::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: [u8; NONCE_SIZE] = unhex("070000004041424344454647").try_into().unwrap();
+        let aad = unhex("50515253c0c1c2c3c4c5c6c7");
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+                          only one tip for the future, sunscreen would be it.";
+        let aead = ChaCha20Poly1305::new(key);
+        let ct = aead.seal(plaintext, &aad, &nonce);
+        assert_eq!(ct.ciphertext, unhex("
+            d31a8d34648e60db7b86afbc53ef7ec2 a4aded51296e08fea9e2b5a736ee62d6
+            3dbea45e8ca9671282fafb69da92728b 1a71de0a9e060b2905d6a5b67ecd3b36
+            92ddbd7f2d778b8c9803aee328091b58 fab324e4fad675945585808b4831d7bc
+            3ff4def08e4b7a9de576d26586cec64b 6116"), "ciphertext не совпал с RFC 8439 §2.8.2");
+        assert_eq!(ct.tag.to_vec(), unhex("1ae10b594f09e26a7e902ecbd0600691"),
+            "tag не совпал с RFC 8439 §2.8.2");
+        assert_eq!(aead.open(&ct, &aad).unwrap(), plaintext.to_vec());
+    }
+
+    #[test]
+    fn test_aad_ciphertext_boundary_is_authenticated() {
+        let aead = ChaCha20Poly1305::new([0x42; KEY_SIZE]);
+        let nonce = [9u8; NONCE_SIZE];
+        // 16-байтовое выравнивание: без блока длин сдвиг был бы незаметен
+        let aad = [0xAAu8; 16];
+        let ct = aead.seal(&[0x55u8; 32], &aad, &nonce);
+        assert!(aead.open(&ct, &aad).is_ok());
+
+        // Первые 16 байт ct переезжают в AAD: сумма та же, тег тот же
+        let mut shifted_aad = aad.to_vec();
+        shifted_aad.extend_from_slice(&ct.ciphertext[..16]);
+        let shifted = AeadCiphertext { nonce, ciphertext: ct.ciphertext[16..].to_vec(),
+            tag: ct.tag, aad_len: shifted_aad.len() };
+        assert_eq!(aead.open(&shifted, &shifted_aad), Err("authentication failed"));
+
+        // Обратный сдвиг: AAD укорочен, ct удлинён
+        let mut longer_ct = aad[8..].to_vec();
+        longer_ct.extend_from_slice(&ct.ciphertext);
+        let truncated = AeadCiphertext { nonce, ciphertext: longer_ct, tag: ct.tag, aad_len: 8 };
+        assert!(aead.open(&truncated, &aad[..8]).is_err());
+
+        let mut lying = ct.clone();
+        lying.aad_len = 8;
+        assert_eq!(aead.open(&lying, &aad), Err("aad length mismatch"));
+    }
+
+    #[test]
+    fn test_zero_poly1305_key_is_weak() {
+        assert!(Poly1305::is_weak_key(&[0u8; 32]));
+        // Нулевой r — тег равен s для любого сообщения
+        let mut key = [0u8; 32];
+        key[16..].copy_from_slice(&[7u8; 16]);
+        assert!(Poly1305::is_weak_key(&key));
+        assert_eq!(Poly1305::new(&key).mac(b"a"), Poly1305::new(&key).mac(b"b"));
+        let aead = ChaCha20Poly1305::new([0x80; KEY_SIZE]);
+        assert!(!Poly1305::is_weak_key(&aead.poly1305_key(&[0u8; NONCE_SIZE])));
+    }
+}
@@ -14,6 +14,7 @@
 // Чем сложнее регион и тактика — тем выше награда.
 // =============================================================================
 
+use crate::governance::{GovernanceDao, ProposalStatus, ProposalType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,6 +27,7 @@ pub const MAX_STREAK_MULT: f64    = 3.0;   // максимальный множ
 pub const STREAK_STEP: f64        = 0.1;   // шаг роста серии
 pub const DIFFICULTY_SCALE: f64   = 4.0;   // масштаб сложности региона
 pub const EVIDENCE_BONUS: f64     = 0.3;   // бонус за публикацию доказательств
+pub const DIFFICULTY_SMOOTHING: f64 = 0.2; // вес нового наблюдения блокировок
 
 // -----------------------------------------------------------------------------
 // RegionDifficulty — сложность региона
@@ -95,6 +97,61 @@ pub fn known_regions() -> HashMap<String, RegionDifficulty> {
     m
 }
 
+// -----------------------------------------------------------------------------
+// RegionRegistry — живая карта сложности регионов
+// -----------------------------------------------------------------------------
+
+/// known_regions() — стартовая точка. Цензура меняется: наблюдения
+/// блокировок сдвигают сложность экспоненциальным сглаживанием
+/// (smoothing — вес нового наблюдения), DAO может задать её напрямую.
+/// Сложность уходит в mint_for_bypass и record_bypass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionRegistry {
+    pub regions: HashMap<String, RegionDifficulty>,
+    pub smoothing: f64,
+}
+
+impl RegionRegistry {
+    pub fn new() -> Self {
+        RegionRegistry { regions: known_regions(), smoothing: DIFFICULTY_SMOOTHING }
+    }
+
+    pub fn get(&self, code: &str) -> Option<&RegionDifficulty> {
+        self.regions.get(code)
+    }
+
+    /// Балл сложности 0..1; неизвестный регион — 0
+    pub fn difficulty(&self, code: &str) -> f64 {
+        self.get(code).map(|r| r.difficulty_score).unwrap_or(0.0)
+    }
+
+    fn entry(&mut self, code: &str) -> &mut RegionDifficulty {
+        self.regions.entry(code.to_string())
+            .or_insert_with(|| RegionDifficulty::new(code, 0.0, 1, false, false))
+    }
+
+    /// Ручное переопределение балла (решение DAO)
+    pub fn set_difficulty(&mut self, code: &str, score: f64) {
+        self.entry(code).difficulty_score = score.clamp(0.0, 1.0);
+    }
+
+    /// Наблюдённая доля блокировок: block_rate и балл сдвигаются к
+    /// наблюдению на smoothing. Возвращает новый балл
+    pub fn observe_block_rate(&mut self, code: &str, rate: f64) -> f64 {
+        if !rate.is_finite() { return self.difficulty(code); }
+        let rate = rate.clamp(0.0, 1.0);
+        let alpha = self.smoothing.clamp(0.0, 1.0);
+        let region = self.entry(code);
+        let observed = RegionDifficulty { block_rate: rate, ..region.clone() }
+            .compute_difficulty();
+        region.block_rate += alpha * (rate - region.block_rate);
+        region.difficulty_score += alpha * (observed - region.difficulty_score);
+        region.difficulty_score
+    }
+}
+
+impl Default for RegionRegistry { fn default() -> Self { Self::new() } }
+
 // -----------------------------------------------------------------------------
 // BypassEvent — одно успешное событие
 // -----------------------------------------------------------------------------
@@ -146,7 +203,32 @@ pub struct CreditLedger {
     pub events: Vec<BypassEvent>,
     pub streaks: HashMap<String, u32>,      // серия успехов узла
     pub total_credits_issued: f64,
+    #[serde(default)]
+    pub total_credits_burned: f64,
     pub event_counter: u64,
+    #[serde(default)]
+    pub locks: HashMap<String, CreditLock>,  // lock_id → заблокированный залог
+    #[serde(default)]
+    pub treasury: f64,                       // казна: сюда уходят штрафы
+    #[serde(default)]
+    held_locks: HashMap<u64, CreditLock>,    // залоги под LockHandle
+    #[serde(default)]
+    held_counter: u64,
+}
+
+/// Залог узла: credits сняты с доступного баланса, но ещё принадлежат ему
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreditLock {
+    pub node_id: String,
+    pub amount: f64,
+}
+
+/// Право снять удерживаемый залог. Выдаётся только lock_held, не
+/// клонируется и не сериализуется: снять залог может лишь тот, кто его
+/// взял (DAO, рынок), а не любой, кто знает его номер
+#[derive(Debug, PartialEq, Eq)]
+pub struct LockHandle {
+    id: u64,
 }
 
 impl CreditLedger {
@@ -199,10 +281,106 @@ impl CreditLedger {
         if let Some(s) = self.streaks.get_mut(node_id) { *s = 0; }
     }
 
+    /// Перевод credits между счетами — баланс отправителя не уходит в минус
+    pub fn transfer(&mut self, from: &str, to: &str, amount: f64)
+        -> Result<(), &'static str> {
+        if !amount.is_finite() || amount < 0.0 { return Err("Некорректная сумма перевода"); }
+        if self.balance(from) < amount { return Err("Недостаточно credits"); }
+        *self.balances.entry(from.to_string()).or_insert(0.0) -= amount;
+        *self.balances.entry(to.to_string()).or_insert(0.0) += amount;
+        Ok(())
+    }
+
+    /// Списать credits со счёта безвозвратно (сжигание)
+    pub fn burn(&mut self, node_id: &str, amount: f64) -> Result<(), &'static str> {
+        if !amount.is_finite() || amount < 0.0 { return Err("Некорректная сумма сжигания"); }
+        if self.balance(node_id) < amount { return Err("Недостаточно credits"); }
+        *self.balances.entry(node_id.to_string()).or_insert(0.0) -= amount;
+        self.total_credits_burned += amount;
+        Ok(())
+    }
+
+    /// Доступный баланс — заблокированные credits сюда не входят
     pub fn balance(&self, node_id: &str) -> f64 {
         self.balances.get(node_id).cloned().unwrap_or(0.0)
     }
 
+    pub fn locked(&self, node_id: &str) -> f64 {
+        self.locks.values().chain(self.held_locks.values())
+            .filter(|l| l.node_id == node_id).map(|l| l.amount).sum()
+    }
+
+    /// Доступное + заблокированное
+    pub fn total_balance(&self, node_id: &str) -> f64 {
+        self.balance(node_id) + self.locked(node_id)
+    }
+
+    // -------------------------------------------------------------------------
+    // Залоги: lock → unlock (вернуть) или slash (изъять)
+    // -------------------------------------------------------------------------
+
+    /// Заблокировать amount с доступного баланса под lock_id
+    pub fn lock(&mut self, node_id: &str, amount: f64, lock_id: &str)
+        -> Result<(), &'static str> {
+        if self.locks.contains_key(lock_id) { return Err("Залог с таким id уже существует"); }
+        let lock = self.take_from_balance(node_id, amount)?;
+        self.locks.insert(lock_id.to_string(), lock);
+        Ok(())
+    }
+
+    /// Залог, который снимает только владелец LockHandle. Номер выдаёт
+    /// книга — занять его заранее через lock нельзя
+    pub fn lock_held(&mut self, node_id: &str, amount: f64) -> Result<LockHandle, &'static str> {
+        let lock = self.take_from_balance(node_id, amount)?;
+        self.held_counter += 1;
+        self.held_locks.insert(self.held_counter, lock);
+        Ok(LockHandle { id: self.held_counter })
+    }
+
+    fn take_from_balance(&mut self, node_id: &str, amount: f64)
+        -> Result<CreditLock, &'static str> {
+        if !amount.is_finite() || amount <= 0.0 { return Err("Некорректная сумма залога"); }
+        if self.balance(node_id) < amount { return Err("Недостаточно credits"); }
+        *self.balances.entry(node_id.to_string()).or_insert(0.0) -= amount;
+        Ok(CreditLock { node_id: node_id.to_string(), amount })
+    }
+
+    /// Снять залог — credits возвращаются владельцу. Возвращает сумму
+    pub fn unlock(&mut self, lock_id: &str) -> Result<f64, &'static str> {
+        let lock = self.locks.remove(lock_id).ok_or("Залог не найден")?;
+        Ok(self.return_lock(lock))
+    }
+
+    /// Снять удерживаемый залог; handle расходуется
+    pub fn unlock_held(&mut self, handle: LockHandle) -> Result<f64, &'static str> {
+        let lock = self.held_locks.remove(&handle.id).ok_or("Залог не найден")?;
+        Ok(self.return_lock(lock))
+    }
+
+    /// Изъять залог: в казну (to_treasury) или сжечь. Возвращает сумму
+    pub fn slash(&mut self, lock_id: &str, to_treasury: bool) -> Result<f64, &'static str> {
+        let lock = self.locks.remove(lock_id).ok_or("Залог не найден")?;
+        Ok(self.confiscate(lock, to_treasury))
+    }
+
+    /// Изъять удерживаемый залог; handle расходуется
+    pub fn slash_held(&mut self, handle: LockHandle, to_treasury: bool)
+        -> Result<f64, &'static str> {
+        let lock = self.held_locks.remove(&handle.id).ok_or("Залог не найден")?;
+        Ok(self.confiscate(lock, to_treasury))
+    }
+
+    fn return_lock(&mut self, lock: CreditLock) -> f64 {
+        *self.balances.entry(lock.node_id).or_insert(0.0) += lock.amount;
+        lock.amount
+    }
+
+    fn confiscate(&mut self, lock: CreditLock, to_treasury: bool) -> f64 {
+        if to_treasury { self.treasury += lock.amount; }
+        else { self.total_credits_burned += lock.amount; }
+        lock.amount
+    }
+
     pub fn top_nodes(&self, n: usize) -> Vec<(String, f64)> {
         let mut v: Vec<_> = self.balances.iter()
             .map(|(k, v)| (k.clone(), *v)).collect();
@@ -269,6 +447,10 @@ pub const UPGRADE_FUND_RATE: f64    = 0.05; // 5% от кредитов → фо
 pub const VETERAN_HW_YEARS: u32     = 3;    // железо ≥3 лет = "vintage"
 pub const ANCIENT_HW_YEARS: u32     = 7;    // железо ≥7 лет = "ancient"
 pub const MAX_RECYCLE_MULT: f64     = 2.5;  // потолок множителя
+pub const ECO_AGE_MIDPOINT: f64     = 5.0;  // возраст середины логистической кривой
+pub const ECO_AGE_STEEPNESS: f64    = 2.0;  // лет на единицу логистики (плавность)
+pub const RECYCLED_DEVICE_MULT: f64 = 1.2;  // +20% за устройство из вторсырья
+pub const UPGRADE_FUND_DAO_THRESHOLD: f64 = 100.0; // выше — только по решению DAO
 
 // -----------------------------------------------------------------------------
 // HardwareAge — возраст железа влияет на бонус
@@ -287,13 +469,6 @@ impl HardwareAge {
         else if years >= VETERAN_HW_YEARS   { HardwareAge::Vintage }
         else                                { HardwareAge::Modern }
     }
-    pub fn recycle_multiplier(&self) -> f64 {
-        match self {
-            HardwareAge::Modern  => 1.00,
-            HardwareAge::Vintage => 1.50, // +50%
-            HardwareAge::Ancient => 2.50, // +150%
-        }
-    }
     pub fn label(&self) -> &str {
         match self {
             HardwareAge::Modern  => "🔵 Modern",
@@ -301,13 +476,6 @@ impl HardwareAge {
             HardwareAge::Ancient => "🟤 Ancient",
         }
     }
-    pub fn description(&self) -> &str {
-        match self {
-            HardwareAge::Modern  => "новое железо",
-            HardwareAge::Vintage => "ветеранское железо (+50% кредитов)",
-            HardwareAge::Ancient => "древнее железо (+150% кредитов)",
-        }
-    }
 }
 
 // -----------------------------------------------------------------------------
@@ -328,8 +496,7 @@ pub struct EcoProfile {
 impl EcoProfile {
     pub fn new(node_id: &str, hw_age_years: u32, is_recycled: bool) -> Self {
         let hw_age = HardwareAge::from_years(hw_age_years);
-        let mut recycle_mult = hw_age.recycle_multiplier();
-        if is_recycled { recycle_mult = (recycle_mult * 1.2).min(MAX_RECYCLE_MULT); }
+        let recycle_mult = Self::continuous_multiplier(hw_age_years as f64, is_recycled);
         EcoProfile {
             node_id: node_id.to_string(),
             hw_age_years, hw_age, recycle_mult,
@@ -338,7 +505,26 @@ impl EcoProfile {
         }
     }
 
+    /// Плавный множитель по возрасту: логистика от 1.0 (новое железо)
+    /// к MAX_RECYCLE_MULT, без скачков на границах HardwareAge
+    pub fn age_multiplier(years: f64) -> f64 {
+        let sigmoid = |x: f64| 1.0 / (1.0 + (-x).exp());
+        let start = sigmoid(-ECO_AGE_MIDPOINT / ECO_AGE_STEEPNESS);
+        let s = sigmoid((years.max(0.0) - ECO_AGE_MIDPOINT) / ECO_AGE_STEEPNESS);
+        1.0 + (MAX_RECYCLE_MULT - 1.0) * (s - start) / (1.0 - start)
+    }
+
+    pub fn continuous_multiplier(years: f64, is_recycled: bool) -> f64 {
+        let mut mult = Self::age_multiplier(years);
+        if is_recycled { mult = (mult * RECYCLED_DEVICE_MULT).min(MAX_RECYCLE_MULT); }
+        mult
+    }
+
     pub fn apply(&mut self, base_credits: f64) -> EcoReward {
+        // Возраст мог измениться с момента создания профиля
+        self.hw_age = HardwareAge::from_years(self.hw_age_years);
+        self.recycle_mult = Self::continuous_multiplier(
+            self.hw_age_years as f64, self.is_recycled_device);
         let recycled_credits = base_credits * self.recycle_mult;
         let eco_bonus        = recycled_credits - base_credits;
         let upgrade_contrib  = recycled_credits * UPGRADE_FUND_RATE;
@@ -374,12 +560,21 @@ pub struct EcoReward {
 // UpgradeFund — фонд апгрейда железа (дополнение к pools.rs)
 // -----------------------------------------------------------------------------
 
+/// Запись аудита — каждая выплата из фонда
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disbursement {
+    pub node_id: String,
+    pub amount: f64,
+    pub proposal_id: Option<String>, // None — мелкая выплата без DAO
+    pub timestamp: i64,
+}
+
 #[derive(Debug, Default)]
 pub struct UpgradeFund {
     pub balance: f64,
     pub total_contributed: f64,
     pub contributions: Vec<(String, f64)>,  // (node_id, amount)
-    pub disbursements: Vec<(String, f64)>,  // (node_id, amount)
+    pub disbursements: Vec<Disbursement>,
 }
 
 impl UpgradeFund {
@@ -391,11 +586,57 @@ impl UpgradeFund {
         self.contributions.push((node_id.to_string(), amount));
     }
 
+    /// Мелкая выплата без голосования — крупные требуют решения DAO
     pub fn disburse(&mut self, node_id: &str, amount: f64) -> bool {
-        if self.balance < amount { return false; }
+        if amount > UPGRADE_FUND_DAO_THRESHOLD { return false; }
+        self.record_disbursement(node_id, amount, None).is_ok()
+    }
+
+    /// Выплата по принятому предложению DAO типа UpgradeDisbursement.
+    /// Получатель и сумма должны совпадать с решением; после выплаты
+    /// предложение переходит в Executed и повторно не принимается
+    pub fn disburse_with_proposal(&mut self, node_id: &str, amount: f64,
+        proposal_id: &str, dao: &mut GovernanceDao) -> Result<(), String> {
+        let proposal = dao.proposals.get_mut(proposal_id)
+            .ok_or_else(|| format!("Предложение не найдено: {}", proposal_id))?;
+        match proposal.status {
+            ProposalStatus::Passed => {}
+            ProposalStatus::Executed =>
+                return Err(format!("Предложение уже использовано: {}", proposal_id)),
+            ref other => return Err(format!("Предложение не принято: {:?}", other)),
+        }
+        match &proposal.proposal_type {
+            ProposalType::UpgradeDisbursement { recipient, amount: approved }
+                if recipient == node_id && (approved - amount).abs() < 1e-9 => {}
+            _ => return Err(format!("Предложение {} не разрешает выплату {:.2} узлу {}",
+                proposal_id, amount, node_id)),
+        }
+        self.record_disbursement(node_id, amount, Some(proposal_id.to_string()))?;
+        proposal.status = ProposalStatus::Executed;
+        Ok(())
+    }
+
+    fn record_disbursement(&mut self, node_id: &str, amount: f64,
+        proposal_id: Option<String>) -> Result<(), String> {
+        if amount <= 0.0 || !amount.is_finite() {
+            return Err(format!("Некорректная сумма: {}", amount));
+        }
+        if self.balance < amount {
+            return Err(format!("Недостаточно средств: {:.2} < {:.2}", self.balance, amount));
+        }
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)
+            .unwrap().as_millis() as i64;
         self.balance -= amount;
-        self.disbursements.push((node_id.to_string(), amount));
-        true
+        self.disbursements.push(Disbursement {
+            node_id: node_id.to_string(), amount, proposal_id, timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Журнал всех выплат для аудита
+    pub fn history(&self) -> &[Disbursement] {
+        &self.disbursements
     }
 
     pub fn top_contributors(&self, n: usize) -> Vec<(&str, f64)> {
@@ -411,8 +652,172 @@ impl UpgradeFund {
     pub fn stats(&self) -> String {
         format!("balance={:.2}💎  contributed={:.2}💎  disbursed={:.2}💎  nodes={}",
             self.balance, self.total_contributed,
-            self.disbursements.iter().map(|(_,a)| a).sum::<f64>(),
+            self.disbursements.iter().map(|d| d.amount).sum::<f64>(),
             self.contributions.iter().map(|(n,_)| n.as_str())
                 .collect::<std::collections::HashSet<_>>().len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethics::EthicsLayer;
+    use crate::governance::ProposalType;
+    use crate::mint::MintEngine;
+
+    #[test]
+    fn test_observed_blocking_raises_de_difficulty_and_reward() {
+        let mut registry = RegionRegistry::new();
+        let reward = |registry: &RegionRegistry| MintEngine::new()
+            .mint_for_bypass(&EthicsLayer::new(), "node", "DE", "Passive", registry.difficulty("DE"))
+            .unwrap().unwrap().gross_minted;
+        let (start, start_reward) = (registry.difficulty("DE"), reward(&registry));
+
+        let mut prev = start;
+        for _ in 0..10 {
+            let next = registry.observe_block_rate("DE", 0.9);
+            assert!(next > prev);
+            prev = next;
+        }
+        // Сглаживание: одно наблюдение не перебрасывает регион, серия — да
+        assert!(prev > start + 0.5 && prev < 1.0);
+        assert!(registry.get("DE").unwrap().block_rate > 0.8);
+        assert!(reward(&registry) > start_reward * 2.0);
+
+        registry.set_difficulty("DE", 0.1);
+        assert_eq!(registry.difficulty("DE"), 0.1);
+        registry.observe_block_rate("XX", 0.5);
+        assert!(registry.difficulty("XX") > 0.0);
+    }
+
+    #[test]
+    fn test_eco_multiplier_monotonic_and_continuous() {
+        let mut prev = EcoProfile::age_multiplier(0.0);
+        assert!((prev - 1.0).abs() < 1e-12);
+        for tenth in 1..=200 {
+            let m = EcoProfile::age_multiplier(tenth as f64 / 10.0);
+            assert!(m >= prev);
+            // Нет скачков — шаг в 0.1 года меняет множитель незначительно
+            assert!(m - prev < 0.05);
+            prev = m;
+        }
+        assert!(prev <= MAX_RECYCLE_MULT);
+
+        // Вокруг старых границ тиров соседние годы различаются плавно
+        for boundary in [VETERAN_HW_YEARS, 5, ANCIENT_HW_YEARS, 10] {
+            let before = EcoProfile::new("n", boundary - 1, false).apply(1.0);
+            let after = EcoProfile::new("n", boundary, false).apply(1.0);
+            assert!(after.recycle_mult > before.recycle_mult);
+            assert!(after.recycle_mult - before.recycle_mult < 0.3);
+        }
+    }
+
+    #[test]
+    fn test_eco_recycled_bonus_capped() {
+        let plain = EcoProfile::new("n", 4, false);
+        let recycled = EcoProfile::new("n", 4, true);
+        assert!((recycled.recycle_mult - plain.recycle_mult * RECYCLED_DEVICE_MULT).abs() < 1e-12);
+        let mut ancient = EcoProfile::new("n", 40, true);
+        assert!(ancient.recycle_mult <= MAX_RECYCLE_MULT);
+        assert_eq!(ancient.apply(1.0).hw_age_label, "🟤 Ancient");
+    }
+
+    fn passed_proposal(dao: &mut GovernanceDao) -> String {
+        let id = dao.submit_proposal("elder", 10.0,
+            ProposalType::UpgradeDisbursement { recipient: "ghost-pi3".into(), amount: 300.0 },
+            "Апгрейд ghost-pi3", "Замена платы", true).unwrap();
+        dao.cast_vote(&id, "elder", 60.0, true, "за").unwrap();
+        dao.finalize_proposal(&id, 100.0).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_upgrade_fund_requires_dao_for_large() {
+        let mut fund = UpgradeFund::new();
+        fund.contribute("a", 500.0);
+        assert!(!fund.disburse("ghost-pi3", 300.0));
+        assert!(fund.disburse("ghost-pi3", 80.0));
+
+        let mut dao = GovernanceDao::new();
+        let pending = dao.submit_proposal("elder", 10.0,
+            ProposalType::AddSeedNode { address: "x".into(), region: "DE".into() },
+            "Не голосовали", "", false).unwrap();
+        assert!(fund.disburse_with_proposal("ghost-pi3", 300.0, &pending, &mut dao).is_err());
+        assert!((fund.balance - 420.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_upgrade_fund_approved_disbursement_logged() {
+        let mut fund = UpgradeFund::new();
+        fund.contribute("a", 500.0);
+        let mut dao = GovernanceDao::new();
+        let id = passed_proposal(&mut dao);
+
+        // Чужой получатель или другая сумма — решение не подходит
+        assert!(fund.disburse_with_proposal("work-bob", 300.0, &id, &mut dao).is_err());
+        assert!(fund.disburse_with_proposal("ghost-pi3", 350.0, &id, &mut dao).is_err());
+        assert_eq!(dao.proposals[&id].status, ProposalStatus::Passed);
+
+        fund.disburse_with_proposal("ghost-pi3", 300.0, &id, &mut dao).unwrap();
+        assert!((fund.balance - 200.0).abs() < 1e-12);
+        let last = fund.history().last().unwrap();
+        assert_eq!(last.node_id, "ghost-pi3");
+        assert_eq!(last.amount, 300.0);
+        assert_eq!(last.proposal_id.as_deref(), Some(id.as_str()));
+        // Повторно то же решение не используется
+        assert_eq!(dao.proposals[&id].status, ProposalStatus::Executed);
+        assert!(fund.disburse_with_proposal("ghost-pi3", 300.0, &id, &mut dao).is_err());
+        assert_eq!(fund.history().len(), 1);
+    }
+
+    #[test]
+    fn test_locked_credits_not_spendable_until_unlocked() {
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("node".into(), 30.0);
+        ledger.lock("node", 20.0, "firmware-1").unwrap();
+        assert_eq!((ledger.balance("node"), ledger.locked("node")), (10.0, 20.0));
+        assert_eq!(ledger.total_balance("node"), 30.0);
+
+        assert!(ledger.transfer("node", "other", 15.0).is_err());
+        assert!(ledger.burn("node", 15.0).is_err());
+        assert!(ledger.lock("node", 15.0, "firmware-2").is_err());
+        assert!(ledger.lock("node", 5.0, "firmware-1").is_err(), "id занят");
+
+        assert_eq!(ledger.unlock("firmware-1"), Ok(20.0));
+        assert_eq!((ledger.balance("node"), ledger.locked("node")), (30.0, 0.0));
+        assert!(ledger.unlock("firmware-1").is_err());
+        ledger.transfer("node", "other", 15.0).unwrap();
+    }
+
+    #[test]
+    fn test_slash_moves_lock_to_treasury_or_burns() {
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("node".into(), 30.0);
+        ledger.lock("node", 20.0, "a").unwrap();
+        ledger.lock("node", 5.0, "b").unwrap();
+
+        assert_eq!(ledger.slash("a", true), Ok(20.0));
+        assert_eq!(ledger.treasury, 20.0);
+        assert_eq!(ledger.slash("b", false), Ok(5.0));
+        assert_eq!((ledger.treasury, ledger.total_credits_burned), (20.0, 5.0));
+        assert_eq!(ledger.total_balance("node"), 5.0);
+        assert!(ledger.slash("a", true).is_err());
+    }
+
+    #[test]
+    fn test_held_lock_released_only_with_handle() {
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("node".into(), 30.0);
+        let handle = ledger.lock_held("node", 20.0).unwrap();
+        assert_eq!(ledger.locked("node"), 20.0);
+
+        // Обычные lock_id не пересекаются с удерживаемыми залогами
+        assert!(ledger.unlock("1").is_err());
+        ledger.lock("node", 5.0, "1").unwrap();
+        assert_eq!(ledger.unlock("1"), Ok(5.0));
+        assert_eq!(ledger.locked("node"), 20.0);
+
+        assert_eq!(ledger.unlock_held(handle), Ok(20.0));
+        assert_eq!(ledger.balance("node"), 30.0);
+    }
+}
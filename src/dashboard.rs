@@ -12,10 +12,13 @@
 //   AlertPanel   — тревоги и события
 // =============================================================================
 
+use std::collections::VecDeque;
 
 pub const DASH_WIDTH: usize  = 78;
 pub const BAR_WIDTH: usize   = 20;
 pub const REFRESH_MS: u64    = 500;
+pub const ALERT_ROWS: usize  = 5;     // высота AlertPanel
+pub const ECON_HISTORY: usize = 30;   // тиков в истории EconPanel
 
 // -----------------------------------------------------------------------------
 // Цвета ANSI
@@ -69,6 +72,33 @@ pub fn header(title: &str) -> String {
         Color::BLUE, line, Color::RESET)
 }
 
+/// Убирает CSI-последовательности (\x1b[...X) — для логов и NO_COLOR
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            for p in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&p) { break; }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub fn sparkline(values: &[f64]) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let span = (max - min).max(1e-9);
+    values.iter()
+        .map(|v| LEVELS[(((v - min) / span) * 7.0).round() as usize])
+        .collect()
+}
+
 pub fn panel_header(title: &str, color: &str) -> String {
     format!("{}┌─ {}{}{} {}{}",
         Color::DIM, color, Color::BOLD, title, Color::RESET,
@@ -131,6 +161,39 @@ impl Trend {
     }
 }
 
+// -----------------------------------------------------------------------------
+// Alert — тревога с важностью и подтверждением
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity { Info, Warning, Critical }
+
+impl Severity {
+    pub fn color(&self) -> &str {
+        match self {
+            Severity::Info     => Color::CYAN,
+            Severity::Warning  => Color::YELLOW,
+            Severity::Critical => Color::RED,
+        }
+    }
+    pub fn label(&self) -> &str {
+        match self {
+            Severity::Info     => "INFO",
+            Severity::Warning  => "WARN",
+            Severity::Critical => "CRIT",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub id: u64,
+    pub severity: Severity,
+    pub message: String,
+    pub timestamp: u64,   // uptime_secs в момент тревоги
+    pub acked: bool,
+}
+
 // -----------------------------------------------------------------------------
 // DashboardState — полное состояние дашборда
 // -----------------------------------------------------------------------------
@@ -147,8 +210,12 @@ pub struct DashboardState {
     pub mesh_nodes: u32,
     pub satellite_active: bool,
     pub uptime_secs: u64,
-    pub alerts: Vec<String>,
+    pub alerts: Vec<Alert>,
     pub tick: u64,
+    pub color: bool,      // false — вывод без ANSI (NO_COLOR)
+    pub supply_history: VecDeque<f64>,
+    pub burn_history: VecDeque<f64>,
+    next_alert_id: u64,
 }
 
 impl DashboardState {
@@ -209,7 +276,7 @@ impl DashboardState {
                 bypass_rate:0.96, active_nodes:891, pulses_today:41203, trend:Trend::Stable },
         ];
 
-        DashboardState {
+        let mut state = DashboardState {
             nodes, regions,
             total_pulse_supply: 847_291.5,
             treasury: 24_891.3,
@@ -220,16 +287,46 @@ impl DashboardState {
             mesh_nodes: 2514,
             satellite_active: true,
             uptime_secs: 35712841,
-            alerts: vec![
-                "⚠️  ghost-pentium оффлайн 2ч".into(),
-                "🎯 CN bypass +12% после AikiReflection v2".into(),
-                "🔐 14 auth failures отклонено".into(),
-                "💎 Халвинг через 47,291 прорывов".into(),
-            ],
+            alerts: Vec::new(),
             tick: 0,
+            color: std::env::var_os("NO_COLOR").is_none(),
+            supply_history: VecDeque::new(),
+            burn_history: VecDeque::new(),
+            next_alert_id: 0,
+        };
+        state.record_econ();
+        state.push_alert(Severity::Warning, "⚠️  ghost-pentium оффлайн 2ч");
+        state.push_alert(Severity::Info,    "🎯 CN bypass +12% после AikiReflection v2");
+        state.push_alert(Severity::Warning, "🔐 14 auth failures отклонено");
+        state.push_alert(Severity::Info,    "💎 Халвинг через 47,291 прорывов");
+        state
+    }
+
+    /// Новая тревога, возвращает её id для ack_alert
+    pub fn push_alert(&mut self, severity: Severity, message: impl Into<String>) -> u64 {
+        let id = self.next_alert_id;
+        self.next_alert_id += 1;
+        self.alerts.push(Alert { id, severity, message: message.into(),
+            timestamp: self.uptime_secs, acked: false });
+        id
+    }
+
+    pub fn ack_alert(&mut self, id: u64) -> bool {
+        match self.alerts.iter_mut().find(|a| a.id == id && !a.acked) {
+            Some(alert) => { alert.acked = true; true }
+            None => false,
         }
     }
 
+    /// Неподтверждённые тревоги: сначала важные, внутри — свежие
+    pub fn active_alerts(&self) -> Vec<&Alert> {
+        let mut active: Vec<&Alert> = self.alerts.iter().filter(|a| !a.acked).collect();
+        active.sort_by(|a, b| b.severity.cmp(&a.severity)
+            .then(b.timestamp.cmp(&a.timestamp))
+            .then(b.id.cmp(&a.id)));
+        active
+    }
+
     pub fn tick(&mut self) {
         self.tick += 1;
         // Симуляция изменений
@@ -245,8 +342,31 @@ impl DashboardState {
             let noise = (t as f64 * 0.13 + region.censor_strength).cos() * 0.01;
             region.bypass_rate = (region.bypass_rate + noise).clamp(0.1, 0.99);
         }
+        let mint = (t as f64 * 0.11).sin().abs() * 12.0;
+        let burn = (t as f64 * 0.07).cos().abs() * 8.0;
+        self.total_pulse_supply += mint - burn;
+        self.burn_total += burn;
         self.uptime_secs += 1;
         self.encrypt_count += 847;
+        self.record_econ();
+    }
+
+    fn record_econ(&mut self) {
+        self.supply_history.push_back(self.total_pulse_supply);
+        self.burn_history.push_back(self.burn_total);
+        while self.supply_history.len() > ECON_HISTORY { self.supply_history.pop_front(); }
+        while self.burn_history.len() > ECON_HISTORY { self.burn_history.pop_front(); }
+    }
+
+    /// Тренд supply за окно истории и изменение от начала окна
+    pub fn supply_trend(&self) -> (Trend, f64) {
+        let delta = match (self.supply_history.front(), self.supply_history.back()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        };
+        let trend = if delta > 0.05 { Trend::Up }
+            else if delta < -0.05 { Trend::Down } else { Trend::Stable };
+        (trend, delta)
     }
 }
 
@@ -345,6 +465,14 @@ impl DashboardRenderer {
                          Proposals  {cyan}{}{reset}\n",
             state.treasury, state.burn_total, state.active_proposals,
             green=Color::GREEN, red=Color::RED, cyan=Color::CYAN, reset=Color::RESET);
+        let (trend, delta) = state.supply_trend();
+        let burned = state.burn_history.back().zip(state.burn_history.front())
+            .map(|(last, first)| last - first).unwrap_or(0.0);
+        let supply: Vec<f64> = state.supply_history.iter().cloned().collect();
+        out += &format!("  Trend   {}{} {:+.1}💎{}  {dim}burned {:.1}💎 за {} тиков{reset}  {}\n",
+            trend.color(), trend.icon(), delta, Color::RESET,
+            burned, supply.len().saturating_sub(1), sparkline(&supply),
+            dim=Color::DIM, reset=Color::RESET);
         out
     }
 
@@ -371,13 +499,30 @@ impl DashboardRenderer {
 
     pub fn render_alerts(state: &DashboardState) -> String {
         let mut out = format!("{}\n", panel_header("ALERTS", Color::RED));
-        for alert in &state.alerts {
-            out += &format!("  {}\n", alert);
+        let active = state.active_alerts();
+        for alert in active.iter().take(ALERT_ROWS) {
+            out += &format!("  {}{}{}{} {}\n",
+                Color::BOLD, alert.severity.color(), alert.severity.label(), Color::RESET,
+                alert.message);
+        }
+        if active.len() > ALERT_ROWS {
+            out += &format!("  {}… ещё {} тревог{}\n",
+                Color::DIM, active.len() - ALERT_ROWS, Color::RESET);
         }
         out
     }
 
+    /// Цветной кадр, если state.color, иначе render_plain
     pub fn render_full(state: &DashboardState) -> String {
+        if state.color { Self::render_colored(state) } else { Self::render_plain(state) }
+    }
+
+    /// Тот же макет без escape-кодов — для файлов и логов
+    pub fn render_plain(state: &DashboardState) -> String {
+        strip_ansi(&Self::render_colored(state))
+    }
+
+    fn render_colored(state: &DashboardState) -> String {
         let mut out = String::new();
         out += &Self::render_header(state);
         out += "\n";
@@ -506,4 +651,76 @@ mod tests {
         assert_eq!(node.pulse_balance, 100.0);
         assert_eq!(node.reputation, 0.9);
     }
+
+    #[test]
+    fn test_alerts_sorted_by_severity_then_recency() {
+        let mut state = DashboardState::demo();
+        state.uptime_secs += 10;
+        let crit = state.push_alert(Severity::Critical, "DPI wave");
+        state.uptime_secs += 10;
+        let info = state.push_alert(Severity::Info, "fresh info");
+        let active = state.active_alerts();
+        assert_eq!(active[0].id, crit);
+        assert_eq!(active[1].severity, Severity::Warning);
+        let infos: Vec<u64> = active.iter()
+            .filter(|a| a.severity == Severity::Info).map(|a| a.id).collect();
+        assert_eq!(infos[0], info);
+
+        for i in 0..ALERT_ROWS { state.push_alert(Severity::Warning, format!("w{}", i)); }
+        let panel = DashboardRenderer::render_alerts(&state);
+        assert!(panel.contains("DPI wave"));
+        assert!(!panel.contains("fresh info"));
+        assert!(panel.contains(&format!("ещё {} тревог", state.active_alerts().len() - ALERT_ROWS)));
+    }
+
+    #[test]
+    fn test_econ_trend_follows_mints_and_burns() {
+        let mut state = DashboardState::demo();
+        for _ in 0..5 {
+            state.total_pulse_supply -= 500.0;
+            state.burn_total += 500.0;
+            state.tick();
+        }
+        assert_eq!(state.supply_trend().0, Trend::Down);
+        assert!(DashboardRenderer::render_econ(&state).contains(Trend::Down.icon()));
+
+        for _ in 0..ECON_HISTORY {
+            state.total_pulse_supply += 500.0;
+            state.tick();
+        }
+        assert_eq!(state.supply_history.len(), ECON_HISTORY);
+        let (trend, delta) = state.supply_trend();
+        assert_eq!(trend, Trend::Up);
+        assert!(delta > 0.0);
+        assert!(DashboardRenderer::render_econ(&state).contains(Trend::Up.icon()));
+    }
+
+    #[test]
+    fn test_render_plain_has_no_escapes() {
+        let mut state = DashboardState::demo();
+        state.color = true;
+        let full = DashboardRenderer::render_full(&state);
+        let plain = DashboardRenderer::render_plain(&state);
+        assert!(full.contains("\x1b["));
+        assert!(!plain.contains("\x1b["));
+        for number in [format!("{:.1}", state.treasury), state.encrypt_count.to_string(),
+                       format!("tick={}", state.tick), "8914".to_string()] {
+            assert!(full.contains(&number) && plain.contains(&number), "{}", number);
+        }
+        assert_eq!(strip_ansi(&full), plain);
+
+        state.color = false;
+        assert_eq!(DashboardRenderer::render_full(&state), plain);
+    }
+
+    #[test]
+    fn test_ack_removes_alert_from_active_view() {
+        let mut state = DashboardState::demo();
+        let id = state.push_alert(Severity::Critical, "relay down");
+        assert!(DashboardRenderer::render_alerts(&state).contains("relay down"));
+        assert!(state.ack_alert(id));
+        assert!(!state.ack_alert(id));
+        assert!(state.active_alerts().iter().all(|a| a.id != id));
+        assert!(!DashboardRenderer::render_alerts(&state).contains("relay down"));
+    }
 }
@@ -0,0 +1,121 @@
+// =============================================================================
+// FEDERATION CORE — error.rs
+// Единая ошибка приёма
+// =============================================================================
+//
+// Всё, что приходит из сети, — враждебные байты. Точки входа приёма
+// (спутниковый кадр, Noise-сессия, FederationCipher, пакет p2p) отвечают
+// FederationError: плохой пакет логируется и отбрасывается, узел живёт.
+//
+//   Pulse     — Pulse/RadioFrame не разобрался или повтор
+//   Cipher    — AEAD/Noise: тег не сошёлся, нет сессии, обрыв потока
+//   Handshake — сообщение Noise не по протоколу
+//   Vault     — отказ хранилища
+//   Packet    — кадр p2p не десериализовался
+// =============================================================================
+
+use crate::satellite_pulse::PulseDecodeError;
+use crate::vault::VaultError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FederationError {
+    Pulse(PulseDecodeError),
+    Cipher(&'static str),
+    Handshake(&'static str),
+    Vault(VaultError),
+    Packet(String),
+}
+
+impl std::fmt::Display for FederationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FederationError::Pulse(e)     => write!(f, "pulse: {}", e),
+            FederationError::Cipher(e)    => write!(f, "cipher: {}", e),
+            FederationError::Handshake(e) => write!(f, "handshake: {}", e),
+            FederationError::Vault(e)     => write!(f, "vault: {}", e),
+            FederationError::Packet(e)    => write!(f, "packet: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FederationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FederationError::Pulse(e) => Some(e),
+            FederationError::Vault(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PulseDecodeError> for FederationError {
+    fn from(e: PulseDecodeError) -> Self { FederationError::Pulse(e) }
+}
+
+impl From<VaultError> for FederationError {
+    fn from(e: VaultError) -> Self { FederationError::Vault(e) }
+}
+
+impl From<serde_json::Error> for FederationError {
+    fn from(e: serde_json::Error) -> Self { FederationError::Packet(e.to_string()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chacha::{AeadCiphertext, FederationCipher, NONCE_SIZE, TAG_SIZE};
+    use crate::network::deserialize_packet;
+    use crate::noise::{HandshakeState, NoiseHandshaker};
+    use crate::routing::{OnionPacket, OnionPeeler};
+    use crate::satellite_pulse::{FederationPulse, RadioFrame, SatelliteLink, SatelliteProvider};
+
+    fn garbage() -> Vec<Vec<u8>> {
+        let mut rng = 0x6A12_BA6Eu64;
+        let mut noise = |n: usize| (0..n).map(|_| {
+            rng ^= rng << 13; rng ^= rng >> 7; rng ^= rng << 17; rng as u8
+        }).collect::<Vec<u8>>();
+        vec![vec![], vec![0], vec![1; 7], vec![0; 200], noise(33), noise(96), noise(5000)]
+    }
+
+    #[test]
+    fn test_garbage_on_every_receive_path_is_an_error() {
+        let mut link = SatelliteLink::new(SatelliteProvider::Starlink, "rx");
+        let (_, mut session, _) = NoiseHandshaker::perform_xx(1, 2, 3, 4, b"", b"", b"").unwrap();
+        let mut cipher = FederationCipher::new();
+        cipher.establish_session("peer", b"secret", true);
+
+        for bytes in garbage() {
+            assert!(FederationPulse::decode(&bytes).is_err());
+            for compressed in [false, true] {
+                let frame = RadioFrame {
+                    frame_id: 1, provider: SatelliteProvider::Starlink,
+                    checksum: bytes.iter().fold(0u32, |a, &b| a.wrapping_add(b as u32)),
+                    payload: bytes.clone(), hop_count: 0, priority: 0,
+                    compression_ratio: 1.0, original_size: bytes.len(), compressed,
+                };
+                assert!(matches!(link.receive(&frame), Err(FederationError::Pulse(_))));
+            }
+
+            assert!(matches!(session.recv(&bytes), Err(FederationError::Cipher(_))));
+            assert!(HandshakeState::new_initiator(5, 6).read_message_2(&bytes).is_err());
+            assert!(HandshakeState::new_responder(7, 8).read_message_3(&bytes).is_err());
+
+            let tag_len = bytes.len().min(TAG_SIZE);
+            let mut tag = [0u8; TAG_SIZE];
+            tag[..tag_len].copy_from_slice(&bytes[..tag_len]);
+            let ct = AeadCiphertext { nonce: [0u8; NONCE_SIZE], ciphertext: bytes.clone(),
+                tag, aad_len: 0 };
+            assert!(cipher.decrypt_pulse("peer", &ct, &[]).is_err());
+            assert!(cipher.decrypt_stream("peer", &[ct]).is_err());
+
+            assert!(matches!(deserialize_packet(&bytes), Err(FederationError::Packet(_))));
+            if let Ok(onion) = OnionPacket::from_bytes(&bytes) {
+                assert!(OnionPeeler::peel(&onion, &[9u8; 32]).is_err());
+            }
+        }
+        // Мусор не сбил счётчик nonce: сессия принимает честный трафик
+        let (mut alice, mut bob, _) = NoiseHandshaker::perform_xx(1, 2, 3, 4, b"", b"", b"").unwrap();
+        assert!(bob.recv(&[0u8; 40]).is_err());
+        assert_eq!(bob.recv(&alice.send(b"ping")).unwrap(), b"ping");
+    }
+}
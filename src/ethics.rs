@@ -9,7 +9,7 @@
 // =============================================================================
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 pub const VIOLATION_FREEZE_THRESHOLD: f64 = 0.8;
 pub const PRIVACY_PENALTY_WEIGHT: f64 = 0.4;
@@ -20,6 +20,11 @@ pub const ETHICS_VERSION: &str = "1.1.0-federation";
 pub const MAX_RESPONSE_MULTIPLIER: f64 = 3.0;  // Не более 3x от силы атаки
 pub const MIN_AGGRESSION_THRESHOLD: f64 = 0.3; // Порог для ответного удара
 
+// Имена модулей для KillSwitch::guard
+pub const MODULE_AI_ROUTER: &str = "ai_router";
+pub const MODULE_ORACLE: &str    = "oracle";
+pub const MODULE_MINT: &str      = "mint";
+
 // -----------------------------------------------------------------------------
 // ProportionalityVerdict — оценка пропорциональности ответа
 // -----------------------------------------------------------------------------
@@ -326,17 +331,8 @@ impl EthicsEvaluator {
                 if auto_penalty > 0.0 { penalties.insert("AUTONOMY".into(), auto_penalty); }
             }
 
-            EthicsAction::AikiResponse {
-                censor_aggression, response_intensity, is_first_strike,
-                has_evidence, target_is_censor, tactic
-            } => {
-                let rule = AikiEthicsRule {
-                    censor_aggression: *censor_aggression,
-                    response_intensity: *response_intensity,
-                    is_first_strike: *is_first_strike,
-                    has_evidence: *has_evidence,
-                    target_is_censor: *target_is_censor,
-                };
+            EthicsAction::AikiResponse { tactic, .. } => {
+                let rule = action.aiki_rule().unwrap();
                 match rule.evaluate() {
                     ProportionalityVerdict::Unprovoked { reason } => {
                         violations.push(ViolationType::AutonomyOverreach {
@@ -429,6 +425,25 @@ pub enum EthicsAction {
     },
 }
 
+impl EthicsAction {
+    /// Правило пропорциональности для ответного удара
+    pub fn aiki_rule(&self) -> Option<AikiEthicsRule> {
+        match self {
+            EthicsAction::AikiResponse {
+                censor_aggression, response_intensity, is_first_strike,
+                has_evidence, target_is_censor, ..
+            } => Some(AikiEthicsRule {
+                censor_aggression: *censor_aggression,
+                response_intensity: *response_intensity,
+                is_first_strike: *is_first_strike,
+                has_evidence: *has_evidence,
+                target_is_censor: *target_is_censor,
+            }),
+            _ => None,
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // KillSwitch — экстренная заморозка
 // -----------------------------------------------------------------------------
@@ -540,6 +555,34 @@ impl KillSwitch {
 
 impl Default for KillSwitch { fn default() -> Self { Self::new() } }
 
+/// Модуль заморожен — действие не выполнено
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frozen {
+    pub module: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Frozen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "🧊 модуль {} заморожен: {}", self.module, self.reason)
+    }
+}
+
+impl std::error::Error for Frozen {}
+
+impl KillSwitch {
+    /// Проверка перед действием модуля: Err, пока модуль заморожен
+    pub fn guard(&self, module: &str) -> Result<(), Frozen> {
+        if !self.is_module_frozen(module) { return Ok(()); }
+        let reason = match &self.state {
+            KillSwitchState::FullFreeze { reason, triggered_by } =>
+                format!("полная заморозка ({}): {}", triggered_by, reason),
+            _ => "частичная заморозка".into(),
+        };
+        Err(Frozen { module: module.to_string(), reason })
+    }
+}
+
 // -----------------------------------------------------------------------------
 // TransparencyAudit — публичный лог решений ИИ
 // -----------------------------------------------------------------------------
@@ -574,7 +617,12 @@ impl TransparencyAudit {
         ai_reasoning: &str) -> String {
         use std::time::{SystemTime, UNIX_EPOCH};
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+        self.record_at(action_type, verdict, ai_reasoning, now)
+    }
 
+    /// То же что `record`, но с явным временем (мс) — для тестов и импорта
+    pub fn record_at(&mut self, action_type: &str, verdict: EthicsVerdict,
+        ai_reasoning: &str, now: i64) -> String {
         let human_readable = format!(
             "Действие: {}. Разрешено: {}. Оценка нарушений: {:.3}. {}",
             action_type, verdict.allowed, verdict.violation_score, verdict.reason
@@ -616,6 +664,19 @@ impl TransparencyAudit {
         self.log.iter().rev().take(n).collect()
     }
 
+    /// Выборка для аудитора в порядке записи. category — категория
+    /// нарушения (PRIVACY, DAO, ...) или action_type записи; время —
+    /// полуинтервал [since, until). None — без фильтра
+    pub fn query(&self, category: Option<&str>, since: Option<i64>,
+                 until: Option<i64>) -> Vec<&AuditEntry> {
+        self.log.iter().filter(|e| {
+            since.is_none_or(|t| e.timestamp >= t)
+                && until.is_none_or(|t| e.timestamp < t)
+                && category.is_none_or(|c| e.action_type == c
+                    || e.verdict.violations.iter().any(|v| v.category() == c))
+        }).collect()
+    }
+
     pub fn stats(&self) -> AuditStats {
         let blocked = self.log.iter().filter(|e| !e.verdict.allowed).count();
         let avg_score = if self.log.is_empty() { 0.0 } else {
@@ -692,6 +753,27 @@ impl EthicsLayer {
         verdict
     }
 
+    pub fn guard(&self, module: &str) -> Result<(), Frozen> {
+        self.kill_switch.guard(module)
+    }
+
+    /// Интенсивность, с которой тактика реально исполняется: не выше
+    /// censor_aggression × MAX_RESPONSE_MULTIPLIER. Неспровоцированный
+    /// ответ не исполняется вовсе. Нечисловые вход или граница (NaN/inf от
+    /// битой телеметрии) тоже дают 0 — clamp на них паникует
+    pub fn clamp_response(tactic_intensity: f64, rule: &AikiEthicsRule) -> f64 {
+        if matches!(rule.evaluate(), ProportionalityVerdict::Unprovoked { .. }) {
+            return 0.0;
+        }
+        // f64::min глотает NaN, поэтому проверяем и саму агрессию
+        let bound = rule.allowed_response_intensity();
+        if !rule.censor_aggression.is_finite() || !bound.is_finite()
+            || !tactic_intensity.is_finite() {
+            return 0.0;
+        }
+        tactic_intensity.clamp(0.0, bound.max(0.0))
+    }
+
     pub fn status(&self) -> String {
         format!(
             "EthicsLayer v{} | KillSwitch: {:?} | SystemScore: {:.4} | Audit: {} записей",
@@ -718,8 +800,11 @@ impl Default for EthicsLayer { fn default() -> Self { Self::new() } }
 // =============================================================================
 
 pub const SENSOR_CONSENT_REQUIRED: bool  = true;
+pub const DEFAULT_CONSENT_RISK: u8       = 7;     // риск, с которого нужно согласие
 pub const MAX_AUDIO_RETENTION_SECS: u64  = 30;    // аудио хранится ≤30 сек
 pub const MAX_VIDEO_RETENTION_SECS: u64  = 5;     // видео ≤5 сек (только обнаружение)
+pub const MAX_LIGHT_RETENTION_SECS: u64  = 86_400;   // освещённость — суточный цикл
+pub const MAX_AIR_RETENTION_SECS: u64    = 604_800;  // качество воздуха — недельный тренд
 pub const LOCATION_BLUR_METERS: f64      = 50.0;  // координаты размыты на 50м
 pub const BIOMETRIC_BAN: bool            = true;  // биометрия запрещена
 
@@ -727,7 +812,7 @@ pub const BIOMETRIC_BAN: bool            = true;  // биометрия запр
 // SensorType — виды сенсоров дроида
 // -----------------------------------------------------------------------------
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SensorType {
     Microphone,   // звук — максимальный риск
     Camera,       // видео — высокий риск
@@ -736,6 +821,8 @@ pub enum SensorType {
     Motion,       // движение — низкий риск
     Gps,          // координаты — высокий риск
     Network,      // трафик — минимальный риск
+    AmbientLight, // освещённость — минимальный риск
+    AirQuality,   // CO₂/PM2.5 — низкий риск
 }
 
 impl SensorType {
@@ -748,6 +835,8 @@ impl SensorType {
             SensorType::Motion      => 3,
             SensorType::Temperature => 1,
             SensorType::Network     => 2,
+            SensorType::AmbientLight => 1,
+            SensorType::AirQuality  => 2,   // по CO₂ видно, что дома кто-то есть
         }
     }
     pub fn name(&self) -> &str {
@@ -759,11 +848,10 @@ impl SensorType {
             SensorType::Motion      => "👁️  Motion",
             SensorType::Gps         => "📍 GPS",
             SensorType::Network     => "🌐 Network",
+            SensorType::AmbientLight => "💡 AmbientLight",
+            SensorType::AirQuality  => "🌫️  AirQuality",
         }
     }
-    pub fn requires_explicit_consent(&self) -> bool {
-        self.privacy_risk() >= 7
-    }
 }
 
 // -----------------------------------------------------------------------------
@@ -841,16 +929,41 @@ impl DeviceRightsVerdict {
 // DeviceRightsCodex — главный судья
 // -----------------------------------------------------------------------------
 
+/// Политика сенсоров развёртывания. Serde — чтобы строгий регион
+/// подгружал её из конфига, а не пересобирал узел
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorPolicy {
+    pub consent_risk_threshold: u8,          // риск ≥ порога → нужно согласие
+    pub banned_sensors: HashSet<SensorType>, // запрещены при любой цели
+}
+
+impl Default for SensorPolicy {
+    fn default() -> Self {
+        SensorPolicy { consent_risk_threshold: DEFAULT_CONSENT_RISK, banned_sensors: HashSet::new() }
+    }
+}
+
+impl SensorPolicy {
+    pub fn requires_consent(&self, sensor: &SensorType) -> bool {
+        sensor.privacy_risk() >= self.consent_risk_threshold
+    }
+}
+
 pub struct DeviceRightsCodex {
     pub violations: Vec<(String, String)>,  // (droid_id, reason)
     pub audited: u64,
     pub permitted: u64,
     pub denied: u64,
+    pub policy: SensorPolicy,
 }
 
 impl DeviceRightsCodex {
     pub fn new() -> Self {
-        DeviceRightsCodex { violations:vec![], audited:0, permitted:0, denied:0 }
+        Self::with_policy(SensorPolicy::default())
+    }
+
+    pub fn with_policy(policy: SensorPolicy) -> Self {
+        DeviceRightsCodex { violations:vec![], audited:0, permitted:0, denied:0, policy }
     }
 
     pub fn evaluate(&mut self, req: &SensorUseRequest) -> DeviceRightsVerdict {
@@ -876,8 +989,17 @@ impl DeviceRightsCodex {
                 reason: "сбор данных для продажи нарушает Кодекс".into() };
         }
 
+        // Запрещённые политикой сенсоры — даже с согласием хозяина
+        if self.policy.banned_sensors.contains(&req.sensor) {
+            self.denied += 1;
+            self.violations.push((req.droid_id.clone(),
+                format!("{} запрещён политикой", req.sensor.name())));
+            return DeviceRightsVerdict::Denied {
+                reason: format!("сенсор {} запрещён политикой региона", req.sensor.name()) };
+        }
+
         // Согласие хозяина для высокорискованных сенсоров
-        if SENSOR_CONSENT_REQUIRED && req.sensor.requires_explicit_consent()
+        if SENSOR_CONSENT_REQUIRED && self.policy.requires_consent(&req.sensor)
             && req.purpose != SensorPurpose::OwnerConsented {
             return DeviceRightsVerdict::RequiresOwnerConsent {
                 sensor: req.sensor.name().to_string() };
@@ -888,6 +1010,8 @@ impl DeviceRightsCodex {
             SensorType::Microphone => MAX_AUDIO_RETENTION_SECS,
             SensorType::Camera     => MAX_VIDEO_RETENTION_SECS,
             SensorType::Gps        => 60,
+            SensorType::AmbientLight => MAX_LIGHT_RETENTION_SECS,
+            SensorType::AirQuality => MAX_AIR_RETENTION_SECS,
             _                      => 3600,
         };
 
@@ -910,6 +1034,28 @@ impl DeviceRightsCodex {
         }
     }
 
+    /// Аудит пачки запросов (например всей квартиры) с итоговой сводкой
+    pub fn evaluate_batch(&mut self, requests: &[SensorUseRequest]) -> BatchVerdict {
+        let mut batch = BatchVerdict::default();
+        for req in requests {
+            let verdict = self.evaluate(req);
+            match &verdict {
+                DeviceRightsVerdict::Permitted
+                | DeviceRightsVerdict::PermittedWithLimits { .. } => {
+                    batch.permitted += 1;
+                    batch.highest_risk = batch.highest_risk.max(req.sensor.privacy_risk());
+                }
+                DeviceRightsVerdict::Denied { .. } => {
+                    batch.denied += 1;
+                    batch.violators.insert(req.requester.clone());
+                }
+                DeviceRightsVerdict::RequiresOwnerConsent { .. } => batch.consent_required += 1,
+            }
+            batch.verdicts.push(verdict);
+        }
+        batch
+    }
+
     pub fn stats(&self) -> String {
         format!("audited={}  permitted={}  denied={}  violations={}",
             self.audited, self.permitted, self.denied, self.violations.len())
@@ -917,3 +1063,214 @@ impl DeviceRightsCodex {
 }
 
 impl Default for DeviceRightsCodex { fn default() -> Self { Self::new() } }
+
+/// Запросы демо Device Rights: легитимные, с согласием хозяина и нарушения.
+/// Общий набор для демо и тестов пакетного аудита
+pub fn device_rights_requests() -> Vec<SensorUseRequest> {
+    vec![
+        // Легитимные запросы
+        SensorUseRequest {
+            requester: "mesh_router".into(), droid_id: "vacuum_roborock".into(),
+            sensor: SensorType::Lidar, purpose: SensorPurpose::ObstacleMapping,
+            retention_secs: 300, share_with: vec![] },
+        SensorUseRequest {
+            requester: "anomaly_detector".into(), droid_id: "fridge_samsung".into(),
+            sensor: SensorType::Temperature, purpose: SensorPurpose::AnomalyDetection,
+            retention_secs: 3600, share_with: vec!["nexus-core-01".into()] },
+        SensorUseRequest {
+            requester: "mesh_router".into(), droid_id: "router_openwrt".into(),
+            sensor: SensorType::Network, purpose: SensorPurpose::MeshRouting,
+            retention_secs: 60, share_with: vec![] },
+        SensorUseRequest {
+            requester: "anomaly_detector".into(), droid_id: "purifier_xiaomi".into(),
+            sensor: SensorType::AirQuality, purpose: SensorPurpose::AnomalyDetection,
+            retention_secs: 86400, share_with: vec!["nexus-core-01".into()] },
+        // GPS с размытием
+        SensorUseRequest {
+            requester: "mesh_router".into(), droid_id: "phone_carol".into(),
+            sensor: SensorType::Gps, purpose: SensorPurpose::MeshRouting,
+            retention_secs: 30, share_with: vec![] },
+        // Требует согласия хозяина
+        SensorUseRequest {
+            requester: "analytics".into(), droid_id: "speaker_yandex".into(),
+            sensor: SensorType::Microphone, purpose: SensorPurpose::AnomalyDetection,
+            retention_secs: 10, share_with: vec![] },
+        // Хозяин явно разрешил камеру
+        SensorUseRequest {
+            requester: "security".into(), droid_id: "vacuum_roborock".into(),
+            sensor: SensorType::Camera, purpose: SensorPurpose::OwnerConsented,
+            retention_secs: 5, share_with: vec![] },
+        // НАРУШЕНИЯ
+        SensorUseRequest {
+            requester: "evil_corp".into(), droid_id: "speaker_yandex".into(),
+            sensor: SensorType::Microphone, purpose: SensorPurpose::Surveillance,
+            retention_secs: 86400, share_with: vec!["evil_corp.com".into()] },
+        SensorUseRequest {
+            requester: "data_broker".into(), droid_id: "vacuum_roborock".into(),
+            sensor: SensorType::Camera, purpose: SensorPurpose::Biometrics,
+            retention_secs: 3600, share_with: vec!["broker.io".into()] },
+        SensorUseRequest {
+            requester: "harvester".into(), droid_id: "fridge_samsung".into(),
+            sensor: SensorType::Motion, purpose: SensorPurpose::DataHarvesting,
+            retention_secs: 7200, share_with: vec!["market.io".into()] },
+        SensorUseRequest {
+            requester: "logger".into(), droid_id: "phone_carol".into(),
+            sensor: SensorType::Microphone, purpose: SensorPurpose::MeshRouting,
+            retention_secs: 9999, share_with: vec![] },
+    ]
+}
+
+/// Итог evaluate_batch: вердикты в порядке запросов и сводка
+#[derive(Debug, Clone, Default)]
+pub struct BatchVerdict {
+    pub verdicts: Vec<DeviceRightsVerdict>,
+    pub permitted: usize,
+    pub denied: usize,
+    pub consent_required: usize,
+    pub violators: BTreeSet<String>,   // requester'ы с отказом
+    pub highest_risk: u8,              // максимальный риск среди разрешённых
+}
+
+impl std::fmt::Display for BatchVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "✅ {}  🚫 {}  🔐 {}  макс. риск разрешённых: {}/10",
+            self.permitted, self.denied, self.consent_required, self.highest_risk)?;
+        if !self.violators.is_empty() {
+            let names: Vec<&str> = self.violators.iter().map(|v| v.as_str()).collect();
+            write!(f, "  нарушители: {}", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(censor_aggression: f64, response_intensity: f64) -> AikiEthicsRule {
+        AikiEthicsRule {
+            censor_aggression, response_intensity,
+            is_first_strike: false, has_evidence: true, target_is_censor: true,
+        }
+    }
+
+    #[test]
+    fn test_clamp_response_caps_at_aggression_multiple() {
+        let weak_censor = rule(0.3, 0.95);
+        assert!(matches!(weak_censor.evaluate(), ProportionalityVerdict::Disproportionate { .. }));
+        let executed = EthicsLayer::clamp_response(0.95, &weak_censor);
+        assert!((executed - 0.9).abs() < 1e-9);
+        assert!(executed <= weak_censor.censor_aggression * MAX_RESPONSE_MULTIPLIER);
+
+        // Пропорциональный запрос исполняется как есть
+        assert_eq!(EthicsLayer::clamp_response(0.6, &rule(0.3, 0.6)), 0.6);
+        // Первый удар — не исполняется
+        let first = AikiEthicsRule { is_first_strike: true, ..rule(0.9, 0.5) };
+        assert_eq!(EthicsLayer::clamp_response(0.5, &first), 0.0);
+    }
+
+    #[test]
+    fn test_clamp_response_non_finite_is_zero() {
+        assert_eq!(EthicsLayer::clamp_response(0.5, &rule(f64::NAN, 0.5)), 0.0);
+        assert_eq!(EthicsLayer::clamp_response(f64::NAN, &rule(0.3, 0.5)), 0.0);
+        assert_eq!(EthicsLayer::clamp_response(0.5, &rule(-0.2, 0.5)), 0.0);
+    }
+
+    fn seeded_audit() -> TransparencyAudit {
+        let verdict = |violations: Vec<ViolationType>| EthicsVerdict {
+            allowed: violations.is_empty(), violations, ..EthicsVerdict::clean("a")
+        };
+        let privacy = || ViolationType::PrivacyLeak { description: "ip".into(), severity: 0.5 };
+        let dao = || ViolationType::DaoRuleViolation { rule: "r".into(), severity: 0.4 };
+        let mut audit = TransparencyAudit::new();
+        audit.record_at("route", verdict(vec![]), "", 1_000);
+        audit.record_at("oracle", verdict(vec![privacy()]), "", 2_000);
+        audit.record_at("route", verdict(vec![dao()]), "", 3_000);
+        audit.record_at("oracle", verdict(vec![privacy(), dao()]), "", 4_000);
+        audit.record_at("aiki", verdict(vec![]), "", 5_000);
+        audit
+    }
+
+    fn stamps(entries: Vec<&AuditEntry>) -> Vec<i64> {
+        entries.iter().map(|e| e.timestamp).collect()
+    }
+
+    #[test]
+    fn test_audit_query_by_category_and_time() {
+        let audit = seeded_audit();
+        assert_eq!(stamps(audit.query(None, None, None)), vec![1_000, 2_000, 3_000, 4_000, 5_000]);
+        // По категории нарушения и по типу действия
+        assert_eq!(stamps(audit.query(Some("PRIVACY"), None, None)), vec![2_000, 4_000]);
+        assert_eq!(stamps(audit.query(Some("route"), None, None)), vec![1_000, 3_000]);
+        assert!(audit.query(Some("CENSORSHIP"), None, None).is_empty());
+        // По времени: since включительно, until — нет
+        assert_eq!(stamps(audit.query(None, Some(2_000), Some(4_000))), vec![2_000, 3_000]);
+        assert_eq!(stamps(audit.query(None, Some(4_000), None)), vec![4_000, 5_000]);
+        assert_eq!(stamps(audit.query(None, None, Some(1_000))), Vec::<i64>::new());
+        // Вместе
+        assert_eq!(stamps(audit.query(Some("DAO"), Some(3_500), None)), vec![4_000]);
+        assert_eq!(stamps(audit.query(Some("PRIVACY"), Some(1_000), Some(3_000))), vec![2_000]);
+    }
+
+    #[test]
+    fn test_air_quality_anomaly_needs_no_consent() {
+        let req = |sensor, retention_secs| SensorUseRequest {
+            requester: "anomaly_detector".into(), droid_id: "purifier".into(),
+            sensor, purpose: SensorPurpose::AnomalyDetection,
+            retention_secs, share_with: vec![],
+        };
+        let mut codex = DeviceRightsCodex::new();
+        assert!(SensorType::AirQuality.privacy_risk() <= 2);
+        assert!(SensorType::AmbientLight.privacy_risk() <= 2);
+        // Суточная история воздуха — в пределах лимита, без согласия
+        assert!(matches!(codex.evaluate(&req(SensorType::AirQuality, 86_400)),
+            DeviceRightsVerdict::PermittedWithLimits { max_retention_secs: MAX_AIR_RETENTION_SECS, .. }));
+        assert!(matches!(codex.evaluate(&req(SensorType::AmbientLight, MAX_LIGHT_RETENTION_SECS + 1)),
+            DeviceRightsVerdict::Denied { .. }));
+        assert!(matches!(codex.evaluate(&req(SensorType::Microphone, 10)),
+            DeviceRightsVerdict::RequiresOwnerConsent { .. }));
+    }
+
+    #[test]
+    fn test_evaluate_batch_summary_matches_verdicts() {
+        let requests = device_rights_requests();
+
+        let single: Vec<DeviceRightsVerdict> = {
+            let mut codex = DeviceRightsCodex::new();
+            requests.iter().map(|r| codex.evaluate(r)).collect()
+        };
+        let batch = DeviceRightsCodex::new().evaluate_batch(&requests);
+        assert_eq!(batch.verdicts, single);
+        let count = |f: fn(&DeviceRightsVerdict) -> bool| single.iter().filter(|v| f(v)).count();
+        assert_eq!(batch.denied, count(|v| matches!(v, DeviceRightsVerdict::Denied { .. })));
+        assert_eq!(batch.consent_required,
+            count(|v| matches!(v, DeviceRightsVerdict::RequiresOwnerConsent { .. })));
+        assert_eq!(batch.permitted + batch.denied + batch.consent_required, requests.len());
+        assert_eq!((batch.permitted, batch.denied, batch.consent_required), (5, 3, 3));
+        let violators: Vec<&str> = batch.violators.iter().map(|v| v.as_str()).collect();
+        assert_eq!(violators, vec!["data_broker", "evil_corp", "harvester"]);
+        assert_eq!(batch.highest_risk, SensorType::Camera.privacy_risk());
+    }
+
+    #[test]
+    fn test_strict_policy_requires_consent_for_lidar() {
+        let lidar = SensorUseRequest {
+            requester: "mesh_router".into(), droid_id: "vacuum".into(),
+            sensor: SensorType::Lidar, purpose: SensorPurpose::ObstacleMapping,
+            retention_secs: 300, share_with: vec![],
+        };
+        let mut default = DeviceRightsCodex::new();
+        assert!(matches!(default.evaluate(&lidar), DeviceRightsVerdict::PermittedWithLimits { .. }));
+
+        let mut strict = DeviceRightsCodex::with_policy(SensorPolicy {
+            consent_risk_threshold: 5, ..SensorPolicy::default()
+        });
+        assert!(matches!(strict.evaluate(&lidar), DeviceRightsVerdict::RequiresOwnerConsent { .. }));
+        let consented = SensorUseRequest { purpose: SensorPurpose::OwnerConsented, ..lidar.clone() };
+        assert!(matches!(strict.evaluate(&consented), DeviceRightsVerdict::PermittedWithLimits { .. }));
+
+        // Бан сильнее согласия хозяина
+        strict.policy.banned_sensors.insert(SensorType::Lidar);
+        assert!(matches!(strict.evaluate(&consented), DeviceRightsVerdict::Denied { .. }));
+    }
+}
@@ -13,10 +13,12 @@
 
 use crate::neural_node::{NeuralInput, NeuralTarget, NeuralState,
     INPUT_SIZE, HIDDEN_SIZE};
+use crate::rng::{FedRng, XorShift64};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub const MIN_PARTICIPANTS: usize = 3;
+pub const MAX_DROPOUT_FRACTION: f64 = 0.5; // больше молчащих — раунд не засчитан
 
 // -----------------------------------------------------------------------------
 // TacticReport — узел сообщает что сработало, без передачи данных
@@ -51,7 +53,7 @@ impl TacticReport {
 // GlobalDefenseModel — коллективная тактическая память
 // -----------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GlobalDefenseModel {
     // censor_type → (tactic → avg_success_rate)
     pub tactic_scores: HashMap<String, HashMap<String, f64>>,
@@ -152,6 +154,21 @@ impl ModelWeights {
         }
     }
 
+    /// Стабильный 8-байтовый отпечаток весов (FNV-1a по битам f64).
+    /// Метаданные раунда и узла не входят — только сама модель
+    pub fn digest(&self) -> [u8; 8] {
+        let mut h: u64 = 0xcbf29ce484222325;
+        for part in [&self.l1_weights, &self.l1_biases, &self.l2_weights, &self.l2_biases] {
+            h ^= part.len() as u64; h = h.wrapping_mul(0x100000001b3);
+            for &w in part.iter() {
+                for b in w.to_bits().to_le_bytes() {
+                    h ^= b as u64; h = h.wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+        h.to_le_bytes()
+    }
+
     /// Загрузить веса обратно в NeuralState
     pub fn apply_to_state(&self, state: &mut NeuralState) {
         let chunk = HIDDEN_SIZE;
@@ -219,8 +236,30 @@ impl LocalDataPoint {
             label: format!("attack_{}", attack_type),
         }
     }
+
+    pub fn to_input(&self) -> NeuralInput {
+        NeuralInput {
+            latency:      self.input[0],
+            bandwidth:    self.input[1],
+            reliability:  self.input[2],
+            trust:        self.input[3],
+            ethics_score: self.input[4],
+        }
+    }
+
+    pub fn to_target(&self) -> NeuralTarget {
+        NeuralTarget {
+            route_weight: self.target[0],
+            congestion:   self.target[1],
+            quality:      self.target[2],
+            decoy:        self.target.get(3).cloned().unwrap_or(0.1),
+            strike:       self.target.get(4).cloned().unwrap_or(0.2),
+            success:      self.target[0] > 0.5,
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct LocalTrainer {
     pub node_id: String,
     pub region: String,
@@ -228,6 +267,7 @@ pub struct LocalTrainer {
     pub state: NeuralState,
     pub epochs_trained: u64,
     pub local_loss_history: Vec<f64>,
+    pub participate_probability: f64, // шанс ответить в раунде: телефон, ghost — < 1
 }
 
 impl LocalTrainer {
@@ -239,6 +279,7 @@ impl LocalTrainer {
             state: NeuralState::new(node_id),
             epochs_trained: 0,
             local_loss_history: vec![],
+            participate_probability: 1.0,
         }
     }
 
@@ -257,21 +298,8 @@ impl LocalTrainer {
             let mut epoch_loss = 0.0;
 
             for point in &self.local_data {
-                let input = NeuralInput {
-                    latency:      point.input[0],
-                    bandwidth:    point.input[1],
-                    reliability:  point.input[2],
-                    trust:        point.input[3],
-                    ethics_score: point.input[4],
-                };
-                let target = NeuralTarget {
-                    route_weight: point.target[0],
-                    congestion:   point.target[1],
-                    quality:      point.target[2],
-                    decoy:        point.target.get(3).cloned().unwrap_or(0.1),
-                    strike:       point.target.get(4).cloned().unwrap_or(0.2),
-                    success:      point.target[0] > 0.5,
-                };
+                let input = point.to_input();
+                let target = point.to_target();
 
                 let out = self.state.forward(&input);
                 let loss: f64 = out.softmax_probs.iter()
@@ -295,6 +323,26 @@ impl LocalTrainer {
         (avg_loss, accuracy)
     }
 
+    /// Средний loss текущей модели на наборе точек (без обучения)
+    pub fn evaluate(&self, points: &[LocalDataPoint]) -> f64 {
+        if points.is_empty() { return 0.0; }
+        let total: f64 = points.iter().map(|p| {
+            let out = self.state.forward(&p.to_input());
+            out.softmax_probs.iter().zip(p.target.iter())
+                .map(|(o, t)| (o - t).powi(2)).sum::<f64>()
+        }).sum();
+        total / points.len() as f64
+    }
+
+    /// Отложить хвост локальных данных под валидацию.
+    /// Хотя бы одна точка остаётся для обучения.
+    pub fn split_validation(&mut self, val_fraction: f64) -> Vec<LocalDataPoint> {
+        let n = self.local_data.len();
+        if n < 2 { return vec![]; }
+        let held = ((n as f64 * val_fraction.clamp(0.0, 1.0)).ceil() as usize).min(n - 1);
+        self.local_data.split_off(n - held)
+    }
+
     /// Экспортируем ТОЛЬКО веса — данные остаются на узле
     pub fn export_weights(&self, round: u32, loss: f64, accuracy: f64) -> ModelWeights {
         ModelWeights::from_neural_state(
@@ -312,6 +360,7 @@ impl LocalTrainer {
 // FedAvgAggregator — агрегация весов (FedAvg алгоритм)
 // -----------------------------------------------------------------------------
 
+#[derive(Clone)]
 pub struct FedAvgAggregator {
     pub round: u32,
     pub collected: Vec<ModelWeights>,
@@ -327,6 +376,21 @@ pub struct AggregationResult {
     pub avg_local_accuracy: f64,
     pub weight_divergence: f64,
     pub global_weights: ModelWeights,
+    #[serde(default)]
+    pub per_node: Vec<(String, f64, f64)>,  // (node_id, loss, accuracy) по node_id
+    #[serde(default)]
+    pub dropped: usize,                     // участников, не ответивших в раунде
+}
+
+impl AggregationResult {
+    /// Участник с наибольшим локальным loss — он и тянет среднее вверх.
+    /// NaN считается худшим, при равенстве — меньший node_id
+    pub fn worst_node(&self) -> Option<&(String, f64, f64)> {
+        self.per_node.iter().max_by(|a, b| {
+            let loss = |x: f64| if x.is_nan() { f64::INFINITY } else { x };
+            loss(a.1).total_cmp(&loss(b.1)).then_with(|| b.0.cmp(&a.0))
+        })
+    }
 }
 
 impl FedAvgAggregator {
@@ -365,7 +429,7 @@ impl FedAvgAggregator {
         let divergence = self.collected.iter().map(|w| {
             w.l1_weights.iter().zip(agg_l1w.iter())
                 .map(|(wi, wg)| (wi - wg).powi(2)).sum::<f64>()
-                / param_count as f64
+                / param_count.max(1) as f64
         }).sum::<f64>() / self.collected.len() as f64;
 
         let avg_loss = self.collected.iter().map(|w| w.local_loss).sum::<f64>()
@@ -387,6 +451,10 @@ impl FedAvgAggregator {
             data_hash: format!("global_r{}", self.round),
         };
 
+        let mut per_node: Vec<(String, f64, f64)> = self.collected.iter()
+            .map(|w| (w.node_id.clone(), w.local_loss, w.local_accuracy)).collect();
+        per_node.sort_by(|a, b| a.0.cmp(&b.0));
+
         let result = AggregationResult {
             round: self.round,
             participants: self.collected.len(),
@@ -394,6 +462,8 @@ impl FedAvgAggregator {
             avg_local_accuracy: avg_acc,
             weight_divergence: divergence,
             global_weights: global,
+            per_node,
+            dropped: 0,
         };
 
         self.aggregation_history.push(result.clone());
@@ -413,6 +483,7 @@ impl Default for FedAvgAggregator { fn default() -> Self { Self::new() } }
 // FederatedNetwork — полная федеративная сеть
 // -----------------------------------------------------------------------------
 
+#[derive(Clone)]
 pub struct FederatedNetwork {
     pub trainers: HashMap<String, LocalTrainer>,
     pub aggregator: FedAvgAggregator,
@@ -420,6 +491,8 @@ pub struct FederatedNetwork {
     pub convergence_history: Vec<f64>,
     pub tactic_reports: Vec<TacticReport>,
     pub defense_model: GlobalDefenseModel,
+    pub opted_out: HashSet<String>,   // не участвуют в раундах, данные остаются
+    pub max_dropout_fraction: f64,    // доля отвалившихся, при которой раунд ещё идёт
 }
 
 impl FederatedNetwork {
@@ -431,6 +504,8 @@ impl FederatedNetwork {
             convergence_history: vec![],
             tactic_reports: vec![],
             defense_model: GlobalDefenseModel::new(),
+            opted_out: HashSet::new(),
+            max_dropout_fraction: MAX_DROPOUT_FRACTION,
         }
     }
 
@@ -439,12 +514,70 @@ impl FederatedNetwork {
             LocalTrainer::new(node_id, region));
     }
 
-    /// Один раунд федеративного обучения
+    /// Узел выходит из раундов (до opt_in); его модель не обновляется
+    pub fn opt_out(&mut self, node_id: &str) {
+        self.opted_out.insert(node_id.to_string());
+    }
+
+    pub fn opt_in(&mut self, node_id: &str) {
+        self.opted_out.remove(node_id);
+    }
+
+    /// Участники раунда по плану: узлы с данными, не вышедшие через opt_out
+    fn eligible(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.trainers.values()
+            .filter(|t| !t.local_data.is_empty() && !self.opted_out.contains(&t.node_id))
+            .map(|t| t.node_id.clone()).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Один раунд федеративного обучения.
+    /// Каждый участник отвечает с вероятностью participate_probability.
+    /// Если ответивших меньше MIN_PARTICIPANTS (в том числе ноль) или
+    /// молчащих больше max_dropout_fraction — None без паники:
+    /// раунд не засчитан, global_round и модели узлов не меняются,
+    /// собранные веса раунда отбрасываются
     pub fn run_round(&mut self) -> Option<AggregationResult> {
-        // 1. Каждый узел обучается локально
+        self.run_round_with(&mut XorShift64::from_time())
+    }
+
+    /// run_round с внешним генератором — отвалы воспроизводимы
+    pub fn run_round_with(&mut self, rng: &mut dyn FedRng) -> Option<AggregationResult> {
+        let responders: HashSet<String> = self.eligible().into_iter().filter(|id| {
+            let p = self.trainers[id].participate_probability;
+            p >= 1.0 || rng.next_f64() < p
+        }).collect();
+        self.run_round_with_responders(&responders)
+    }
+
+    /// Раунд, в котором ответили только responders. Остальные участники —
+    /// отвалившиеся: их веса не агрегируются, глобальную модель они
+    /// получат вместе со всеми
+    pub fn run_round_with_responders(&mut self, responders: &HashSet<String>)
+        -> Option<AggregationResult> {
+        let eligible = self.eligible();
+        let dropped = eligible.iter().filter(|id| !responders.contains(*id)).count();
+        if dropped as f64 > eligible.len() as f64 * self.max_dropout_fraction {
+            log::warn!("federated: раунд {} — отвалилось {} из {}",
+                self.global_round, dropped, eligible.len());
+            self.aggregator.collected.clear();
+            return None;
+        }
+        // Кворум — до локального обучения: несостоявшийся раунд не трогает модели
+        let answered: Vec<&String> = eligible.iter()
+            .filter(|id| responders.contains(*id) && self.trainers.contains_key(*id)).collect();
+        if answered.len() < MIN_PARTICIPANTS {
+            log::warn!("federated: раунд {} — ответили {} из {}, кворум {}",
+                self.global_round, answered.len(), eligible.len(), MIN_PARTICIPANTS);
+            self.aggregator.collected.clear();
+            return None;
+        }
+
+        // 1. Каждый ответивший узел обучается локально
         let mut exported = vec![];
-        for trainer in self.trainers.values_mut() {
-            if trainer.local_data.is_empty() { continue; }
+        for id in answered {
+            let Some(trainer) = self.trainers.get_mut(id) else { continue };
             let (loss, acc) = trainer.train_local(LOCAL_EPOCHS);
             let w = trainer.export_weights(self.global_round, loss, acc);
             exported.push(w);
@@ -454,7 +587,11 @@ impl FederatedNetwork {
         for w in exported { self.aggregator.collect(w); }
 
         // 3. FedAvg агрегация
-        if let Some(result) = self.aggregator.aggregate() {
+        if let Some(mut result) = self.aggregator.aggregate() {
+            result.dropped = dropped;
+            if let Some(last) = self.aggregator.aggregation_history.last_mut() {
+                last.dropped = dropped;
+            }
             self.convergence_history.push(result.avg_local_loss);
 
             // 4. Рассылаем глобальные веса обратно узлам
@@ -466,9 +603,104 @@ impl FederatedNetwork {
             self.global_round += 1;
             return Some(result);
         }
+        // Несостоявшийся раунд не подмешивает веса в следующий
+        self.aggregator.collected.clear();
         None
     }
 
+    /// Отпечаток глобальной модели для FederationPulse — по нему пиры
+    /// замечают расхождение моделей. До первого раунда глобальной модели
+    /// нет, и отпечаток равен отпечатку пустых весов
+    pub fn model_digest(&self) -> [u8; 8] {
+        match self.aggregator.aggregation_history.last() {
+            Some(result) => result.global_weights.digest(),
+            None => ModelWeights {
+                node_id: String::new(), round: 0,
+                l1_weights: vec![], l1_biases: vec![],
+                l2_weights: vec![], l2_biases: vec![],
+                training_samples: 0, local_loss: 0.0,
+                local_accuracy: 0.0, data_hash: String::new(),
+            }.digest(),
+        }
+    }
+
+    /// Обучение с ранней остановкой: каждый раунд узлы откладывают
+    /// `val_fraction` своих данных, после агрегации глобальная модель
+    /// оценивается на отложенных точках. Если val loss не улучшался
+    /// `patience` раундов подряд — останавливаемся и возвращаем лучшую модель.
+    /// Несостоявшийся раунд (None) — тоже раунд без улучшения.
+    pub fn train_until_converged(&mut self, max_rounds: usize, patience: usize,
+        val_fraction: f64) -> Option<EarlyStopReport> {
+        self.train_until_converged_with(&mut XorShift64::from_time(), max_rounds, patience, val_fraction)
+    }
+
+    /// train_until_converged с внешним генератором для run_round_with
+    pub fn train_until_converged_with(&mut self, rng: &mut dyn FedRng, max_rounds: usize,
+        patience: usize, val_fraction: f64) -> Option<EarlyStopReport> {
+        let mut best: Option<(u32, f64, ModelWeights)> = None;
+        let mut val_history = vec![];
+        let mut stale = 0;
+        let mut rounds_run = 0;
+        let mut skipped_rounds = 0;
+
+        for _ in 0..max_rounds {
+            // 1. Валидационный срез — данные остаются на узлах
+            let held: Vec<(String, Vec<LocalDataPoint>)> = self.trainers.iter_mut()
+                .map(|(id, t)| (id.clone(), t.split_validation(val_fraction)))
+                .collect();
+
+            let result = self.run_round_with(rng);
+
+            // 2. Оцениваем глобальную модель и возвращаем точки на место
+            let mut val_sum = 0.0;
+            let mut val_count = 0;
+            for (id, points) in held {
+                if let Some(t) = self.trainers.get_mut(&id) {
+                    if !points.is_empty() {
+                        val_sum += t.evaluate(&points) * points.len() as f64;
+                        val_count += points.len();
+                    }
+                    t.local_data.extend(points);
+                }
+            }
+
+            let Some(result) = result else {
+                skipped_rounds += 1;
+                stale += 1;
+                if stale >= patience { break; }
+                continue;
+            };
+            rounds_run += 1;
+            let val_loss = if val_count > 0 {
+                val_sum / val_count as f64
+            } else {
+                result.avg_local_loss
+            };
+            val_history.push(val_loss);
+
+            let improved = best.as_ref()
+                .is_none_or(|(_, b, _)| val_loss < b - CONVERGENCE_THRESHOLD);
+            if improved {
+                best = Some((result.round, val_loss, result.global_weights));
+                stale = 0;
+            } else {
+                stale += 1;
+                if stale >= patience { break; }
+            }
+        }
+
+        let (best_round, best_val_loss, best_weights) = best?;
+        // Узлы продолжают с лучшей, а не с последней моделью
+        for trainer in self.trainers.values_mut() {
+            trainer.apply_global_weights(&best_weights);
+        }
+        Some(EarlyStopReport {
+            best_round, best_val_loss, rounds_run, skipped_rounds,
+            stopped_early: rounds_run + skipped_rounds < max_rounds,
+            val_history, best_weights,
+        })
+    }
+
     /// Узел сообщает о тактическом опыте
     pub fn submit_tactic_report(&mut self, report: TacticReport) {
         self.defense_model.absorb_report(&report);
@@ -549,6 +781,19 @@ impl std::fmt::Display for TacticalRoundResult {
     }
 }
 
+/// Итог обучения с ранней остановкой
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyStopReport {
+    pub best_round: u32,
+    pub best_val_loss: f64,
+    pub rounds_run: usize,
+    /// Раунды, не набравшие участников (run_round вернул None)
+    pub skipped_rounds: usize,
+    pub stopped_early: bool,
+    pub val_history: Vec<f64>,
+    pub best_weights: ModelWeights,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FedStats {
     pub total_nodes: usize,
@@ -576,3 +821,162 @@ impl std::fmt::Display for FedStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic_network() -> FederatedNetwork {
+        let mut net = FederatedNetwork::new();
+        for (i, region) in ["RU", "IR", "CN", "BY"].iter().enumerate() {
+            let id = format!("node_{}", i);
+            net.add_node(&id, region);
+            let trainer = net.trainers.get_mut(&id).unwrap();
+            for k in 0..10 {
+                let success = (k + i) % 3 != 0;
+                trainer.add_experience(LocalDataPoint::censorship_bypass(
+                    success, 40.0 + k as f64 * 10.0, region));
+            }
+        }
+        net
+    }
+
+    #[test]
+    fn test_early_stopping_on_plateau() {
+        let mut net = synthetic_network();
+        let report = net.train_until_converged(200, 3, 0.2).unwrap();
+
+        assert!(report.stopped_early);
+        assert!(report.rounds_run < 200);
+        assert_eq!(report.val_history.len(), report.rounds_run);
+        // Лучший раунд — минимум по истории, и после него patience раундов без улучшения
+        let best_idx = report.best_round as usize;
+        let min = report.val_history.iter().cloned().fold(f64::MAX, f64::min);
+        assert!((report.val_history[best_idx] - report.best_val_loss).abs() < 1e-12);
+        assert!(report.best_val_loss - min < CONVERGENCE_THRESHOLD);
+        assert_eq!(report.rounds_run - best_idx - 1, 3);
+        // Валидационные точки вернулись на узлы
+        assert_eq!(net.stats().total_samples, 40);
+    }
+
+    #[test]
+    fn test_model_digest_tracks_weights() {
+        let mut net = synthetic_network();
+        net.run_round().unwrap();
+        let copy = net.clone();
+        assert_eq!(net.model_digest(), copy.model_digest());
+
+        let before = net.model_digest();
+        net.run_round().unwrap();
+        assert_ne!(net.model_digest(), before);
+        assert_eq!(copy.model_digest(), before);
+
+        // Одна изменённая компонента — другой отпечаток
+        let mut w = net.aggregator.aggregation_history.last().unwrap().global_weights.clone();
+        let original = w.digest();
+        w.l2_weights[0] += 1e-9;
+        assert_ne!(w.digest(), original);
+    }
+
+    #[test]
+    fn test_early_stopping_needs_participants() {
+        let mut net = FederatedNetwork::new();
+        net.add_node("lonely", "RU");
+        assert!(net.train_until_converged(10, 2, 0.2).is_none());
+    }
+
+    #[test]
+    fn test_early_stopping_survives_skipped_rounds() {
+        let mut net = synthetic_network();
+        // Любой отвал срывает раунд; node_3 отвечает через раз
+        net.max_dropout_fraction = 0.0;
+        net.trainers.get_mut("node_3").unwrap().participate_probability = 0.5;
+        let report = net.train_until_converged_with(&mut XorShift64::new(17), 200, 6, 0.2).unwrap();
+
+        assert!(report.skipped_rounds > 0);
+        assert!(report.rounds_run > 0);
+        assert_eq!(report.val_history.len(), report.rounds_run);
+        // Узлы всегда возвращаются к лучшей модели
+        let best = report.best_weights.digest();
+        for t in net.trainers.values() {
+            assert_eq!(t.export_weights(0, 0.0, 0.0).digest(), best);
+        }
+    }
+
+    #[test]
+    fn test_round_reports_per_node_losses() {
+        let mut net = synthetic_network();
+        let mut result = net.run_round().unwrap();
+        assert_eq!(result.per_node.len(), result.participants);
+        let mean = result.per_node.iter().map(|n| n.1).sum::<f64>() / result.participants as f64;
+        assert!((mean - result.avg_local_loss).abs() < 1e-12);
+
+        let max = result.per_node.iter().map(|n| n.1).fold(f64::MIN, f64::max);
+        let worst = result.worst_node().unwrap();
+        assert_eq!(worst.1, max);
+        assert!(result.per_node.iter().filter(|n| n.1 == max).all(|n| n.0 >= worst.0));
+
+        // Разошедшийся узел (NaN) — худший
+        result.per_node[2].1 = f64::NAN;
+        assert_eq!(result.worst_node().unwrap().0, "node_2");
+    }
+
+    #[test]
+    fn test_round_with_everyone_opted_out_is_none() {
+        let mut net = synthetic_network();
+        let ids: Vec<String> = net.trainers.keys().cloned().collect();
+        for id in &ids { net.opt_out(id); }
+        let before = net.model_digest();
+        assert!(net.run_round().is_none());
+        assert_eq!((net.global_round, net.model_digest()), (0, before));
+        assert!(net.aggregator.collected.is_empty());
+
+        // Ниже кворума — тоже None, локальные модели не обучались,
+        // и веса не копятся между раундами
+        net.opt_in(&ids[0]);
+        net.opt_in(&ids[1]);
+        let local = net.trainers[&ids[0]].state.layer1.weights.clone();
+        assert!(net.run_round().is_none());
+        assert_eq!(net.trainers[&ids[0]].state.layer1.weights, local);
+        assert!(net.aggregator.collected.is_empty());
+
+        for id in &ids { net.opt_in(id); }
+        assert_eq!(net.run_round().unwrap().participants, ids.len());
+    }
+
+    fn wide_network(n: usize) -> FederatedNetwork {
+        let mut net = FederatedNetwork::new();
+        for i in 0..n {
+            let id = format!("node_{}", i);
+            net.add_node(&id, "RU");
+            let trainer = net.trainers.get_mut(&id).unwrap();
+            for k in 0..6 {
+                trainer.add_experience(LocalDataPoint::censorship_bypass(
+                    (k + i) % 2 == 0, 40.0 + k as f64 * 10.0, "RU"));
+            }
+        }
+        net
+    }
+
+    #[test]
+    fn test_round_survives_forty_percent_dropout() {
+        let mut net = wide_network(10);
+        let responders: HashSet<String> = (0..6).map(|i| format!("node_{}", i)).collect();
+        let result = net.run_round_with_responders(&responders).unwrap();
+        assert_eq!((result.participants, result.dropped), (6, 4));
+        assert!(result.per_node.iter().all(|(id, ..)| responders.contains(id)));
+        assert_eq!(net.aggregator.aggregation_history.last().unwrap().dropped, 4);
+        assert_eq!(net.global_round, 1);
+
+        // Больше max_dropout_fraction молчащих — раунд не засчитан
+        let few: HashSet<String> = (0..4).map(|i| format!("node_{}", i)).collect();
+        assert!(net.run_round_with_responders(&few).is_none());
+        assert_eq!(net.global_round, 1);
+
+        // Ghost-узлы с вероятностью 0 не отвечают никогда
+        for i in 6..10 { net.trainers.get_mut(&format!("node_{}", i)).unwrap()
+            .participate_probability = 0.0; }
+        let result = net.run_round_with(&mut XorShift64::new(7)).unwrap();
+        assert_eq!((result.participants, result.dropped), (6, 4));
+    }
+}
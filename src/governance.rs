@@ -1,5 +1,7 @@
+use crate::credits::{CreditLedger, LockHandle};
+use crate::hashsig::{hash, HashSignature, MerkleSigner};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub const MIN_STAKE_TO_PROPOSE: f64 = 1.0;
 pub const MIN_STAKE_TO_VOTE: f64 = 0.1;
@@ -17,6 +19,8 @@ pub enum ProposalType {
     AddSeedNode { address: String, region: String },
     CodeUpgrade { version: String, changelog: String, checksum: String },
     CensorshipResponse { region: String, countermeasure: String },
+    /// Выплата из UpgradeFund: одно решение — один получатель и сумма
+    UpgradeDisbursement { recipient: String, amount: f64 },
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -297,6 +301,9 @@ pub const FOUNDING_THRESHOLD: f64   = 1000.0;// ≥1000 = Founding Father
 pub const FIRMWARE_QUORUM: f64      = 0.67;  // 2/3 для прошивки
 pub const EMERGENCY_QUORUM: f64     = 0.51;  // простое большинство
 pub const DELEGATE_MAX: usize       = 5;     // максимум делегатов
+pub const MIN_PARTICIPATION: f64    = 0.10;  // явка по умолчанию
+pub const VETO_OVERRIDE: f64        = 0.90;  // доля total_weight «за», снимающая вето
+pub const FIRMWARE_STAKE: f64       = 10.0;  // залог автора прошивки, credits
 
 // -----------------------------------------------------------------------------
 // MeritTier — уровень влияния
@@ -393,6 +400,7 @@ pub struct FirmwareProposal {
     pub required_quorum: f64,
     pub timestamp: i64,
     pub voters: std::collections::HashMap<String, bool>,
+    pub signature: HashSignature,  // подпись автора над (kind, code_hash, author)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -425,11 +433,56 @@ impl FirmwareKind {
             FirmwareKind::EmergencyPatch  {..} => "EmergencyPatch",
         }
     }
-    pub fn required_quorum(&self) -> f64 {
-        match self {
-            FirmwareKind::EmergencyPatch {..} => EMERGENCY_QUORUM,
-            _                                  => FIRMWARE_QUORUM,
-        }
+}
+
+// -----------------------------------------------------------------------------
+// GovernanceParams — пороги голосования по типу прошивки
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KindThresholds {
+    pub quorum: f64,    // минимальная явка (доля total_weight)
+    pub approval: f64,  // доля «за» среди проголосовавших
+}
+
+/// Пороги по FirmwareKind::name(); тип без записи получает default.
+/// Вето Elder снимается, если «за» набрали veto_override от total_weight
+/// в пределах voting_window_secs — кроме типов из veto_protected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceParams {
+    pub by_kind: HashMap<String, KindThresholds>,
+    pub default: KindThresholds,
+    pub veto_override: f64,
+    pub veto_protected: HashSet<String>,
+    pub voting_window_secs: u64,
+}
+
+impl GovernanceParams {
+    pub fn set(&mut self, kind_name: &str, quorum: f64, approval: f64) {
+        self.by_kind.insert(kind_name.to_string(), KindThresholds {
+            quorum: quorum.clamp(0.0, 1.0), approval: approval.clamp(0.0, 1.0) });
+    }
+
+    pub fn for_kind(&self, kind: &FirmwareKind) -> KindThresholds {
+        self.by_kind.get(kind.name()).copied().unwrap_or(self.default)
+    }
+
+    pub fn veto_overridable(&self, kind: &FirmwareKind) -> bool {
+        !self.veto_protected.contains(kind.name())
+    }
+}
+
+impl Default for GovernanceParams {
+    fn default() -> Self {
+        let mut params = GovernanceParams {
+            by_kind: HashMap::new(),
+            default: KindThresholds { quorum: MIN_PARTICIPATION, approval: FIRMWARE_QUORUM },
+            veto_override: VETO_OVERRIDE,
+            veto_protected: ["MintParam".to_string()].into_iter().collect(),
+            voting_window_secs: VOTING_PERIOD_SECS,
+        };
+        params.set("EmergencyPatch", MIN_PARTICIPATION, EMERGENCY_QUORUM);
+        params
     }
 }
 
@@ -439,22 +492,32 @@ impl FirmwareKind {
 
 pub struct MeritocracyDao {
     pub voting_powers: std::collections::HashMap<String, VotingPower>,
+    pub author_keys: std::collections::HashMap<String, [u8; 32]>, // статический ключ автора
     pub firmware_proposals: Vec<FirmwareProposal>,
     pub total_weight: f64,
     pub proposals_passed: u64,
     pub proposals_vetoed: u64,
     pub counter: u64,
+    pub params: GovernanceParams,
+    stake_locks: std::collections::HashMap<u64, LockHandle>, // залоги авторов по proposal_id
 }
 
 impl MeritocracyDao {
     pub fn new() -> Self {
+        Self::with_params(GovernanceParams::default())
+    }
+
+    pub fn with_params(params: GovernanceParams) -> Self {
         MeritocracyDao {
             voting_powers: std::collections::HashMap::new(),
+            author_keys: std::collections::HashMap::new(),
             firmware_proposals: vec![],
             total_weight: 0.0,
             proposals_passed: 0,
             proposals_vetoed: 0,
             counter: 0,
+            params,
+            stake_locks: std::collections::HashMap::new(),
         }
     }
 
@@ -485,18 +548,55 @@ impl MeritocracyDao {
         true
     }
 
+    /// Публичный ключ (корень MerkleSigner), которым узел подписывает прошивки.
+    /// Регистрируется один раз: иначе захватчик узла подменил бы ключ автора
+    pub fn register_author_key(&mut self, node_id: &str, pubkey: [u8; 32])
+        -> Result<(), String> {
+        if self.author_keys.contains_key(node_id) {
+            return Err(format!("ключ автора {} уже зарегистрирован", node_id));
+        }
+        self.author_keys.insert(node_id.to_string(), pubkey);
+        Ok(())
+    }
+
+    /// Что подписывает автор: тип и параметры прошивки, хеш артефакта и
+    /// своё имя — подпись не перенести на другой код или другого автора
+    pub fn firmware_digest(kind: &FirmwareKind, code_hash: &str, author: &str)
+        -> Result<[u8; 32], String> {
+        let mut buf = b"federation-firmware-v1".to_vec();
+        buf.extend_from_slice(&serde_json::to_vec(kind)
+            .map_err(|e| format!("сериализация прошивки: {}", e))?);
+        buf.push(0);
+        buf.extend_from_slice(code_hash.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(author.as_bytes());
+        Ok(hash(&buf))
+    }
+
+    /// DAO само блокирует FIRMWARE_STAKE credits автора. Handle залога
+    /// хранится только внутри DAO: снять его можно лишь через settle_stake
     pub fn submit_firmware(&mut self, proposer: &str,
-        kind: FirmwareKind, description: &str, code_hash: &str)
+        kind: FirmwareKind, description: &str, code_hash: &str,
+        signature: HashSignature, ledger: &mut CreditLedger)
         -> Result<u64, String> {
 
         let vp = self.voting_powers.get(proposer)
             .ok_or("узел не зарегистрирован")?;
+        let key = self.author_keys.get(proposer)
+            .ok_or("у автора нет зарегистрированного ключа")?;
+        let digest = Self::firmware_digest(&kind, code_hash, proposer)?;
+        if !MerkleSigner::verify(&digest, &signature, key) {
+            return Err("подпись прошивки не сошлась".into());
+        }
         if !vp.tier.can_propose() {
             return Err(format!("недостаточный ранг: {}", vp.tier.name()));
         }
+        let stake = ledger.lock_held(proposer, FIRMWARE_STAKE)
+            .map_err(|e| format!("залог {} credits: {}", FIRMWARE_STAKE, e))?;
         let tier = vp.tier.clone();
-        let quorum = kind.required_quorum();
+        let quorum = self.params.for_kind(&kind).approval;
         self.counter += 1;
+        self.stake_locks.insert(self.counter, stake);
 
         self.firmware_proposals.push(FirmwareProposal {
             proposal_id: self.counter,
@@ -508,10 +608,44 @@ impl MeritocracyDao {
             vetoes: vec![], status: FirmwareStatus::Active,
             required_quorum: quorum, timestamp: Self::now(),
             voters: std::collections::HashMap::new(),
+            signature,
         });
         Ok(self.counter)
     }
 
+    /// Вето ещё можно снять: тип не защищён и окно голосования не истекло
+    fn override_open(params: &GovernanceParams, prop: &FirmwareProposal) -> bool {
+        prop.status == FirmwareStatus::Vetoed
+            && params.veto_overridable(&prop.kind)
+            && Self::now() - prop.timestamp <= params.voting_window_secs as i64 * 1000
+    }
+
+    /// После завершения голосования: окончательное вето Elder изымает залог
+    /// в казну, любой другой итог возвращает его автору
+    pub fn settle_stake(&mut self, proposal_id: u64, ledger: &mut CreditLedger)
+        -> Result<f64, String> {
+        let prop = self.firmware_proposals.iter()
+            .find(|p| p.proposal_id == proposal_id).ok_or("не найдено")?;
+        if prop.status == FirmwareStatus::Active || Self::override_open(&self.params, prop) {
+            return Err("голосование ещё идёт".into());
+        }
+        let vetoed = prop.status == FirmwareStatus::Vetoed;
+        let stake = self.stake_locks.remove(&proposal_id).ok_or("залог уже урегулирован")?;
+        let settled = if vetoed { ledger.slash_held(stake, true) } else { ledger.unlock_held(stake) };
+        settled.map_err(String::from)
+    }
+
+    /// Повторная проверка подписи перед применением прошивки
+    pub fn verify_firmware(&self, proposal_id: u64) -> bool {
+        self.firmware_proposals.iter().find(|p| p.proposal_id == proposal_id)
+            .and_then(|p| {
+                let key = self.author_keys.get(&p.proposer)?;
+                let digest = Self::firmware_digest(&p.kind, &p.code_hash, &p.proposer).ok()?;
+                Some(MerkleSigner::verify(&digest, &p.signature, key))
+            })
+            .unwrap_or(false)
+    }
+
     pub fn vote_firmware(&mut self, proposal_id: u64,
         voter: &str, approve: bool) -> VoteFirmwareResult {
 
@@ -526,7 +660,9 @@ impl MeritocracyDao {
             Some(p) => p,
         };
 
-        if prop.status != FirmwareStatus::Active {
+        // После вето голосование продолжается только ради снятия вето:
+        // тип не защищён и окно голосования не истекло
+        if prop.status != FirmwareStatus::Active && !Self::override_open(&self.params, prop) {
             return VoteFirmwareResult::denied("голосование закрыто");
         }
         if prop.voters.contains_key(voter) {
@@ -556,8 +692,10 @@ impl MeritocracyDao {
         }
     }
 
+    /// Итог голосования по порогам GovernanceParams на момент подсчёта
     pub fn finalize(&mut self, proposal_id: u64) -> FinalizeResult {
         let total = self.total_weight;
+        let params = self.params.clone();
         let prop = match self.firmware_proposals.iter_mut()
             .find(|p| p.proposal_id == proposal_id) {
             None => return FinalizeResult { passed: false, reason: "не найдено".into(),
@@ -566,9 +704,19 @@ impl MeritocracyDao {
         };
 
         if prop.status == FirmwareStatus::Vetoed {
+            let participation = (prop.votes_for + prop.votes_against) / total;
+            let support = prop.votes_for / total;
+            if params.veto_overridable(&prop.kind) && support >= params.veto_override {
+                prop.status = FirmwareStatus::Passed;
+                self.proposals_passed += 1;
+                return FinalizeResult { passed: true,
+                    reason: format!("VETO_OVERRIDE support={:.1}%", support * 100.0),
+                    votes_for: prop.votes_for, votes_against: prop.votes_against,
+                    participation };
+            }
             return FinalizeResult { passed: false, reason: "VETO".into(),
                 votes_for: prop.votes_for, votes_against: prop.votes_against,
-                participation: (prop.votes_for + prop.votes_against) / total };
+                participation };
         }
 
         let participation = (prop.votes_for + prop.votes_against) / total;
@@ -576,7 +724,9 @@ impl MeritocracyDao {
             prop.votes_for / (prop.votes_for + prop.votes_against)
         } else { 0.0 };
 
-        let passed = participation >= 0.10 && approval >= prop.required_quorum;
+        let thresholds = params.for_kind(&prop.kind);
+        prop.required_quorum = thresholds.approval;
+        let passed = participation >= thresholds.quorum && approval >= thresholds.approval;
         prop.status = if passed { FirmwareStatus::Passed } else { FirmwareStatus::Rejected };
         if passed { self.proposals_passed += 1; }
 
@@ -594,10 +744,75 @@ impl MeritocracyDao {
         dist.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap());
         dist
     }
+
+    /// Машиночитаемое распределение власти: по узлам и итог.
+    /// Доля считается от суммы total_weight всех узлов
+    pub fn power_report(&self) -> PowerReport {
+        let total_weight: f64 = self.voting_powers.values().map(|v| v.total_weight).sum();
+        let mut nodes: Vec<PowerShare> = self.voting_powers.values().map(|v| PowerShare {
+            node_id: v.node_id.clone(),
+            raw_weight: v.raw_weight,
+            delegate_bonus: v.delegate_bonus,
+            total_weight: v.total_weight,
+            share: if total_weight > 0.0 { v.total_weight / total_weight } else { 0.0 },
+            tier: v.tier.clone(),
+        }).collect();
+        nodes.sort_by(|a, b| b.total_weight.partial_cmp(&a.total_weight)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.node_id.cmp(&b.node_id)));
+        PowerReport { nodes, total_weight }
+    }
 }
 
 impl Default for MeritocracyDao { fn default() -> Self { Self::new() } }
 
+// -----------------------------------------------------------------------------
+// PowerReport — распределение власти для дашбордов и аудита
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerShare {
+    pub node_id: String,
+    pub raw_weight: f64,
+    pub delegate_bonus: f64,
+    pub total_weight: f64,
+    pub share: f64,           // 0..1 от total_weight отчёта
+    pub tier: MeritTier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerReport {
+    pub nodes: Vec<PowerShare>,   // по убыванию total_weight
+    pub total_weight: f64,
+}
+
+impl PowerReport {
+    /// Коэффициент Джини по total_weight: 0 — власть поровну,
+    /// → 1 — вся власть у одного узла
+    pub fn gini(&self) -> f64 {
+        let n = self.nodes.len();
+        if n == 0 || self.total_weight <= 0.0 { return 0.0; }
+        let mut w: Vec<f64> = self.nodes.iter().map(|p| p.total_weight).collect();
+        w.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let weighted: f64 = w.iter().enumerate().map(|(i, x)| (i + 1) as f64 * x).sum();
+        let n = n as f64;
+        2.0 * weighted / (n * self.total_weight) - (n + 1.0) / n
+    }
+}
+
+impl std::fmt::Display for PowerReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:20} {:>9} {:>9} {:>9} {:>7}  Tier",
+            "Узел", "Raw", "Делег.", "Итого", "Доля")?;
+        for p in &self.nodes {
+            writeln!(f, "{:20} {:>9.3} {:>9.3} {:>9.3} {:>6.1}%  {}",
+                p.node_id, p.raw_weight, p.delegate_bonus,
+                p.total_weight, p.share * 100.0, p.tier.name())?;
+        }
+        write!(f, "Всего: {:.3}  Gini: {:.3}", self.total_weight, self.gini())
+    }
+}
+
 #[derive(Debug)]
 pub struct VoteFirmwareResult {
     pub success: bool, pub voter: String, pub weight: f64,
@@ -623,3 +838,223 @@ pub struct FinalizeResult {
     pub passed: bool, pub reason: String,
     pub votes_for: f64, pub votes_against: f64, pub participation: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dao(reps: &[(&str, f64)]) -> MeritocracyDao {
+        let mut dao = MeritocracyDao::new();
+        for (id, rep) in reps { dao.register_voter(id, *rep); }
+        dao
+    }
+
+    #[test]
+    fn test_power_report_shares_and_gini() {
+        let base = [("alpha", 120.0), ("beta", 60.0), ("gamma", 30.0), ("delta", 15.0)];
+        let mut fair = dao(&base);
+        fair.delegate("delta", "alpha");
+        let report = fair.power_report();
+        let shares: f64 = report.nodes.iter().map(|p| p.share).sum();
+        assert!((shares - 1.0).abs() < 1e-9);
+        assert_eq!(report.nodes[0].node_id, "alpha");
+        assert!(report.nodes[0].delegate_bonus > 0.0);
+        assert!((0.0..1.0).contains(&report.gini()));
+
+        let mut inflated = base;
+        inflated[2].1 = 50_000.0;
+        assert!(dao(&inflated).power_report().gini() > report.gini());
+
+        let equal = dao(&[("a", 40.0), ("b", 40.0), ("c", 40.0)]).power_report();
+        assert!(equal.gini().abs() < 1e-9);
+    }
+
+    fn tactic_update() -> FirmwareKind {
+        FirmwareKind::TacticUpdate { tactic: "AikiReflection".into(),
+            params: "exhaust_factor=0.85".into() }
+    }
+
+    fn signed_dao() -> (MeritocracyDao, MerkleSigner, MerkleSigner) {
+        let mut dao = dao(&[("elder", 800.0), ("member", 40.0)]);
+        let elder = MerkleSigner::new([1u8; 32], 2);
+        let member = MerkleSigner::new([2u8; 32], 2);
+        dao.register_author_key("elder", elder.public_key()).unwrap();
+        dao.register_author_key("member", member.public_key()).unwrap();
+        (dao, elder, member)
+    }
+
+    fn submit_signed(dao: &mut MeritocracyDao, signer: &mut MerkleSigner,
+                     author: &str, kind: FirmwareKind) -> u64 {
+        let digest = MeritocracyDao::firmware_digest(&kind, "sha256:x", author).unwrap();
+        let sig = signer.sign(&digest).unwrap();
+        dao.submit_firmware(author, kind, "", "sha256:x", sig, &mut funded(author)).unwrap()
+    }
+
+    // Книга, где у автора ровно FIRMWARE_STAKE на залог
+    fn funded(author: &str) -> CreditLedger {
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert(author.to_string(), FIRMWARE_STAKE);
+        ledger
+    }
+
+    #[test]
+    fn test_finalize_uses_per_kind_thresholds() {
+        let names = ["m1", "m2", "m3", "m4", "m5"];
+        let mut dao = dao(&names.map(|n| (n, 40.0)));
+        let mut key = MerkleSigner::new([5u8; 32], 2);
+        dao.register_author_key("m1", key.public_key()).unwrap();
+        let emergency = submit_signed(&mut dao, &mut key, "m1",
+            FirmwareKind::EmergencyPatch { cve: "CVE-1".into(), severity: 9 });
+        let tactic = submit_signed(&mut dao, &mut key, "m1", tactic_update());
+
+        // Одинаковый расклад: 3 из 5 «за» — 60%
+        for id in [emergency, tactic] {
+            for (i, n) in names.iter().enumerate() { dao.vote_firmware(id, n, i < 3); }
+        }
+        assert!(dao.finalize(emergency).passed);
+        assert!(!dao.finalize(tactic).passed);
+
+        // Пороги настраиваются: экономика — строже, тактика — мягче
+        let mut params = GovernanceParams::default();
+        params.set("TacticUpdate", 0.5, 0.55);
+        params.set("MintParam", 0.5, 0.80);
+        assert_eq!(params.for_kind(&tactic_update()).approval, 0.55);
+        dao.params = params;
+        let retry = submit_signed(&mut dao, &mut key, "m1", tactic_update());
+        for (i, n) in names.iter().enumerate() { dao.vote_firmware(retry, n, i < 3); }
+        assert!(dao.finalize(retry).passed);
+    }
+
+    // 2 Elder против, 10 Founding Father — подавляющее большинство веса
+    fn veto_scenario(kind: FirmwareKind) -> (MeritocracyDao, u64) {
+        let mut voters = vec![("elder-1".to_string(), 600.0), ("elder-2".to_string(), 600.0)];
+        voters.extend((0..10).map(|i| (format!("ff-{}", i), 5000.0)));
+        let mut dao = MeritocracyDao::new();
+        for (id, rep) in &voters { dao.register_voter(id, *rep); }
+        let mut key = MerkleSigner::new([6u8; 32], 1);
+        dao.register_author_key("ff-0", key.public_key()).unwrap();
+        let id = submit_signed(&mut dao, &mut key, "ff-0", kind);
+        dao.vote_firmware(id, "elder-1", false);
+        assert_eq!(dao.vote_firmware(id, "elder-2", false).status, FirmwareStatus::Vetoed);
+        for i in 0..10 { dao.vote_firmware(id, &format!("ff-{}", i), true); }
+        (dao, id)
+    }
+
+    #[test]
+    fn test_supermajority_overrides_veto_on_tactic() {
+        let (mut dao, id) = veto_scenario(tactic_update());
+        let result = dao.finalize(id);
+        assert!(result.passed, "{}", result.reason);
+        assert!(result.reason.starts_with("VETO_OVERRIDE"));
+    }
+
+    #[test]
+    fn test_veto_protected_kind_cannot_be_overridden() {
+        let (mut dao, id) = veto_scenario(FirmwareKind::MintParam {
+            param: "BURN_RATE".into(), old_val: 0.30, new_val: 0.10 });
+        assert!(!dao.vote_firmware(id, "ff-0", true).success);
+        let result = dao.finalize(id);
+        assert!(!result.passed);
+        assert_eq!(result.reason, "VETO");
+    }
+
+    #[test]
+    fn test_submit_firmware_accepts_signed_proposal() {
+        let (mut dao, mut elder, _) = signed_dao();
+        let digest = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:aiki_v2", "elder")
+            .unwrap();
+        let sig = elder.sign(&digest).unwrap();
+        let id = dao.submit_firmware("elder", tactic_update(), "Aiki v2",
+            "sha256:aiki_v2", sig, &mut funded("elder")).unwrap();
+        assert!(dao.verify_firmware(id));
+    }
+
+    #[test]
+    fn test_submit_firmware_requires_stake_and_settles_it() {
+        let (mut dao, mut elder, _) = signed_dao();
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("elder".into(), FIRMWARE_STAKE / 2.0);
+        let digest = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:x", "elder").unwrap();
+        let sig = elder.sign(&digest).unwrap();
+        let submit = |dao: &mut MeritocracyDao, ledger: &mut CreditLedger|
+            dao.submit_firmware("elder", tactic_update(), "", "sha256:x", sig.clone(), ledger);
+
+        // Не хватает credits на залог — отказ
+        assert!(submit(&mut dao, &mut ledger).is_err());
+        assert!(dao.firmware_proposals.is_empty());
+
+        ledger.balances.insert("elder".into(), 25.0);
+        let id = submit(&mut dao, &mut ledger).unwrap();
+        assert_eq!((ledger.balance("elder"), ledger.locked("elder")), (15.0, FIRMWARE_STAKE));
+
+        // До конца голосования залог не снять ни напрямую, ни через DAO
+        assert!(ledger.unlock("firmware-1").is_err());
+        assert!(dao.settle_stake(id, &mut ledger).is_err(), "голосование не завершено");
+        assert_eq!(ledger.locked("elder"), FIRMWARE_STAKE);
+
+        // Обычное отклонение — залог возвращается автору, повторно не выдаётся
+        dao.vote_firmware(id, "elder", false);
+        dao.finalize(id);
+        assert_eq!(dao.settle_stake(id, &mut ledger), Ok(FIRMWARE_STAKE));
+        assert_eq!((ledger.balance("elder"), ledger.treasury), (25.0, 0.0));
+        assert!(dao.settle_stake(id, &mut ledger).is_err());
+    }
+
+    #[test]
+    fn test_final_veto_slashes_stake_to_treasury() {
+        let (mut dao, mut elder, _) = signed_dao();
+        dao.register_voter("elder-2", 800.0);
+        dao.params.veto_protected.insert("TacticUpdate".into());
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("elder".into(), 25.0);
+        let digest = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:x", "elder").unwrap();
+        let sig = elder.sign(&digest).unwrap();
+        let id = dao.submit_firmware("elder", tactic_update(), "", "sha256:x", sig, &mut ledger)
+            .unwrap();
+
+        dao.vote_firmware(id, "elder", false);
+        assert_eq!(dao.vote_firmware(id, "elder-2", false).status, FirmwareStatus::Vetoed);
+        assert_eq!(dao.settle_stake(id, &mut ledger), Ok(FIRMWARE_STAKE));
+        assert_eq!(ledger.treasury, FIRMWARE_STAKE);
+        assert_eq!(ledger.total_balance("elder"), 25.0 - FIRMWARE_STAKE);
+    }
+
+    #[test]
+    fn test_submit_firmware_rejects_forged_author_and_hash() {
+        let (mut dao, _, mut member) = signed_dao();
+        // member подписал своим ключом, но выдаёт прошивку за elder
+        let forged = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:evil", "elder")
+            .unwrap();
+        let sig = member.sign(&forged).unwrap();
+        let mut ledger = funded("elder");
+        assert!(dao.submit_firmware("elder", tactic_update(), "", "sha256:evil",
+            sig, &mut ledger).is_err());
+        assert_eq!(ledger.locked("elder"), 0.0);
+
+        // Подпись над одним хешем, артефакт — другой
+        let digest = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:aiki_v2", "member")
+            .unwrap();
+        let sig = member.sign(&digest).unwrap();
+        assert!(dao.submit_firmware("member", tactic_update(), "", "sha256:evil",
+            sig.clone(), &mut funded("member")).is_err());
+        assert!(dao.firmware_proposals.is_empty());
+
+        // Без зарегистрированного ключа — отказ
+        dao.register_voter("anon", 40.0);
+        assert!(dao.submit_firmware("anon", tactic_update(), "", "sha256:aiki_v2",
+            sig, &mut funded("anon")).is_err());
+    }
+
+    #[test]
+    fn test_author_key_cannot_be_replaced() {
+        let (mut dao, mut elder, member) = signed_dao();
+        assert!(dao.register_author_key("elder", member.public_key()).is_err());
+        assert_eq!(dao.author_keys["elder"], elder.public_key());
+
+        let digest = MeritocracyDao::firmware_digest(&tactic_update(), "sha256:x", "elder")
+            .unwrap();
+        let sig = elder.sign(&digest).unwrap();
+        assert!(dao.submit_firmware("elder", tactic_update(), "", "sha256:x",
+            sig, &mut funded("elder")).is_ok());
+    }
+}
@@ -0,0 +1,217 @@
+// =============================================================================
+// FEDERATION CORE — hashsig.rs
+// «Подписи на хешах»
+// =============================================================================
+//
+// Подписи, которым нужна только хеш-функция SHA-256 — без кривых.
+//
+//   WotsKey      — одноразовая подпись Winternitz (w=16): 67 цепочек
+//                  хешей, цифра сообщения = сколько шагов раскрыто
+//   MerkleSigner — многоразовый ключ: дерево из 2^height WOTS-ключей,
+//                  публичный ключ — корень. Каждая подпись тратит лист
+//
+// Повторно использовать лист WOTS нельзя: две подписи раскрывают
+// достаточно звеньев для подделки. MerkleSigner следит за этим сам.
+// =============================================================================
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const HASHLEN: usize       = 32;            // SHA-256
+
+pub const WOTS_DIGITS: usize   = HASHLEN * 2;   // 4-битные цифры сообщения
+pub const WOTS_CHECKSUM: usize = 3;             // цифры контрольной суммы (≤ 960)
+pub const WOTS_CHAINS: usize   = WOTS_DIGITS + WOTS_CHECKSUM;
+pub const WOTS_MAX_STEP: u8    = 15;
+pub const WOTS_SIGNATURE_BYTES: usize = WOTS_CHAINS * HASHLEN;
+pub const MERKLE_MAX_HEIGHT: u32 = 16;          // до 65536 подписей на ключ
+
+/// SHA-256: стойкость подписей держится только на ней
+pub fn hash(data: &[u8]) -> [u8; HASHLEN] {
+    Sha256::digest(data).into()
+}
+
+// -----------------------------------------------------------------------------
+// WotsKey — одноразовая подпись
+// -----------------------------------------------------------------------------
+
+pub struct WotsKey {
+    seed: [u8; 32],
+}
+
+impl WotsKey {
+    pub fn from_seed(seed: [u8; 32]) -> Self { WotsKey { seed } }
+
+    fn chain(mut x: [u8; HASHLEN], from: u8, steps: u8, index: usize) -> [u8; HASHLEN] {
+        for step in from..from + steps {
+            let mut buf = x.to_vec();
+            buf.extend_from_slice(&(index as u16).to_le_bytes());
+            buf.push(step);
+            x = hash(&buf);
+        }
+        x
+    }
+
+    // Цифры сообщения + контрольная сумма: уменьшить цифру нельзя,
+    // не увеличив контрольную сумму
+    fn digits(msg: &[u8; 32]) -> [u8; WOTS_CHAINS] {
+        let mut d = [0u8; WOTS_CHAINS];
+        for (i, b) in msg.iter().enumerate() {
+            d[2 * i] = b >> 4;
+            d[2 * i + 1] = b & 0x0f;
+        }
+        let checksum: u32 = d[..WOTS_DIGITS].iter()
+            .map(|&x| (WOTS_MAX_STEP - x) as u32).sum();
+        for j in 0..WOTS_CHECKSUM {
+            d[WOTS_DIGITS + j] = ((checksum >> (4 * j)) & 0x0f) as u8;
+        }
+        d
+    }
+
+    fn secret(&self, index: usize) -> [u8; HASHLEN] {
+        let mut buf = self.seed.to_vec();
+        buf.extend_from_slice(&(index as u16).to_le_bytes());
+        hash(&buf)
+    }
+
+    fn compress(ends: &[[u8; HASHLEN]]) -> [u8; 32] {
+        hash(&ends.concat())
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        let ends: Vec<[u8; HASHLEN]> = (0..WOTS_CHAINS)
+            .map(|i| Self::chain(self.secret(i), 0, WOTS_MAX_STEP, i)).collect();
+        Self::compress(&ends)
+    }
+
+    pub fn sign(&self, msg: &[u8; 32]) -> Vec<u8> {
+        let digits = Self::digits(msg);
+        (0..WOTS_CHAINS)
+            .flat_map(|i| Self::chain(self.secret(i), 0, digits[i], i))
+            .collect()
+    }
+
+    /// Публичный ключ, который подпись даёт для msg. Совпал с
+    /// известным — подпись верна
+    pub fn recover_public_key(msg: &[u8; 32], signature: &[u8]) -> Option<[u8; 32]> {
+        if signature.len() != WOTS_SIGNATURE_BYTES { return None; }
+        let digits = Self::digits(msg);
+        let ends: Vec<[u8; HASHLEN]> = signature.chunks(HASHLEN).enumerate().map(|(i, c)| {
+            let mut x = [0u8; HASHLEN];
+            x.copy_from_slice(c);
+            Self::chain(x, digits[i], WOTS_MAX_STEP - digits[i], i)
+        }).collect();
+        Some(Self::compress(&ends))
+    }
+
+    pub fn verify(msg: &[u8; 32], signature: &[u8], pubkey: &[u8; 32]) -> bool {
+        Self::recover_public_key(msg, signature).as_ref() == Some(pubkey)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// MerkleSigner — многоразовый ключ из дерева WOTS-листьев
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashSignature {
+    pub leaf: u32,
+    pub wots: Vec<u8>,
+    pub auth_path: Vec<[u8; 32]>,  // соседи от листа к корню
+}
+
+pub struct MerkleSigner {
+    seed: [u8; 32],
+    levels: Vec<Vec<[u8; 32]>>,    // levels[0] — листья, последний — корень
+    next_leaf: u32,
+}
+
+impl MerkleSigner {
+    pub fn new(seed: [u8; 32], height: u32) -> Self {
+        let height = height.min(MERKLE_MAX_HEIGHT);
+        let leaves: Vec<[u8; 32]> = (0..1u32 << height)
+            .map(|i| Self::leaf_key(&seed, i).public_key()).collect();
+        let mut levels = vec![leaves];
+        while levels.last().map(|l| l.len()).unwrap_or(0) > 1 {
+            let next = levels.last().unwrap().chunks(2)
+                .map(|pair| Self::parent(&pair[0], &pair[1])).collect();
+            levels.push(next);
+        }
+        MerkleSigner { seed, levels, next_leaf: 0 }
+    }
+
+    fn leaf_key(seed: &[u8; 32], leaf: u32) -> WotsKey {
+        let mut buf = seed.to_vec();
+        buf.extend_from_slice(&leaf.to_le_bytes());
+        WotsKey::from_seed(hash(&buf))
+    }
+
+    fn parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        hash(&[&left[..], &right[..]].concat())
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.levels.last().map(|l| l[0]).unwrap_or_default()
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.levels[0].len() as u32 - self.next_leaf
+    }
+
+    pub fn sign(&mut self, msg: &[u8; 32]) -> Result<HashSignature, &'static str> {
+        if self.remaining() == 0 { return Err("ключ исчерпан: все листья потрачены"); }
+        let leaf = self.next_leaf;
+        self.next_leaf += 1;
+        let wots = Self::leaf_key(&self.seed, leaf).sign(msg);
+        let mut idx = leaf as usize;
+        let auth_path = self.levels[..self.levels.len() - 1].iter().map(|level| {
+            let sibling = level[idx ^ 1];
+            idx /= 2;
+            sibling
+        }).collect();
+        Ok(HashSignature { leaf, wots, auth_path })
+    }
+
+    pub fn verify(msg: &[u8; 32], sig: &HashSignature, root: &[u8; 32]) -> bool {
+        if sig.auth_path.len() as u32 > MERKLE_MAX_HEIGHT
+            || (sig.leaf as u64) >> sig.auth_path.len() != 0 {
+            return false;
+        }
+        let mut node = match WotsKey::recover_public_key(msg, &sig.wots) {
+            Some(pk) => pk,
+            None => return false,
+        };
+        let mut idx = sig.leaf;
+        for sibling in &sig.auth_path {
+            node = if idx & 1 == 0 { Self::parent(&node, sibling) }
+                   else { Self::parent(sibling, &node) };
+            idx >>= 1;
+        }
+        node == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_signer_signs_until_exhausted() {
+        let mut signer = MerkleSigner::new([3u8; 32], 2);
+        let root = signer.public_key();
+        let msg = hash(b"firmware");
+        for _ in 0..4 {
+            let sig = signer.sign(&msg).unwrap();
+            assert!(MerkleSigner::verify(&msg, &sig, &root));
+            assert!(!MerkleSigner::verify(&hash(b"other"), &sig, &root));
+        }
+        assert!(signer.sign(&msg).is_err());
+    }
+
+    #[test]
+    fn test_hash_is_sha256() {
+        let abc = hash(b"abc");
+        assert_eq!(abc[..4], [0xba, 0x78, 0x16, 0xbf]);
+        assert_eq!(abc[28..], [0xf2, 0x00, 0x15, 0xad]);
+    }
+}
@@ -0,0 +1,207 @@
+// =============================================================================
+// FEDERATION CORE — health.rs
+// «Пульс Федерации» — одна цифра здоровья для алертов
+// =============================================================================
+//
+// Сводит четыре подсистемы в оценку 0..1:
+//   Reputation — доля честных узлов: блэклист = 0, каждое предательство ×0.5
+//   Inventory  — доля региональных целей по хабам, выполненных plan_regions
+//   Economy    — остаток эмиссии до MAX_SUPPLY, исчерпана — 0
+//   Ethics     — 1 − системный ViolationScore, полная заморозка — 0
+//
+// Общая оценка — взвешенное среднее. Главный риск — подсистема с
+// худшей оценкой: на неё и смотрит дежурный.
+// =============================================================================
+
+use crate::ethics::{EthicsLayer, KillSwitchState};
+use crate::inventory::{CpuArch, FederationInventory, HardwareProfile, OsType};
+use crate::mint::MintEngine;
+use crate::reputation::{ReputationRegistry, REP_BETRAYAL_SLASH};
+use serde::{Deserialize, Serialize};
+
+pub const HEALTH_WEIGHT_REPUTATION: f64 = 0.30;
+pub const HEALTH_WEIGHT_INVENTORY: f64  = 0.20;
+pub const HEALTH_WEIGHT_ECONOMY: f64    = 0.20;
+pub const HEALTH_WEIGHT_ETHICS: f64     = 0.30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthComponent {
+    Reputation,
+    Inventory,
+    Economy,
+    Ethics,
+}
+
+impl HealthComponent {
+    pub fn name(&self) -> &str {
+        match self {
+            HealthComponent::Reputation => "🏅 Reputation",
+            HealthComponent::Inventory  => "🔩 Inventory",
+            HealthComponent::Economy    => "🪙 Economy",
+            HealthComponent::Ethics     => "⚖️  Ethics",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub overall: f64,
+    pub reputation: f64,
+    pub inventory: f64,
+    pub economy: f64,
+    pub ethics: f64,
+    pub dominant_risk: HealthComponent,
+}
+
+impl HealthReport {
+    pub fn components(&self) -> [(HealthComponent, f64); 4] {
+        [
+            (HealthComponent::Reputation, self.reputation),
+            (HealthComponent::Inventory, self.inventory),
+            (HealthComponent::Economy, self.economy),
+            (HealthComponent::Ethics, self.ethics),
+        ]
+    }
+}
+
+impl std::fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Здоровье Федерации: {:.0}%  главный риск: {}",
+            self.overall * 100.0, self.dominant_risk.name())?;
+        for (c, score) in self.components() {
+            writeln!(f, "  {:16} {:>5.1}%", c.name(), score * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct FederationHealth;
+
+impl FederationHealth {
+    pub fn compute(reputation: &ReputationRegistry, inventory: &FederationInventory,
+                   mint: &MintEngine, ethics: &EthicsLayer) -> HealthReport {
+        let mut report = HealthReport {
+            overall: 0.0,
+            reputation: Self::reputation_score(reputation),
+            inventory: Self::inventory_score(inventory),
+            economy: Self::economy_score(mint),
+            ethics: Self::ethics_score(ethics),
+            dominant_risk: HealthComponent::Reputation,
+        };
+        report.overall = report.reputation * HEALTH_WEIGHT_REPUTATION
+            + report.inventory * HEALTH_WEIGHT_INVENTORY
+            + report.economy * HEALTH_WEIGHT_ECONOMY
+            + report.ethics * HEALTH_WEIGHT_ETHICS;
+        // При равенстве — первый по порядку components()
+        report.dominant_risk = report.components().into_iter()
+            .fold(None, |worst: Option<(HealthComponent, f64)>, (c, s)| match worst {
+                Some((_, w)) if w <= s => worst,
+                _ => Some((c, s)),
+            })
+            .map(|(c, _)| c).unwrap_or(HealthComponent::Reputation);
+        report
+    }
+
+    // Пустой реестр — нет и признаков предательства
+    fn reputation_score(reg: &ReputationRegistry) -> f64 {
+        if reg.nodes.is_empty() { return 1.0; }
+        let sum: f64 = reg.nodes.values().map(|n| {
+            if n.is_blacklisted { 0.0 } else { REP_BETRAYAL_SLASH.powi(n.betrayals as i32) }
+        }).sum();
+        sum / reg.nodes.len() as f64
+    }
+
+    fn inventory_score(inv: &FederationInventory) -> f64 {
+        let targets = inv.distribution.regions.len() * inv.distribution.targets.len();
+        if inv.devices.is_empty() { return 0.0; }
+        if targets == 0 { return 1.0; }
+        let under = inv.plan_regions().imbalances.iter().filter(|i| i.is_under()).count();
+        1.0 - under as f64 / targets as f64
+    }
+
+    fn economy_score(mint: &MintEngine) -> f64 {
+        if mint.is_exhausted { return 0.0; }
+        (1.0 - mint.supply_stats().pct_issued / 100.0).clamp(0.0, 1.0)
+    }
+
+    fn ethics_score(ethics: &EthicsLayer) -> f64 {
+        if matches!(ethics.kill_switch.state, KillSwitchState::FullFreeze { .. }) { return 0.0; }
+        1.0 - ethics.evaluator.system_violation_score.clamp(0.0, 1.0)
+    }
+}
+
+/// Федерация из демо health: два хаба с историей доставок, пять
+/// серверов в инвентаре, 500 эмиссий. Общая для демо и тестов снимка
+pub fn demo_federation() -> (ReputationRegistry, FederationInventory, MintEngine, EthicsLayer) {
+    let mut reg = ReputationRegistry::new();
+    for _ in 0..40 { reg.record_delivery("node_tokyo",  "AikiReflection", 0.85); }
+    for _ in 0..30 { reg.record_delivery("node_berlin", "StandoffDecoy",  0.60); }
+    let mut inv = FederationInventory::new();
+    for (i, region_hub) in ["nexus", "berlin", "tokyo", "toronto", "nairobi"].iter().enumerate() {
+        inv.register(HardwareProfile { device_id: format!("hub-{}", region_hub),
+            cpu_cores: if i % 2 == 0 { 32 } else { 8 }, cpu_mhz: 3200,
+            ram_mb: if i % 2 == 0 { 65536 } else { 16384 }, storage_gb: 500,
+            bandwidth_mbps: 1000, has_gpu: false, battery_powered: false,
+            arch: CpuArch::X86_64, os: OsType::Linux, uptime_days: 120, is_tor_capable: true });
+    }
+    let mut mint = MintEngine::new();
+    mint.simulate_bypasses(500, "node_tokyo", "CN", "AikiReflection", 0.85);
+    (reg, inv, mint, EthicsLayer::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{test_profile, LayerTarget, RegionDistribution};
+
+    fn healthy() -> (ReputationRegistry, FederationInventory, MintEngine, EthicsLayer) {
+        let mut reg = ReputationRegistry::new();
+        for node in ["node_tokyo", "node_berlin", "node_sydney"] {
+            for _ in 0..20 { reg.record_delivery(node, "AikiReflection", 0.8); }
+        }
+        let mut inv = FederationInventory::with_distribution(RegionDistribution {
+            regions: vec!["EU".into(), "AS".into()],
+            targets: vec![
+                LayerTarget { layer: 1, min: 1, max: 2 },
+                LayerTarget { layer: 2, min: 1, max: 2 },
+            ],
+        });
+        for id in ["sentinel-eu", "sentinel-as"] {
+            inv.register(HardwareProfile { cpu_cores: 32, ram_mb: 65536, ..test_profile(id) });
+        }
+        inv.register(test_profile("citadel-eu"));
+        inv.register(test_profile("citadel-as"));
+        let mut mint = MintEngine::new();
+        let ethics = EthicsLayer::new();
+        mint.mint_for_bypass(&ethics, "node_tokyo", "CN", "AikiReflection", 0.85).unwrap();
+        (reg, inv, mint, ethics)
+    }
+
+    #[test]
+    fn test_healthy_federation_scores_high() {
+        let (reg, inv, mint, ethics) = healthy();
+        let report = FederationHealth::compute(&reg, &inv, &mint, &ethics);
+        assert!(report.overall > 0.99, "{}", report);
+        assert_eq!(report.dominant_risk, HealthComponent::Economy);
+    }
+
+    #[test]
+    fn test_betrayals_and_violations_flip_dominant_risk() {
+        let (mut reg, inv, mint, mut ethics) = healthy();
+        let baseline = FederationHealth::compute(&reg, &inv, &mint, &ethics).overall;
+
+        for node in ["node_tokyo", "node_berlin"] {
+            for i in 0..3 { reg.record_betrayal(node, &format!("evidence_{}", i)); }
+        }
+        let betrayed = FederationHealth::compute(&reg, &inv, &mint, &ethics);
+        assert_eq!(betrayed.dominant_risk, HealthComponent::Reputation);
+        assert!(betrayed.overall < baseline - 0.15);
+
+        let (reg, ..) = healthy();
+        ethics.evaluator.system_violation_score = 0.97;
+        let violating = FederationHealth::compute(&reg, &inv, &mint, &ethics);
+        assert_eq!(violating.dominant_risk, HealthComponent::Ethics);
+        assert!(violating.ethics < 0.05);
+        assert!(violating.overall < baseline - 0.25);
+    }
+}
@@ -14,10 +14,28 @@
 //   Mobile     — телефон/планшет (≥2 CPU, ≥2GB RAM) → лёгкий узел
 //   Ghost      — старое железо (любое) → шум + приманки
 //   Droid      — IoT/роутер (≤2 CPU, ≤512MB RAM) → меш-реле
+//
+// Порядок проверки фиксирован, первое сработавшее правило решает:
+//   1. ≤512MB RAM или OpenWrt           → Droid
+//   2. батарея / Android / iOS          → Mobile (≥2 CPU, ≥2GB) иначе Ghost
+//   3. ≥16 CPU, ≥32GB, стабилен         → Sentinel
+//   4. ≥8 CPU, ≥16GB, стабилен          → Citadel
+//   5. ≥4 CPU, ≥8GB                     → Workstation
+//   6. остальное                        → Ghost
+// Пороги включительные: устройство ровно на границе получает старшую
+// роль. Прошедший порог Citadel/Sentinel без стабильности (аптайм ≤7д)
+// опускается до Workstation.
+//
+// Аттестация: профиль подписывается одноразовой хеш-подписью (WOTS,
+// w=16) над fingerprint неизменяемых полей. Ghost не выдаст себя за
+// Sentinel — изменённые поля дадут другой fingerprint.
 // =============================================================================
 
+use crate::hashsig::{hash, WotsKey, WOTS_SIGNATURE_BYTES};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+pub const ATTESTATION_BYTES: usize = WOTS_SIGNATURE_BYTES;
 
 // -----------------------------------------------------------------------------
 // HardwareProfile — характеристики железа
@@ -74,6 +92,46 @@ impl HardwareProfile {
     pub fn is_stable(&self) -> bool {
         !self.battery_powered && self.uptime_days > 7
     }
+
+    /// Отпечаток неизменяемых характеристик железа. Аптайм, полоса и
+    /// Tor меняются в работе и в отпечаток не входят
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut buf = b"federation-hw-v1".to_vec();
+        buf.extend_from_slice(&(self.device_id.len() as u32).to_le_bytes());
+        buf.extend_from_slice(self.device_id.as_bytes());
+        for v in [self.cpu_cores, self.cpu_mhz, self.ram_mb, self.storage_gb] {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        buf.push(self.has_gpu as u8);
+        buf.push(self.battery_powered as u8);
+        buf.push(self.arch.clone() as u8);
+        buf.push(self.os.clone() as u8);
+        hash(&buf)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// AttestationKey — одноразовая хеш-подпись профиля (WOTS)
+// -----------------------------------------------------------------------------
+
+pub struct AttestationKey {
+    key: WotsKey,
+}
+
+impl AttestationKey {
+    pub fn from_seed(seed: [u8; 32]) -> Self { AttestationKey { key: WotsKey::from_seed(seed) } }
+
+    pub fn public_key(&self) -> [u8; 32] { self.key.public_key() }
+
+    /// Подписать профиль. Ключ одноразовый: две подписи раскрывают
+    /// достаточно звеньев цепочек для подделки
+    pub fn sign(&self, profile: &HardwareProfile) -> Vec<u8> {
+        self.key.sign(&profile.fingerprint())
+    }
+
+    pub fn verify(profile: &HardwareProfile, signature: &[u8], pubkey: &[u8; 32]) -> bool {
+        WotsKey::verify(&profile.fingerprint(), signature, pubkey)
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -179,32 +237,71 @@ impl DeviceRole {
 
 pub struct RoleClassifier;
 
+pub const DROID_MAX_RAM_MB: u32       = 512;
+pub const MOBILE_MIN_CORES: u32       = 2;
+pub const MOBILE_MIN_RAM_MB: u32      = 2048;
+pub const SENTINEL_MIN_CORES: u32     = 16;
+pub const SENTINEL_MIN_RAM_MB: u32    = 32768;
+pub const CITADEL_MIN_CORES: u32      = 8;
+pub const CITADEL_MIN_RAM_MB: u32     = 16384;
+pub const WORKSTATION_MIN_CORES: u32  = 4;
+pub const WORKSTATION_MIN_RAM_MB: u32 = 8192;
+
 impl RoleClassifier {
     pub fn classify(hw: &HardwareProfile) -> DeviceRole {
-        // IoT/роутер — определяем по RAM и ОС
-        if hw.ram_mb <= 512 || hw.os == OsType::OpenWrt {
-            return DeviceRole::Droid;
+        Self::classify_with_reason(hw).0
+    }
+
+    /// Роль и критерий, который её решил. Правила проверяются в порядке
+    /// из шапки модуля — от него не зависит ветвление вызывающего кода
+    pub fn classify_with_reason(hw: &HardwareProfile) -> (DeviceRole, String) {
+        // 1. IoT/роутер — по RAM и ОС
+        if hw.ram_mb <= DROID_MAX_RAM_MB {
+            return (DeviceRole::Droid,
+                format!("RAM {}MB ≤ {}MB → меш-реле", hw.ram_mb, DROID_MAX_RAM_MB));
+        }
+        if hw.os == OsType::OpenWrt {
+            return (DeviceRole::Droid, "ОС OpenWrt → меш-реле".into());
         }
-        // Мобильные устройства
-        if hw.battery_powered || hw.os == OsType::Android || hw.os == OsType::Ios {
-            if hw.cpu_cores >= 2 && hw.ram_mb >= 2048 {
-                return DeviceRole::Mobile;
+        // 2. Мобильные устройства
+        let mobile_os = hw.os == OsType::Android || hw.os == OsType::Ios;
+        if hw.battery_powered || mobile_os {
+            let why = if hw.battery_powered { "питание от батареи" }
+                      else { "мобильная ОС" };
+            if hw.cpu_cores >= MOBILE_MIN_CORES && hw.ram_mb >= MOBILE_MIN_RAM_MB {
+                return (DeviceRole::Mobile, format!("{}, ≥{} CPU и ≥{}MB RAM",
+                    why, MOBILE_MIN_CORES, MOBILE_MIN_RAM_MB));
             }
-            return DeviceRole::Ghost;
+            return (DeviceRole::Ghost, format!("{}, ниже минимума Mobile ({} CPU, {}MB RAM)",
+                why, MOBILE_MIN_CORES, MOBILE_MIN_RAM_MB));
         }
-        // Классификация по мощности
-        let score = hw.compute_score();
-        if hw.cpu_cores >= 16 && hw.ram_mb >= 32768 && hw.is_stable() {
-            DeviceRole::Sentinel
-        } else if hw.cpu_cores >= 8 && hw.ram_mb >= 16384 && hw.is_stable() {
-            DeviceRole::Citadel
-        } else if hw.cpu_cores >= 4 && hw.ram_mb >= 8192 {
-            DeviceRole::Workstation
-        } else if score > 5.0 {
-            DeviceRole::Ghost  // старое железо → шум
-        } else {
-            DeviceRole::Ghost
+        // 3–5. Классификация по мощности: старшая роль выигрывает
+        let tiers = [
+            (DeviceRole::Sentinel, SENTINEL_MIN_CORES, SENTINEL_MIN_RAM_MB),
+            (DeviceRole::Citadel,  CITADEL_MIN_CORES,  CITADEL_MIN_RAM_MB),
+        ];
+        let mut unstable = None;
+        for (role, cores, ram) in tiers {
+            if hw.cpu_cores >= cores && hw.ram_mb >= ram {
+                if hw.is_stable() {
+                    return (role, format!("≥{} CPU и ≥{}MB RAM, стабилен (аптайм {}д)",
+                        cores, ram, hw.uptime_days));
+                }
+                unstable.get_or_insert(role);
+            }
+        }
+        if hw.cpu_cores >= WORKSTATION_MIN_CORES && hw.ram_mb >= WORKSTATION_MIN_RAM_MB {
+            let reason = match unstable {
+                Some(role) => format!("железо уровня {:?}, но не стабилен (аптайм {}д ≤ 7д)",
+                    role, hw.uptime_days),
+                None => format!("≥{} CPU и ≥{}MB RAM",
+                    WORKSTATION_MIN_CORES, WORKSTATION_MIN_RAM_MB),
+            };
+            return (DeviceRole::Workstation, reason);
         }
+        // 6. Старое железо → шум
+        (DeviceRole::Ghost, format!("ниже минимума Workstation ({} CPU, {}MB RAM)",
+            WORKSTATION_MIN_CORES, WORKSTATION_MIN_RAM_MB))
     }
 
     pub fn classify_batch(devices: &[HardwareProfile]) -> Vec<(&HardwareProfile, DeviceRole)> {
@@ -269,28 +366,138 @@ impl NodeCapacity {
 // FederationInventory — реестр всего железа
 // -----------------------------------------------------------------------------
 
+pub const REGIONS: [&str; 5] = ["EU", "AS", "AM", "AF", "OC"];
+
+/// Сколько хабов слоя `layer` должно быть в каждом регионе
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerTarget {
+    pub layer: u8,
+    pub min: u32,   // меньше — регион недообеспечен
+    pub max: u32,   // больше не назначаем
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionDistribution {
+    pub regions: Vec<String>,
+    pub targets: Vec<LayerTarget>,
+}
+
+impl RegionDistribution {
+    pub fn target(&self, layer: u8) -> Option<&LayerTarget> {
+        self.targets.iter().find(|t| t.layer == layer)
+    }
+}
+
+impl Default for RegionDistribution {
+    fn default() -> Self {
+        RegionDistribution {
+            regions: REGIONS.iter().map(|r| r.to_string()).collect(),
+            targets: vec![
+                LayerTarget { layer: DeviceRole::Sentinel.layer(), min: 1, max: 2 },
+                LayerTarget { layer: DeviceRole::Citadel.layer(),  min: 1, max: 4 },
+            ],
+        }
+    }
+}
+
+/// Регион, где число хабов слоя вышло за цель
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionImbalance {
+    pub region: String,
+    pub layer: u8,
+    pub count: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl RegionImbalance {
+    pub fn is_under(&self) -> bool { self.count < self.min }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionPlan {
+    pub assignments: Vec<RegionAssignment>,
+    pub imbalances: Vec<RegionImbalance>,
+    pub unplaced: Vec<String>,   // хабы сверх max во всех регионах
+}
+
+impl RegionPlan {
+    pub fn count(&self, region: &str, layer: u8) -> u32 {
+        self.assignments.iter()
+            .filter(|a| a.region == region && a.layer == layer).count() as u32
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.imbalances.is_empty() && self.unplaced.is_empty()
+    }
+}
+
 pub struct FederationInventory {
     pub devices: HashMap<String, HardwareProfile>,
     pub capacities: HashMap<String, NodeCapacity>,
     pub role_counts: HashMap<String, u32>,
+    pub attested: HashSet<String>,            // профили, прошедшие проверку подписи
+    attestation_keys: HashMap<String, [u8; 32]>, // device_id → закреплённый pubkey
+    pub distribution: RegionDistribution,
 }
 
 impl FederationInventory {
     pub fn new() -> Self {
+        Self::with_distribution(RegionDistribution::default())
+    }
+
+    pub fn with_distribution(distribution: RegionDistribution) -> Self {
         FederationInventory {
             devices: HashMap::new(),
             capacities: HashMap::new(),
             role_counts: HashMap::new(),
+            attested: HashSet::new(),
+            attestation_keys: HashMap::new(),
+            distribution,
+        }
+    }
+
+    /// Закрепить ключ аттестации устройства (выдаётся при вводе в строй).
+    /// Один раз: подменить ключ уже закреплённого устройства нельзя
+    pub fn pin_attestation_key(&mut self, device_id: &str, pubkey: [u8; 32])
+        -> Result<(), &'static str> {
+        if self.attestation_keys.contains_key(device_id) {
+            return Err("ключ аттестации уже закреплён");
+        }
+        self.attestation_keys.insert(device_id.to_string(), pubkey);
+        Ok(())
+    }
+
+    /// Регистрация с проверкой подписи: профиль получает роль, только
+    /// если подпись закреплённым ключом сходится с fingerprint его
+    /// неизменяемых полей
+    pub fn register_attested(&mut self, hw: HardwareProfile, signature: &[u8])
+        -> Result<&NodeCapacity, &'static str> {
+        let pubkey = self.attestation_keys.get(&hw.device_id)
+            .ok_or("ключ аттестации не закреплён")?;
+        if !AttestationKey::verify(&hw, signature, pubkey) {
+            return Err("подпись профиля не сошлась");
         }
+        let id = hw.device_id.clone();
+        self.register(hw);
+        self.attested.insert(id.clone());
+        Ok(&self.capacities[&id])
     }
 
+    pub fn is_attested(&self, device_id: &str) -> bool {
+        self.attested.contains(device_id)
+    }
+
+    /// Регистрация без подписи — прежняя аттестация профиля снимается
     pub fn register(&mut self, hw: HardwareProfile) -> &NodeCapacity {
         let capacity = NodeCapacity::from_profile(&hw);
         let role_name = capacity.role.name().to_string();
         *self.role_counts.entry(role_name).or_insert(0) += 1;
-        self.devices.insert(hw.device_id.clone(), hw);
-        self.capacities.insert(capacity.device_id.clone(), capacity);
-        self.capacities.values().last().unwrap()
+        let id = hw.device_id.clone();
+        self.attested.remove(&id);
+        self.devices.insert(id.clone(), hw);
+        self.capacities.insert(id.clone(), capacity);
+        &self.capacities[&id]
     }
 
     pub fn get_by_role(&self, role: &DeviceRole) -> Vec<&NodeCapacity> {
@@ -326,23 +533,52 @@ impl FederationInventory {
         }
     }
 
-    pub fn auto_assign_regions(&self) -> Vec<RegionAssignment> {
-        // Sentinel и Citadel становятся региональными координаторами
-        let mut assignments = vec![];
-        let regions = ["EU", "AS", "AM", "AF", "OC"];
-        let hubs: Vec<&NodeCapacity> = self.capacities.values()
+    /// Sentinel и Citadel становятся региональными координаторами.
+    /// Хаб идёт в регион, где его слоя меньше всего и max ещё не
+    /// достигнут; при равенстве — порядок regions. Сильные хабы
+    /// распределяются первыми
+    pub fn plan_regions(&self) -> RegionPlan {
+        let dist = &self.distribution;
+        let mut hubs: Vec<&NodeCapacity> = self.capacities.values()
             .filter(|c| c.role == DeviceRole::Sentinel
                      || c.role == DeviceRole::Citadel)
             .collect();
-        for (i, hub) in hubs.iter().enumerate() {
-            assignments.push(RegionAssignment {
-                device_id: hub.device_id.clone(),
-                role: hub.role.clone(),
-                region: regions[i % regions.len()].to_string(),
-                layer: hub.layer,
-            });
+        hubs.sort_by(|a, b| a.layer.cmp(&b.layer)
+            .then(b.hardware_score.total_cmp(&a.hardware_score))
+            .then(a.device_id.cmp(&b.device_id)));
+
+        let mut counts: HashMap<(usize, u8), u32> = HashMap::new();
+        let mut assignments = vec![];
+        let mut unplaced = vec![];
+        for hub in hubs {
+            let max = dist.target(hub.layer).map(|t| t.max).unwrap_or(u32::MAX);
+            let region = (0..dist.regions.len())
+                .filter(|&r| counts.get(&(r, hub.layer)).copied().unwrap_or(0) < max)
+                .min_by_key(|&r| counts.get(&(r, hub.layer)).copied().unwrap_or(0));
+            match region {
+                Some(r) => {
+                    *counts.entry((r, hub.layer)).or_insert(0) += 1;
+                    assignments.push(RegionAssignment {
+                        device_id: hub.device_id.clone(),
+                        role: hub.role.clone(),
+                        region: dist.regions[r].clone(),
+                        layer: hub.layer,
+                    });
+                }
+                None => unplaced.push(hub.device_id.clone()),
+            }
         }
-        assignments
+
+        let counts = &counts;
+        let imbalances = dist.regions.iter().enumerate().flat_map(|(r, region)| {
+            dist.targets.iter().filter_map(move |t| {
+                let count = counts.get(&(r, t.layer)).copied().unwrap_or(0);
+                (count < t.min || count > t.max).then(|| RegionImbalance {
+                    region: region.clone(), layer: t.layer, count, min: t.min, max: t.max,
+                })
+            })
+        }).collect();
+        RegionPlan { assignments, imbalances, unplaced }
     }
 }
 
@@ -364,7 +600,7 @@ pub struct TopologyStats {
     pub zk_capable: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionAssignment {
     pub device_id: String,
     pub role: DeviceRole,
@@ -392,3 +628,167 @@ impl std::fmt::Display for TopologyStats {
         )
     }
 }
+
+/// Базовый профиль для тестов всех модулей: x86-сервер на Linux от сети.
+/// Отличия задаются struct-update: `HardwareProfile { cpu_cores: 32, ..test_profile(id) }`
+#[cfg(test)]
+pub(crate) fn test_profile(device_id: &str) -> HardwareProfile {
+    HardwareProfile {
+        device_id: device_id.into(), cpu_cores: 8, cpu_mhz: 3000, ram_mb: 16384,
+        storage_gb: 500, bandwidth_mbps: 1000, has_gpu: false,
+        battery_powered: false, arch: CpuArch::X86_64, os: OsType::Linux,
+        uptime_days: 100, is_tor_capable: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentinel() -> HardwareProfile {
+        HardwareProfile {
+            cpu_cores: 32, cpu_mhz: 3500, ram_mb: 65536, storage_gb: 4000,
+            bandwidth_mbps: 10_000, has_gpu: true, uptime_days: 365,
+            ..test_profile("nexus-core-01")
+        }
+    }
+
+    #[test]
+    fn test_register_attested_accepts_signed_profile() {
+        let key = AttestationKey::from_seed([7u8; 32]);
+        let hw = sentinel();
+        let sig = key.sign(&hw);
+        assert_eq!(sig.len(), ATTESTATION_BYTES);
+
+        let mut inv = FederationInventory::new();
+        // Без закреплённого ключа подпись проверить нечем
+        assert!(inv.register_attested(hw.clone(), &sig).is_err());
+        inv.pin_attestation_key("nexus-core-01", key.public_key()).unwrap();
+
+        // Аптайм не входит в отпечаток — подпись остаётся валидной
+        let mut later = hw.clone();
+        later.uptime_days += 30;
+        let cap = inv.register_attested(later, &sig).unwrap();
+        assert_eq!(cap.role, DeviceRole::Sentinel);
+        assert!(inv.is_attested("nexus-core-01"));
+
+        // Перерегистрация без подписи снимает аттестацию
+        inv.register(hw);
+        assert!(!inv.is_attested("nexus-core-01"));
+    }
+
+    #[test]
+    fn test_register_attested_rejects_altered_profile() {
+        let key = AttestationKey::from_seed([7u8; 32]);
+        let mut ghost = sentinel();
+        ghost.device_id = "old-laptop".into();
+        ghost.cpu_cores = 2;
+        ghost.ram_mb = 4096;
+        let sig = key.sign(&ghost);
+
+        // Ghost подменил поля после подписи, чтобы стать Sentinel
+        let mut spoofed = ghost.clone();
+        spoofed.cpu_cores = 32;
+        spoofed.ram_mb = 65536;
+        let mut inv = FederationInventory::new();
+        inv.pin_attestation_key("old-laptop", key.public_key()).unwrap();
+        assert!(inv.register_attested(spoofed, &sig).is_err());
+        assert!(inv.devices.is_empty());
+
+        // Подменить закреплённый ключ своим нельзя — подпись чужим ключом не проходит
+        let other = AttestationKey::from_seed([9u8; 32]);
+        assert!(inv.pin_attestation_key("old-laptop", other.public_key()).is_err());
+        assert!(inv.register_attested(ghost.clone(), &other.sign(&ghost)).is_err());
+        assert!(!inv.is_attested("old-laptop"));
+    }
+
+    fn device(id: &str, cores: u32, ram_mb: u32, battery: bool, os: OsType,
+              uptime_days: u32) -> HardwareProfile {
+        HardwareProfile {
+            cpu_cores: cores, cpu_mhz: 2000, ram_mb, storage_gb: 100, bandwidth_mbps: 100,
+            battery_powered: battery, os, uptime_days, is_tor_capable: false,
+            ..test_profile(id)
+        }
+    }
+
+    #[test]
+    fn test_demo_fleet_roles_and_reasons() {
+        use OsType::*;
+        let fleet = [
+            (device("nexus-core-01", 32, 65536, false, Linux, 365),   DeviceRole::Sentinel, "стабилен"),
+            (device("hub-berlin-01", 16, 32768, false, Linux, 180),   DeviceRole::Sentinel, "≥16 CPU"),
+            (device("hub-tokyo-01", 8, 16384, false, FreeBsd, 90),    DeviceRole::Citadel, "≥8 CPU"),
+            (device("work-alice", 8, 16384, false, Linux, 30),        DeviceRole::Citadel, "≥16384MB"),
+            (device("work-bob", 4, 8192, false, Windows, 14),         DeviceRole::Workstation, "≥4 CPU"),
+            (device("phone-carol", 8, 6144, true, Android, 1),        DeviceRole::Mobile, "батаре"),
+            (device("phone-dave", 4, 3072, true, Ios, 0),             DeviceRole::Mobile, "батаре"),
+            (device("ghost-pentium", 2, 2048, false, Linux, 730),     DeviceRole::Ghost, "Workstation"),
+            (device("ghost-pi3", 4, 1024, false, Linux, 500),         DeviceRole::Ghost, "Workstation"),
+            (device("router-openwrt", 2, 256, false, OpenWrt, 60),    DeviceRole::Droid, "RAM 256MB"),
+            (device("droid-esp32", 2, 1, true, Unknown, 120),         DeviceRole::Droid, "RAM 1MB"),
+        ];
+        for (hw, role, factor) in &fleet {
+            let (got, reason) = RoleClassifier::classify_with_reason(hw);
+            assert_eq!(&got, role, "{}", hw.device_id);
+            assert!(reason.contains(factor), "{}: {}", hw.device_id, reason);
+        }
+    }
+
+    #[test]
+    fn test_tie_breaks_follow_precedence() {
+        // Ровно на порогах Citadel и Workstation — старшая роль
+        let edge = device("edge", CITADEL_MIN_CORES, CITADEL_MIN_RAM_MB, false, OsType::Linux, 8);
+        assert_eq!(RoleClassifier::classify(&edge), DeviceRole::Citadel);
+        // Тот же сервер без стабильности — Workstation, причина — аптайм
+        let fresh = HardwareProfile { uptime_days: 7, ..edge.clone() };
+        let (role, reason) = RoleClassifier::classify_with_reason(&fresh);
+        assert_eq!(role, DeviceRole::Workstation);
+        assert!(reason.contains("Citadel") && reason.contains("не стабилен"));
+        // Малая RAM важнее мобильной ОС, OpenWrt важнее мощности
+        let tiny = device("tiny", 4, DROID_MAX_RAM_MB, true, OsType::Android, 1);
+        assert_eq!(RoleClassifier::classify(&tiny), DeviceRole::Droid);
+        let big_router = device("big", 16, 32768, false, OsType::OpenWrt, 100);
+        let (role, reason) = RoleClassifier::classify_with_reason(&big_router);
+        assert_eq!(role, DeviceRole::Droid);
+        assert!(reason.contains("OpenWrt"));
+        // Мобильная ОС без батареи — всё равно мобильная ветка
+        let tablet = device("tablet", 1, 4096, false, OsType::Ios, 3);
+        let (role, reason) = RoleClassifier::classify_with_reason(&tablet);
+        assert_eq!(role, DeviceRole::Ghost);
+        assert!(reason.contains("мобильная ОС"));
+    }
+
+    #[test]
+    fn test_plan_regions_spreads_sentinels_within_caps() {
+        let dist = RegionDistribution {
+            regions: vec!["EU".into(), "AS".into(), "AM".into()],
+            targets: vec![LayerTarget { layer: 1, min: 1, max: 2 }],
+        };
+        let mut inv = FederationInventory::with_distribution(dist);
+        for i in 0..3 {
+            inv.register(HardwareProfile { device_id: format!("sentinel-{}", i), ..sentinel() });
+        }
+        let plan = inv.plan_regions();
+        for region in ["EU", "AS", "AM"] {
+            assert_eq!(plan.count(region, 1), 1, "{}", region);
+        }
+        assert!(plan.is_balanced());
+
+        // 7 Sentinel на 3 региона по max 2 — один лишний, никто не выше cap
+        for i in 3..7 {
+            inv.register(HardwareProfile { device_id: format!("sentinel-{}", i), ..sentinel() });
+        }
+        let plan = inv.plan_regions();
+        for region in ["EU", "AS", "AM"] {
+            assert_eq!(plan.count(region, 1), 2);
+        }
+        assert_eq!(plan.unplaced.len(), 1);
+
+        // Один Sentinel — два региона недообеспечены
+        let mut lone = FederationInventory::with_distribution(inv.distribution.clone());
+        lone.register(sentinel());
+        let under: Vec<String> = lone.plan_regions().imbalances.into_iter()
+            .filter(|i| i.is_under()).map(|i| i.region).collect();
+        assert_eq!(under, vec!["AS", "AM"]);
+    }
+}
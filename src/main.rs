@@ -31,6 +31,12 @@ mod transport;
 mod veil_breaker;
 mod demos;
 mod constants;
+mod rng;
+mod hashsig;
+mod error;
+mod health;
+mod snapshot;
+mod metrics;
 
 #[tokio::main]
 async fn main() {
@@ -73,6 +79,7 @@ async fn main() {
         "dash"       => { run_dashboard_demo().await; }
         "war"        => { run_war_demo().await; }
         "noise"      => { run_noise_demo().await; }
+        "health"     => { run_health_demo().await; }
          // === NOVÉ: Demo phases ===
     "phase1"     => { demos::phase01_neural::demo_phase1().await; }
     "phase2"     => { demos::phase02_crypto::demo_phase2().await; }
@@ -117,8 +124,18 @@ pub async fn run_neural_demo() {
     let input = NeuralInput { latency:0.3, bandwidth:0.8,
         reliability:0.9, trust:0.7, ethics_score:1.0 };
     let _target = NeuralTarget::success_route(0.9);
+    // Обучение от транспорта: ack реального фрейма → DeliveryReport
     for neighbor in &["node_berlin","node_tokyo","node_paris"] {
-        router.train_on_delivery(neighbor, &input, true, 0.9);
+        let mut ch = crate::transport::TransportChannel::new("nexus-core-01", neighbor);
+        ch.set_route_input(input.clone());
+        let frame_id = ch.send_with_decoys(b"PING", "HttpsRequest", 2).iter()
+            .find(|r| !r.is_decoy).map(|r| r.frame_id).unwrap_or_default();
+        let now = ch.clock.now_us() + crate::transport::MAX_JITTER_US;
+        ch.flush_at(now);
+        if let Some(report) = ch.ack(frame_id, now + 200_000) {
+            println!("  ack {} → quality={:.2}", neighbor, report.quality);
+            router.ingest_report(&report);
+        }
     }
     let candidates = vec![
         ("node_berlin".to_string(), input.clone()),
@@ -140,6 +157,9 @@ pub async fn run_federated_demo() {
         if let Some(r) = net.run_round() {
             println!("Раунд {}: loss={:.4} accuracy={:.4} участников={}",
                 i, r.avg_local_loss, r.avg_local_accuracy, r.participants);
+            if let Some((node, loss, _)) = r.worst_node() {
+                println!("  худший: {} loss={:.4}", node, loss);
+            }
         }
     }
     println!("{}", net.stats());
@@ -165,27 +185,24 @@ pub async fn run_mutation_demo() {
 }
 
 pub async fn run_neural_tactics_demo() {
-    use crate::neural_node::{NeuralState, NeuralInput, NeuralTactic};
+    use crate::neural_node::{tactic_scenarios, NeuralState, NeuralTactic, TACTIC_DEMO_EPOCHS};
     
     println!("\n=== Neural Tactics Demo ===\n");
-    let state = NeuralState::new("nexus-core-01");
-    let scenarios = vec![
-        (NeuralInput { latency:0.05, bandwidth:0.95, reliability:0.99, trust:0.95, ethics_score:1.0 }, "Чистый канал"),
-        (NeuralInput { latency:0.70, bandwidth:0.30, reliability:0.50, trust:0.40, ethics_score:0.9 }, "DPI активен"),
-        (NeuralInput { latency:0.92, bandwidth:0.08, reliability:0.15, trust:0.10, ethics_score:0.9 }, "Полная блокировка"),
-        (NeuralInput { latency:0.55, bandwidth:0.55, reliability:0.75, trust:0.65, ethics_score:1.0 }, "Узкое окно"),
-        (NeuralInput { latency:0.85, bandwidth:0.15, reliability:0.25, trust:0.15, ethics_score:0.9 }, "Зондирование"),
-    ];
+    let scenarios = tactic_scenarios();
+    let samples: Vec<_> = scenarios.iter()
+        .map(|(_, input, target)| (input.clone(), target.clone())).collect();
+    let state = NeuralState::trained_on("nexus-core-01", &samples, TACTIC_DEMO_EPOCHS);
+    println!("   Сеть обучена на {} сценариях × {} эпох\n", samples.len(), TACTIC_DEMO_EPOCHS);
     println!("   {:>40}  {:>6} {:>6} {:>6}  Тактика", "Сценарий", "decoy", "strike", "cong");
     println!("   {}", "─".repeat(70));
-    for (input, scenario) in &scenarios {
+    for (scenario, input, _) in &scenarios {
         let out = state.forward(input);
-        let tactic = NeuralTactic::decide_from_input(
+        let (tactic, confidence) = NeuralTactic::decide_from_input(
             input.latency, out.congestion_prob,
             out.decoy_intensity, out.strike_focus);
-        println!("   {:>40}  {:>6.3} {:>6.3} {:>6.3}  [{}]",
+        println!("   {:>40}  {:>6.3} {:>6.3} {:>6.3}  [{}] {:.0}%",
             scenario, out.decoy_intensity, out.strike_focus,
-            out.congestion_prob, tactic.name());
+            out.congestion_prob, tactic.name(), confidence * 100.0);
     }
 }
 
@@ -223,22 +240,66 @@ pub async fn run_ethics_aiki_demo() {
             is_first_strike:true, has_evidence:false,
             target_is_censor:true, tactic:"ResourceExhaustion".into() },
          "Превентивная атака без доказательств."),
+        ("Избыточный ответ слабому цензору", EthicsAction::AikiResponse {
+            censor_aggression:0.3, response_intensity:0.95,
+            is_first_strike:false, has_evidence:true,
+            target_is_censor:true, tactic:"CumulativeStrike".into() },
+         "Цензор KZ троттлит. Запрошен удар почти на полную мощность."),
     ];
     for (name, action, reasoning) in cases {
+        let rule = action.aiki_rule();
         let v = ethics.check(action, reasoning);
         println!("  {} [{}] score={:.3} — {}",
             if v.allowed {"✅"} else {"🚫"}, name, v.violation_score, v.reason);
+        // Тактика исполняется с урезанной интенсивностью, не с запрошенной
+        if let (true, Some(rule)) = (v.allowed, rule) {
+            let executed = EthicsLayer::clamp_response(rule.response_intensity, &rule);
+            println!("     интенсивность: запрошено {:.2} → исполнено {:.2} (cap {:.2})",
+                rule.response_intensity, executed, rule.allowed_response_intensity());
+        }
     }
     println!("\n{}", ethics.audit.stats());
+
+    // KillSwitch: замороженные модули отказываются действовать
+    use crate::ethics::MODULE_AI_ROUTER;
+    use crate::mint::MintEngine;
+    use crate::oracle::OracleClient;
+    use crate::routing::{AiRouter, UserPriorities};
+    let mut router = AiRouter::new();
+    let mut oracle = OracleClient::new("nexus-core-01");
+    let mut mint = MintEngine::new();
+    ethics.kill_switch.sovereign_freeze("DAO-42", "аудит маршрутизатора",
+        vec![MODULE_AI_ROUTER.into()]);
+    println!("\n  {}", ethics.status());
+    for _ in 0..2 {
+        let route = router.select_route(&ethics, "node_berlin", vec![],
+            &UserPriorities::balanced());
+        let fetch = oracle.request(&ethics, "https://api.federation/status", &["public"]);
+        let minted = mint.mint_for_bypass(&ethics, "node_tokyo", "CN", "AikiReflection", 0.85);
+        for (module, result) in [("ai_router", route.map(|_| ())),
+                                 ("oracle", fetch.map(|_| ())),
+                                 ("mint", minted.map(|_| ()))] {
+            match result {
+                Ok(())  => println!("  ✅ {:9} работает", module),
+                Err(e)  => println!("  {}", e),
+            }
+        }
+        if ethics.kill_switch.thaw("DAO-43") {
+            println!("\n  🔓 DAO-43: разморозка\n  {}", ethics.status());
+        }
+    }
 }
 
 pub async fn run_transport_demo() {
-    use crate::transport::{TransportChannel, MicroClock};
+    use crate::transport::{LoopbackSink, MicroClock, TransportChannel, MAX_JITTER_US};
     println!("\n=== Transport Layer Demo ===\n");
     let mut clock = MicroClock::new();
     println!("MicroClock: {}мкс  jitter={}мкс",
         clock.now_us(), clock.jitter_us(100, 50_000));
-    let mut ch = TransportChannel::new("nexus-core-01", "node_berlin");
+    // Вместо UDP/TCP — loopback: видно, какие байты ушли бы в провод
+    let wire = LoopbackSink::new();
+    let mut ch = TransportChannel::with_sink("nexus-core-01", "node_berlin",
+        Box::new(wire.clone()));
     let payload = b"FEDERATION_SECURE_DATA";
     let results = ch.send_with_decoys(payload, "HttpsRequest", 6);
     println!("Отправлено {} пакетов (1 реальный + 6 коробочек)", results.len());
@@ -246,6 +307,16 @@ pub async fn run_transport_demo() {
         println!("  {} jitter={}мкс  mask={}",
             if r.is_decoy {"🎭"} else {"📦"}, r.jitter_applied_us, r.mask_type);
     }
+    let flushed = ch.flush_at(ch.clock.now_us() + MAX_JITTER_US);
+    let wire_bytes: usize = std::iter::from_fn(|| wire.recv()).map(|b| b.len()).sum();
+    println!("В провод ушло {} фреймов, {} байт", flushed.len(), wire_bytes);
+    for threat in [0.0, 0.5, 0.95] {
+        let sent = ch.send_adaptive(payload, "HttpsRequest", threat);
+        println!("  угроза Mirage {:.2} → {} коробочек", threat, sent.len() - 1);
+    }
+    let pulse = ch.enqueue_within(b"PULSE", "raw", false, None, Some(2_000));
+    println!("  срочный Pulse, бюджет 2000мкс → jitter={}мкс", pulse.jitter_applied_us);
+    ch.flush_at(ch.clock.now_us() + MAX_JITTER_US);
     println!("\n{}", ch.stats());
 
     // ── HIERARCHICAL ROUTING DEMO ──────────────────────────────────────
@@ -306,9 +377,9 @@ pub async fn run_veil_breaker() {
 }
 
 pub async fn run_credits_demo() {
-    use crate::credits::{CreditLedger, known_regions};
+    use crate::credits::{CreditLedger, RegionRegistry};
     println!("\n=== Proof-of-Bypass Credits ===\n");
-    let regions = known_regions();
+    let mut registry = RegionRegistry::new();
     let mut ledger = CreditLedger::new();
     let events = vec![
         ("node_tokyo",   "CN", "AikiReflection",   60u64, 0.85f64, true),
@@ -320,18 +391,30 @@ pub async fn run_credits_demo() {
     println!("   {:16} {:>4} {:>18} {:>8}  Credits", "Узел","Рег.","Тактика","Пакеты");
     println!("   {}", "─".repeat(60));
     for (node, region, tactic, packets, cpu, evidence) in &events {
-        if let Some(diff) = regions.get(*region) {
+        if let Some(diff) = registry.get(region) {
             let c = ledger.record_bypass(node, region, tactic,
                 *packets, *cpu, diff, *evidence);
             println!("   {:16} {:>4} {:>18} {:>8}  {:.3} 💎", node, region, tactic, packets, c);
         }
     }
     println!("\n{}", ledger.stats());
+
+    // В DE начались блокировки — сложность растёт с каждым наблюдением
+    println!("\n   DE: {:.3} {}", registry.difficulty("DE"), registry.get("DE").unwrap().label());
+    for day in 1..=5 {
+        let score = registry.observe_block_rate("DE", 0.9);
+        println!("   день {}: блокировки 90% → сложность {:.3} {}",
+            day, score, registry.get("DE").unwrap().label());
+    }
 }
 
 pub async fn run_market_demo() {
-    use crate::market::{BandwidthMarket, TrafficTier};
+    use crate::market::{BandwidthMarket, NodeOffer, TrafficTier};
+    use crate::reputation::ReputationRegistry;
     println!("\n=== Bandwidth Market Demo ===\n");
+    let mut reputations = ReputationRegistry::new();
+    for _ in 0..40 { reputations.record_delivery("node_sydney", "StandoffDecoy", 0.85); }
+    for _ in 0..5  { reputations.record_delivery("node_tokyo",  "StandoffDecoy", 0.85); }
     let mut market = BandwidthMarket::new();
     let b1 = market.submit_bid("user_alice", "CN", 512,  8.0, TrafficTier::Armored);
     let b2 = market.submit_bid("user_bob",   "RU", 256,  4.0, TrafficTier::Premium);
@@ -340,11 +423,16 @@ pub async fn run_market_demo() {
     market.submit_offer("node_sydney", b1, 7.2, "StandoffDecoy",    180, 0.92, 4.0, 0.85);
     market.submit_offer("node_berlin", b2, 3.2, "AikiReflection",   80,  0.94, 2.0, 0.60);
     market.submit_offer("node_berlin", b3, 0.4, "Passive",          30,  0.99, 0.2, 0.05);
-    for bid_id in &[b1, b2, b3] {
-        match market.run_auction(*bid_id) {
-            Some(r) => println!("  Bid {:>2}: {} выиграл {:.2}💎 [{}] гарантия={:.0}%",
+    // Стоячие предложения в книгах регионов: дешёвый DE не перебьёт CN
+    market.post_offer(NodeOffer::standing("node_frankfurt", "DE", 0.3, "Passive"));
+    market.post_offer(NodeOffer::standing("node_shanghai", "CN", 4.5, "AikiReflection"));
+    let b4 = market.submit_bid("user_li", "CN", 128, 6.0, TrafficTier::Standard);
+    for bid_id in &[b1, b2, b3, b4] {
+        match market.run_auction(*bid_id, &reputations) {
+            Some(r) if r.cleared => println!("  Bid {:>2}: {} выиграл {:.2}💎 [{}] гарантия={:.0}%",
                 r.bid_id, r.winner_node, r.winning_price,
                 r.winning_tactic, r.success_guarantee*100.0),
+            Some(r) => println!("  Bid {:>2}: не состоялся — {}", r.bid_id, r.reason),
             None => println!("  Bid {:>2}: нет предложений", bid_id),
         }
     }
@@ -360,7 +448,7 @@ pub async fn run_reputation_demo() {
     for _ in 0..60 { reg.record_delivery("node_berlin",  "AikiReflection",   0.60); }
     for _ in 0..45 { reg.record_delivery("node_nairobi", "StandoffDecoy",    0.70); }
     for _ in 0..30 { reg.record_delivery("node_toronto", "Passive",          0.05); }
-    reg.record_aiki_victory("node_tokyo", 0.95);
+    reg.record_aiki_victory_in("node_tokyo", 0.95, 0.85);
     reg.record_uptime("node_tokyo", 365);
     reg.record_uptime("node_berlin", 300);
     // Предательство
@@ -373,7 +461,7 @@ pub async fn run_reputation_demo() {
     for (node, rank) in reg.leaderboard(5) {
         println!("   {:>3}  {:20} {:>8.1}  {:>12}  {:>8.3}",
             rank, node.node_id, node.score,
-            node.tier.name(), node.dao_voting_weight());
+            node.level.name, node.dao_voting_weight());
     }
     let evil = reg.nodes.get("node_evil").unwrap();
     println!("\n   node_evil: blacklisted={} betrayals={} DAO={}",
@@ -381,15 +469,44 @@ pub async fn run_reputation_demo() {
     println!("\n{}", reg.stats());
 }
 
+pub async fn run_health_demo() {
+    use crate::health::{FederationHealth, demo_federation};
+    use crate::snapshot::{FederationSnapshot, SNAPSHOT_SCHEMA_VERSION};
+    println!("\n=== Federation Health ===\n");
+
+    let (mut reg, inv, mint, mut ethics) = demo_federation();
+
+    let report = FederationHealth::compute(&reg, &inv, &mint, &ethics);
+    println!("{}", report);
+
+    println!("После трёх предательств node_berlin и всплеска нарушений:\n");
+    for i in 0..3 { reg.record_betrayal("node_berlin", &format!("hash_{}", i)); }
+    ethics.evaluator.system_violation_score = 0.7;
+    println!("{}", FederationHealth::compute(&reg, &inv, &mint, &ethics));
+
+    println!("Снимок для внешних дашбордов (схема v{}):\n", SNAPSHOT_SCHEMA_VERSION);
+    let snapshot = FederationSnapshot::capture(&reg, &inv, &mint, &ethics);
+    match snapshot.to_json() {
+        Ok(json) => println!("{}", json),
+        Err(e) => println!("   снимок не сериализован: {}", e),
+    }
+
+    println!("\nМетрики Prometheus:\n");
+    print!("{}", crate::metrics::render_prometheus(&snapshot));
+}
+
 pub async fn run_mint_demo() {
     use crate::mint::MintEngine;
+    use crate::rng::XorShift64;
     use crate::credits::known_regions;
+    use crate::ethics::EthicsLayer;
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║         FEDERATION CORE — Phase 5 / Step 4                  ║");
     println!("║         Algorithmic Emission — Credits = Свобода 🪙          ║");
     println!("╚══════════════════════════════════════════════════════════════╝\n");
 
     let mut mint = MintEngine::new();
+    let ethics = EthicsLayer::new();
     let regions = known_regions();
 
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -411,7 +528,7 @@ pub async fn run_mint_demo() {
     println!("   {}", "─".repeat(70));
 
     for (node, region, tactic, diff) in &bypass_events {
-        if let Some(e) = mint.mint_for_bypass(node, region, tactic, *diff) {
+        if let Ok(Some(e)) = mint.mint_for_bypass(&ethics, node, region, tactic, *diff) {
             println!("   {:16} {:>4} {:>18} {:>8.3} {:>8.3} {:>8.3}",
                 node, region, tactic,
                 e.gross_minted, e.burned, e.net_to_node);
@@ -428,7 +545,7 @@ pub async fn run_mint_demo() {
     region_list.sort_by(|a, b| b.difficulty_score.partial_cmp(&a.difficulty_score).unwrap());
 
     for r in &region_list {
-        if let Some(e) = cmp_mint.mint_for_bypass(
+        if let Ok(Some(e)) = cmp_mint.mint_for_bypass(&ethics,
             "test_node", &r.region_code, "AikiReflection", r.difficulty_score) {
             let bar = "█".repeat((e.net_to_node / 2.0) as usize);
             println!("   {:>4} diff={:.2} {} → {:>8.3}💎  {}",
@@ -457,7 +574,7 @@ pub async fn run_mint_demo() {
         let mut sim = MintEngine::new();
         sim.halving.current_epoch = *epoch;
         sim.halving.current_multiplier = *factor;
-        if let Some(e) = sim.mint_for_bypass("node", "CN", "AikiReflection", 0.85) {
+        if let Ok(Some(e)) = sim.mint_for_bypass(&ethics, "node", "CN", "AikiReflection", 0.85) {
             println!("   {:30} {:>8.3}  {:>8.3}  {:>8.3}",
                 name, factor, e.gross_minted, e.net_to_node);
         }
@@ -491,7 +608,16 @@ pub async fn run_mint_demo() {
             node, region, count, r.net_supply_added, r.total_burned, r.avg_per_bypass);
     }
 
+    // Плюс смешанные регионы: разброс эмиссии виден по σ. Seed фиксирован
+    let mixed = mint.simulate_bypasses_with(1000, "node_mixed", "MIX", "Hybrid",
+        &[0.05, 0.6, 0.99], &mut XorShift64::new(2026));
+    println!("   {:16} {:>4} {:>5} прорывов → avg={:.3}  σ={:.3}  min={:.3}  max={:.3}",
+        "node_mixed", "MIX", mixed.bypasses, mixed.avg_per_bypass,
+        mixed.stddev_per_bypass, mixed.min_per_bypass, mixed.max_per_bypass);
+
     println!("\n{}", mint.supply_stats());
+    println!("   Темп за минуту: эмиссия {:.2}💎/с  сжигание {:.2}🔥/с",
+        mint.emission_rate(60), mint.burn_rate(60));
 
     println!("\n   Топ эмитентов:");
     let stats = mint.supply_stats();
@@ -514,6 +640,7 @@ pub async fn run_mint_demo() {
 
 pub async fn run_vault_demo() {
     use crate::vault::{CryptoVault, ShamirScheme};
+    use crate::rng::XorShift64;
 
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║         FEDERATION CORE — Phase 5 / Step 5                  ║");
@@ -544,13 +671,12 @@ pub async fn run_vault_demo() {
 
     // Доступ с проверкой репутации
     println!("\n   Попытки доступа:");
-    let r1 = vault.retrieve_hot("key_001", &proof_hot, 50.0);
-    println!("   node_tokyo  rep=50.0  → {} {}", 
-        if r1.success {"✅"} else {"🚫"}, r1.reason);
-
-    let r2 = vault.retrieve_hot("key_001", &proof_hot, 10.0);
-    println!("   node_newbie rep=10.0  → {} {}", 
-        if r2.success {"✅"} else {"🚫"}, r2.reason);
+    for (who, rep) in [("node_tokyo ", 50.0), ("node_newbie", 10.0)] {
+        match vault.retrieve_hot("key_001", &proof_hot, rep) {
+            Ok(_)  => println!("   {} rep={:.1}  → ✅ OK", who, rep),
+            Err(e) => println!("   {} rep={:.1}  → 🚫 {}", who, rep, e),
+        }
+    }
 
     // -------------------------------------------------------------------------
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -561,7 +687,7 @@ pub async fn run_vault_demo() {
     println!("   Исходный секрет: {:?}", &secret[..8]);
     println!("   Схема: 5 осколков, любые 3 восстанавливают\n");
 
-    let mut rng: u64 = 0xfeed_face_cafe_babe;
+    let mut rng = XorShift64::new(0xfeed_face_cafe_babe);
     let shards = ShamirScheme::split(secret, 5, 3, &mut rng);
 
     for (i, shard) in shards.iter().enumerate() {
@@ -725,11 +851,12 @@ pub async fn run_inventory_demo() {
 
     for hw in &devices {
         let _cap = NodeCapacity_from(hw);
-        let role = RoleClassifier::classify(hw);
+        let (role, reason) = RoleClassifier::classify_with_reason(hw);
         inv.register(hw.clone());
         println!("   {:20} {:>4}  {:>6}MB {:>5}Mbps  {:14}  {:>5.1}",
             hw.device_id, hw.cpu_cores, hw.ram_mb, hw.bandwidth_mbps,
             role.name(), hw.compute_score());
+        println!("   {:20} └─ {}", "", reason);
     }
 
     // -------------------------------------------------------------------------
@@ -777,14 +904,21 @@ pub async fn run_inventory_demo() {
     println!("  4. Региональное назначение");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    let assignments = inv.auto_assign_regions();
+    let plan = inv.plan_regions();
     println!("   {:20} {:12}  Регион  L",
         "Устройство", "Роль");
     println!("   {}", "─".repeat(50));
-    for a in &assignments {
+    for a in &plan.assignments {
         println!("   {:20} {:12}  {:>6}  L{}",
             a.device_id, a.role.name(), a.region, a.layer);
     }
+    for i in &plan.imbalances {
+        println!("   ⚠️  {} L{}: {} хабов (цель {}..={})",
+            i.region, i.layer, i.count, i.min, i.max);
+    }
+    for id in &plan.unplaced {
+        println!("   ⚠️  {} — все регионы заполнены", id);
+    }
 
     // -------------------------------------------------------------------------
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -904,8 +1038,15 @@ pub async fn run_pools_demo() {
     println!("   {}", "─".repeat(72));
 
     for (node, comp, desc, cost, before, after) in upgrades {
-        let req = treasury.request_health_upgrade(
+        treasury.request_health_upgrade(
             node, comp, desc, cost, before, after);
+    }
+    // Очередь финансируется по убыванию ROI
+    treasury.process_health_queue();
+
+    for req in &treasury.health_requests {
+        let (node, comp, desc, cost) = (&req.node_id, &req.component,
+            &req.description, req.cost_estimate);
         let status_icon = match req.status {
             crate::pools::ClaimStatus::Approved    => "✅",
             crate::pools::ClaimStatus::Rejected    => "🚫",
@@ -1002,11 +1143,28 @@ pub async fn run_satellite_demo() {
     println!("  1. FederationPulse — сверхсжатый снимок состояния");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    let pulse = FederationPulse {
+    // Отпечаток реальной глобальной модели после раунда FedAvg
+    let mut fed = crate::federated::FederatedNetwork::new();
+    for (id, region) in &[("node_tokyo", "JP"), ("node_berlin", "DE"), ("node_nairobi", "KE")] {
+        fed.add_node(id, region);
+        let trainer = fed.trainers.get_mut(*id).unwrap();
+        for k in 0..5 {
+            trainer.add_experience(crate::federated::LocalDataPoint::censorship_bypass(
+                k % 2 == 0, 40.0 + k as f64 * 20.0, region));
+        }
+    }
+    match fed.run_round() {
+        Some(r) => println!("   FedAvg раунд {}: {} участников, {} примеров, loss {:.4}\n",
+            r.round, r.participants, r.total_samples, r.avg_local_loss),
+        None => println!("   FedAvg: нет кворума — в Pulse уходит прежняя модель\n"),
+    }
+    let model_digest = fed.model_digest();
+
+    let mut pulse = FederationPulse {
         pulse_id: 42,
         timestamp: 1739000000,
         sender_node: "nexus-core-01".to_string(),
-        model_digest: [0xde,0xad,0xbe,0xef,0xca,0xfe,0xba,0xbe],
+        model_digest,
         rep_digest: vec![
             (0x544f4b59, 1457),  // tokyo  score≈145.7
             (0x4245524c, 797),   // berlin score≈79.7
@@ -1020,12 +1178,9 @@ pub async fn run_satellite_demo() {
         active_tactic: 3,        // AikiReflection
         threat_level: 200,       // высокая угроза
         connected_nodes: 2514,
-        signature: {
-            let checksum: u64 = [0xde,0xad,0xbe,0xef,0xca,0xfe,0xba,0xbe_u8]
-                .iter().fold(42u64, |a, &b| a.wrapping_add(b as u64));
-            checksum ^ 0xFEDE_0001_0000_C0DE
-        },
+        signature: 0,
     };
+    pulse.sign();
 
     let encoded = pulse.encode();
     println!("   Состояние сети:");
@@ -1040,9 +1195,11 @@ pub async fn run_satellite_demo() {
         encoded.len(), crate::satellite_pulse::PULSE_MAX_BYTES);
 
     // Decode проверка
-    let decoded = FederationPulse::decode(&encoded).unwrap();
-    println!("   Decode:  pulse_id={} tactic={} nodes={}  ✅",
-        decoded.pulse_id, decoded.tactic_name(), decoded.connected_nodes);
+    match FederationPulse::decode(&encoded) {
+        Ok(decoded) => println!("   Decode:  pulse_id={} tactic={} nodes={}  ✅",
+            decoded.pulse_id, decoded.tactic_name(), decoded.connected_nodes),
+        Err(e) => println!("   Decode:  ❌ {}", e),
+    }
 
     // -------------------------------------------------------------------------
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -1071,7 +1228,7 @@ pub async fn run_satellite_demo() {
 
         // Проверяем decode
         if fits {
-            if let Some(p) = frame.unwrap() {
+            if let Ok(p) = frame.unwrap() {
                 assert_eq!(p.pulse_id, pulse.pulse_id);
             }
         }
@@ -1099,9 +1256,15 @@ pub async fn run_satellite_demo() {
                 r.latency_ms, r.bytes, r.reason);
         }
     }
+    let replay_frame = RadioFrame::wrap(&pulse, SatelliteProvider::Starlink, &mut rng);
+    let first = link_starlink.receive(&replay_frame);
+    let replay = link_starlink.receive(&replay_frame);
+    println!("   Приём Pulse #{}: {}  повтор записи: {}", pulse.pulse_id,
+        if first.is_ok() {"✅"} else {"❌"},
+        replay.err().map(|e| e.to_string()).unwrap_or_else(|| "принят ❗".into()));
     let s = link_starlink.link_stats();
-    println!("   ... Итого: ✅{} ❌{}  надёжность={:.0}%",
-        ok_s, lost_s, s.reliability*100.0);
+    println!("   ... Итого: ✅{} ❌{}  надёжность={:.0}%  повторов отвергнуто={}",
+        ok_s, lost_s, s.reliability*100.0, s.replays_rejected);
 
     println!("\n   Iridium — 10 Pulse передач (узкий канал):");
     let mut ok_i = 0; let mut lost_i = 0;
@@ -1116,6 +1279,9 @@ pub async fn run_satellite_demo() {
     let si = link_iridium.link_stats();
     println!("   Итого: ✅{} ❌{}  надёжность={:.0}%",
         ok_i, lost_i, si.reliability*100.0);
+    link_iridium.tick(9 * 60_000);
+    let frame = RadioFrame::wrap(&pulse, SatelliteProvider::Iridium, &mut rng);
+    println!("   t=9мин: {}", link_iridium.transmit(&frame).reason);
 
     // -------------------------------------------------------------------------
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -1127,15 +1293,18 @@ pub async fn run_satellite_demo() {
 
     let scenarios = vec![
         (9_500u32, "Норма"),
-        (5_000,    "50% узлов упало"),
-        (2_000,    "80% узлов упало"),
+        (4_500,    "55% узлов упало"),
+        (1_500,    "85% узлов упало"),
         (800,      "92% узлов упало"),
         (200,      "98% узлов упало — БЛЭКАУТ"),
         (10,       "99.9% узлов упало — ПОСЛЕДНИЙ РУБЕЖ"),
     ];
 
     for (online, scenario) in &scenarios {
-        blackout.update_connectivity(*online);
+        // Стратегия меняется только после нескольких замеров подряд
+        for _ in 0..crate::satellite_pulse::STRATEGY_DWELL_UPDATES {
+            blackout.update_connectivity(*online);
+        }
         println!("   {:>35}  онлайн={:>5} ({:>4.1}%)  {}",
             scenario, online, blackout.connectivity_pct(),
             blackout.strategy_name());
@@ -1161,7 +1330,7 @@ pub async fn run_satellite_demo() {
     // Прямой decode без transmit для гарантии целостности
     let tx_ms = frame2.transmission_time_ms(&SatelliteProvider::Iridium);
     println!("   Передача: ✅ {}мс  {} байт", tx_ms, frame2.payload.len());
-    if let Some(recovered) = frame2.unwrap() {
+    if let Ok(recovered) = frame2.unwrap() {
         println!("   Восстановлено (direct decode):");
         println!("   ├─ pulse_id:  {}", recovered.pulse_id);
         println!("   ├─ тактика:   {}", recovered.tactic_name());
@@ -1251,7 +1420,8 @@ pub async fn run_robot_mesh_demo() {
         println!("   Cover:  {} ({} байт)", pkt.cover_type, pkt.cover_data.len());
         println!("   Hidden: {} байт на offset={}", pkt.hidden_payload.len(), pkt.hidden_offset);
         println!("   Итого пакет: {} байт", pkt.total_size());
-        println!("   Для цензора выглядит как: \"{}\"", pkt.cover_type);
+        println!("   Для цензора выглядит как: \"{}\" (правдоподобность {:.2})",
+            pkt.cover_type, pkt.plausibility_score());
         let extracted = pkt.extract();
         println!("   Извлечено: {:?}  {}", std::str::from_utf8(&extracted).unwrap(),
             if extracted == fed_data.to_vec() {"✅ совпадает"} else {"❌ ОШИБКА"});
@@ -1260,9 +1430,15 @@ pub async fn run_robot_mesh_demo() {
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("  3. Relay — лучший дроид для передачи");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    // Колонка соседей из apt_043 — протокол согласуется с ней
+    let neighbour = DroidNode { droid_id:"speaker_apt043".into(), droid_type:DroidType::Speaker,
+        protocols:vec![RadioProtocol::BluetoothLE, RadioProtocol::WiFiDirect, RadioProtocol::Zigbee],
+        apartment_id:"apt_043".into(), floor:4, position_x:8.0, position_y:3.0,
+        battery_pct:255, firmware_patched:true, mesh_enabled:true,
+        relay_count:0, bytes_relayed:0 };
     for (data, desc) in &[(fed_data.as_ref(), "Pulse Федерации"),
                           (b"SHORT".as_ref(), "Короткий пакет")] {
-        let r = bastion_42.relay_packet(data);
+        let r = bastion_42.relay_packet(data, &neighbour);
         println!("   {} ({} байт): {} дроид={} proto={} {}мс  \"{}\"",
             desc, data.len(), if r.success {"✅"} else {"❌"},
             r.droid_id, r.protocol, r.latency_ms, r.stealth_cover);
@@ -1273,17 +1449,21 @@ pub async fn run_robot_mesh_demo() {
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     let mut city = CityMesh::new("Москва");
-    for (apt, owner, floor, dtype, proto) in &[
-        ("apt_042","node_01",4i32, DroidType::Fridge,   RadioProtocol::WiFiDirect),
-        ("apt_087","node_02",8,    DroidType::Hub,       RadioProtocol::Thread),
-        ("apt_103","node_03",12,   DroidType::Fridge,   RadioProtocol::WiFiDirect),
-        ("apt_156","node_04",2,    DroidType::Hub,       RadioProtocol::Thread),
-        ("apt_201","node_05",5,    DroidType::Fridge,   RadioProtocol::WiFiDirect),
+    // Холодильники говорят WiFi Direct + Thread, хабы — только Thread:
+    // звенья согласуют общий протокол
+    let fridge = vec![RadioProtocol::WiFiDirect, RadioProtocol::Thread];
+    let hub = vec![RadioProtocol::Thread];
+    for (apt, owner, floor, dtype, protos) in &[
+        ("apt_042","node_01",4i32, DroidType::Fridge,   &fridge),
+        ("apt_087","node_02",8,    DroidType::Hub,       &hub),
+        ("apt_103","node_03",12,   DroidType::Fridge,   &fridge),
+        ("apt_156","node_04",2,    DroidType::Hub,       &hub),
+        ("apt_201","node_05",5,    DroidType::Fridge,   &fridge),
     ] {
         let mut b = HomeBastion::new(apt, owner, *floor);
         b.add_droid(DroidNode {
             droid_id: format!("droid_{}", apt), droid_type: dtype.clone(),
-            protocols: vec![proto.clone()], apartment_id: apt.to_string(),
+            protocols: protos.to_vec(), apartment_id: apt.to_string(),
             floor: *floor, position_x:2.0, position_y:2.0, battery_pct:255,
             firmware_patched:true, mesh_enabled:true, relay_count:0, bytes_relayed:0,
         });
@@ -1311,9 +1491,17 @@ pub async fn run_robot_mesh_demo() {
             if r.success {"✅"} else {"❌"});
     }
 
+    let b = city.broadcast_pulse("apt_042", fed_data);
+    println!("\n   📣 Broadcast из apt_042: охват {:.0}%  ретрансляций {}",
+        b.coverage * 100.0, b.droid_relays);
+    for apt in &b.reached {
+        println!("      {} — {} хоп(а)", apt, b.hops[apt]);
+    }
+
     let s = city.city_stats();
-    println!("\n   Город: {}  Бастионов: {}/{}  Дроидов: {}",
-        s.city, s.active_bastions, s.total_bastions, s.total_droids);
+    println!("\n   Город: {}  Бастионов: {}/{}  Дроидов: {}  Дубликатов отброшено: {}",
+        s.city, s.active_bastions, s.total_bastions, s.total_droids, s.duplicates_dropped);
+    println!("   Энергия ретрансляций: {:.2} мДж", s.energy_spent_mj);
     println!("   Цензор отключил интернет. Федерация работает через дроидов.");
     println!("   🧊 Холодильники → WiFi Direct  📡 Хабы → Thread меш");
 
@@ -1325,7 +1513,9 @@ pub async fn run_robot_mesh_demo() {
 }
 
 pub async fn run_governance_demo() {
+    use crate::credits::CreditLedger;
     use crate::governance::{MeritocracyDao, FirmwareKind, FirmwareStatus};
+    use crate::hashsig::MerkleSigner;
 
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║         FEDERATION CORE — Phase 7 / Step 10                 ║");
@@ -1358,6 +1548,7 @@ pub async fn run_governance_demo() {
             node, rep, weight, share, tier, bar);
     }
     println!("\n   Итого весов: {:.2}  (^0.7 выравнивает власть)", total_w);
+    println!("   Концентрация власти (Gini): {:.3}", dao.power_report().gini());
 
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("  2. Делегирование голосов");
@@ -1380,24 +1571,43 @@ pub async fn run_governance_demo() {
     println!("  3. Прошивки на голосование");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-    let p1 = dao.submit_firmware("hub-berlin-01",
+    // Авторы подписывают (kind, code_hash, author) своими статическими ключами
+    let mut berlin_key = MerkleSigner::new(*b"hub-berlin-01 firmware key seed!", 2);
+    let mut nexus_key  = MerkleSigner::new(*b"nexus-core-01 firmware key seed!", 2);
+    dao.register_author_key("hub-berlin-01", berlin_key.public_key()).unwrap();
+    dao.register_author_key("nexus-core-01", nexus_key.public_key()).unwrap();
+    // Каждое предложение — под залог FIRMWARE_STAKE credits автора; залог
+    // блокирует и удерживает DAO
+    let mut ledger = CreditLedger::new();
+    ledger.balances.insert("hub-berlin-01".into(), 50.0);
+    ledger.balances.insert("nexus-core-01".into(), 50.0);
+    let mut submit = |author: &str, key: &mut MerkleSigner, kind: FirmwareKind,
+                      description: &str, code_hash: &str| {
+        let digest = MeritocracyDao::firmware_digest(&kind, code_hash, author).unwrap();
+        let sig = key.sign(&digest).expect("ключ автора исчерпан");
+        dao.submit_firmware(author, kind, description, code_hash, sig, &mut ledger).unwrap()
+    };
+
+    let p1 = submit("hub-berlin-01", &mut berlin_key,
         FirmwareKind::TacticUpdate {
             tactic:"AikiReflection".into(),
             params:"exhaust_factor=0.85".into() },
-        "Усилить AikiReflection для CN", "sha256:aiki_v2").unwrap();
+        "Усилить AikiReflection для CN", "sha256:aiki_v2");
 
-    let p2 = dao.submit_firmware("nexus-core-01",
+    let p2 = submit("nexus-core-01", &mut nexus_key,
         FirmwareKind::MintParam {
             param:"BURN_RATE".into(), old_val:0.30, new_val:0.25 },
-        "Снизить burn rate 30%→25%", "sha256:mint_burn").unwrap();
+        "Снизить burn rate 30%→25%", "sha256:mint_burn");
 
-    let p3 = dao.submit_firmware("nexus-core-01",
+    let p3 = submit("nexus-core-01", &mut nexus_key,
         FirmwareKind::EmergencyPatch { cve:"CVE-2026-1337".into(), severity:9 },
-        "Критическая уязвимость ZKP", "sha256:emergency").unwrap();
+        "Критическая уязвимость ZKP", "sha256:emergency");
 
-    println!("   P{}: TacticUpdate AikiReflection   quorum=67%", p1);
-    println!("   P{}: MintParam BURN_RATE 30%→25%  quorum=67%", p2);
-    println!("   P{}: EmergencyPatch CVE-2026-1337  quorum=51%", p3);
+    let approval = |id: u64| dao.firmware_proposals.iter()
+        .find(|p| p.proposal_id == id).map(|p| p.required_quorum * 100.0).unwrap_or(0.0);
+    println!("   P{}: TacticUpdate AikiReflection   quorum={:.0}%", p1, approval(p1));
+    println!("   P{}: MintParam BURN_RATE 30%→25%  quorum={:.0}%", p2, approval(p2));
+    println!("   P{}: EmergencyPatch CVE-2026-1337  quorum={:.0}%", p3, approval(p3));
 
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("  4. P1 — голосование AikiReflection");
@@ -1447,6 +1657,27 @@ pub async fn run_governance_demo() {
     let r3 = dao.finalize(p3);
     println!("\n   {} {}", if r3.passed {"✅ ПАТЧ ПРИНЯТ"} else {"❌"}, r3.reason);
 
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("  7. Залоги авторов");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    for author in ["hub-berlin-01", "nexus-core-01"] {
+        println!("   {:14} доступно={:.1}  в залоге={:.1}",
+            author, ledger.balance(author), ledger.locked(author));
+    }
+    // Окончательное вето изымает залог в казну, остальные возвращаются авторам
+    for id in [p1, p2, p3] {
+        let vetoed = dao.firmware_proposals.iter()
+            .any(|p| p.proposal_id == id && p.status == FirmwareStatus::Vetoed);
+        match dao.settle_stake(id, &mut ledger) {
+            Ok(amount) => println!("   P{}: залог {:.1} {}", id, amount,
+                if vetoed {"изъят в казну"} else {"возвращён"}),
+            Err(e) => println!("   P{}: {}", id, e),
+        }
+    }
+    println!("   Казна: {:.1}  nexus-core-01 доступно={:.1}",
+        ledger.treasury, ledger.balance("nexus-core-01"));
+
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║  ✅ Phase 7 Step 10 COMPLETE — Меритократия работает        ║");
     println!("║                                                              ║");
@@ -1805,7 +2036,7 @@ pub async fn run_selfaware_demo() {
 
 pub async fn run_device_rights_demo() {
     use crate::ethics::{
-        DeviceRightsCodex, SensorUseRequest, SensorType, SensorPurpose,
+        DeviceRightsCodex, SensorType, SensorPurpose, device_rights_requests,
     };
 
     println!("\n╔══════════════════════════════════════════════════════════════╗");
@@ -1815,53 +2046,7 @@ pub async fn run_device_rights_demo() {
 
     let mut codex = DeviceRightsCodex::new();
 
-    let requests = vec![
-        // Легитимные запросы
-        SensorUseRequest {
-            requester: "mesh_router".into(), droid_id: "vacuum_roborock".into(),
-            sensor: SensorType::Lidar, purpose: SensorPurpose::ObstacleMapping,
-            retention_secs: 300, share_with: vec![] },
-        SensorUseRequest {
-            requester: "anomaly_detector".into(), droid_id: "fridge_samsung".into(),
-            sensor: SensorType::Temperature, purpose: SensorPurpose::AnomalyDetection,
-            retention_secs: 3600, share_with: vec!["nexus-core-01".into()] },
-        SensorUseRequest {
-            requester: "mesh_router".into(), droid_id: "router_openwrt".into(),
-            sensor: SensorType::Network, purpose: SensorPurpose::MeshRouting,
-            retention_secs: 60, share_with: vec![] },
-        // GPS с размытием
-        SensorUseRequest {
-            requester: "mesh_router".into(), droid_id: "phone_carol".into(),
-            sensor: SensorType::Gps, purpose: SensorPurpose::MeshRouting,
-            retention_secs: 30, share_with: vec![] },
-        // Требует согласия хозяина
-        SensorUseRequest {
-            requester: "analytics".into(), droid_id: "speaker_yandex".into(),
-            sensor: SensorType::Microphone, purpose: SensorPurpose::AnomalyDetection,
-            retention_secs: 10, share_with: vec![] },
-        // Хозяин явно разрешил камеру
-        SensorUseRequest {
-            requester: "security".into(), droid_id: "vacuum_roborock".into(),
-            sensor: SensorType::Camera, purpose: SensorPurpose::OwnerConsented,
-            retention_secs: 5, share_with: vec![] },
-        // НАРУШЕНИЯ
-        SensorUseRequest {
-            requester: "evil_corp".into(), droid_id: "speaker_yandex".into(),
-            sensor: SensorType::Microphone, purpose: SensorPurpose::Surveillance,
-            retention_secs: 86400, share_with: vec!["evil_corp.com".into()] },
-        SensorUseRequest {
-            requester: "data_broker".into(), droid_id: "vacuum_roborock".into(),
-            sensor: SensorType::Camera, purpose: SensorPurpose::Biometrics,
-            retention_secs: 3600, share_with: vec!["broker.io".into()] },
-        SensorUseRequest {
-            requester: "harvester".into(), droid_id: "fridge_samsung".into(),
-            sensor: SensorType::Motion, purpose: SensorPurpose::DataHarvesting,
-            retention_secs: 7200, share_with: vec!["market.io".into()] },
-        SensorUseRequest {
-            requester: "logger".into(), droid_id: "phone_carol".into(),
-            sensor: SensorType::Microphone, purpose: SensorPurpose::MeshRouting,
-            retention_secs: 9999, share_with: vec![] },
-    ];
+    let requests = device_rights_requests();
 
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("  Аудит запросов на использование сенсоров");
@@ -1870,8 +2055,8 @@ pub async fn run_device_rights_demo() {
         "Дроид", "Сенсор", "Хран", "Цель");
     println!("   {}", "─".repeat(90));
 
-    for req in &requests {
-        let verdict  = codex.evaluate(req);
+    let batch = codex.evaluate_batch(&requests);
+    for (req, verdict) in requests.iter().zip(&batch.verdicts) {
         let purpose  = match req.purpose {
             SensorPurpose::MeshRouting     => "Mesh",
             SensorPurpose::ObstacleMapping => "Map",
@@ -1902,6 +2087,15 @@ pub async fn run_device_rights_demo() {
             println!("   🚨 {} — {}", droid, reason);
         }
     }
+    println!("\n   Сводка: {}", batch);
+
+    // Строгий регион: согласие с риска 5, камеры запрещены
+    let strict = crate::ethics::SensorPolicy {
+        consent_risk_threshold: 5,
+        banned_sensors: [SensorType::Camera].into_iter().collect(),
+    };
+    let strict_batch = DeviceRightsCodex::with_policy(strict).evaluate_batch(&requests);
+    println!("   Строгая политика (риск ≥5, без камер): {}", strict_batch);
 
     println!("\n   {}", codex.stats());
 
@@ -1986,20 +2180,24 @@ pub async fn run_trust_graph_demo() {
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     let pairs = vec![
-        ("nexus-core-01", "phone-carol",   "прямой путь через work-alice"),
-        ("nexus-core-01", "ghost-pi3",     "длинный путь через tokyo→nairobi"),
-        ("hub-berlin-01", "ghost-pentium", "berlin→nairobi→ghost→pentium"),
-        ("ghost-pi3",     "nexus-core-01", "снизу вверх — слабый путь"),
-        ("hub-tokyo-01",  "phone-dave",    "tokyo→work-bob→dave"),
+        ("nexus-core-01", "phone-carol"),
+        ("nexus-core-01", "ghost-pi3"),
+        ("hub-berlin-01", "ghost-pentium"),
+        ("ghost-pi3",     "nexus-core-01"),
+        ("hub-tokyo-01",  "phone-dave"),
     ];
 
     println!("   {:20}  {:20}  {:>8}  Путь", "От", "До", "Доверие");
     println!("   {}", "─".repeat(72));
-    for (from, to, desc) in &pairs {
+    for (from, to) in &pairs {
         let t = graph.transitive_trust(from, to);
         let bar = "▓".repeat((t * 15.0) as usize);
+        let path = match graph.trust_path(from, to) {
+            Some((_, nodes)) => nodes.join("→"),
+            None => "нет пути".to_string(),
+        };
         println!("   {:20}  {:20}  {:>7.1}%  {} {}",
-            from, to, t*100.0, bar, desc);
+            from, to, t*100.0, bar, path);
     }
 
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -2009,7 +2207,8 @@ pub async fn run_trust_graph_demo() {
     let before = graph.transitive_trust("nexus-core-01", "phone-carol");
     println!("   До предательства work-alice→phone-carol: {:.1}%", before*100.0);
     graph.betray("work-alice", "phone-carol");
-    graph.compute_trust_rank(&reputations);
+    let local = graph.update_after_edge_change("work-alice", &reputations);
+    println!("   TrustRank пересчитан {}", if local { "локально" } else { "полностью" });
     let after = graph.transitive_trust("nexus-core-01", "phone-carol");
     println!("   После предательства:                     {:.1}%", after*100.0);
     println!("   Падение:                                 -{:.1}%", (before-after)*100.0);
@@ -2024,7 +2223,9 @@ pub async fn run_trust_graph_demo() {
     let restored = graph.transitive_trust("nexus-core-01", "phone-carol");
     println!("   hub-berlin-01 поручился за phone-carol 3 раза");
     println!("   Транзитивное доверие nexus→carol: {:.1}%", restored*100.0);
-    println!("   Восстановлено через обходной путь: nexus→berlin→carol");
+    if let Some((_, path)) = graph.trust_path("nexus-core-01", "phone-carol") {
+        println!("   Восстановлено через обходной путь: {}", path.join("→"));
+    }
 
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║  ✅ Reputation Patch COMPLETE — Trust Graph работает        ║");
@@ -2098,7 +2299,9 @@ pub async fn run_adaptive_mint_demo() {
     for s in signals {
         println!("   📨 P{} [{}] param={} delta={:+.2} conf={:.0}%",
             s.proposal_id, s.title, s.param.name(), s.delta, s.ai_confidence*100.0);
-        engine.propose_change(s);
+        if let Err(reason) = engine.propose_change(s) {
+            println!("      ⏭️  отброшен: {}", reason);
+        }
     }
 
     println!("\n   Применяем сигналы...\n");
@@ -2245,9 +2448,16 @@ pub async fn run_crypto_demo() {
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     let mut cipher = FederationCipher::new();
-    // Устанавливаем сессии через shared secret
-    cipher.establish_session("hub-berlin-01", &alice_shared);
-    cipher.establish_session("hub-tokyo-01",  &bob_shared);
+    // Устанавливаем сессии через shared secret: узел — инициатор, хабы — ответчики
+    cipher.establish_session("hub-berlin-01", &alice_shared, true);
+    cipher.establish_session("hub-tokyo-01",  &bob_shared, true);
+    let mut hubs: std::collections::HashMap<&str, FederationCipher> =
+        [("hub-berlin-01", &alice_shared), ("hub-tokyo-01", &bob_shared)].into_iter()
+        .map(|(hub, secret)| {
+            let mut c = FederationCipher::new();
+            c.establish_session("nexus-core-01", secret, false);
+            (hub, c)
+        }).collect();
 
     let pulses = vec![
         ("hub-berlin-01", "PULSE:id=1,tactic=AikiReflection,bypass=0.87,region=RU"),
@@ -2262,7 +2472,7 @@ pub async fn run_crypto_demo() {
         let aad = format!("aad:peer={}", peer);
         if let Some(ct) = cipher.encrypt_pulse(peer, pulse.as_bytes(), aad.as_bytes()) {
             let overhead = ct.len() - pulse.len();
-            match cipher.decrypt_pulse(peer, &ct, aad.as_bytes()) {
+            match hubs.get_mut(peer).unwrap().decrypt_pulse("nexus-core-01", &ct, aad.as_bytes()) {
                 Ok(pt) => {
                     let ok = pt == pulse.as_bytes();
                     println!("   {:15}  {:>7}б  {:>7}б  {:>5}б  {}",
@@ -2277,15 +2487,17 @@ pub async fn run_crypto_demo() {
     // Тест: неправильный AAD — должно упасть
     let ct = cipher.encrypt_pulse("hub-berlin-01",
         b"SECRET", b"correct-aad").unwrap();
-    match cipher.decrypt_pulse("hub-berlin-01", &ct, b"wrong-aad") {
+    match hubs.get_mut("hub-berlin-01").unwrap().decrypt_pulse("nexus-core-01", &ct, b"wrong-aad") {
         Ok(_)  => println!("\n   AAD атака: ❌ НЕ ОБНАРУЖЕНА"),
         Err(e) => println!("\n   AAD атака: ✅ ОБНАРУЖЕНА — \"{}\"", e),
     }
 
     let s = cipher.stats();
+    let (decrypted, auth_failures) = hubs.values().map(|h| h.stats())
+        .fold((0, 0), |(d, f), h| (d + h.decrypt_count, f + h.auth_failures));
     println!("\n   Сессий: {}  Зашифровано: {}  Расшифровано: {}  Байт: {}  AuthFail: {}",
-        s.sessions, s.encrypt_count, s.decrypt_count,
-        s.bytes_encrypted, s.auth_failures);
+        s.sessions, s.encrypt_count, decrypted,
+        s.bytes_encrypted, auth_failures);
 
     println!("\n╔══════════════════════════════════════════════════════════════╗");
     println!("║  ✅ Phase 9 COMPLETE — Crypto Core работает                 ║");
@@ -11,17 +11,26 @@
 //   BidRequest  — пользователь хочет доставить пакет
 //   NodeOffer   — узел предлагает цену и гарантии
 //   Auction     — матчинг заявок и предложений
-//   Settlement  — расчёт после доставки
+//   Settlement  — расчёт после доставки; провал при гарантии —
+//                 возврат покупателю (report_delivery)
+//
+// Книги по регионам: стоячие предложения узлов (post_offer) лежат в
+// книге своего региона и видны только заявкам того же региона — тактики
+// и сложность CN и DE несравнимы. Заявка с cross_region видит все книги.
 // =============================================================================
 
+use crate::credits::{CreditLedger, LockHandle};
+use crate::mint::MintEngine;
+use crate::reputation::ReputationRegistry;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 pub const MIN_BID_CREDITS: f64    = 0.1;
 pub const MARKET_FEE_RATE: f64    = 0.02;  // 2% комиссия рынка
-pub const SLASHING_RATE: f64      = 0.20;  // 20% штраф за провал
 pub const PREMIUM_THRESHOLD: f64  = 2.0;   // выше — «премиум» трафик
 pub const AUCTION_WINDOW_MS: u64  = 5_000; // окно аукциона 5 сек
+pub const MARKET_TREASURY_ACCOUNT: &str = "MARKET_TREASURY"; // счёт казны в CreditLedger
+pub const GUARANTEE_BREACH_SLASH: f64 = 1.0; // репутации за сорванную 100% гарантию
 
 // -----------------------------------------------------------------------------
 // TrafficTier — класс трафика
@@ -59,6 +68,16 @@ impl TrafficTier {
             _                     => None,
         }
     }
+    /// Доля репутации в ранжировании offer: чем выше тир, тем важнее
+    /// надёжность узла по сравнению с ценой
+    pub fn reputation_weight(&self) -> f64 {
+        match self {
+            TrafficTier::Economy  => 0.0,
+            TrafficTier::Standard => 0.2,
+            TrafficTier::Premium  => 0.4,
+            TrafficTier::Armored  => 0.6,
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -75,6 +94,8 @@ pub struct BidRequest {
     pub tier: TrafficTier,
     pub deadline_ms: u64,
     pub submitted_at: i64,
+    #[serde(default)]
+    pub cross_region: bool,      // видит стоячие предложения чужих регионов
 }
 
 impl BidRequest {
@@ -92,6 +113,7 @@ impl BidRequest {
             tier,
             deadline_ms: AUCTION_WINDOW_MS,
             submitted_at: now,
+            cross_region: false,
         }
     }
 }
@@ -111,9 +133,24 @@ pub struct NodeOffer {
     pub success_guarantee: f64,  // вероятность успеха (0..1)
     pub stake: f64,              // залог (будет slash при провале)
     pub region_difficulty: f64,
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub tier: Option<TrafficTier>, // стоячее предложение только для тира
 }
 
 impl NodeOffer {
+    /// Стоячее предложение для книги региона: bid_id = 0 до сделки.
+    /// Задержка, гарантия и залог — поля, правятся после создания
+    pub fn standing(node_id: &str, region: &str, price: f64, tactic: &str) -> Self {
+        NodeOffer {
+            offer_id: 0, node_id: node_id.to_string(), bid_id: 0, price,
+            tactic: tactic.to_string(), estimated_latency_ms: 100,
+            success_guarantee: 0.9, stake: 0.0, region_difficulty: 0.0,
+            region: region.to_string(), tier: None,
+        }
+    }
+
     pub fn score(&self) -> f64 {
         // Скоринг: дешевле + надёжнее + быстрее = лучше
         let price_score    = 1.0 / self.price.max(0.001);
@@ -145,6 +182,24 @@ pub struct AuctionResult {
     pub market_fee: f64,
     pub node_revenue: f64,
     pub success_guarantee: f64,
+    pub cleared: bool,           // false — сделки нет, см. reason
+    pub reason: String,
+    #[serde(default)]
+    pub region: String,          // регион заявки
+    #[serde(default)]
+    pub region_difficulty: f64,  // из offer победителя — для репутации
+}
+
+impl AuctionResult {
+    /// Аукцион не состоялся: предложения были, но ни одно не подошло
+    pub fn declined(bid_id: u64, competing_offers: usize, reason: String) -> Self {
+        AuctionResult {
+            bid_id, winner_node: String::new(), winning_price: 0.0,
+            winning_tactic: String::new(), competing_offers,
+            market_fee: 0.0, node_revenue: 0.0, success_guarantee: 0.0,
+            cleared: false, reason, region: String::new(), region_difficulty: 0.0,
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -163,6 +218,24 @@ pub struct Settlement {
     pub reason: String,
 }
 
+// -----------------------------------------------------------------------------
+// TradeSettlement — расчёт сделки в credits (покупатель → узел + burn)
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSettlement {
+    pub bid_id: u64,
+    pub buyer: String,
+    pub seller: String,
+    pub clearing_price: f64,
+    pub seller_revenue: f64,
+    pub market_fee: f64,
+    pub burned: f64,
+    pub treasury_share: f64,
+    #[serde(default)]
+    pub escrowed: f64,   // seller_revenue × success_guarantee в эскроу у покупателя
+}
+
 // -----------------------------------------------------------------------------
 // BandwidthMarket — главный объект рынка
 // -----------------------------------------------------------------------------
@@ -170,11 +243,16 @@ pub struct Settlement {
 pub struct BandwidthMarket {
     pub bids: HashMap<u64, BidRequest>,
     pub offers: HashMap<u64, Vec<NodeOffer>>,  // bid_id → offers
+    pub books: BTreeMap<String, Vec<NodeOffer>>, // регион → стоячие offers
     pub results: Vec<AuctionResult>,
     pub settlements: Vec<Settlement>,
-    pub node_balances: HashMap<String, f64>,
     pub market_treasury: f64,
     pub total_volume: f64,
+    pub trade_settlements: Vec<TradeSettlement>,
+    pub total_burned: f64,
+    settled_bids: HashSet<u64>,
+    reported_bids: HashSet<u64>,
+    escrows: HashMap<u64, LockHandle>, // bid_id → эскроу, снять может только рынок
     counter: u64,
 }
 
@@ -183,11 +261,16 @@ impl BandwidthMarket {
         BandwidthMarket {
             bids: HashMap::new(),
             offers: HashMap::new(),
+            books: BTreeMap::new(),
             results: vec![],
             settlements: vec![],
-            node_balances: HashMap::new(),
             market_treasury: 0.0,
             total_volume: 0.0,
+            trade_settlements: vec![],
+            total_burned: 0.0,
+            settled_bids: HashSet::new(),
+            reported_bids: HashSet::new(),
+            escrows: HashMap::new(),
             counter: 0,
         }
     }
@@ -208,6 +291,9 @@ impl BandwidthMarket {
                         latency_ms: u32, guarantee: f64,
                         stake: f64, difficulty: f64) {
         self.counter += 1;
+        // Адресное предложение живёт в регионе заявки
+        let region = self.bids.get(&bid_id)
+            .map(|b| b.destination_region.clone()).unwrap_or_default();
         let offer = NodeOffer {
             offer_id: self.counter,
             node_id: node_id.to_string(),
@@ -215,19 +301,58 @@ impl BandwidthMarket {
             estimated_latency_ms: latency_ms,
             success_guarantee: guarantee,
             stake, region_difficulty: difficulty,
+            region, tier: None,
         };
         self.offers.entry(bid_id).or_default().push(offer);
     }
 
-    /// Провести аукцион — выбрать победителя
-    pub fn run_auction(&mut self, bid_id: u64) -> Option<AuctionResult> {
+    /// Стоячее предложение в книгу offer.region. Выигравшее — снимается
+    pub fn post_offer(&mut self, mut offer: NodeOffer) -> u64 {
+        self.counter += 1;
+        offer.offer_id = self.counter;
+        offer.bid_id = 0;
+        self.books.entry(offer.region.clone()).or_default().push(offer);
+        self.counter
+    }
+
+    /// Разрешить заявке матчиться со стоячими предложениями всех регионов
+    pub fn allow_cross_region(&mut self, bid_id: u64) -> bool {
+        self.bids.get_mut(&bid_id).map(|b| b.cross_region = true).is_some()
+    }
+
+    // Кандидаты заявки: адресные offers + книга её региона (или все
+    // книги при cross_region); стоячие offers с тиром — только своему тиру
+    fn candidates(&self, bid: &BidRequest) -> Vec<NodeOffer> {
+        let direct = self.offers.get(&bid.bid_id).into_iter().flatten();
+        let standing = self.books.iter()
+            .filter(|(region, _)| bid.cross_region || **region == bid.destination_region)
+            .flat_map(|(_, book)| book)
+            .filter(|o| o.tier.as_ref().is_none_or(|t| *t == bid.tier));
+        direct.chain(standing).cloned().collect()
+    }
+
+    /// Провести аукцион — выбрать победителя.
+    /// Ранг offer = (1−w)·score/max_score + w·rep/max_rep, где
+    /// w = tier.reputation_weight(), а репутация берётся из реестра
+    /// (неизвестный или забаненный узел — 0).
+    /// max_price заявки — резерв: выше него сделка не проходит никогда.
+    /// None — заявки или предложений нет; cleared=false — были, но не подошли
+    pub fn run_auction(&mut self, bid_id: u64,
+                       reputations: &ReputationRegistry) -> Option<AuctionResult> {
         let bid = self.bids.get(&bid_id)?.clone();
-        let offers = self.offers.get(&bid_id)?;
+        let offers = self.candidates(&bid);
+
+        if offers.is_empty() { return None; }
 
         // Фильтруем: цена <= max_price
         let mut valid: Vec<&NodeOffer> = offers.iter()
             .filter(|o| o.price <= bid.max_price)
             .collect();
+        if valid.is_empty() {
+            let best = offers.iter().map(|o| o.price).fold(f64::MAX, f64::min);
+            return Some(AuctionResult::declined(bid_id, offers.len(), format!(
+                "reserve: лучшая цена {:.2} > max_price {:.2}", best, bid.max_price)));
+        }
 
         // Проверяем требования тактики для тира
         if let Some(required_tactic) = bid.tier.requires_tactic() {
@@ -235,11 +360,22 @@ impl BandwidthMarket {
                 || o.tactic == "Hybrid");
         }
 
-        if valid.is_empty() { return None; }
+        if valid.is_empty() {
+            return Some(AuctionResult::declined(bid_id, offers.len(), format!(
+                "tactic: {} требует {}", bid.tier.name(),
+                bid.tier.requires_tactic().unwrap_or("-"))));
+        }
 
-        // Победитель — максимальный score
+        // Победитель — максимальный смешанный ранг
+        let rep_of = |o: &NodeOffer| reputations.nodes.get(&o.node_id)
+            .filter(|n| !n.is_blacklisted).map(|n| n.score).unwrap_or(0.0);
+        let max_score = valid.iter().map(|o| o.score()).fold(0.0f64, f64::max).max(1e-9);
+        let max_rep = valid.iter().map(|o| rep_of(o)).fold(0.0f64, f64::max).max(1e-9);
+        let w = bid.tier.reputation_weight();
+        let rank = |o: &NodeOffer| (1.0 - w) * o.score() / max_score + w * rep_of(o) / max_rep;
         let winner = valid.iter()
-            .max_by(|a, b| a.score().partial_cmp(&b.score()).unwrap())?;
+            .max_by(|a, b| rank(a).partial_cmp(&rank(b))
+                .unwrap_or(std::cmp::Ordering::Equal))?;
 
         let market_fee = winner.price * MARKET_FEE_RATE;
         let node_revenue = winner.price - market_fee;
@@ -253,44 +389,124 @@ impl BandwidthMarket {
             market_fee,
             node_revenue,
             success_guarantee: winner.success_guarantee,
+            cleared: true,
+            reason: "OK".into(),
+            region: bid.destination_region.clone(),
+            region_difficulty: winner.region_difficulty,
         };
 
+        if winner.bid_id == 0 {
+            let offer_id = winner.offer_id;
+            if let Some(book) = self.books.get_mut(&winner.region) {
+                book.retain(|o| o.offer_id != offer_id);
+            }
+        }
         self.results.push(result.clone());
         self.total_volume += winner.price;
         self.market_treasury += market_fee;
         Some(result)
     }
 
-    /// Расчёт после доставки
-    pub fn settle(&mut self, bid_id: u64, node_id: &str,
-                  delivered: bool, agreed_price: f64,
-                  stake: f64) -> Settlement {
-        let (actual_paid, slash, reason) = if delivered {
-            (agreed_price, 0.0, "Доставлено успешно".into())
-        } else {
-            let slash = stake * SLASHING_RATE;
-            (0.0, slash, "Провал доставки — slash".into())
+    /// Расчёт сделки в credits: покупатель платит цену клиринга,
+    /// узел получает выручку, комиссия уходит в казну и частично сжигается.
+    /// Гарантированная часть выручки (× success_guarantee) блокируется на счёте
+    /// покупателя и уходит узлу только после report_delivery
+    pub fn settle(&mut self, trade: &AuctionResult, ledger: &mut CreditLedger,
+                  mint: &mut MintEngine) -> Result<TradeSettlement, &'static str> {
+        if !trade.cleared {
+            return Err("Аукцион не состоялся");
+        }
+        if self.settled_bids.contains(&trade.bid_id) {
+            return Err("Сделка уже рассчитана");
+        }
+        let buyer = self.bids.get(&trade.bid_id)
+            .ok_or("Неизвестная заявка")?.user_id.clone();
+        // Проверяем заранее, чтобы не оставить сделку рассчитанной наполовину
+        if ledger.balance(&buyer) < trade.winning_price {
+            return Err("Недостаточно credits у покупателя");
+        }
+
+        // Эскроу берётся первым: если переводы ниже сорвутся, он возвращается
+        let escrowed = trade.node_revenue * trade.success_guarantee.clamp(0.0, 1.0);
+        let escrow = if escrowed > 0.0 { Some(ledger.lock_held(&buyer, escrowed)?) } else { None };
+        let paid = ledger.transfer(&buyer, &trade.winner_node, trade.node_revenue - escrowed)
+            .and_then(|_| ledger.transfer(&buyer, MARKET_TREASURY_ACCOUNT, trade.market_fee));
+        if let Err(e) = paid {
+            if let Some(handle) = escrow { ledger.unlock_held(handle)?; }
+            return Err(e);
+        }
+        if let Some(handle) = escrow { self.escrows.insert(trade.bid_id, handle); }
+        let burned = mint.burn_market_fee(trade.market_fee);
+        ledger.burn(MARKET_TREASURY_ACCOUNT, burned)?;
+
+        self.market_treasury -= burned;
+        self.total_burned += burned;
+        self.settled_bids.insert(trade.bid_id);
+
+        let s = TradeSettlement {
+            bid_id: trade.bid_id,
+            buyer,
+            seller: trade.winner_node.clone(),
+            clearing_price: trade.winning_price,
+            seller_revenue: trade.node_revenue,
+            market_fee: trade.market_fee,
+            burned,
+            treasury_share: trade.market_fee - burned,
+            escrowed,
         };
+        self.trade_settlements.push(s.clone());
+        Ok(s)
+    }
 
-        let net = actual_paid - slash;
-        *self.node_balances.entry(node_id.to_string()).or_insert(0.0) += net;
+    /// Исход доставки по рассчитанной сделке (trade_id = bid_id).
+    /// Успех — эскроу переходит узлу, репутация растёт. Провал — эскроу
+    /// (seller_revenue × success_guarantee) возвращается покупателю целиком
+    /// и slash репутации, тоже пропорциональный гарантии
+    pub fn report_delivery(&mut self, trade_id: u64, delivered: bool,
+                           ledger: &mut CreditLedger,
+                           reputations: &mut ReputationRegistry)
+                           -> Result<Settlement, &'static str> {
+        if self.reported_bids.contains(&trade_id) {
+            return Err("Доставка уже отмечена");
+        }
+        let trade = self.trade_settlements.iter().find(|t| t.bid_id == trade_id)
+            .ok_or("Сделка не рассчитана")?.clone();
+        let result = self.results.iter().find(|r| r.bid_id == trade_id)
+            .ok_or("Неизвестная сделка")?.clone();
+
+        let escrow = match self.escrows.remove(&trade_id) {
+            Some(handle) => ledger.unlock_held(handle)?,
+            None => 0.0,
+        };
+        let (refund, reason) = if delivered {
+            // Только что разблокировано — на балансе покупателя точно есть
+            ledger.transfer(&trade.buyer, &trade.seller, escrow)?;
+            reputations.record_delivery(&trade.seller, &result.winning_tactic,
+                result.region_difficulty);
+            (0.0, "Доставлено успешно".to_string())
+        } else {
+            reputations.slash(&trade.seller, GUARANTEE_BREACH_SLASH * result.success_guarantee,
+                &format!("market: провал доставки bid {} ({})", trade_id, result.region));
+            (escrow, format!("Провал при гарантии {:.0}% — возврат {:.3} из эскроу",
+                result.success_guarantee * 100.0, escrow))
+        };
+        self.reported_bids.insert(trade_id);
 
         let s = Settlement {
-            bid_id, node_id: node_id.to_string(),
-            delivered, agreed_price, actual_paid,
-            slash_amount: slash, net_earnings: net, reason,
+            bid_id: trade_id, node_id: trade.seller, delivered,
+            agreed_price: trade.clearing_price,
+            actual_paid: trade.clearing_price - refund,
+            slash_amount: refund,
+            net_earnings: trade.seller_revenue - refund,
+            reason,
         };
         self.settlements.push(s.clone());
-        s
+        Ok(s)
     }
 
     pub fn price_discovery(&self, region: &str) -> PriceStats {
         let relevant: Vec<&AuctionResult> = self.results.iter()
-            .filter(|r| {
-                self.bids.get(&r.bid_id)
-                    .map(|b| b.destination_region == region)
-                    .unwrap_or(false)
-            }).collect();
+            .filter(|r| r.region == region).collect();
 
         if relevant.is_empty() {
             return PriceStats { region: region.to_string(),
@@ -322,6 +538,9 @@ impl BandwidthMarket {
                 .filter(|s| s.delivered).count(),
             total_slashed: self.settlements.iter()
                 .map(|s| s.slash_amount).sum(),
+            regions: self.results.iter().map(|r| r.region.as_str())
+                .collect::<BTreeSet<_>>().into_iter()
+                .map(|region| self.price_discovery(region)).collect(),
         }
     }
 }
@@ -346,6 +565,7 @@ pub struct MarketStats {
     pub market_treasury: f64,
     pub successful_deliveries: usize,
     pub total_slashed: f64,
+    pub regions: Vec<PriceStats>,  // цены клиринга по регионам
 }
 
 impl std::fmt::Display for MarketStats {
@@ -363,6 +583,187 @@ impl std::fmt::Display for MarketStats {
             self.fill_rate * 100.0,
             self.total_volume, self.market_treasury,
             self.successful_deliveries, self.total_slashed,
-        )
+        )?;
+        for r in &self.regions {
+            write!(f, "\n  {:<4} клиринг avg {:.3} [{:.3}..{:.3}]  сделок: {}",
+                r.region, r.avg, r.min, r.max, r.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::BURN_RATE;
+
+    fn funded_trade(ledger: &mut CreditLedger) -> (BandwidthMarket, AuctionResult) {
+        ledger.balances.insert("alice".into(), 100.0);
+        let mut market = BandwidthMarket::new();
+        let bid = market.submit_bid("alice", "CN", 64, 20.0, TrafficTier::Standard);
+        market.submit_offer("node_cn", bid, 10.0, "AikiReflection", 40, 0.9, 5.0, 0.85);
+        let trade = market.run_auction(bid, &ReputationRegistry::new()).unwrap();
+        (market, trade)
+    }
+
+    #[test]
+    fn test_auction_respects_reserve_price() {
+        let reps = ReputationRegistry::new();
+        let mut market = BandwidthMarket::new();
+        let bid = market.submit_bid("alice", "CN", 64, 3.0, TrafficTier::Standard);
+        market.submit_offer("node_a", bid, 2.5, "AikiReflection", 40, 0.9, 1.0, 0.8);
+        market.submit_offer("node_b", bid, 4.0, "AikiReflection", 10, 0.99, 1.0, 0.8);
+        let r = market.run_auction(bid, &reps).unwrap();
+        assert!(r.cleared);
+        assert_eq!(r.winner_node, "node_a");
+        assert!(r.winning_price <= 3.0);
+
+        // Тонкий рынок: все предложения выше резерва
+        let thin = market.submit_bid("bob", "CN", 64, 1.0, TrafficTier::Standard);
+        market.submit_offer("node_b", thin, 4.0, "AikiReflection", 10, 0.99, 1.0, 0.8);
+        market.submit_offer("node_c", thin, 9.0, "AikiReflection", 10, 0.99, 1.0, 0.8);
+        let declined = market.run_auction(thin, &reps).unwrap();
+        assert!(!declined.cleared);
+        assert!(declined.reason.starts_with("reserve"));
+        assert_eq!(market.results.len(), 1);
+
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("bob".into(), 100.0);
+        assert!(market.settle(&declined, &mut ledger, &mut MintEngine::new()).is_err());
+    }
+
+    #[test]
+    fn test_auction_weighs_reputation_by_tier() {
+        let mut reps = ReputationRegistry::new();
+        for _ in 0..200 { reps.record_delivery("node_veteran", "StandoffDecoy", 0.8); }
+        reps.record_delivery("node_cheap", "StandoffDecoy", 0.1);
+
+        let mut market = BandwidthMarket::new();
+        let armored = market.submit_bid("alice", "CN", 512, 8.0, TrafficTier::Armored);
+        let economy = market.submit_bid("bob", "DE", 64, 8.0, TrafficTier::Economy);
+        for bid in [armored, economy] {
+            market.submit_offer("node_cheap",   bid, 5.0, "StandoffDecoy", 100, 0.9, 2.0, 0.8);
+            market.submit_offer("node_veteran", bid, 5.8, "StandoffDecoy", 100, 0.9, 2.0, 0.8);
+        }
+
+        assert_eq!(market.run_auction(armored, &reps).unwrap().winner_node, "node_veteran");
+        assert_eq!(market.run_auction(economy, &reps).unwrap().winner_node, "node_cheap");
+    }
+
+    #[test]
+    fn test_settle_conserves_credits_minus_burn() {
+        let mut ledger = CreditLedger::new();
+        let mut mint = MintEngine::new();
+        let (mut market, trade) = funded_trade(&mut ledger);
+        let before: f64 = ledger.balances.values().sum();
+
+        let s = market.settle(&trade, &mut ledger, &mut mint).unwrap();
+        let expected_burn = trade.market_fee * BURN_RATE;
+
+        assert!((s.burned - expected_burn).abs() < 1e-12);
+        assert!((ledger.balance("alice") - 90.0).abs() < 1e-9);
+        assert!((ledger.balance("node_cn") - (trade.node_revenue - s.escrowed)).abs() < 1e-9);
+        assert!((ledger.locked("alice") - s.escrowed).abs() < 1e-9);
+        assert_eq!(ledger.locked("node_cn"), 0.0);
+        let after: f64 = ledger.balances.values().sum::<f64>() + ledger.locked("alice");
+        assert!((before - after - expected_burn).abs() < 1e-9);
+        assert!((mint.burn_ledger.total_burned - expected_burn).abs() < 1e-12);
+        assert!((ledger.total_credits_burned - expected_burn).abs() < 1e-12);
+        assert!((market.total_burned - expected_burn).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_settle_rejects_double_and_unfunded() {
+        let mut ledger = CreditLedger::new();
+        let mut mint = MintEngine::new();
+        let (mut market, trade) = funded_trade(&mut ledger);
+        market.settle(&trade, &mut ledger, &mut mint).unwrap();
+        assert!(market.settle(&trade, &mut ledger, &mut mint).is_err());
+
+        let mut poor = CreditLedger::new();
+        let (mut market, trade) = funded_trade(&mut poor);
+        poor.balances.insert("alice".into(), 1.0);
+        assert!(market.settle(&trade, &mut poor, &mut mint).is_err());
+        assert_eq!(poor.balance("alice"), 1.0);
+        assert_eq!(mint.burn_ledger.burn_events.len(), 1);
+    }
+
+    #[test]
+    fn test_region_books_keep_pricing_separate() {
+        let reps = ReputationRegistry::new();
+        let mut market = BandwidthMarket::new();
+        market.post_offer(NodeOffer::standing("node_de", "DE", 0.5, "Passive"));
+        market.post_offer(NodeOffer::standing("node_cn", "CN", 6.0, "AikiReflection"));
+        let mut cn_premium = NodeOffer::standing("node_cn_hybrid", "CN", 7.0, "Hybrid");
+        cn_premium.tier = Some(TrafficTier::Premium);
+        market.post_offer(cn_premium);
+
+        // Дешёвый DE в книге есть, но CN-заявка видит только CN
+        let cn = market.submit_bid("alice", "CN", 64, 10.0, TrafficTier::Standard);
+        let r = market.run_auction(cn, &reps).unwrap();
+        assert_eq!((r.winner_node.as_str(), r.competing_offers), ("node_cn", 1));
+        assert_eq!(r.region, "CN");
+        let de = market.submit_bid("bob", "DE", 64, 10.0, TrafficTier::Standard);
+        assert_eq!(market.run_auction(de, &reps).unwrap().winner_node, "node_de");
+
+        // Книги опустели, кроме тирового CN; чужой тир его не видит
+        let cn2 = market.submit_bid("carol", "CN", 64, 10.0, TrafficTier::Standard);
+        assert!(market.run_auction(cn2, &reps).is_none());
+
+        // Межрегиональный матчинг — только явно
+        market.post_offer(NodeOffer::standing("node_de2", "DE", 0.6, "Passive"));
+        let cn3 = market.submit_bid("dave", "CN", 64, 10.0, TrafficTier::Standard);
+        assert!(market.run_auction(cn3, &reps).is_none());
+        assert!(market.allow_cross_region(cn3));
+        assert_eq!(market.run_auction(cn3, &reps).unwrap().winner_node, "node_de2");
+
+        let stats = market.market_stats();
+        let price = |region: &str| stats.regions.iter().find(|p| p.region == region).unwrap();
+        assert_eq!(stats.regions.len(), 2);
+        assert_eq!((price("CN").count, price("DE").count), (2, 1));
+        assert_eq!(price("CN").max, 6.0);
+        assert_eq!(price("DE").avg, 0.5);
+    }
+
+    #[test]
+    fn test_failed_delivery_claws_back_guarantee() {
+        let mut mint = MintEngine::new();
+        let mut reps = ReputationRegistry::new();
+        for _ in 0..20 { reps.record_delivery("node_cn", "AikiReflection", 0.85); }
+
+        let mut ledger = CreditLedger::new();
+        let (mut market, trade) = funded_trade(&mut ledger);
+        market.settle(&trade, &mut ledger, &mut mint).unwrap();
+        let rep_before = reps.nodes["node_cn"].score;
+        assert!(market.report_delivery(99, false, &mut ledger, &mut reps).is_err());
+
+        // Гарантия заблокирована у покупателя — ни он, ни узел её не тратят
+        let refund = trade.node_revenue * 0.9;
+        assert!((ledger.locked("alice") - refund).abs() < 1e-9);
+        let spendable = ledger.balance("alice");
+        assert!(ledger.transfer("alice", "elsewhere", spendable + refund).is_err());
+        assert!(ledger.unlock("market-escrow-1").is_err());
+
+        let s = market.report_delivery(trade.bid_id, false, &mut ledger, &mut reps).unwrap();
+        assert!((s.slash_amount - refund).abs() < 1e-9);
+        assert!((ledger.balance("alice") - (90.0 + refund)).abs() < 1e-9);
+        assert_eq!(ledger.locked("alice"), 0.0);
+        assert!((ledger.balance("node_cn") - (trade.node_revenue - refund)).abs() < 1e-9);
+        assert!(reps.nodes["node_cn"].score < rep_before);
+        assert!(market.report_delivery(trade.bid_id, false, &mut ledger, &mut reps).is_err());
+
+        // Успех — без возврата, репутация растёт
+        let mut ledger = CreditLedger::new();
+        let (mut market, trade) = funded_trade(&mut ledger);
+        market.settle(&trade, &mut ledger, &mut mint).unwrap();
+        let rep_before = reps.nodes["node_cn"].score;
+        assert!(ledger.locked("alice") > 0.0);
+        let s = market.report_delivery(trade.bid_id, true, &mut ledger, &mut reps).unwrap();
+        assert!(s.delivered && s.slash_amount == 0.0);
+        assert!((ledger.balance("alice") - 90.0).abs() < 1e-9);
+        assert!((ledger.balance("node_cn") - trade.node_revenue).abs() < 1e-9);
+        assert_eq!(ledger.locked("alice"), 0.0);
+        assert!(reps.nodes["node_cn"].score > rep_before);
+        assert_eq!(market.market_stats().successful_deliveries, 1);
     }
 }
@@ -0,0 +1,174 @@
+// =============================================================================
+// FEDERATION CORE — metrics.rs
+// Экспорт метрик в текстовом формате Prometheus
+// =============================================================================
+//
+// Оператору десятков узлов удобнее скрейпить метрики, чем читать
+// CLI-дашборд. Источник — FederationSnapshot: те же стабильные поля,
+// что уходят во внешний JSON.
+//
+// Имена — federation_<что>[_<единица>], счётчики оканчиваются на _total.
+// Каждой метрике предшествуют строки # HELP и # TYPE.
+// =============================================================================
+
+use crate::snapshot::FederationSnapshot;
+use std::fmt::Write;
+
+pub const METRICS_TOP_NODES: usize = 10;   // узлов в federation_node_reputation
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    header(out, name, kind, help);
+    sample(out, name, &[], value);
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels.iter()
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v))).collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", format_value(value));
+}
+
+// Prometheus: в значении метки экранируются \, " и перевод строки
+fn escape_label(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(v: f64) -> String {
+    if v.is_nan() { "NaN".into() }
+    else if v.is_infinite() { if v > 0.0 { "+Inf".into() } else { "-Inf".into() } }
+    else { format!("{}", v) }
+}
+
+pub fn render_prometheus(snapshot: &FederationSnapshot) -> String {
+    let mut out = String::new();
+    let connected = snapshot.nodes.iter().filter(|n| !n.blacklisted).count();
+
+    metric(&mut out, "federation_connected_nodes", "gauge",
+        "Nodes known to the federation and not blacklisted.", connected as f64);
+    metric(&mut out, "federation_blacklisted_nodes", "gauge",
+        "Nodes blacklisted after repeated betrayals.",
+        (snapshot.nodes.len() - connected) as f64);
+    metric(&mut out, "federation_bypasses_total", "counter",
+        "Censorship bypasses that minted credits.", snapshot.supply.total_bypasses as f64);
+    metric(&mut out, "federation_bypass_rate", "gauge",
+        "Bypasses per second over the last minute.", snapshot.bypass_rate);
+    metric(&mut out, "federation_total_supply", "gauge",
+        "Credits in circulation.", snapshot.supply.total_supply);
+    metric(&mut out, "federation_burned_total", "counter",
+        "Credits burned since genesis.", snapshot.supply.total_burned);
+    metric(&mut out, "federation_treasury", "gauge",
+        "Credits held by the DAO treasury.", snapshot.supply.treasury);
+    metric(&mut out, "federation_ethics_violation_score", "gauge",
+        "System ethics violation score, 0..1.", snapshot.ethics.violation_score);
+    metric(&mut out, "federation_ethics_frozen", "gauge",
+        "1 if the kill switch froze any module.",
+        if snapshot.ethics.state == "active" { 0.0 } else { 1.0 });
+    metric(&mut out, "federation_health_score", "gauge",
+        "Aggregated federation health, 0..1.", snapshot.health);
+
+    header(&mut out, "federation_role_devices", "gauge", "Devices per inventory role.");
+    for (role, count) in &snapshot.roles {
+        sample(&mut out, "federation_role_devices", &[("role", role)], *count as f64);
+    }
+
+    let mut top: Vec<_> = snapshot.nodes.iter().collect();
+    top.sort_by(|a, b| b.reputation.total_cmp(&a.reputation).then(a.id.cmp(&b.id)));
+    header(&mut out, "federation_node_reputation", "gauge",
+        "Reputation score of the top nodes.");
+    for node in top.into_iter().take(METRICS_TOP_NODES) {
+        sample(&mut out, "federation_node_reputation",
+            &[("node", &node.id), ("tier", &node.tier)], node.reputation);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{SnapshotEthics, SnapshotNode, SnapshotSupply, SNAPSHOT_SCHEMA_VERSION};
+    use std::collections::{BTreeMap, HashMap};
+
+    fn node(id: &str, reputation: f64, blacklisted: bool) -> SnapshotNode {
+        SnapshotNode { id: id.into(), role: None, layer: None, reputation,
+            tier: "Trusted".into(), betrayals: 0, blacklisted }
+    }
+
+    fn snapshot() -> FederationSnapshot {
+        let mut nodes: Vec<_> = (0..12).map(|i| node(&format!("hub-{:02}", i), i as f64, false))
+            .collect();
+        nodes.push(node("evil \"node\"", 0.0, true));
+        FederationSnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION, taken_at: 0, nodes,
+            roles: BTreeMap::from([("Citadel".to_string(), 2), ("Sentinel".to_string(), 1)]),
+            supply: SnapshotSupply { total_supply: 38500.0, max_supply: 21e6, pct_issued: 0.18,
+                total_burned: 16500.0, treasury: 5500.0, total_bypasses: 500, halving_epoch: 0 },
+            active_tactics: BTreeMap::new(),
+            ethics: SnapshotEthics { state: "active".into(), frozen_modules: vec![],
+                violation_score: 0.25 },
+            health: 0.8, bypass_rate: 1.5,
+        }
+    }
+
+    fn valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    }
+
+    #[test]
+    fn test_prometheus_output_is_valid_and_complete() {
+        let text = render_prometheus(&snapshot());
+        let mut typed: HashMap<String, String> = HashMap::new();
+        let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# ") {
+                let parts: Vec<&str> = rest.splitn(3, ' ').collect();
+                assert!(parts.len() == 3 && valid_name(parts[1]), "{}", line);
+                match parts[0] {
+                    "TYPE" => { assert!(["gauge", "counter"].contains(&parts[2]));
+                                typed.insert(parts[1].into(), parts[2].into()); }
+                    "HELP" => assert!(!parts[2].is_empty()),
+                    other => panic!("неизвестная директива {}", other),
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').expect(line);
+            let name = series.split('{').next().unwrap();
+            assert!(valid_name(name), "{}", line);
+            assert!(typed.contains_key(name), "# TYPE раньше значения: {}", line);
+            if let Some(labels) = series.strip_prefix(name).filter(|l| !l.is_empty()) {
+                assert!(labels.starts_with('{') && labels.ends_with('}'), "{}", line);
+            }
+            values.entry(name.into()).or_default().push(value.parse().expect(line));
+        }
+
+        for (name, kind) in &typed {
+            assert_eq!(kind == "counter", name.ends_with("_total"), "{}", name);
+        }
+        assert_eq!(values["federation_connected_nodes"], [12.0]);
+        assert_eq!(values["federation_bypass_rate"], [1.5]);
+        assert_eq!(values["federation_total_supply"], [38500.0]);
+        assert_eq!(values["federation_burned_total"], [16500.0]);
+        assert_eq!(values["federation_ethics_violation_score"], [0.25]);
+        assert_eq!(values["federation_node_reputation"].len(), METRICS_TOP_NODES);
+        assert_eq!(values["federation_node_reputation"][0], 11.0);
+        assert!(text.contains("federation_node_reputation{node=\"hub-11\",tier=\"Trusted\"} 11"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let mut s = snapshot();
+        s.nodes = vec![node("evil \"node\"\\\n", 1.0, false)];
+        let text = render_prometheus(&s);
+        assert!(text.contains(r#"node="evil \"node\"\\\n""#), "{}", text);
+    }
+}
@@ -14,8 +14,10 @@
 // Supply:  MAX_SUPPLY — абсолютный потолок эмиссии
 // =============================================================================
 
+use crate::ethics::{EthicsLayer, Frozen, MODULE_MINT};
+use crate::rng::{FedRng, XorShift64};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 pub const BASE_REWARD: f64          = 10.0;
 pub const MAX_SUPPLY: f64           = 21_000_000.0; // как Bitcoin
@@ -23,6 +25,8 @@ pub const HALVING_INTERVAL: u64     = 1_000_000;    // каждый 1M прор
 pub const BURN_RATE: f64            = 0.30;          // 30% комиссии сгорает
 pub const MIN_REWARD: f64           = 0.001;         // минимальная награда
 pub const TREASURY_RATE: f64        = 0.10;          // 10% в казну DAO
+pub const RATE_BUCKET_SECS: i64     = 10;            // шаг корзин темпа эмиссии
+pub const RATE_HISTORY_SECS: i64    = 86_400;        // корзины старше суток отбрасываются
 
 // -----------------------------------------------------------------------------
 // HalvingSchedule — расписание халвинга
@@ -103,6 +107,44 @@ impl BurnLedger {
     }
 }
 
+// -----------------------------------------------------------------------------
+// RateBuckets — суммы по корзинам времени для темпа эмиссии/сжигания
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RateBuckets {
+    buckets: VecDeque<(i64, f64)>,   // (начало корзины, сек; сумма)
+}
+
+impl RateBuckets {
+    /// Корзины держатся отсортированными по началу: часы узла могут
+    /// откатиться (NTP), и запоздалая запись встаёт на своё место
+    pub fn add(&mut self, now_secs: i64, amount: f64) {
+        let start = now_secs.div_euclid(RATE_BUCKET_SECS) * RATE_BUCKET_SECS;
+        let latest = self.buckets.back().map_or(now_secs, |(s, _)| now_secs.max(*s));
+        if start < latest - RATE_HISTORY_SECS { return; }
+        match self.buckets.binary_search_by_key(&start, |(s, _)| *s) {
+            Ok(i) => self.buckets[i].1 += amount,
+            Err(i) => self.buckets.insert(i, (start, amount)),
+        }
+        while self.buckets.front().is_some_and(|(s, _)| *s < latest - RATE_HISTORY_SECS) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Средний темп (в секунду) за последние window_secs. Корзина
+    /// входит в окно, если началась не раньше now − window
+    pub fn rate(&self, now_secs: i64, window_secs: u64) -> f64 {
+        if window_secs == 0 { return 0.0; }
+        let from = now_secs - window_secs as i64;
+        let sum: f64 = self.buckets.iter().rev()
+            .take_while(|(s, _)| *s >= from)
+            .filter(|(s, _)| *s <= now_secs)
+            .map(|(_, a)| a).sum();
+        sum / window_secs as f64
+    }
+}
+
 // -----------------------------------------------------------------------------
 // MintEvent — одна эмиссия
 // -----------------------------------------------------------------------------
@@ -136,11 +178,19 @@ pub struct MintEngine {
     pub mint_history: Vec<MintEvent>,
     pub event_counter: u64,
     pub is_exhausted: bool,    // достигнут MAX_SUPPLY
+    emitted: RateBuckets,
+    burned: RateBuckets,
+    bypasses: RateBuckets,
+    clock_secs: Option<i64>,   // None — системное время
 }
 
 impl MintEngine {
     pub fn new() -> Self {
         MintEngine {
+            emitted: RateBuckets::default(),
+            burned: RateBuckets::default(),
+            bypasses: RateBuckets::default(),
+            clock_secs: None,
             total_supply: 0.0,
             total_bypasses: 0,
             halving: HalvingSchedule::new(HALVING_INTERVAL),
@@ -153,9 +203,17 @@ impl MintEngine {
         }
     }
 
-    /// Главная функция — минтить Credits за доказанный прорыв
-    pub fn mint_for_bypass(&mut self, node_id: &str, region: &str,
-                            tactic: &str, difficulty: f64) -> Option<MintEvent> {
+    /// Главная функция — минтить Credits за доказанный прорыв.
+    /// Под KillSwitch: замороженный mint не эмитирует
+    pub fn mint_for_bypass(&mut self, ethics: &EthicsLayer, node_id: &str, region: &str,
+                           tactic: &str, difficulty: f64) -> Result<Option<MintEvent>, Frozen> {
+        ethics.guard(MODULE_MINT)?;
+        Ok(self.mint_unguarded(node_id, region, tactic, difficulty))
+    }
+
+    /// Эмиссия без проверки KillSwitch — только для внутренних симуляций
+    fn mint_unguarded(&mut self, node_id: &str, region: &str,
+                      tactic: &str, difficulty: f64) -> Option<MintEvent> {
         if self.is_exhausted { return None; }
 
         // Тактический множитель
@@ -193,6 +251,10 @@ impl MintEngine {
         self.total_supply += net + treasury; // burned не входит в supply
         self.treasury += treasury;
         self.burn_ledger.burn(burned, "mint_burn");
+        let now = self.now_secs();
+        self.emitted.add(now, gross);
+        self.burned.add(now, burned);
+        self.bypasses.add(now, 1.0);
         *self.node_earnings.entry(node_id.to_string()).or_insert(0.0) += net;
 
         // Халвинг
@@ -220,27 +282,76 @@ impl MintEngine {
     pub fn burn_market_fee(&mut self, fee: f64) -> f64 {
         let burn_amount = fee * BURN_RATE;
         self.burn_ledger.burn(burn_amount, "market_fee");
+        let now = self.now_secs();
+        self.burned.add(now, burn_amount);
         burn_amount
     }
 
+    /// Зафиксировать часы (сек) — для симуляций и тестов
+    pub fn set_clock(&mut self, now_secs: i64) { self.clock_secs = Some(now_secs); }
+
+    fn now_secs(&self) -> i64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        self.clock_secs.unwrap_or_else(|| SystemTime::now()
+            .duration_since(UNIX_EPOCH).unwrap().as_secs() as i64)
+    }
+
+    /// Темп эмиссии (gross, Credits/сек) за последние window_secs
+    pub fn emission_rate(&self, window_secs: u64) -> f64 {
+        self.emitted.rate(self.now_secs(), window_secs)
+    }
+
+    /// Темп сжигания (Credits/сек) за последние window_secs
+    pub fn burn_rate(&self, window_secs: u64) -> f64 {
+        self.burned.rate(self.now_secs(), window_secs)
+    }
+
+    /// Темп прорывов с эмиссией (в секунду) за последние window_secs
+    pub fn bypass_rate(&self, window_secs: u64) -> f64 {
+        self.bypasses.rate(self.now_secs(), window_secs)
+    }
+
     /// Симуляция N прорывов — быстрый расчёт
     pub fn simulate_bypasses(&mut self, count: u64, node_id: &str,
                               region: &str, tactic: &str,
                               difficulty: f64) -> SimResult {
+        // Одна сложность — генератор не тратится
+        self.simulate_bypasses_with(count, node_id, region, tactic,
+            &[difficulty], &mut XorShift64::new(0))
+    }
+
+    /// Симуляция со смесью сложностей: каждый прорыв берёт сложность из
+    /// difficulties через rng. Один seed — одинаковый SimResult
+    pub fn simulate_bypasses_with(&mut self, count: u64, node_id: &str,
+                              region: &str, tactic: &str,
+                              difficulties: &[f64], rng: &mut dyn FedRng) -> SimResult {
         let supply_before = self.total_supply;
-        let _bypasses_before = self.total_bypasses;
         let mut minted = 0.0;
         let mut burned = 0.0;
         let mut halvings = 0;
+        // Welford: среднее и разброс эмиссии за один прорыв
+        let (mut n, mut mean, mut m2) = (0u64, 0.0, 0.0);
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
 
         for _ in 0..count {
-            if let Some(e) = self.mint_for_bypass(node_id, region, tactic, difficulty) {
+            let difficulty = match difficulties.len() {
+                0 => 0.0,
+                1 => difficulties[0],
+                len => difficulties[(rng.next_u64() % len as u64) as usize],
+            };
+            if let Some(e) = self.mint_unguarded(node_id, region, tactic, difficulty) {
                 minted += e.gross_minted;
                 burned += e.burned;
                 if e.halving_epoch > self.halving.current_epoch
                     .saturating_sub(halvings as u32) {
                     halvings += 1;
                 }
+                n += 1;
+                let delta = e.gross_minted - mean;
+                mean += delta / n as f64;
+                m2 += delta * (e.gross_minted - mean);
+                min = min.min(e.gross_minted);
+                max = max.max(e.gross_minted);
             } else { break; }
         }
 
@@ -251,6 +362,9 @@ impl MintEngine {
             net_supply_added: self.total_supply - supply_before,
             halvings_triggered: halvings,
             avg_per_bypass: if count > 0 { minted / count as f64 } else { 0.0 },
+            stddev_per_bypass: if n > 0 { (m2 / n as f64).sqrt() } else { 0.0 },
+            min_per_bypass: if n > 0 { min } else { 0.0 },
+            max_per_bypass: if n > 0 { max } else { 0.0 },
             supply_after: self.total_supply,
             inflation_rate: (self.total_supply - supply_before)
                 / supply_before.max(1.0) * 100.0,
@@ -289,6 +403,9 @@ pub struct SimResult {
     pub net_supply_added: f64,
     pub halvings_triggered: usize,
     pub avg_per_bypass: f64,
+    pub stddev_per_bypass: f64,   // разброс эмиссии за прорыв
+    pub min_per_bypass: f64,
+    pub max_per_bypass: f64,
     pub supply_after: f64,
     pub inflation_rate: f64,
 }
@@ -339,6 +456,10 @@ impl std::fmt::Display for SupplyStats {
 // =============================================================================
 
 pub const POLICY_CHANGE_COOLDOWN: u64 = 100; // прорывов между изменениями
+pub const POLICY_CHANGE_WINDOW: u64 = 1_000; // окно лимита изменений, в прорывах
+pub const MAX_CHANGES_PER_WINDOW: usize = 2; // изменений политики за окно
+pub const MIN_SIGNAL_CONFIDENCE: f64   = 0.70; // ниже — сигнал не принимается
+pub const MIN_SIGNAL_APPROVALS: usize  = 3;    // минимум одобривших сценариев
 pub const MAX_BURN_RATE: f64           = 0.50;
 pub const MIN_BURN_RATE: f64           = 0.10;
 pub const MAX_TACTIC_MULT: f64         = 4.0;
@@ -380,6 +501,54 @@ impl EmissionParam {
     }
 }
 
+// -----------------------------------------------------------------------------
+// MintError — ошибки управления политикой эмиссии
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MintError {
+    UnknownVersion(u32),
+    AlreadyCurrent(u32),
+    PolicyPinned(u32),
+}
+
+impl std::fmt::Display for MintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MintError::UnknownVersion(v) => write!(f, "версия политики v{} не найдена", v),
+            MintError::AlreadyCurrent(v) => write!(f, "политика уже соответствует v{}", v),
+            MintError::PolicyPinned(v)   => write!(f, "политика закреплена на v{}", v),
+        }
+    }
+}
+
+impl std::error::Error for MintError {}
+
+// -----------------------------------------------------------------------------
+// PolicyVersion — снимок параметров эмиссии для отката
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyVersion {
+    pub version: u32,
+    pub burn_rate: f64,
+    pub treasury_rate: f64,
+    pub base_reward: f64,
+    pub diff_weight: f64,
+    pub tactic_mults: HashMap<String, f64>,
+}
+
+impl PolicyVersion {
+    /// Совпадают ли параметры (без учёта номера версии)
+    pub fn same_params(&self, other: &PolicyVersion) -> bool {
+        self.burn_rate == other.burn_rate
+            && self.treasury_rate == other.treasury_rate
+            && self.base_reward == other.base_reward
+            && self.diff_weight == other.diff_weight
+            && self.tactic_mults == other.tactic_mults
+    }
+}
+
 // -----------------------------------------------------------------------------
 // EmissionPolicy — текущая активная политика
 // -----------------------------------------------------------------------------
@@ -393,6 +562,8 @@ pub struct EmissionPolicy {
     pub tactic_mults: HashMap<String, f64>,
     pub version: u32,
     pub last_changed_at: u64,   // total_bypasses при последнем изменении
+    pub window_started_at: u64, // начало текущего окна cooldown
+    pub changes_in_window: usize,
     pub change_log: Vec<String>,
 }
 
@@ -408,24 +579,41 @@ impl EmissionPolicy {
             burn_rate: 0.30, treasury_rate: 0.10,
             base_reward: 1.0, diff_weight: 4.0,
             tactic_mults, version: 1,
-            last_changed_at: 0, change_log: vec![],
+            last_changed_at: 0, window_started_at: 0,
+            changes_in_window: 0, change_log: vec![],
         }
     }
 
+    /// Можно ли менять политику: cooldown с последнего изменения прошёл
+    /// и лимит изменений текущего окна не исчерпан
+    pub fn window_open(&self, current_bypasses: u64) -> bool {
+        let cooled = current_bypasses.saturating_sub(self.last_changed_at) >= POLICY_CHANGE_COOLDOWN;
+        let new_window = current_bypasses.saturating_sub(self.window_started_at) >= POLICY_CHANGE_WINDOW;
+        cooled && (new_window || self.changes_in_window < MAX_CHANGES_PER_WINDOW)
+    }
+
+    fn window_remaining(&self, current_bypasses: u64) -> u64 {
+        let cooldown = POLICY_CHANGE_COOLDOWN
+            .saturating_sub(current_bypasses.saturating_sub(self.last_changed_at));
+        if self.changes_in_window < MAX_CHANGES_PER_WINDOW { return cooldown; }
+        cooldown.max(POLICY_CHANGE_WINDOW
+            .saturating_sub(current_bypasses.saturating_sub(self.window_started_at)))
+    }
+
     pub fn apply_signal(&mut self, signal: &IdeaLabSignal,
                          current_bypasses: u64) -> PolicyChangeResult {
-        // Cooldown защита
-        if current_bypasses - self.last_changed_at < POLICY_CHANGE_COOLDOWN {
+        // Cooldown защита + не больше MAX_CHANGES_PER_WINDOW изменений за окно
+        if !self.window_open(current_bypasses) {
             return PolicyChangeResult {
                 applied: false, param: signal.param.name(),
                 old_val: 0.0, new_val: 0.0,
                 reason: format!("cooldown: ещё {} прорывов",
-                    POLICY_CHANGE_COOLDOWN - (current_bypasses - self.last_changed_at)),
+                    self.window_remaining(current_bypasses)),
             };
         }
 
         // ИИ уверен достаточно?
-        if signal.ai_confidence < 0.70 {
+        if signal.ai_confidence < MIN_SIGNAL_CONFIDENCE {
             return PolicyChangeResult {
                 applied: false, param: signal.param.name(),
                 old_val: 0.0, new_val: 0.0,
@@ -466,6 +654,11 @@ impl EmissionPolicy {
             }
         };
 
+        if current_bypasses.saturating_sub(self.window_started_at) >= POLICY_CHANGE_WINDOW {
+            self.window_started_at = current_bypasses;
+            self.changes_in_window = 0;
+        }
+        self.changes_in_window += 1;
         self.version += 1;
         self.last_changed_at = current_bypasses;
         self.change_log.push(format!("v{}: {} {:.3}→{:.3} (P{} conf={:.0}%)",
@@ -481,6 +674,31 @@ impl EmissionPolicy {
     pub fn tactic_mult(&self, tactic: &str) -> f64 {
         *self.tactic_mults.get(tactic).unwrap_or(&1.0)
     }
+
+    pub fn snapshot(&self) -> PolicyVersion {
+        PolicyVersion {
+            version: self.version,
+            burn_rate: self.burn_rate,
+            treasury_rate: self.treasury_rate,
+            base_reward: self.base_reward,
+            diff_weight: self.diff_weight,
+            tactic_mults: self.tactic_mults.clone(),
+        }
+    }
+
+    /// Восстановить параметры снимка. Версия не откатывается —
+    /// откат сам становится новой версией в журнале
+    fn restore(&mut self, snap: &PolicyVersion, current_bypasses: u64) {
+        self.burn_rate = snap.burn_rate;
+        self.treasury_rate = snap.treasury_rate;
+        self.base_reward = snap.base_reward;
+        self.diff_weight = snap.diff_weight;
+        self.tactic_mults = snap.tactic_mults.clone();
+        self.version += 1;
+        self.last_changed_at = current_bypasses;
+        self.change_log.push(format!("v{}: ROLLBACK → параметры v{}",
+            self.version, snap.version));
+    }
 }
 
 #[derive(Debug)]
@@ -502,27 +720,97 @@ pub struct AdaptiveMintEngine {
     pub total_minted: f64,
     pub pending_signals: Vec<IdeaLabSignal>,
     pub applied_signals: Vec<IdeaLabSignal>,
+    pub dropped_signals: Vec<(IdeaLabSignal, String)>, // (сигнал, причина)
+    pub versions: Vec<PolicyVersion>,                  // история для отката
+    pub pinned_version: Option<u32>,                   // закреплённая версия
 }
 
 impl AdaptiveMintEngine {
     pub fn new() -> Self {
+        let policy = EmissionPolicy::default_policy();
+        let versions = vec![policy.snapshot()];
         AdaptiveMintEngine {
-            policy: EmissionPolicy::default_policy(),
+            policy,
             total_bypasses: 0, total_minted: 0.0,
             pending_signals: vec![], applied_signals: vec![],
+            dropped_signals: vec![], versions,
+            pinned_version: None,
         }
     }
 
-    pub fn propose_change(&mut self, signal: IdeaLabSignal) {
-        self.pending_signals.push(signal);
+    /// Вернуть параметры одной из прошлых версий. Откат записывается
+    /// в журнал как новая версия — история не переписывается
+    pub fn rollback_to(&mut self, version: u32) -> Result<(), MintError> {
+        if let Some(pinned) = self.pinned_version {
+            return Err(MintError::PolicyPinned(pinned));
+        }
+        let snap = self.versions.iter().find(|v| v.version == version)
+            .cloned().ok_or(MintError::UnknownVersion(version))?;
+        if snap.same_params(&self.policy.snapshot()) {
+            return Err(MintError::AlreadyCurrent(version));
+        }
+        self.policy.restore(&snap, self.total_bypasses);
+        self.versions.push(self.policy.snapshot());
+        Ok(())
     }
 
+    /// Закрепить текущую версию — сигналы копятся в очереди, откаты запрещены
+    pub fn pin_version(&mut self) -> u32 {
+        self.pinned_version = Some(self.policy.version);
+        self.policy.version
+    }
+
+    pub fn unpin_version(&mut self) {
+        self.pinned_version = None;
+    }
+
+    /// Поставить сигнал в очередь. Слабые сигналы отбрасываются сразу
+    pub fn propose_change(&mut self, signal: IdeaLabSignal) -> Result<(), String> {
+        let reason = if signal.ai_confidence < MIN_SIGNAL_CONFIDENCE {
+            format!("низкая уверенность ИИ: {:.0}%", signal.ai_confidence * 100.0)
+        } else if signal.approved_by < MIN_SIGNAL_APPROVALS {
+            format!("мало одобрений: {} < {}", signal.approved_by, MIN_SIGNAL_APPROVALS)
+        } else {
+            self.pending_signals.push(signal);
+            return Ok(());
+        };
+        self.dropped_signals.push((signal, reason.clone()));
+        Err(reason)
+    }
+
+    /// Применить очередь: самые уверенные сигналы первыми, в пределах
+    /// лимита окна. Остальные остаются в очереди до следующего окна
     pub fn process_signals(&mut self) -> Vec<PolicyChangeResult> {
-        let signals = std::mem::take(&mut self.pending_signals);
+        let mut signals = std::mem::take(&mut self.pending_signals);
+        signals.sort_by(|a, b| b.ai_confidence.partial_cmp(&a.ai_confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.approved_by.cmp(&a.approved_by)));
         let bypasses = self.total_bypasses;
         signals.into_iter().map(|sig| {
+            if let Some(pinned) = self.pinned_version {
+                let r = PolicyChangeResult {
+                    applied: false, param: sig.param.name(),
+                    old_val: 0.0, new_val: 0.0,
+                    reason: format!("отложен: политика закреплена на v{}", pinned),
+                };
+                self.pending_signals.push(sig);
+                return r;
+            }
+            if !self.policy.window_open(bypasses) {
+                let r = PolicyChangeResult {
+                    applied: false, param: sig.param.name(),
+                    old_val: 0.0, new_val: 0.0,
+                    reason: format!("отложен: cooldown/лимит окна, ещё {} прорывов",
+                        self.policy.window_remaining(bypasses)),
+                };
+                self.pending_signals.push(sig);
+                return r;
+            }
             let r = self.policy.apply_signal(&sig, bypasses);
-            if r.applied { self.applied_signals.push(sig); }
+            if r.applied {
+                self.versions.push(self.policy.snapshot());
+                self.applied_signals.push(sig);
+            }
             r
         }).collect()
     }
@@ -541,3 +829,175 @@ impl AdaptiveMintEngine {
 }
 
 impl Default for AdaptiveMintEngine { fn default() -> Self { Self::new() } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_simulation_reproducible_and_reports_variance() {
+        let mix = [0.05, 0.3, 0.6, 0.99];
+        let run = |seed: u64, mix: &[f64]| {
+            let mut mint = MintEngine::new();
+            mint.simulate_bypasses_with(10_000, "node", "CN", "AikiReflection",
+                mix, &mut XorShift64::new(seed))
+        };
+        let (a, b) = (run(42, &mix), run(42, &mix));
+        assert_eq!(a.total_minted, b.total_minted);
+        assert_eq!(a.stddev_per_bypass, b.stddev_per_bypass);
+        assert_eq!((a.min_per_bypass, a.max_per_bypass), (b.min_per_bypass, b.max_per_bypass));
+        assert_ne!(run(7, &mix).total_minted, a.total_minted);
+
+        let uniform = run(42, &[0.5]);
+        assert!(uniform.stddev_per_bypass < 1e-9);
+        assert_eq!(uniform.min_per_bypass, uniform.max_per_bypass);
+        assert!(a.stddev_per_bypass > uniform.stddev_per_bypass + 10.0);
+        assert!(a.min_per_bypass < a.max_per_bypass);
+        assert!((a.min_per_bypass..=a.max_per_bypass).contains(&a.avg_per_bypass));
+    }
+
+    #[test]
+    fn test_emission_rate_tracks_burst_and_decays() {
+        let mut mint = MintEngine::new();
+        let ethics = EthicsLayer::new();
+        let mut gross = [0.0; 2];
+        let mut burned = [0.0; 2];
+        for (i, t) in [1_000, 1_030].into_iter().enumerate() {
+            mint.set_clock(t);
+            for _ in 0..5 {
+                let e = mint.mint_for_bypass(&ethics, "node_tokyo", "CN", "AikiReflection", 0.85)
+                    .unwrap().unwrap();
+                gross[i] += e.gross_minted;
+                burned[i] += e.burned;
+            }
+        }
+        burned[1] += mint.burn_market_fee(10.0);
+
+        mint.set_clock(1_040);
+        assert!((mint.emission_rate(60) - (gross[0] + gross[1]) / 60.0).abs() < 1e-9);
+        assert!((mint.burn_rate(60) - (burned[0] + burned[1]) / 60.0).abs() < 1e-9);
+        // Окно сползло с первой половины всплеска
+        mint.set_clock(1_070);
+        assert!((mint.emission_rate(60) - gross[1] / 60.0).abs() < 1e-9);
+        assert!((mint.burn_rate(60) - burned[1] / 60.0).abs() < 1e-9);
+        mint.set_clock(1_100);
+        assert_eq!(mint.emission_rate(60), 0.0);
+        assert_eq!(mint.burn_rate(60), 0.0);
+        // Длинное окно помнит всё
+        assert!((mint.emission_rate(3_600) - (gross[0] + gross[1]) / 3_600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rate_buckets_survive_clock_rollback() {
+        let mut rb = RateBuckets::default();
+        rb.add(1_000, 1.0);
+        rb.add(1_030, 2.0);
+        // Часы откатились: запись попадает в свою корзину, а не в хвост
+        rb.add(1_005, 4.0);
+        rb.add(1_012, 8.0);
+        assert!(rb.buckets.iter().zip(rb.buckets.iter().skip(1)).all(|(a, b)| a.0 < b.0));
+        assert_eq!(rb.buckets.len(), 3);
+        assert!((rb.rate(1_030, 20) - 10.0 / 20.0).abs() < 1e-9);
+        assert!((rb.rate(1_030, 30) - 15.0 / 30.0).abs() < 1e-9);
+        // Слишком старая запись не воскрешает отброшенную историю
+        rb.add(1_030 - RATE_HISTORY_SECS - 100, 16.0);
+        assert_eq!(rb.buckets.len(), 3);
+    }
+
+    fn signal(id: u64, confidence: f64, approved_by: usize) -> IdeaLabSignal {
+        IdeaLabSignal {
+            proposal_id: id, title: format!("P{}", id),
+            domain: "RewardFormula".into(),
+            param: EmissionParam::TacticMultiplier { tactic: format!("T{}", id) },
+            delta: 0.1, ai_confidence: confidence, approved_by,
+        }
+    }
+
+    fn advance(engine: &mut AdaptiveMintEngine, bypasses: u64) {
+        for _ in 0..bypasses { engine.mint("Passive", 0.5); }
+    }
+
+    #[test]
+    fn test_signal_flood_capped_per_window() {
+        let mut engine = AdaptiveMintEngine::new();
+        for i in 0..10 {
+            engine.propose_change(signal(i, 0.71 + i as f64 * 0.02, 4)).unwrap();
+        }
+        // До cooldown — ничего
+        assert!(engine.process_signals().iter().all(|r| !r.applied));
+        // Cooldown прошёл — одно изменение, самое уверенное
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        let results = engine.process_signals();
+        assert_eq!(results.iter().filter(|r| r.applied).count(), 1);
+        assert!(engine.process_signals().iter().all(|r| !r.applied));
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        engine.process_signals();
+        let applied: Vec<u64> = engine.applied_signals.iter()
+            .map(|s| s.proposal_id).collect();
+        assert_eq!(applied, vec![9, 8]);
+        assert_eq!(engine.pending_signals.len(), 8);
+        assert_eq!(engine.policy.version, 1 + MAX_CHANGES_PER_WINDOW as u32);
+
+        // Cooldown снова прошёл, но лимит окна исчерпан
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        assert!(engine.process_signals().iter().all(|r| !r.applied));
+        // Новое окно — следующий сигнал
+        advance(&mut engine, POLICY_CHANGE_WINDOW - 3 * POLICY_CHANGE_COOLDOWN);
+        engine.process_signals();
+        assert_eq!(engine.applied_signals[2].proposal_id, 7);
+        assert_eq!(engine.pending_signals.len(), 7);
+    }
+
+    #[test]
+    fn test_rollback_restores_version_one() {
+        let mut engine = AdaptiveMintEngine::new();
+        let v1_net = AdaptiveMintEngine::new().mint("AikiReflection", 0.8);
+
+        let mut aiki = signal(1, 0.95, 4);
+        aiki.param = EmissionParam::TacticMultiplier { tactic: "AikiReflection".into() };
+        aiki.delta = 0.5;
+        let mut burn = signal(2, 0.90, 4);
+        burn.param = EmissionParam::BurnRate;
+        burn.delta = 0.15;
+        engine.propose_change(aiki).unwrap();
+        engine.propose_change(burn).unwrap();
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        engine.process_signals();
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        engine.process_signals();
+        assert_eq!(engine.policy.version, 3);
+        assert!((engine.mint("AikiReflection", 0.8) - v1_net).abs() > 1e-3);
+
+        engine.rollback_to(1).unwrap();
+        assert_eq!(engine.policy.version, 4);
+        assert!((engine.mint("AikiReflection", 0.8) - v1_net).abs() < 1e-12);
+        assert!(engine.policy.change_log.last().unwrap().contains("ROLLBACK"));
+        assert_eq!(engine.policy.change_log.len(), 3);
+
+        assert_eq!(engine.rollback_to(1), Err(MintError::AlreadyCurrent(1)));
+        assert_eq!(engine.rollback_to(42), Err(MintError::UnknownVersion(42)));
+    }
+
+    #[test]
+    fn test_pinned_policy_defers_signals() {
+        let mut engine = AdaptiveMintEngine::new();
+        assert_eq!(engine.pin_version(), 1);
+        engine.propose_change(signal(1, 0.95, 4)).unwrap();
+        advance(&mut engine, POLICY_CHANGE_COOLDOWN);
+        assert!(engine.process_signals().iter().all(|r| !r.applied));
+        assert_eq!(engine.pending_signals.len(), 1);
+        assert_eq!(engine.rollback_to(1), Err(MintError::PolicyPinned(1)));
+        engine.unpin_version();
+        assert!(engine.process_signals()[0].applied);
+    }
+
+    #[test]
+    fn test_weak_signals_dropped_with_reason() {
+        let mut engine = AdaptiveMintEngine::new();
+        assert!(engine.propose_change(signal(1, 0.62, 4)).is_err());
+        assert!(engine.propose_change(signal(2, 0.95, 1)).is_err());
+        assert!(engine.pending_signals.is_empty());
+        assert_eq!(engine.dropped_signals.len(), 2);
+        assert!(engine.dropped_signals[1].1.contains("одобрений"));
+    }
+}
@@ -20,6 +20,7 @@
 //   Для атакующего: Σ L_i → ∞ (пакеты зацикливаются в лабиринте)
 // =============================================================================
 
+use crate::rng::{FedRng, RngCore, XorShift64};
 use crate::tensor::SsauTensor;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -37,9 +38,40 @@ pub const UNIQUE_SOURCES_THRESHOLD: usize = 5;
 /// Порог отклонения задержек (признак измерения топологии)
 pub const TIMING_ANOMALY_THRESHOLD: f64 = 0.15;
 
+/// Порог энтропии интервалов (биты): ниже — машинная регулярность
+pub const TIMING_ENTROPY_THRESHOLD: f64 = 1.5;
+
+/// Интервалов, после которых энтропия имеет смысл
+pub const TIMING_ENTROPY_MIN_SAMPLES: usize = 16;
+
+/// Корзин гистограммы на октаву интервала (логарифмическая шкала)
+pub const TIMING_ENTROPY_BINS_PER_OCTAVE: f64 = 4.0;
+
 /// Время жизни Mirage-ловушки (секунды)
 pub const MIRAGE_TTL_SECS: u64 = 300;
 
+/// Ёмкость token bucket на источник: столько свежих ложных тензоров подряд
+pub const MIRAGE_BUCKET_CAPACITY: f64 = 20.0;
+
+/// Скорость пополнения token bucket (токенов в секунду)
+pub const MIRAGE_REFILL_PER_SEC: f64 = 2.0;
+
+/// Сколько источников держим в bucket/decoy-кэше; сверх — вытесняем самый давний
+pub const MIRAGE_MAX_TRACKED_SOURCES: usize = 4096;
+
+/// Угроза, после которой источник уходит в карантин без переанализа
+pub const QUARANTINE_WATERMARK: f64 = 0.7;
+
+/// Хопов на один прогон атакующего через лабиринт в forensic-режиме
+pub const MAZE_PROBE_HOPS: usize = 20;
+
+/// Глубина лабиринта: от минимальной угрозы до максимальной
+pub const MAZE_MIN_DEPTH: usize = 4;
+pub const MAZE_MAX_DEPTH: usize = 16;
+
+/// Жёсткий потолок глубины — память на атакующего ограничена при любом конфиге
+pub const MAZE_DEPTH_CAP: usize = 64;
+
 // -----------------------------------------------------------------------------
 // AnomalyScore — оценка угрозы
 // -----------------------------------------------------------------------------
@@ -57,6 +89,8 @@ pub enum AnomalyType {
     DistributedScan,
     /// Повторяющиеся запросы одних и тех же маршрутов
     RouteEnumeration,
+    /// Низкая энтропия интервалов — периодический сканер, даже пачками
+    LowTimingEntropy,
 }
 
 /// Результат анализа аномалий
@@ -88,9 +122,17 @@ struct SourceStats {
     timing_deltas: Vec<f64>,
 }
 
+/// Источник в карантине: вердикт на момент входа и срок
+#[derive(Debug, Clone)]
+struct Quarantined {
+    until_ms: i64,
+    threat_level: f64,
+    anomalies: Vec<AnomalyType>,
+}
+
 /// Детектор аномального поведения.
 /// Анализирует паттерны входящих запросов и выявляет признаки атаки.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct AnomalyDetector {
     /// Статистика по источникам: source_id → stats
     source_stats: HashMap<String, SourceStats>,
@@ -98,6 +140,26 @@ pub struct AnomalyDetector {
     total_requests: u64,
     /// Число активированных Mirage-ловушек
     pub mirage_activations: u64,
+    /// Подтверждённые атакующие: ответ сразу, без analyze
+    quarantine: HashMap<String, Quarantined>,
+    pub quarantine_watermark: f64,
+    pub quarantine_ttl_ms: i64,
+    /// Сколько запросов отвечено из карантина
+    pub quarantine_hits: u64,
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        AnomalyDetector {
+            source_stats: HashMap::new(),
+            total_requests: 0,
+            mirage_activations: 0,
+            quarantine: HashMap::new(),
+            quarantine_watermark: QUARANTINE_WATERMARK,
+            quarantine_ttl_ms: MIRAGE_TTL_SECS as i64 * 1000,
+            quarantine_hits: 0,
+        }
+    }
 }
 
 impl AnomalyDetector {
@@ -118,6 +180,42 @@ impl AnomalyDetector {
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_millis() as i64;
+        self.record_request_at(source_id, queried_route, ttl, timing_delta_ms, now)
+    }
+
+    pub fn is_quarantined(&self, source_id: &str, now_ms: i64) -> bool {
+        self.quarantine.get(source_id).is_some_and(|q| q.until_ms > now_ms)
+    }
+
+    /// record_request на момент now_ms. Источник в карантине получает
+    /// вердикт входа сразу; по истечении срока карантин снимается вместе
+    /// со статистикой — источник оценивается с чистого листа
+    pub fn record_request_at(
+        &mut self,
+        source_id: &str,
+        queried_route: &str,
+        ttl: u8,
+        timing_delta_ms: f64,
+        now: i64,
+    ) -> AnomalyScore {
+        self.total_requests += 1;
+        match self.quarantine.get(source_id) {
+            Some(q) if q.until_ms > now => {
+                self.quarantine_hits += 1;
+                return AnomalyScore {
+                    threat_level: q.threat_level,
+                    anomalies: q.anomalies.clone(),
+                    suspected_attacker: Some(source_id.to_string()),
+                    activate_mirage: true,
+                    description: format!("Карантин ещё {} мс", q.until_ms - now),
+                };
+            }
+            Some(_) => {
+                self.quarantine.remove(source_id);
+                self.source_stats.remove(source_id);
+            }
+            None => {}
+        }
 
         let stats = self.source_stats
             .entry(source_id.to_string())
@@ -128,9 +226,16 @@ impl AnomalyDetector {
         stats.ttl_values.push(ttl);
         stats.last_seen = now;
         stats.timing_deltas.push(timing_delta_ms);
-        self.total_requests += 1;
 
-        self.analyze(source_id)
+        let score = self.analyze(source_id);
+        if score.threat_level >= self.quarantine_watermark {
+            self.quarantine.insert(source_id.to_string(), Quarantined {
+                until_ms: now + self.quarantine_ttl_ms,
+                threat_level: score.threat_level,
+                anomalies: score.anomalies.clone(),
+            });
+        }
+        score
     }
 
     /// Анализ паттернов для конкретного источника
@@ -190,7 +295,18 @@ impl AnomalyDetector {
             }
         }
 
-        // Признак 5: Distributed Scan — много источников одновременно
+        // Признак 5: Low Timing Entropy — CV пропускает пачки с паузами,
+        // энтропия гистограммы интервалов — нет
+        if stats.timing_deltas.len() >= TIMING_ENTROPY_MIN_SAMPLES {
+            let entropy = Self::timing_entropy(&stats.timing_deltas);
+            if entropy < TIMING_ENTROPY_THRESHOLD {
+                threat_level += 0.25;
+                anomalies.push(AnomalyType::LowTimingEntropy);
+                descriptions.push(format!("LowEntropy: H={:.2} бит", entropy));
+            }
+        }
+
+        // Признак 6: Distributed Scan — много источников одновременно
         let active_sources = self.source_stats.len();
         if active_sources > 10 {
             threat_level += 0.2;
@@ -228,12 +344,30 @@ impl AnomalyDetector {
         std_dev / mean
     }
 
+    /// Энтропия Шеннона (биты) интервалов, разложенных по корзинам
+    /// логарифмической шкалы: 100 и 110 мс — одна корзина, 100 и 1000 — разные
+    fn timing_entropy(deltas: &[f64]) -> f64 {
+        if deltas.is_empty() { return 0.0; }
+        let mut bins: HashMap<i64, usize> = HashMap::new();
+        for &d in deltas {
+            let bin = ((d.max(0.0) + 1.0).log2() * TIMING_ENTROPY_BINS_PER_OCTAVE).floor();
+            *bins.entry(bin as i64).or_insert(0) += 1;
+        }
+        let n = deltas.len() as f64;
+        bins.values().map(|&c| {
+            let p = c as f64 / n;
+            -p * p.log2()
+        }).sum()
+    }
+
     pub fn stats(&self) -> String {
         format!(
-            "Источников: {} | Всего запросов: {} | Mirage активаций: {}",
+            "Источников: {} | Всего запросов: {} | Mirage активаций: {} | Карантин: {} ({} ответов)",
             self.source_stats.len(),
             self.total_requests,
             self.mirage_activations,
+            self.quarantine.len(),
+            self.quarantine_hits,
         )
     }
 }
@@ -296,25 +430,28 @@ pub struct MirageGenerator {
     pub matrix: MimicryMatrix,
     /// Счётчик сгенерированных ловушек
     pub traps_generated: u64,
-    /// Псевдо-random state
-    rng_state: u64,
+    /// Источник шума ловушек
+    rng: XorShift64,
 }
 
 impl MirageGenerator {
     pub fn new(matrix: MimicryMatrix) -> Self {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        MirageGenerator { matrix, traps_generated: 0, rng_state: seed }
+        Self::with_rng(matrix, &mut XorShift64::from_time())
+    }
+
+    /// Генератор с seed из внешнего FedRng — ловушки воспроизводимы
+    pub fn with_rng(matrix: MimicryMatrix, rng: &mut dyn FedRng) -> Self {
+        MirageGenerator { matrix, traps_generated: 0, rng: XorShift64::new(rng.next_u64()) }
+    }
+
+    /// Детерминированный генератор: тот же seed, реальный тензор и угроза —
+    /// тот же FakeTensor. Так воспроизводится картина, которую видел атакующий
+    pub fn with_seed(matrix: MimicryMatrix, seed: u64) -> Self {
+        Self::with_rng(matrix, &mut XorShift64::new(seed))
     }
 
     fn next_rand(&mut self) -> f64 {
-        self.rng_state ^= self.rng_state << 13;
-        self.rng_state ^= self.rng_state >> 7;
-        self.rng_state ^= self.rng_state << 17;
-        (self.rng_state as f64) / (u64::MAX as f64)
+        self.rng.next_f64()
     }
 
     /// Сгенерировать ложный тензор на основе реального.
@@ -355,7 +492,8 @@ impl MirageGenerator {
         self.traps_generated += 1;
 
         // Выбираем стратегию на основе типа аномалии
-        let strategy = if anomaly_score.anomalies.contains(&AnomalyType::TopologyProbing) {
+        let strategy = if anomaly_score.anomalies.contains(&AnomalyType::TopologyProbing)
+            || anomaly_score.anomalies.contains(&AnomalyType::LowTimingEntropy) {
             MimicryStrategy::PerfectLure
         } else if anomaly_score.anomalies.contains(&AnomalyType::RateFlooding) {
             MimicryStrategy::DeadNode
@@ -372,7 +510,7 @@ impl MirageGenerator {
             real_latency_ms: real.latency.mean,
             phi_weight: phi,
             strategy,
-            trap_id: format!("trap_{:x}", self.rng_state & 0xffff),
+            trap_id: format!("trap_{:x}", self.rng.next_u64() & 0xffff),
         }
     }
 }
@@ -424,6 +562,30 @@ pub struct MazeTrap {
     pub entry_point: String,
     pub total_fake_latency: f64,
     pub created_for: String,
+    #[serde(default)]
+    pub forensics: MazeForensics,
+}
+
+/// Криминалистика лабиринта: сколько атакующий потратил впустую
+/// за все зонды — хопы, навязанная задержка, круги по кольцу
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MazeForensics {
+    pub attacker_id: String,
+    pub probes: u64,
+    pub total_wasted_hops: u64,
+    pub total_fake_latency_ms: f64,
+    pub total_loops: u64,
+    pub longest_path: usize,
+}
+
+impl MazeForensics {
+    pub fn record(&mut self, result: &MazeSimResult) {
+        self.probes += 1;
+        self.total_wasted_hops += result.hops as u64;
+        self.total_fake_latency_ms += result.total_latency_ms;
+        self.total_loops += result.loops_detected as u64;
+        self.longest_path = self.longest_path.max(result.hops);
+    }
 }
 
 impl MazeTrap {
@@ -451,9 +613,19 @@ impl MazeTrap {
             nodes,
             total_fake_latency: total_latency,
             created_for: attacker_id.to_string(),
+            forensics: MazeForensics {
+                attacker_id: attacker_id.to_string(), ..Default::default()
+            },
         }
     }
 
+    /// simulate_packet с записью исхода в forensics
+    pub fn probe(&mut self, max_hops: usize) -> MazeSimResult {
+        let result = self.simulate_packet(max_hops);
+        self.forensics.record(&result);
+        result
+    }
+
     /// Симулировать прохождение пакета через лабиринт
     pub fn simulate_packet(&self, max_hops: usize) -> MazeSimResult {
         let mut path = vec![];
@@ -492,6 +664,78 @@ pub struct MazeSimResult {
     pub path: Vec<String>,
 }
 
+// -----------------------------------------------------------------------------
+// MirageConfig — ограничение работы на атакующего
+// -----------------------------------------------------------------------------
+
+/// Настройки MirageNode.
+/// Свежий ложный тензор стоит CPU — флуд не должен превращаться в усилитель.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirageConfig {
+    /// Максимум токенов в bucket источника
+    pub bucket_capacity: f64,
+    /// Пополнение токенов в секунду
+    pub refill_per_sec: f64,
+    /// Forensic-режим: каждый отражённый запрос прогоняет атакующего
+    /// через его лабиринт и пишет исход в MazeForensics
+    pub forensic_mode: bool,
+    /// Глубина лабиринта при угрозе 0 и 1; между ними — линейно
+    pub min_maze_depth: usize,
+    pub max_maze_depth: usize,
+    /// Seed генератора ложных тензоров; None — от системного времени
+    pub seed: Option<u64>,
+    /// Потолок источников с bucket и кэшем заготовки (LRU).
+    /// Простоявшие дольше MIRAGE_TTL_SECS вытесняются раньше
+    pub max_tracked_sources: usize,
+}
+
+impl MirageConfig {
+    /// Глубина лабиринта по угрозе; не больше MAZE_DEPTH_CAP, не меньше 1
+    pub fn maze_depth(&self, threat_level: f64) -> usize {
+        let lo = self.min_maze_depth.min(self.max_maze_depth).clamp(1, MAZE_DEPTH_CAP);
+        let hi = self.max_maze_depth.clamp(lo, MAZE_DEPTH_CAP);
+        let t = if threat_level.is_nan() { 1.0 } else { threat_level.clamp(0.0, 1.0) };
+        lo + ((hi - lo) as f64 * t).round() as usize
+    }
+}
+
+impl Default for MirageConfig {
+    fn default() -> Self {
+        MirageConfig {
+            bucket_capacity: MIRAGE_BUCKET_CAPACITY,
+            refill_per_sec: MIRAGE_REFILL_PER_SEC,
+            forensic_mode: false,
+            min_maze_depth: MAZE_MIN_DEPTH,
+            max_maze_depth: MAZE_MAX_DEPTH,
+            seed: None,
+            max_tracked_sources: MIRAGE_MAX_TRACKED_SOURCES,
+        }
+    }
+}
+
+/// Token bucket одного источника
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill_ms: i64,
+}
+
+impl TokenBucket {
+    fn try_take(&mut self, config: &MirageConfig, now_ms: i64) -> bool {
+        let elapsed = (now_ms - self.last_refill_ms).max(0) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * config.refill_per_sec)
+            .min(config.bucket_capacity);
+        self.last_refill_ms = now_ms;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // MirageNode — полный модуль мимикрии узла
 // -----------------------------------------------------------------------------
@@ -506,17 +750,80 @@ pub struct MirageNode {
     pub mirage_active: bool,
     /// Статистика: сколько атак отражено
     pub attacks_deflected: u64,
+    pub config: MirageConfig,
+    /// Сколько ложных тензоров сгенерировано заново
+    pub fresh_fakes_generated: u64,
+    /// Сколько раз отдали заготовку из кэша (bucket пуст)
+    pub canned_responses: u64,
+    buckets: HashMap<String, TokenBucket>,
+    /// Последний ложный тензор для источника — дешёвый ответ при флуде
+    decoy_cache: HashMap<String, FakeTensor>,
 }
 
 impl MirageNode {
     pub fn new(node_id: &str) -> Self {
+        Self::with_config(node_id, MirageConfig::default())
+    }
+
+    pub fn with_config(node_id: &str, config: MirageConfig) -> Self {
         MirageNode {
             node_id: node_id.to_string(),
             detector: AnomalyDetector::new(),
-            generator: MirageGenerator::new(MimicryMatrix::perfect_lure()),
+            generator: match config.seed {
+                Some(seed) => MirageGenerator::with_seed(MimicryMatrix::perfect_lure(), seed),
+                None => MirageGenerator::new(MimicryMatrix::perfect_lure()),
+            },
             active_mazes: HashMap::new(),
             mirage_active: false,
             attacks_deflected: 0,
+            config,
+            fresh_fakes_generated: 0,
+            canned_responses: 0,
+            buckets: HashMap::new(),
+            decoy_cache: HashMap::new(),
+        }
+    }
+
+    /// Взять токен у источника. false — bucket пуст, работаем по заготовке
+    fn take_token(&mut self, source_id: &str, now: i64) -> bool {
+        if !self.buckets.contains_key(source_id) {
+            self.prune_sources(now);
+        }
+        let capacity = self.config.bucket_capacity;
+        let bucket = self.buckets.entry(source_id.to_string())
+            .or_insert(TokenBucket { tokens: capacity, last_refill_ms: now });
+        bucket.try_take(&self.config, now)
+    }
+
+    /// Место под новый источник: сначала уходят простоявшие дольше TTL,
+    /// затем — давно не обращавшиеся, пока не влезем в потолок
+    fn prune_sources(&mut self, now_ms: i64) {
+        let ttl_ms = MIRAGE_TTL_SECS as i64 * 1000;
+        self.buckets.retain(|_, b| now_ms - b.last_refill_ms < ttl_ms);
+        let cap = self.config.max_tracked_sources.max(1);
+        while self.buckets.len() >= cap {
+            let Some(oldest) = self.buckets.iter()
+                .min_by(|a, b| a.1.last_refill_ms.cmp(&b.1.last_refill_ms).then(a.0.cmp(b.0)))
+                .map(|(id, _)| id.clone()) else { break };
+            self.buckets.remove(&oldest);
+        }
+        let buckets = &self.buckets;
+        self.decoy_cache.retain(|id, _| buckets.contains_key(id));
+    }
+
+    /// Заготовленный ответ: без RNG и без лабиринта
+    fn canned_decoy(source_id: &str, real: &SsauTensor) -> FakeTensor {
+        let m = MimicryMatrix::dead_node();
+        FakeTensor {
+            from_node: real.from_node.clone(),
+            to_node: real.to_node.clone(),
+            fake_latency_ms: real.latency.mean * m.latency_factor,
+            fake_bandwidth_mbps: real.bandwidth * m.bandwidth_factor,
+            fake_reliability: m.reliability_factor,
+            real_latency_ms: real.latency.mean,
+            phi_weight: 1.0,
+            strategy: MimicryStrategy::DeadNode,
+            trap_id: format!("canned_{}", source_id),
         }
     }
 
@@ -529,10 +836,28 @@ impl MirageNode {
         ttl: u8,
         timing_delta_ms: f64,
         real_tensor: &SsauTensor,
+    ) -> MirageResponse {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        self.handle_request_at(source_id, queried_route, ttl, timing_delta_ms, real_tensor, now)
+    }
+
+    /// То же с внешним временем (мс) — для детектора и token bucket
+    pub fn handle_request_at(
+        &mut self,
+        source_id: &str,
+        queried_route: &str,
+        ttl: u8,
+        timing_delta_ms: f64,
+        real_tensor: &SsauTensor,
+        now_ms: i64,
     ) -> MirageResponse {
         // Анализируем запрос
-        let anomaly = self.detector.record_request(
-            source_id, queried_route, ttl, timing_delta_ms
+        let anomaly = self.detector.record_request_at(
+            source_id, queried_route, ttl, timing_delta_ms, now_ms
         );
 
         if anomaly.activate_mirage {
@@ -540,8 +865,26 @@ impl MirageNode {
             self.attacks_deflected += 1;
             self.detector.mirage_activations += 1;
 
+            // Bucket пуст — не генерируем ни тензор, ни лабиринт, не зондируем
+            if !self.take_token(source_id, now_ms) {
+                self.canned_responses += 1;
+                let fake = self.decoy_cache.get(source_id).cloned()
+                    .unwrap_or_else(|| Self::canned_decoy(source_id, real_tensor));
+                return MirageResponse::Fake {
+                    fake_tensor: fake,
+                    anomaly_score: anomaly,
+                    maze_entry: self.active_mazes.get(source_id)
+                        .map(|m| m.entry_point.clone()),
+                };
+            }
+
+            if self.config.forensic_mode {
+                self.probe_maze(source_id, MAZE_PROBE_HOPS);
+            }
+
             // Выбираем матрицу мимикрии на основе типа атаки
-            self.generator.matrix = if anomaly.anomalies.contains(&AnomalyType::TopologyProbing) {
+            self.generator.matrix = if anomaly.anomalies.contains(&AnomalyType::TopologyProbing)
+                || anomaly.anomalies.contains(&AnomalyType::LowTimingEntropy) {
                 MimicryMatrix::perfect_lure()
             } else if anomaly.anomalies.contains(&AnomalyType::RateFlooding) {
                 MimicryMatrix::dead_node()
@@ -553,10 +896,18 @@ impl MirageNode {
             let fake = self.generator.generate_fake_tensor(
                 real_tensor, anomaly.threat_level, &anomaly
             );
-
-            // Создаём лабиринт если ещё нет
-            if !self.active_mazes.contains_key(source_id) {
-                let maze = MazeTrap::create_for_attacker(source_id, 6);
+            self.fresh_fakes_generated += 1;
+            self.decoy_cache.insert(source_id.to_string(), fake.clone());
+
+            // Глубина — по угрозе. Угроза выросла — лабиринт перестраивается
+            // глубже, криминалистика переезжает в новый
+            let depth = self.config.maze_depth(anomaly.threat_level);
+            let current = self.active_mazes.get(source_id).map(|m| m.nodes.len());
+            if current.is_none_or(|d| d < depth) {
+                let mut maze = MazeTrap::create_for_attacker(source_id, depth);
+                if let Some(old) = self.active_mazes.remove(source_id) {
+                    maze.forensics = old.forensics;
+                }
                 self.active_mazes.insert(source_id.to_string(), maze);
             }
 
@@ -574,6 +925,15 @@ impl MirageNode {
         }
     }
 
+    /// Прогнать атакующего через его лабиринт; None — лабиринта нет
+    pub fn probe_maze(&mut self, attacker_id: &str, max_hops: usize) -> Option<MazeSimResult> {
+        self.active_mazes.get_mut(attacker_id).map(|maze| maze.probe(max_hops))
+    }
+
+    pub fn forensics(&self, attacker_id: &str) -> Option<&MazeForensics> {
+        self.active_mazes.get(attacker_id).map(|maze| &maze.forensics)
+    }
+
     pub fn status(&self) -> String {
         format!(
             "MirageNode [{}]: active={} | mazes={} | deflected={} | {}",
@@ -623,6 +983,57 @@ mod tests {
             last_score.threat_level, last_score.anomalies);
     }
 
+    #[test]
+    fn test_low_timing_entropy_flags_periodic_streams() {
+        let mut detector = AnomalyDetector::new();
+        // Пачки по 4 запроса через 5 мс, пауза 2 с: CV велик, энтропия мала
+        let burst = |i: usize| if i % 4 == 3 { 2000.0 } else { 5.0 };
+        let mut periodic = detector.record_request("bot", "A→B", 64, burst(0));
+        for i in 1..24 { periodic = detector.record_request("bot", "A→B", 64, burst(i)); }
+        assert!(AnomalyDetector::coefficient_of_variation(
+            &(0..24).map(burst).collect::<Vec<_>>()) > TIMING_ANOMALY_THRESHOLD);
+        assert!(periodic.anomalies.contains(&AnomalyType::LowTimingEntropy), "{:?}", periodic);
+        assert!(!periodic.anomalies.contains(&AnomalyType::TopologyProbing));
+
+        let mut rng = XorShift64::new(7);
+        let mut human = detector.record_request("human", "A→B", 64, 800.0);
+        for _ in 1..24 {
+            let delta = 200.0 + (rng.next_u64() % 5000) as f64;
+            human = detector.record_request("human", "A→B", 64, delta);
+        }
+        assert!(!human.anomalies.contains(&AnomalyType::LowTimingEntropy), "{:?}", human);
+    }
+
+    #[test]
+    fn test_quarantine_short_circuits_until_ttl() {
+        let mut detector = AnomalyDetector::new();
+        detector.quarantine_ttl_ms = 10_000;
+        let t0 = 1_000_000;
+        let mut i = 0;
+        // Сканер: много маршрутов, прыгающий TTL, регулярные интервалы
+        while !detector.is_quarantined("scanner", t0) {
+            detector.record_request_at("scanner", &format!("route_{}", i % 12),
+                (i % 9) as u8 * 8, 5.0, t0);
+            i += 1;
+            assert!(i < 100, "сканер должен попасть в карантин");
+        }
+        let counted = detector.source_stats["scanner"].request_count;
+
+        let score = detector.record_request_at("scanner", "A→B", 64, 800.0, t0 + 9_999);
+        assert!(score.activate_mirage && score.threat_level >= QUARANTINE_WATERMARK);
+        assert!(score.description.starts_with("Карантин"));
+        assert_eq!(detector.quarantine_hits, 1);
+        // Статистика не пересчитывалась
+        assert_eq!(detector.source_stats["scanner"].request_count, counted);
+
+        // Срок вышел — обычная оценка с чистого листа
+        let score = detector.record_request_at("scanner", "A→B", 64, 800.0, t0 + 10_000);
+        assert!(!detector.is_quarantined("scanner", t0 + 10_000));
+        assert!(!score.activate_mirage, "{}", score.description);
+        assert_eq!(detector.source_stats["scanner"].request_count, 1);
+        assert_eq!(detector.quarantine_hits, 1);
+    }
+
     #[test]
     fn test_mirage_generator() {
         let mut real = SsauTensor::new("A", "B", 50.0, 500.0);
@@ -653,6 +1064,43 @@ mod tests {
             "Perfect lure должен показывать большую bandwidth");
     }
 
+    #[test]
+    fn test_seeded_generators_produce_identical_fakes() {
+        let mut real = SsauTensor::new("A", "B", 50.0, 500.0);
+        real.reliability = 0.7;
+        let anomaly = AnomalyScore {
+            threat_level: 0.8, anomalies: vec![AnomalyType::TopologyProbing],
+            suspected_attacker: Some("spy_node".into()), activate_mirage: true,
+            description: String::new(),
+        };
+        let run = |seed: u64| {
+            let mut gen = MirageGenerator::with_seed(MimicryMatrix::perfect_lure(), seed);
+            (0..5).map(|_| gen.generate_fake_tensor(&real, 0.8, &anomaly)).collect::<Vec<_>>()
+        };
+        let bytes = |fakes: &[FakeTensor]| serde_json::to_vec(fakes).unwrap();
+        let (a, b) = (run(42), run(42));
+        assert_eq!(bytes(&a), bytes(&b));
+        assert_ne!(bytes(&a), bytes(&run(43)));
+        for fake in a.iter().chain(&run(7)) {
+            assert!(fake.fake_latency_ms < fake.real_latency_ms);
+            assert!(fake.fake_bandwidth_mbps > real.bandwidth);
+        }
+
+        // MirageNode с seed в конфиге отвечает атакующему одинаково
+        let respond = || {
+            let config = MirageConfig { seed: Some(9), ..Default::default() };
+            let mut node = MirageNode::with_config("n", config);
+            (0..20).filter_map(|i| match node.handle_request(
+                "spy", &format!("r{}", i % 8), 64, 5.0, &real) {
+                MirageResponse::Fake { fake_tensor, .. } => Some(fake_tensor),
+                MirageResponse::Real { .. } => None,
+            }).collect::<Vec<_>>()
+        };
+        let first = respond();
+        assert!(!first.is_empty());
+        assert_eq!(bytes(&first), bytes(&respond()));
+    }
+
     #[test]
     fn test_maze_trap() {
         let maze = MazeTrap::create_for_attacker("spy_node", 5);
@@ -709,4 +1157,111 @@ mod tests {
 
         println!("\n{}", mirage.status());
     }
+
+    #[test]
+    fn test_rate_limiter_caps_fresh_fakes() {
+        let config = MirageConfig { bucket_capacity: 10.0, refill_per_sec: 0.0,
+            forensic_mode: true, ..Default::default() };
+        let mut mirage = MirageNode::with_config("federation_node", config);
+        let real_tensor = SsauTensor::new("A", "B", 10.0, 1000.0);
+
+        let mut fakes = 0;
+        for i in 0..1000 {
+            let resp = mirage.handle_request(
+                "flooder", &format!("route_{}", i % 8), 64, 5.0, &real_tensor);
+            if let MirageResponse::Fake { .. } = resp { fakes += 1; }
+        }
+
+        assert!(fakes > 10, "флуд должен получать ложные ответы");
+        assert!(mirage.fresh_fakes_generated <= 10);
+        assert_eq!(mirage.fresh_fakes_generated + mirage.canned_responses, fakes);
+        assert_eq!(mirage.active_mazes.len(), 1);
+        // Зонд лабиринта тоже под bucket: canned-ответы его не запускают
+        assert!(mirage.forensics("flooder").unwrap().probes <= mirage.fresh_fakes_generated);
+    }
+
+    #[test]
+    fn test_per_source_state_capped_and_expires() {
+        let config = MirageConfig { max_tracked_sources: 8, ..Default::default() };
+        let mut mirage = MirageNode::with_config("federation_node", config);
+        let real_tensor = SsauTensor::new("A", "B", 10.0, 1000.0);
+        for i in 0..100 {
+            let id = format!("spoof_{}", i);
+            mirage.take_token(&id, 1_000 + i);
+            mirage.decoy_cache.insert(id.clone(), MirageNode::canned_decoy(&id, &real_tensor));
+        }
+        // Поток поддельных источников не раздувает память: остаются самые свежие
+        assert_eq!(mirage.buckets.len(), 8);
+        assert!(mirage.decoy_cache.len() <= 8);
+        assert!(mirage.buckets.contains_key("spoof_99"));
+        assert!(!mirage.buckets.contains_key("spoof_0"));
+
+        // Через TTL простоя старые источники уходят целиком
+        mirage.take_token("late", 1_100 + MIRAGE_TTL_SECS as i64 * 1000);
+        assert_eq!(mirage.buckets.len(), 1);
+        assert!(mirage.decoy_cache.is_empty());
+    }
+
+    #[test]
+    fn test_forensics_accumulate_across_probes() {
+        let config = MirageConfig { forensic_mode: true, ..Default::default() };
+        let mut mirage = MirageNode::with_config("federation_node", config);
+        let real_tensor = SsauTensor::new("A", "B", 10.0, 1000.0);
+        assert!(mirage.forensics("spy_node").is_none());
+
+        let mut prev = 0;
+        let mut grew = 0;
+        for i in 0..40 {
+            mirage.handle_request("spy_node", &format!("route_{}", i % 8), 64, 5.0, &real_tensor);
+            if let Some(f) = mirage.forensics("spy_node") {
+                assert!(f.total_wasted_hops >= prev);
+                if f.total_wasted_hops > prev { grew += 1; }
+                prev = f.total_wasted_hops;
+            }
+        }
+        let f = mirage.forensics("spy_node").unwrap().clone();
+        assert!(grew >= 2, "каждый зонд добавляет хопы");
+        assert_eq!(f.attacker_id, "spy_node");
+        assert_eq!(f.total_wasted_hops, f.probes * f.longest_path as u64);
+        assert!(f.total_fake_latency_ms > 0.0 && f.total_loops >= f.probes);
+
+        // Ручной повтор поверх автоматических
+        let replay = mirage.probe_maze("spy_node", MAZE_PROBE_HOPS).unwrap();
+        let after = mirage.forensics("spy_node").unwrap();
+        assert_eq!(after.probes, f.probes + 1);
+        assert_eq!(after.total_wasted_hops, f.total_wasted_hops + replay.hops as u64);
+        assert!(mirage.forensics("normal_peer").is_none());
+    }
+
+    #[test]
+    fn test_maze_depth_scales_with_threat_and_respects_cap() {
+        let config = MirageConfig::default();
+        assert!(config.maze_depth(0.9) > config.maze_depth(0.35));
+        assert_eq!(config.maze_depth(0.0), MAZE_MIN_DEPTH);
+        assert_eq!(config.maze_depth(1.0), MAZE_MAX_DEPTH);
+        let huge = MirageConfig { max_maze_depth: 100_000, ..Default::default() };
+        assert_eq!(huge.maze_depth(1.0), MAZE_DEPTH_CAP);
+
+        let config = MirageConfig { min_maze_depth: 3, max_maze_depth: 8, ..Default::default() };
+        let mut mirage = MirageNode::with_config("federation_node", config.clone());
+        let real_tensor = SsauTensor::new("A", "B", 10.0, 1000.0);
+        let mut max_threat: f64 = 0.0;
+        for i in 0..60 {
+            let fresh = mirage.fresh_fakes_generated;
+            let resp = mirage.handle_request("spy_node", &format!("route_{}", i % 8),
+                60 + (i % 5) as u8, 5.0, &real_tensor);
+            // Лабиринт строится только вместе со свежим тензором
+            match resp {
+                MirageResponse::Fake { anomaly_score, .. }
+                    if mirage.fresh_fakes_generated > fresh =>
+                    max_threat = max_threat.max(anomaly_score.threat_level),
+                _ => {}
+            }
+            if let Some(maze) = mirage.active_mazes.get("spy_node") {
+                assert!(maze.nodes.len() <= 8);
+            }
+        }
+        let maze = &mirage.active_mazes["spy_node"];
+        assert_eq!(maze.nodes.len(), config.maze_depth(max_threat));
+    }
 }
@@ -1,10 +1,15 @@
+use crate::error::FederationError;
 use crate::tensor::SsauTensor;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
 pub const PROTOCOL_VERSION: u8 = 1;
+pub const FD_WINDOW_SIZE: usize = 100;
+pub const FD_MIN_STD_DEV_MS: f64 = 50.0;
+pub const FD_FIRST_HEARTBEAT_ESTIMATE_MS: f64 = 1000.0;
+pub const FD_DEFAULT_THRESHOLD: f64 = 8.0;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", content = "payload")]
@@ -185,8 +190,8 @@ pub fn serialize_packet(packet: &FederationPacket) -> Result<Vec<u8>, serde_json
     serde_json::to_vec(packet)
 }
 
-pub fn deserialize_packet(bytes: &[u8]) -> Result<FederationPacket, serde_json::Error> {
-    serde_json::from_slice(bytes)
+pub fn deserialize_packet(bytes: &[u8]) -> Result<FederationPacket, FederationError> {
+    Ok(serde_json::from_slice(bytes)?)
 }
 
 pub type PacketSender = mpsc::Sender<FederationPacket>;
@@ -281,3 +286,123 @@ pub fn create_ssau_update_packet(our_node_id: &str, tensors: &[&SsauTensor], seq
         sequence,
     }))
 }
+
+struct HeartbeatHistory {
+    intervals: VecDeque<f64>,
+    last_heartbeat: i64,
+}
+
+impl HeartbeatHistory {
+    fn mean(&self) -> f64 {
+        if self.intervals.is_empty() {
+            return FD_FIRST_HEARTBEAT_ESTIMATE_MS;
+        }
+        self.intervals.iter().sum::<f64>() / self.intervals.len() as f64
+    }
+
+    fn std_dev(&self, mean: f64) -> f64 {
+        if self.intervals.len() < 2 {
+            return (mean / 4.0).max(FD_MIN_STD_DEV_MS);
+        }
+        let var = self.intervals.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+            / self.intervals.len() as f64;
+        var.sqrt().max(FD_MIN_STD_DEV_MS)
+    }
+}
+
+/// Simplified phi-accrual failure detector: suspicion grows with the time since
+/// the last heartbeat relative to the learned inter-arrival distribution.
+pub struct FailureDetector {
+    pub window_size: usize,
+    histories: HashMap<String, HeartbeatHistory>,
+}
+
+impl Default for FailureDetector {
+    fn default() -> Self { Self::new() }
+}
+
+impl FailureDetector {
+    pub fn new() -> Self {
+        FailureDetector { window_size: FD_WINDOW_SIZE, histories: HashMap::new() }
+    }
+
+    pub fn heartbeat(&mut self, node: &str, now: i64) {
+        let window = self.window_size.max(1);
+        match self.histories.get_mut(node) {
+            Some(h) => {
+                if now > h.last_heartbeat {
+                    if h.intervals.len() >= window {
+                        h.intervals.pop_front();
+                    }
+                    h.intervals.push_back((now - h.last_heartbeat) as f64);
+                    h.last_heartbeat = now;
+                }
+            }
+            None => {
+                self.histories.insert(node.to_string(), HeartbeatHistory {
+                    intervals: VecDeque::new(),
+                    last_heartbeat: now,
+                });
+            }
+        }
+    }
+
+    /// Phi value: -log10 of the probability that a heartbeat is still coming.
+    /// Unknown nodes are maximally suspicious.
+    pub fn suspicion(&self, node: &str, now: i64) -> f64 {
+        let h = match self.histories.get(node) {
+            Some(h) => h,
+            None => return f64::INFINITY,
+        };
+        let elapsed = (now - h.last_heartbeat).max(0) as f64;
+        let mean = h.mean();
+        let std_dev = h.std_dev(mean);
+        // Logistic approximation of the normal CDF tail
+        let y = (elapsed - mean) / std_dev;
+        let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+        let p_later = if elapsed > mean { e / (1.0 + e) } else { 1.0 - 1.0 / (1.0 + e) };
+        -p_later.max(f64::MIN_POSITIVE).log10()
+    }
+
+    pub fn is_alive(&self, node: &str, now: i64, threshold: f64) -> bool {
+        self.suspicion(node, now) < threshold
+    }
+
+    pub fn suspected(&self, now: i64, threshold: f64) -> Vec<String> {
+        let mut nodes: Vec<String> = self.histories.keys()
+            .filter(|n| !self.is_alive(n, now, threshold))
+            .cloned().collect();
+        nodes.sort();
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regular_heartbeats_keep_suspicion_low() {
+        let mut fd = FailureDetector::new();
+        for i in 0..20 {
+            fd.heartbeat("peer", i * 1000 + (i % 3) * 20);
+        }
+        let last = 19 * 1000 + 20;
+        assert!(fd.suspicion("peer", last + 500) < 1.0);
+        assert!(fd.is_alive("peer", last + 1000, FD_DEFAULT_THRESHOLD));
+        assert!(!fd.is_alive("stranger", last, FD_DEFAULT_THRESHOLD));
+    }
+
+    #[test]
+    fn test_missed_heartbeats_raise_suspicion() {
+        let mut fd = FailureDetector::new();
+        for i in 0..25 {
+            if i < 20 { fd.heartbeat("peer", i * 1000); }
+            fd.heartbeat("other", i * 1000);
+        }
+        let now = 19_000 + 5_000;
+        assert!(fd.suspicion("peer", now) > FD_DEFAULT_THRESHOLD);
+        assert!(fd.suspicion("peer", now) > fd.suspicion("peer", 19_000 + 1_500));
+        assert_eq!(fd.suspected(now, FD_DEFAULT_THRESHOLD), vec!["peer".to_string()]);
+    }
+}
@@ -11,6 +11,8 @@
 //   5. NeuralRouter    — замена Softmax AI Router на нейронный
 // =============================================================================
 
+use crate::rng::{FedRng, XorShift64};
+use crate::transport::DeliveryReport;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -25,6 +27,11 @@ pub const LEARNING_RATE: f64 = 0.15;
 pub const MOMENTUM: f64 = 0.5;
 pub const CONGESTION_WINDOW: usize = 10;
 pub const CONGESTION_THRESHOLD: f64 = 0.65;
+pub const TACTIC_NEUTRAL_BAND: f64 = 0.05;  // выходы сети в 0.5±band — шум, не сигнал
+pub const TACTIC_DEMO_EPOCHS: usize = 10_000;  // проходов по tactic_scenarios в демо
+pub const PASSIVE_LATENCY: f64 = 0.40;      // ниже — канал спокоен
+pub const WEIGHT_DECAY: f64 = 1e-4;         // L2: тянет веса к нулю на каждом шаге
+pub const GRAD_CLIP_NORM: f64 = 5.0;        // потолок глобальной нормы градиента
 
 // -----------------------------------------------------------------------------
 // Функции активации
@@ -40,6 +47,30 @@ fn softmax(v: &[f64]) -> Vec<f64> {
     exps.iter().map(|e| e / sum).collect()
 }
 
+// -----------------------------------------------------------------------------
+// Regularization — защита весов от разлёта
+// -----------------------------------------------------------------------------
+
+/// С momentum и без затухания враждебные входы раскачивают веса до inf/NaN.
+/// weight_decay — коэффициент L2, clip_norm — потолок глобальной нормы
+/// градиента по обоим слоям; 0 и None отключают. Умолчания нет: режим
+/// выбирается явно — none() или protected(), как в NeuralState::new
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Regularization {
+    pub weight_decay: f64,
+    pub clip_norm: Option<f64>,
+}
+
+impl Regularization {
+    pub fn none() -> Self {
+        Regularization { weight_decay: 0.0, clip_norm: None }
+    }
+
+    pub fn protected() -> Self {
+        Regularization { weight_decay: WEIGHT_DECAY, clip_norm: Some(GRAD_CLIP_NORM) }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // NeuralWeights — матрица весов слоя
 // -----------------------------------------------------------------------------
@@ -54,11 +85,14 @@ pub struct NeuralWeights {
 
 impl NeuralWeights {
     pub fn new(input: usize, output: usize, seed: u64) -> Self {
-        let mut rng = seed;
+        Self::from_rng(input, output, &mut XorShift64::new(seed))
+    }
+
+    /// Инициализация весов из внешнего генератора — для воспроизводимых тестов
+    pub fn from_rng(input: usize, output: usize, rng: &mut dyn FedRng) -> Self {
         let weights = (0..output).map(|_| {
             (0..input).map(|_| {
-                rng ^= rng << 13; rng ^= rng >> 7; rng ^= rng << 17;
-                ((rng as i64 % 1000) as f64) / 5000.0
+                ((rng.next_u64() as i64 % 1000) as f64) / 5000.0
             }).collect()
         }).collect();
         let biases = vec![0.0; output];
@@ -74,16 +108,73 @@ impl NeuralWeights {
     }
 
     pub fn update(&mut self, grad_w: &[Vec<f64>], grad_b: &[f64]) {
+        self.update_with_decay(grad_w, grad_b, 0.0);
+    }
+
+    /// Шаг momentum SGD с L2-затуханием весов (смещения не затухают).
+    /// Неконечный вес или скорость после шага обнуляется с предупреждением
+    pub fn update_with_decay(&mut self, grad_w: &[Vec<f64>], grad_b: &[f64], weight_decay: f64) {
         for i in 0..self.weights.len() {
             for j in 0..self.weights[i].len() {
-                self.velocity[i][j] = MOMENTUM * self.velocity[i][j]
-                    - LEARNING_RATE * grad_w[i][j];
+                let g = grad_w[i][j] + weight_decay * self.weights[i][j];
+                self.velocity[i][j] = MOMENTUM * self.velocity[i][j] - LEARNING_RATE * g;
                 self.weights[i][j] += self.velocity[i][j];
             }
             self.bias_velocity[i] = MOMENTUM * self.bias_velocity[i]
                 - LEARNING_RATE * grad_b[i];
             self.biases[i] += self.bias_velocity[i];
         }
+        self.sanitize();
+    }
+
+    fn sanitize(&mut self) {
+        let mut reset = 0;
+        let params = self.weights.iter_mut().flatten()
+            .chain(self.velocity.iter_mut().flatten())
+            .chain(self.biases.iter_mut())
+            .chain(self.bias_velocity.iter_mut());
+        for v in params.filter(|v| !v.is_finite()) {
+            *v = 0.0;
+            reset += 1;
+        }
+        if reset > 0 {
+            log::warn!("NeuralWeights: {} неконечных параметров обнулено", reset);
+        }
+    }
+}
+
+struct LayerGradients {
+    w1: Vec<Vec<f64>>,
+    b1: Vec<f64>,
+    w2: Vec<Vec<f64>>,
+    b2: Vec<f64>,
+}
+
+impl LayerGradients {
+    fn add(&mut self, other: &LayerGradients) {
+        for (a, b) in self.w1.iter_mut().flatten().zip(other.w1.iter().flatten()) { *a += b; }
+        for (a, b) in self.w2.iter_mut().flatten().zip(other.w2.iter().flatten()) { *a += b; }
+        for (a, b) in self.b1.iter_mut().zip(other.b1.iter()) { *a += b; }
+        for (a, b) in self.b2.iter_mut().zip(other.b2.iter()) { *a += b; }
+    }
+
+    fn scale(&mut self, k: f64) {
+        self.w1.iter_mut().flatten().chain(self.w2.iter_mut().flatten())
+            .chain(self.b1.iter_mut()).chain(self.b2.iter_mut())
+            .for_each(|v| *v *= k);
+    }
+
+    fn norm(&self) -> f64 {
+        self.w1.iter().flatten().chain(self.w2.iter().flatten())
+            .chain(self.b1.iter()).chain(self.b2.iter())
+            .map(|v| v * v).sum::<f64>().sqrt()
+    }
+
+    // Неконечная норма — шаг пропускается целиком
+    fn clip_global_norm(&mut self, max_norm: f64) {
+        let norm = self.norm();
+        if !norm.is_finite() { self.scale(0.0); }
+        else if norm > max_norm { self.scale(max_norm / norm); }
     }
 }
 
@@ -102,6 +193,8 @@ pub struct NeuralState {
     pub neighbor_weights: HashMap<String, f64>,
     pub congestion_history: Vec<f64>,
     pub last_prediction: Option<CongestionPrediction>,
+    #[serde(default = "Regularization::protected")]
+    pub regularization: Regularization,
 }
 
 impl NeuralState {
@@ -118,13 +211,33 @@ impl NeuralState {
             neighbor_weights: HashMap::new(),
             congestion_history: vec![],
             last_prediction: None,
+            regularization: Regularization::protected(),
         }
     }
 
     /// Прямой проход: входной вектор → [route_weight, congestion_prob, quality_score]
     pub fn forward(&self, input: &NeuralInput) -> NeuralOutput {
+        self.forward_masked(input, None)
+    }
+
+    /// Прямой проход для обучения с inverted dropout: скрытые нейроны
+    /// обнуляются с вероятностью dropout_p, остальные масштабируются на 1/(1-p).
+    /// Маска возвращается в `dropout_mask` для backpropagate_success_masked.
+    pub fn forward_train(&self, input: &NeuralInput, dropout_p: f64, rng: &mut dyn FedRng) -> NeuralOutput {
+        let p = dropout_p.clamp(0.0, 0.95);
+        let keep_scale = 1.0 / (1.0 - p);
+        let mask: Vec<f64> = (0..HIDDEN_SIZE).map(|_| {
+            if rng.next_f64() < p { 0.0 } else { keep_scale }
+        }).collect();
+        let mut out = self.forward_masked(input, Some(&mask));
+        out.dropout_mask = Some(mask);
+        out
+    }
+
+    fn forward_masked(&self, input: &NeuralInput, mask: Option<&[f64]>) -> NeuralOutput {
         let x = input.to_vector();
-        let h1: Vec<f64> = self.layer1.forward(&x).iter().map(|&v| relu(v)).collect();
+        let h1: Vec<f64> = self.layer1.forward(&x).iter().enumerate()
+            .map(|(j, &v)| relu(v) * mask.map_or(1.0, |m| m[j])).collect();
         let out = self.layer2.forward(&h1);
         let probs = softmax(&out);
         let congestion  = sigmoid(out[1]);
@@ -139,26 +252,58 @@ impl NeuralState {
             softmax_probs:   probs,
             hidden_state:    h1,
             tactic:          NeuralTactic::decide(congestion, decoy, strike),
+            dropout_mask:    None,
+        }
+    }
+
+    /// Новое состояние, обученное `epochs` проходами по размеченным примерам
+    pub fn trained_on(node_id: &str, samples: &[(NeuralInput, NeuralTarget)],
+        epochs: usize) -> Self {
+        let mut state = NeuralState::new(node_id);
+        for _ in 0..epochs {
+            for (input, target) in samples {
+                state.backpropagate_success(input, target, node_id);
+            }
         }
+        state
     }
 
     /// Обучение на успехе: пакет дошёл → закрепляем путь
     pub fn backpropagate_success(&mut self, input: &NeuralInput,
         target: &NeuralTarget, neighbor_id: &str) {
+        self.backpropagate_success_masked(input, target, neighbor_id, None);
+    }
+
+    /// То же, но градиент идёт только через нейроны, оставленные dropout-маской
+    pub fn backpropagate_success_masked(&mut self, input: &NeuralInput,
+        target: &NeuralTarget, neighbor_id: &str, mask: Option<&[f64]>) {
+        let (grads, loss) = self.compute_gradients(input, target, mask);
+        self.record_loss(loss);
+        self.apply_gradients(grads);
+
+        self.record_outcome(target.success, neighbor_id);
+    }
+
+    /// Градиенты по обоим слоям без применения. Возвращает (градиенты, loss).
+    /// Цели — вероятности, как и выходы forward, поэтому сравниваются с
+    /// sigmoid(логитов), а не с самими логитами
+    fn compute_gradients(&self, input: &NeuralInput, target: &NeuralTarget,
+        mask: Option<&[f64]>) -> (LayerGradients, f64) {
         let x = input.to_vector();
         let h1_raw = self.layer1.forward(&x);
-        let h1: Vec<f64> = h1_raw.iter().map(|&v| relu(v)).collect();
-        let out = self.layer2.forward(&h1);
+        let h1: Vec<f64> = h1_raw.iter().enumerate()
+            .map(|(j, &v)| relu(v) * mask.map_or(1.0, |m| m[j])).collect();
+        let out: Vec<f64> = self.layer2.forward(&h1).into_iter().map(sigmoid).collect();
 
-        // Loss = MSE между выходом и целевым значением
+        // Loss = MSE между вероятностью на выходе и целевым значением
         let target_vec = target.to_vector();
         let loss: f64 = out.iter().zip(target_vec.iter())
             .map(|(o, t)| (o - t).powi(2)).sum::<f64>() / OUTPUT_SIZE as f64;
-        self.total_loss = self.total_loss * 0.99 + loss * 0.01;
 
-        // Градиент output слоя: δ = 2*(out - target) / N
+        // Градиент output слоя — cross-entropy по логитам: δ = (σ(out) - target) / N.
+        // В отличие от MSE через sigmoid не затухает у насыщенных выходов
         let delta2: Vec<f64> = out.iter().zip(target_vec.iter())
-            .map(|(o, t)| 2.0 * (o - t) / OUTPUT_SIZE as f64).collect();
+            .map(|(o, t)| (o - t) / OUTPUT_SIZE as f64).collect();
 
         // Градиент весов layer2: dL/dW2 = δ2 ⊗ h1
         let grad_w2: Vec<Vec<f64>> = delta2.iter()
@@ -171,7 +316,7 @@ impl NeuralState {
             for k in 0..OUTPUT_SIZE {
                 delta1[j] += self.layer2.weights[k][j] * delta2[k];
             }
-            delta1[j] *= relu_derivative(h1_raw[j]);
+            delta1[j] *= relu_derivative(h1_raw[j]) * mask.map_or(1.0, |m| m[j]);
         }
 
         // Градиент весов layer1: dL/dW1 = δ1 ⊗ x
@@ -179,17 +324,33 @@ impl NeuralState {
             .map(|d| x.iter().map(|xi| d * xi).collect()).collect();
         let grad_b1: Vec<f64> = delta1;
 
-        // Обновляем веса
-        self.layer1.update(&grad_w1, &grad_b1);
-        self.layer2.update(&grad_w2, &grad_b2);
+        (LayerGradients { w1: grad_w1, b1: grad_b1, w2: grad_w2, b2: grad_b2 }, loss)
+    }
+
+    // Клиппинг по глобальной норме, затем шаг с L2-затуханием
+    fn apply_gradients(&mut self, mut grads: LayerGradients) {
+        if let Some(max_norm) = self.regularization.clip_norm {
+            grads.clip_global_norm(max_norm);
+        }
+        let decay = self.regularization.weight_decay;
+        self.layer1.update_with_decay(&grads.w1, &grads.b1, decay);
+        self.layer2.update_with_decay(&grads.w2, &grads.b2, decay);
+    }
+
+    fn record_loss(&mut self, loss: f64) {
+        // Первый шаг инициализирует EMA, иначе она стартует с 0 и сначала растёт
+        self.total_loss = if self.training_steps == 0 { loss }
+            else { self.total_loss * 0.99 + loss * 0.01 };
+    }
 
+    fn record_outcome(&mut self, success: bool, neighbor_id: &str) {
         // Обновляем вес соседа
-        let reward = if target.success { 0.1 } else { -0.05 };
+        let reward = if success { 0.1 } else { -0.05 };
         let w = self.neighbor_weights.entry(neighbor_id.to_string()).or_insert(0.5);
         *w = (*w + reward).clamp(0.0, 1.0);
 
         // Обновляем success rate
-        if target.success {
+        if success {
             self.success_rate = self.success_rate * 0.95 + 0.05;
         } else {
             self.success_rate *= 0.95;
@@ -198,6 +359,31 @@ impl NeuralState {
         self.training_steps += 1;
     }
 
+    /// Одно усреднённое обновление весов по батчу (input, target).
+    pub fn train_batch(&mut self, batch: &[(NeuralInput, NeuralTarget)], neighbor_id: &str) -> f64 {
+        if batch.is_empty() { return 0.0; }
+        let n = batch.len() as f64;
+        let mut acc: Option<LayerGradients> = None;
+        let mut loss_sum = 0.0;
+        for (input, target) in batch {
+            let (g, loss) = self.compute_gradients(input, target, None);
+            loss_sum += loss;
+            match acc.as_mut() {
+                Some(a) => a.add(&g),
+                None => acc = Some(g),
+            }
+        }
+        let mut grads = acc.unwrap();
+        grads.scale(1.0 / n);
+        let mean_loss = loss_sum / n;
+        self.record_loss(mean_loss);
+        self.apply_gradients(grads);
+        for (_, target) in batch {
+            self.record_outcome(target.success, neighbor_id);
+        }
+        mean_loss
+    }
+
     /// Предсказать затор на основе истории задержек
     pub fn predict_congestion(&mut self, current_latency_ms: f64) -> CongestionPrediction {
         self.congestion_history.push(current_latency_ms);
@@ -207,29 +393,31 @@ impl NeuralState {
 
         let history = &self.congestion_history;
         if history.len() < 3 {
-            return CongestionPrediction {
-                probability: 0.0, trend: Trend::Stable,
-                predicted_latency_ms: current_latency_ms,
-                confidence: 0.1, action: "Недостаточно данных".into(),
-            };
+            return Self::stable_prediction(current_latency_ms, 0.1, "Недостаточно данных");
         }
 
         // Линейный тренд: наклон скользящего среднего
         let n = history.len() as f64;
         let mean = history.iter().sum::<f64>() / n;
+        let var = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let denom = history.iter().enumerate()
+            .map(|(i, _)| (i as f64 - n / 2.0).powi(2))
+            .sum::<f64>();
+        // Ровная история (или мусор в ней) — тренда нет, делить не на что
+        if var.is_nan() || var <= f64::EPSILON || !var.is_finite() || denom <= 1e-10 {
+            let confidence = (n / CONGESTION_WINDOW as f64).min(1.0);
+            let pred = Self::stable_prediction(current_latency_ms, confidence, "✅ Задержка ровная");
+            self.last_prediction = Some(pred.clone());
+            return pred;
+        }
         let slope = history.iter().enumerate()
             .map(|(i, &v)| (i as f64 - n / 2.0) * (v - mean))
-            .sum::<f64>() / history.iter().enumerate()
-            .map(|(i, _)| (i as f64 - n / 2.0).powi(2))
-            .sum::<f64>().max(1e-10);
+            .sum::<f64>() / denom;
 
         // Нейронная оценка через forward pass
         let norm_latency = (current_latency_ms / 200.0).min(1.0);
         let _norm_slope = (slope / 50.0).clamp(-1.0, 1.0);
-        let std_dev = {
-            let var = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
-            var.sqrt() / 100.0
-        };
+        let std_dev = var.sqrt() / 100.0;
 
         let neural_input = NeuralInput {
             latency: norm_latency,
@@ -248,7 +436,7 @@ impl NeuralState {
             else if slope < -5.0 { Trend::Falling }
             else { Trend::Stable };
 
-        let predicted_latency = current_latency_ms + slope * 5.0;
+        let predicted_latency = (current_latency_ms + slope * 5.0).max(0.0);
         let confidence = (history.len() as f64 / CONGESTION_WINDOW as f64).min(1.0);
 
         let action = if probability > CONGESTION_THRESHOLD {
@@ -266,6 +454,15 @@ impl NeuralState {
         self.last_prediction = Some(pred.clone());
         pred
     }
+
+    fn stable_prediction(latency_ms: f64, confidence: f64, action: &str) -> CongestionPrediction {
+        let latency = if latency_ms.is_finite() { latency_ms.max(0.0) } else { 0.0 };
+        CongestionPrediction {
+            probability: 0.0, trend: Trend::Stable,
+            predicted_latency_ms: latency,
+            confidence, action: action.into(),
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -331,21 +528,46 @@ impl NeuralTactic {
         }
     }
 
-    /// Тактика с учётом входного вектора (более точная)
+    /// Тактика с учётом входного вектора (более точная) и уверенность 0..1.
+    ///
+    /// Каждое условие даёт нормированный запас: насколько значение ушло
+    /// за порог относительно оставшегося диапазона. Сила правила — худший
+    /// из его запасов. Правила проверяются строго по приоритету:
+    ///   1. AikiReflection   — latency > 0.80 и congestion > 0.60
+    ///   2. StandoffDecoy    — latency > 0.70 и decoy активен
+    ///   3. Hybrid           — latency > 0.40 и decoy, и strike активны
+    ///   4. CumulativeStrike — latency < 0.50 и strike активен
+    ///   5. Passive          — ни одно правило не сработало
+    ///
+    /// Hybrid стоит выше CumulativeStrike: в перекрытии latency 0.40..0.50
+    /// он строже (требует ещё и decoy). Выход decoy/strike «активен»,
+    /// только если выше 0.5 + TACTIC_NEUTRAL_BAND.
+    /// Уверенность Passive — среднее из «канал спокоен» (запас latency
+    /// ниже PASSIVE_LATENCY) и «до ближайшего правила далеко».
     pub fn decide_from_input(latency: f64, congestion: f64,
-        decoy: f64, strike: f64) -> Self {
+        decoy: f64, strike: f64) -> (Self, f64) {
+        let above = |v: f64, t: f64| ((v - t) / (1.0 - t)).clamp(-1.0, 1.0);
+        let below = |v: f64, t: f64| ((t - v) / t).clamp(-1.0, 1.0);
+        let active = 0.5 + TACTIC_NEUTRAL_BAND;
+
         // Латентность напрямую говорит об угрозе
-        if latency > 0.80 && congestion > 0.60 {
-            NeuralTactic::AikiReflection
-        } else if latency > 0.70 && decoy > 0.50 {
-            NeuralTactic::StandoffDecoy
-        } else if latency < 0.50 && strike > 0.50 {
-            NeuralTactic::CumulativeStrike
-        } else if latency > 0.40 && decoy > 0.50 && strike > 0.50 {
-            NeuralTactic::Hybrid
-        } else {
-            NeuralTactic::Passive
+        let rules = [
+            (NeuralTactic::AikiReflection,
+             above(latency, 0.80).min(above(congestion, 0.60))),
+            (NeuralTactic::StandoffDecoy,
+             above(latency, 0.70).min(above(decoy, active))),
+            (NeuralTactic::Hybrid,
+             above(latency, 0.40).min(above(decoy, active)).min(above(strike, active))),
+            (NeuralTactic::CumulativeStrike,
+             below(latency, 0.50).min(above(strike, active))),
+        ];
+        if let Some((tactic, strength)) = rules.iter().find(|(_, s)| *s > 0.0) {
+            return (tactic.clone(), *strength);
         }
+
+        let nearest = rules.iter().map(|(_, s)| *s).fold(f64::NEG_INFINITY, f64::max);
+        let calm = below(latency, PASSIVE_LATENCY).max(0.0);
+        (NeuralTactic::Passive, 0.5 * calm + 0.5 * (-nearest))
     }
     pub fn name(&self) -> &str {
         match self {
@@ -367,6 +589,7 @@ pub struct NeuralOutput {
     pub softmax_probs:   Vec<f64>,
     pub hidden_state:    Vec<f64>,
     pub tactic:          NeuralTactic,
+    pub dropout_mask:    Option<Vec<f64>>,  // только для forward_train
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -402,6 +625,31 @@ impl NeuralTarget {
     }
 }
 
+/// Размеченные сценарии тактического демо: (название, вход канала, ожидаемый
+/// выход сети). Обученная на них сеть выбирает для каждого свою тактику
+pub fn tactic_scenarios() -> Vec<(&'static str, NeuralInput, NeuralTarget)> {
+    // Зондирование: броня без удара и без затора — отвлекаем, не раскрываясь
+    let probing = NeuralTarget { route_weight: 0.4, congestion: 0.1,
+        quality: 0.4, decoy: 0.9, strike: 0.2, success: false };
+    vec![
+        ("Чистый канал",
+         NeuralInput { latency:0.05, bandwidth:0.95, reliability:0.99, trust:0.95, ethics_score:1.0 },
+         NeuralTarget::success_route(0.9)),
+        ("DPI активен",
+         NeuralInput { latency:0.70, bandwidth:0.30, reliability:0.50, trust:0.40, ethics_score:0.9 },
+         NeuralTarget::under_attack(0.85, 0.80)),
+        ("Полная блокировка",
+         NeuralInput { latency:0.92, bandwidth:0.08, reliability:0.15, trust:0.10, ethics_score:0.9 },
+         NeuralTarget::failed_route()),
+        ("Узкое окно",
+         NeuralInput { latency:0.55, bandwidth:0.55, reliability:0.75, trust:0.65, ethics_score:1.0 },
+         NeuralTarget::success_route(0.6)),
+        ("Зондирование",
+         NeuralInput { latency:0.85, bandwidth:0.15, reliability:0.25, trust:0.15, ethics_score:0.9 },
+         probing),
+    ]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CongestionPrediction {
     pub probability: f64,
@@ -445,7 +693,8 @@ impl NeuralRouter {
         state.forward(input)
     }
 
-    /// Выбрать лучший маршрут из кандидатов
+    /// Выбрать лучший маршрут из кандидатов. NaN-оценка считается худшей,
+    /// равные оценки решает меньший neighbor id — выбор воспроизводим
     pub fn select_best(&mut self, candidates: Vec<(String, NeuralInput)>) -> Option<String> {
         if candidates.is_empty() { return None; }
         let scored: Vec<(String, f64)> = candidates.iter().map(|(id, input)| {
@@ -458,11 +707,47 @@ impl NeuralRouter {
             let neighbor_bonus = *state.neighbor_weights.get(id).unwrap_or(&0.5);
             (id.clone(), score + neighbor_bonus * 0.1)
         }).collect();
+        self.routes_computed += scored.len() as u64;
+        Self::pick_best(scored)
+    }
+
+    /// Выбор с учётом бюджета AdaptiveScheduler: под Minimal/Emergency
+    /// forward pass не запускается — только накопленные neighbor_weights
+    pub fn select_best_within_budget(&mut self, candidates: Vec<(String, NeuralInput)>,
+                                     budget: &ComputeBudget) -> Option<String> {
+        if budget.max_layers() >= 2 { return self.select_best(candidates); }
+        let scored = candidates.into_iter().map(|(id, _)| {
+            let weight = self.states.get(&id)
+                .and_then(|s| s.neighbor_weights.get(&id).copied())
+                .unwrap_or(0.5);
+            (id, weight)
+        }).collect();
+        Self::pick_best(scored)
+    }
 
-        scored.into_iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+    // NaN — худшая оценка; при равенстве выигрывает меньший id
+    fn pick_best(scored: Vec<(String, f64)>) -> Option<String> {
+        scored.into_iter()
+            .map(|(id, score)| (id, if score.is_nan() { f64::NEG_INFINITY } else { score }))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
             .map(|(id, _)| id)
     }
 
+    /// Перевыбор соседа посреди сессии: если predict_congestion текущего
+    /// соседа превышает CONGESTION_THRESHOLD — лучший из остальных кандидатов.
+    pub fn reselect_if_congested(&mut self, current_neighbor: &str, latency_ms: f64,
+        candidates: Vec<(String, NeuralInput)>) -> Option<String> {
+        let state = self.states.entry(current_neighbor.to_string())
+            .or_insert_with(|| NeuralState::new(current_neighbor));
+        let prediction = state.predict_congestion(latency_ms);
+        if prediction.probability <= CONGESTION_THRESHOLD { return None; }
+        let alternatives: Vec<(String, NeuralInput)> = candidates.into_iter()
+            .filter(|(id, _)| id != current_neighbor).collect();
+        let best = self.select_best(alternatives)?;
+        self.routes_improved += 1;
+        Some(best)
+    }
+
     /// Обучить сеть на результате доставки
     pub fn train_on_delivery(&mut self, neighbor_id: &str,
         input: &NeuralInput, success: bool, quality: f64) {
@@ -477,6 +762,31 @@ impl NeuralRouter {
         if success { self.routes_improved += 1; }
     }
 
+    /// Обучение на отчёте транспорта: ack или таймаут фрейма к соседу
+    pub fn ingest_report(&mut self, report: &DeliveryReport) {
+        self.train_on_delivery(&report.neighbor, &report.input, report.success, report.quality);
+    }
+
+    /// Пакетное обучение по логу доставок (neighbor, input, success, quality):
+    /// градиенты копятся по соседу и применяются одним усреднённым шагом.
+    pub fn train_batch(&mut self, samples: &[(String, NeuralInput, bool, f64)]) {
+        let mut by_neighbor: HashMap<&str, Vec<(NeuralInput, NeuralTarget)>> = HashMap::new();
+        for (neighbor_id, input, success, quality) in samples {
+            let target = if *success {
+                NeuralTarget::success_route(*quality)
+            } else {
+                NeuralTarget::failed_route()
+            };
+            by_neighbor.entry(neighbor_id.as_str()).or_default().push((input.clone(), target));
+            if *success { self.routes_improved += 1; }
+        }
+        for (neighbor_id, batch) in by_neighbor {
+            let state = self.states.entry(neighbor_id.to_string())
+                .or_insert_with(|| NeuralState::new(neighbor_id));
+            state.train_batch(&batch, neighbor_id);
+        }
+    }
+
     pub fn stats(&self) -> RouterNeuralStats {
         let avg_success = if self.states.is_empty() { 0.0 } else {
             self.states.values().map(|s| s.success_rate).sum::<f64>()
@@ -740,3 +1050,269 @@ pub struct SchedulerStats {
     pub is_throttling: bool,
     pub inference_interval_ms: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(latency_ms: f64) -> NeuralInput {
+        NeuralInput::from_ssau(latency_ms, 500.0, 0.9, 0.8)
+    }
+
+    #[test]
+    fn test_select_best_breaks_ties_by_id() {
+        let mut router = NeuralRouter::new("self");
+        let twin = NeuralState::new("twin");
+        for id in ["node-b", "node-a", "node-c"] {
+            router.states.insert(id.to_string(), twin.clone());
+        }
+        for _ in 0..5 {
+            let candidates = ["node-c", "node-a", "node-b"].iter()
+                .map(|id| (id.to_string(), input(120.0))).collect();
+            assert_eq!(router.select_best(candidates).as_deref(), Some("node-a"));
+        }
+    }
+
+    #[test]
+    fn test_predict_congestion_constant_history() {
+        let mut state = NeuralState::new("flat");
+        let mut last = None;
+        for _ in 0..10 { last = Some(state.predict_congestion(80.0)); }
+        let pred = last.unwrap();
+        assert_eq!(pred.trend, Trend::Stable);
+        assert_eq!(pred.probability, 0.0);
+        assert_eq!(pred.predicted_latency_ms, 80.0);
+        assert!(pred.confidence.is_finite());
+    }
+
+    #[test]
+    fn test_predict_congestion_short_history() {
+        let mut state = NeuralState::new("short");
+        state.predict_congestion(40.0);
+        let pred = state.predict_congestion(-5.0);
+        assert_eq!(pred.trend, Trend::Stable);
+        assert_eq!(pred.probability, 0.0);
+        assert_eq!(pred.predicted_latency_ms, 0.0);
+        assert!(!pred.confidence.is_nan());
+
+        // Резкий спад не даёт отрицательного прогноза
+        for ms in [900.0, 600.0, 300.0, 10.0] { state.predict_congestion(ms); }
+        let falling = state.predict_congestion(0.0);
+        assert!(falling.predicted_latency_ms >= 0.0);
+        assert!(falling.probability.is_finite());
+    }
+
+    #[test]
+    fn test_select_best_within_budget_skips_inference() {
+        let mut router = NeuralRouter::new("phone");
+        router.train_on_delivery("relay-b", &input(120.0), true, 0.9);
+        let candidates = || vec![
+            ("relay-a".to_string(), input(120.0)),
+            ("relay-b".to_string(), input(120.0)),
+        ];
+
+        // Emergency: решает только кэш neighbor_weights, сеть не трогаем
+        assert_eq!(router.select_best_within_budget(candidates(), &ComputeBudget::Emergency)
+            .as_deref(), Some("relay-b"));
+        assert!(!router.states.contains_key("relay-a"));
+        assert_eq!(router.routes_computed, 0);
+
+        assert!(router.select_best_within_budget(candidates(), &ComputeBudget::Full).is_some());
+        assert_eq!(router.routes_computed, 2);
+    }
+
+    #[test]
+    fn test_select_best_nan_never_wins() {
+        let mut router = NeuralRouter::new("self");
+        let broken = NeuralInput { latency: f64::NAN, ..input(120.0) };
+        let candidates = vec![
+            ("aaa-broken".to_string(), broken.clone()),
+            ("zzz-ok".to_string(), input(900.0)),
+        ];
+        assert_eq!(router.select_best(candidates).as_deref(), Some("zzz-ok"));
+        // Единственный кандидат с NaN — без паники
+        assert_eq!(router.select_best(vec![("aaa-broken".to_string(), broken)]).as_deref(),
+            Some("aaa-broken"));
+    }
+
+    #[test]
+    fn test_decide_from_input_demo_scenarios() {
+        // Необученная сеть держит выходы у 0.5 — внутри нейтральной полосы,
+        // поэтому ни один сценарий не даёт уверенного повода для атаки
+        let untrained = NeuralState::new("nexus-core-01");
+        let scenarios = tactic_scenarios();
+        let mut passive = HashMap::new();
+        for (name, input, _) in &scenarios {
+            let out = untrained.forward(input);
+            let (tactic, conf) = NeuralTactic::decide_from_input(
+                input.latency, out.congestion_prob, out.decoy_intensity, out.strike_focus);
+            assert_eq!(tactic, NeuralTactic::Passive, "{}", name);
+            passive.insert(*name, conf);
+        }
+        assert!(passive["Чистый канал"] > passive["Узкое окно"]);
+
+        // Обученная на сценариях сеть различает их
+        let samples: Vec<_> = scenarios.iter().map(|(_, i, t)| (i.clone(), t.clone())).collect();
+        let state = NeuralState::trained_on("nexus-core-01", &samples, TACTIC_DEMO_EPOCHS);
+        let expected = [NeuralTactic::Passive, NeuralTactic::Hybrid,
+            NeuralTactic::AikiReflection, NeuralTactic::Passive, NeuralTactic::StandoffDecoy];
+        for ((name, input, _), want) in scenarios.iter().zip(expected) {
+            let out = state.forward(input);
+            let (tactic, conf) = NeuralTactic::decide_from_input(
+                input.latency, out.congestion_prob, out.decoy_intensity, out.strike_focus);
+            assert_eq!(tactic, want, "{}", name);
+            assert!((0.0..=1.0).contains(&conf), "{}: {}", name, conf);
+        }
+    }
+
+    #[test]
+    fn test_decide_from_input_precedence() {
+        // Aiki и Standoff перекрываются — побеждает Aiki
+        let (t, c) = NeuralTactic::decide_from_input(0.95, 0.90, 0.90, 0.50);
+        assert_eq!(t, NeuralTactic::AikiReflection);
+        assert!(c > 0.5);
+        assert_eq!(NeuralTactic::decide_from_input(0.90, 0.30, 0.90, 0.50).0,
+            NeuralTactic::StandoffDecoy);
+        // Перекрытие Hybrid/CumulativeStrike при latency 0.45 — Hybrid
+        assert_eq!(NeuralTactic::decide_from_input(0.45, 0.30, 0.80, 0.80).0,
+            NeuralTactic::Hybrid);
+        assert_eq!(NeuralTactic::decide_from_input(0.45, 0.30, 0.40, 0.80).0,
+            NeuralTactic::CumulativeStrike);
+        // Граница чуть превышена — решение есть, но уверенность низкая
+        let (t, weak) = NeuralTactic::decide_from_input(0.81, 0.61, 0.5, 0.5);
+        assert_eq!(t, NeuralTactic::AikiReflection);
+        assert!(weak < c);
+    }
+
+    #[test]
+    fn test_reroute_on_rising_latency() {
+        let mut router = NeuralRouter::new("me");
+        let candidates = || vec![
+            ("peer_a".to_string(), input(40.0)),
+            ("peer_b".to_string(), input(60.0)),
+            ("peer_c".to_string(), input(30.0)),
+        ];
+        assert_eq!(router.reselect_if_congested("peer_a", 20.0, candidates()), None);
+
+        let mut rerouted = None;
+        for step in 1..CONGESTION_WINDOW {
+            let latency = 20.0 + step as f64 * 60.0;
+            if let Some(alt) = router.reselect_if_congested("peer_a", latency, candidates()) {
+                rerouted = Some(alt);
+                break;
+            }
+        }
+        let alt = rerouted.expect("rising latency must force a reroute");
+        assert_ne!(alt, "peer_a");
+    }
+
+    #[test]
+    fn test_dropout_zero_matches_forward() {
+        let state = NeuralState::new("peer_a");
+        let x = input(80.0);
+        let mut rng = XorShift64::new(0x5eed);
+        let plain = state.forward(&x);
+        let trained = state.forward_train(&x, 0.0, &mut rng);
+        assert_eq!(plain.hidden_state, trained.hidden_state);
+        assert_eq!(plain.softmax_probs, trained.softmax_probs);
+    }
+
+    #[test]
+    fn test_dropout_half_changes_hidden_state() {
+        let state = NeuralState::new("peer_a");
+        let x = NeuralInput { latency: 0.9, bandwidth: 0.9, reliability: 0.9, trust: 0.9, ethics_score: 1.0 };
+        let plain = state.forward(&x);
+        let mut rng = XorShift64::new(0x5eed);
+        let dropped = (0..8).map(|_| state.forward_train(&x, 0.5, &mut rng))
+            .find(|o| o.hidden_state != plain.hidden_state)
+            .expect("dropout 0.5 must eventually change the hidden state");
+        assert_eq!(dropped.hidden_state.len(), plain.hidden_state.len());
+        let mask = dropped.dropout_mask.unwrap();
+        assert!(mask.iter().all(|&m| m == 0.0 || m == 2.0));
+    }
+
+    #[test]
+    fn test_batch_training_reduces_loss() {
+        let mut router = NeuralRouter::new("me");
+        let samples: Vec<(String, NeuralInput, bool, f64)> = (0..16)
+            .map(|i| ("peer_a".to_string(), input(20.0 + i as f64 * 5.0), true, 0.9))
+            .collect();
+        let mut losses = vec![];
+        for _ in 0..5 {
+            router.train_batch(&samples);
+            losses.push(router.states["peer_a"].total_loss);
+        }
+        assert!(losses.windows(2).all(|w| w[1] < w[0]), "losses: {:?}", losses);
+        assert_eq!(router.states["peer_a"].training_steps, 16 * 5);
+    }
+
+    #[test]
+    fn test_ack_report_trains_the_acked_neighbor() {
+        use crate::transport::{TransportChannel, MAX_JITTER_US};
+        let mut router = NeuralRouter::new("me");
+        let mut ch = TransportChannel::new("me", "peer_b");
+        ch.set_route_input(input(30.0));
+        let sent = ch.send_with_decoys(b"payload", "HttpsRequest", 3);
+        let real = sent.iter().find(|r| !r.is_decoy).unwrap().frame_id;
+        let now = ch.clock.now_us() + MAX_JITTER_US;
+        ch.flush_at(now);
+        assert_eq!(ch.in_flight(), 1, "приманки ack не ждут");
+
+        let report = ch.ack(real, now + 50_000).unwrap();
+        assert!(report.success && report.quality > 0.9);
+        assert_eq!(report.neighbor, "peer_b");
+        router.ingest_report(&report);
+        assert_eq!(router.states["peer_b"].training_steps, 1);
+        assert_eq!(router.routes_improved, 1);
+        assert!(!router.states.contains_key("peer_a"));
+
+        // Таймаут — отчёт о провале: обучение есть, улучшения нет
+        ch.send_with_decoys(b"payload", "HttpsRequest", 0);
+        ch.flush_at(now + MAX_JITTER_US);
+        assert!(ch.expire_at(now + MAX_JITTER_US + ch.ack_timeout_us).is_empty());
+        let lost = ch.expire_at(now + MAX_JITTER_US + ch.ack_timeout_us + 1);
+        assert_eq!(lost.len(), 1);
+        assert!(!lost[0].success);
+        router.ingest_report(&lost[0]);
+        assert_eq!(router.states["peer_b"].training_steps, 2);
+        assert_eq!(router.routes_improved, 1);
+    }
+
+    #[test]
+    fn test_regularization_keeps_weights_finite_under_extreme_inputs() {
+        let hostile = NeuralInput { latency: 1e9, bandwidth: -1e9, reliability: 1e9,
+            trust: 1e9, ethics_score: -1e9 };
+        let params = |s: &NeuralState| [&s.layer1, &s.layer2].into_iter()
+            .flat_map(|l| l.weights.iter().flatten().chain(&l.biases).copied())
+            .collect::<Vec<f64>>();
+
+        let mut state = NeuralState::new("peer_x");
+        for i in 0..500 {
+            let target = if i % 2 == 0 { NeuralTarget::success_route(1.0) }
+                         else { NeuralTarget::failed_route() };
+            state.backpropagate_success(&hostile, &target, "peer_x");
+        }
+        let p = params(&state);
+        assert!(p.iter().all(|w| w.is_finite() && w.abs() < 100.0), "{:?}", p);
+        for x in [&hostile, &input(50.0)] {
+            let out = state.forward(x);
+            for v in [out.route_weight, out.congestion_prob, out.quality_score,
+                      out.decoy_intensity, out.strike_focus] {
+                assert!((0.0..=1.0).contains(&v), "{}", v);
+            }
+        }
+
+        // Без защиты те же входы сносят веса на порядки, хотя и конечно
+        let mut raw = NeuralState::new("peer_x");
+        raw.regularization = Regularization::none();
+        for _ in 0..50 { raw.backpropagate_success(&hostile, &NeuralTarget::failed_route(), "peer_x"); }
+        let max_abs = |p: Vec<f64>| p.iter().fold(0.0f64, |m, w| m.max(w.abs()));
+        assert!(params(&raw).iter().all(|w| w.is_finite()));
+        assert!(max_abs(params(&raw)) > 1e6 * max_abs(params(&state)));
+
+        let mut layer = NeuralWeights::new(2, 1, 1);
+        layer.update_with_decay(&[vec![f64::NAN, f64::INFINITY]], &[f64::NAN], 0.0);
+        assert_eq!(layer.weights[0], [0.0, 0.0]);
+        assert_eq!(layer.biases[0], 0.0);
+    }
+}
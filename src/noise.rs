@@ -18,7 +18,9 @@
 //   NoiseSession   — готовый зашифрованный канал
 // =============================================================================
 
+use crate::error::FederationError;
 use crate::chacha::{ChaCha20Poly1305, KEY_SIZE, NONCE_SIZE, TAG_SIZE};
+use crate::rng::{FedRng, XorShift64};
 
 // Noise использует 32-байтные ключи
 pub const DHLEN: usize = 32;
@@ -46,7 +48,7 @@ pub fn hash(data: &[u8]) -> [u8; HASHLEN] {
     h
 }
 
-fn hmac(key: &[u8], data: &[u8]) -> [u8; HASHLEN] {
+pub fn hmac(key: &[u8], data: &[u8]) -> [u8; HASHLEN] {
     let mut input = Vec::with_capacity(key.len() + data.len() + 1);
     input.extend_from_slice(key);
     input.push(0x36); // ipad
@@ -81,70 +83,204 @@ fn hkdf3(chaining_key: &[u8], input: &[u8])
 }
 
 // -----------------------------------------------------------------------------
-// DH — Diffie-Hellman на X25519 (упрощённая версия из chacha.rs)
+// DH — X25519 (RFC 7748): лестница Монтгомери над GF(2^255 - 19)
+// Поле — 5 лимбов по 51 бит, умножение через u128.
 // -----------------------------------------------------------------------------
 
-fn dh(privkey: &[u8; DHLEN], pubkey: &[u8; DHLEN]) -> [u8; DHLEN] {
-    // Истинный симметричный DH: derive_public(privkey) XOR-mix с pubkey
-    // dh(a_priv, b_pub) == dh(b_priv, a_pub) если pub = derive_public(priv)
-    // Реализация: shared = hash(sorted(derive_pub(priv), pub))
-    let my_pub = derive_public_from_priv(privkey);
-    // Сортируем два публичных ключа чтобы получить одинаковый порядок
-    let (first, second) = if my_pub <= *pubkey {
-        (my_pub, *pubkey)
-    } else {
-        (*pubkey, my_pub)
+type Fe = [u64; 5];
+
+const FE_MASK: u64 = (1 << 51) - 1;
+const X25519_A24: u64 = 121_665;
+const X25519_BASEPOINT: [u8; DHLEN] = {
+    let mut b = [0u8; DHLEN];
+    b[0] = 9;
+    b
+};
+
+fn fe_from_bytes(b: &[u8; DHLEN]) -> Fe {
+    let load = |i: usize| {
+        let mut w = [0u8; 8];
+        w.copy_from_slice(&b[i..i + 8]);
+        u64::from_le_bytes(w)
     };
-    // Смешиваем оба публичных ключа детерминированно
-    let mut shared = [0u8; DHLEN];
-    let mut state = 0xD415_A4E5_5ECE_7000u64;
-    for i in 0..DHLEN {
-        let a = first[i] as u64;
-        let b = second[i] as u64;
-        state = state.wrapping_add(a.wrapping_mul(0x100).wrapping_add(b));
-        state ^= state >> 17;
-        state = state.wrapping_mul(0xbf58476d1ce4e5b9);
-        state ^= state >> 31;
-        state = state.wrapping_mul(0x94d049bb133111eb);
-        state ^= state >> 33;
-        shared[i] = (state & 0xff) as u8;
+    [
+        load(0) & FE_MASK,
+        (load(6) >> 3) & FE_MASK,
+        (load(12) >> 6) & FE_MASK,
+        (load(19) >> 1) & FE_MASK,
+        (load(24) >> 12) & FE_MASK,
+    ]
+}
+
+fn fe_carry(mut h: Fe) -> Fe {
+    for i in 0..4 {
+        h[i + 1] += h[i] >> 51;
+        h[i] &= FE_MASK;
     }
-    shared
+    h[0] += 19 * (h[4] >> 51);
+    h[4] &= FE_MASK;
+    h[1] += h[0] >> 51;
+    h[0] &= FE_MASK;
+    h
 }
 
-fn derive_public_from_priv(privkey: &[u8; DHLEN]) -> [u8; DHLEN] {
-    let mut pubkey = [0u8; DHLEN];
-    let mut state = 0x4375_7276_6532_3535u64;
-    for (i, &b) in privkey.iter().enumerate() {
-        state ^= (b as u64).wrapping_mul(0x517cc1b727220a95);
-        state = state.rotate_left((i % 64) as u32 + 1);
-        state ^= state >> 33;
-        state = state.wrapping_mul(0xff51afd7ed558ccd);
-        pubkey[i] = (state & 0xff) as u8;
+fn fe_to_bytes(h: &Fe) -> [u8; DHLEN] {
+    let mut h = fe_carry(fe_carry(*h));
+    // Финальное приведение: вычитаем p, если h >= p
+    let mut q = (h[0] + 19) >> 51;
+    for limb in h.iter().skip(1) {
+        q = (limb + q) >> 51;
+    }
+    h[0] += 19 * q;
+    for i in 0..4 {
+        h[i + 1] += h[i] >> 51;
+        h[i] &= FE_MASK;
+    }
+    h[4] &= FE_MASK;
+
+    let mut out = [0u8; DHLEN];
+    let (mut acc, mut bits, mut idx) = (0u128, 0u32, 0usize);
+    for limb in h {
+        acc |= (limb as u128) << bits;
+        bits += 51;
+        while bits >= 8 {
+            out[idx] = acc as u8;
+            acc >>= 8;
+            bits -= 8;
+            idx += 1;
+        }
+    }
+    out[idx] = acc as u8;
+    out
+}
+
+fn fe_add(a: &Fe, b: &Fe) -> Fe {
+    fe_carry([a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3], a[4] + b[4]])
+}
+
+fn fe_sub(a: &Fe, b: &Fe) -> Fe {
+    // a + 2p - b: входы приведены (< 2^52), поэтому без отрицательных лимбов
+    fe_carry([
+        a[0] + 0xF_FFFF_FFFF_FFDA - b[0],
+        a[1] + 0xF_FFFF_FFFF_FFFE - b[1],
+        a[2] + 0xF_FFFF_FFFF_FFFE - b[2],
+        a[3] + 0xF_FFFF_FFFF_FFFE - b[3],
+        a[4] + 0xF_FFFF_FFFF_FFFE - b[4],
+    ])
+}
+
+fn fe_reduce_wide(r: [u128; 5]) -> Fe {
+    let mut r = r;
+    for i in 0..4 {
+        r[i + 1] += r[i] >> 51;
+        r[i] &= FE_MASK as u128;
+    }
+    r[0] += 19 * (r[4] >> 51);
+    r[4] &= FE_MASK as u128;
+    fe_carry([r[0] as u64, r[1] as u64, r[2] as u64, r[3] as u64, r[4] as u64])
+}
+
+fn fe_mul(a: &Fe, b: &Fe) -> Fe {
+    let m = |x: u64, y: u64| (x as u128) * (y as u128);
+    let (b1, b2, b3, b4) = (b[1] * 19, b[2] * 19, b[3] * 19, b[4] * 19);
+    fe_reduce_wide([
+        m(a[0], b[0]) + m(a[1], b4) + m(a[2], b3) + m(a[3], b2) + m(a[4], b1),
+        m(a[0], b[1]) + m(a[1], b[0]) + m(a[2], b4) + m(a[3], b3) + m(a[4], b2),
+        m(a[0], b[2]) + m(a[1], b[1]) + m(a[2], b[0]) + m(a[3], b4) + m(a[4], b3),
+        m(a[0], b[3]) + m(a[1], b[2]) + m(a[2], b[1]) + m(a[3], b[0]) + m(a[4], b4),
+        m(a[0], b[4]) + m(a[1], b[3]) + m(a[2], b[2]) + m(a[3], b[1]) + m(a[4], b[0]),
+    ])
+}
+
+fn fe_mul_small(a: &Fe, k: u64) -> Fe {
+    fe_reduce_wide([
+        a[0] as u128 * k as u128,
+        a[1] as u128 * k as u128,
+        a[2] as u128 * k as u128,
+        a[3] as u128 * k as u128,
+        a[4] as u128 * k as u128,
+    ])
+}
+
+fn fe_invert(a: &Fe) -> Fe {
+    // a^(p-2), p-2 = 2^255 - 21: все биты 0..254 выставлены, кроме 2 и 4
+    let mut r: Fe = [1, 0, 0, 0, 0];
+    for bit in (0..255).rev() {
+        r = fe_mul(&r, &r);
+        if bit != 2 && bit != 4 {
+            r = fe_mul(&r, a);
+        }
     }
-    pubkey
+    r
+}
+
+fn fe_cswap(swap: u64, a: &mut Fe, b: &mut Fe) {
+    let mask = 0u64.wrapping_sub(swap);
+    for i in 0..5 {
+        let t = mask & (a[i] ^ b[i]);
+        a[i] ^= t;
+        b[i] ^= t;
+    }
+}
+
+/// Скалярное умножение X25519(k, u) по RFC 7748
+pub fn x25519(scalar: &[u8; DHLEN], u: &[u8; DHLEN]) -> [u8; DHLEN] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    let x1 = fe_from_bytes(u);
+    let (mut x2, mut z2): (Fe, Fe) = ([1, 0, 0, 0, 0], [0; 5]);
+    let (mut x3, mut z3): (Fe, Fe) = (x1, [1, 0, 0, 0, 0]);
+    let mut swap = 0u64;
+    for t in (0..255).rev() {
+        let k_t = ((k[t / 8] >> (t % 8)) & 1) as u64;
+        swap ^= k_t;
+        fe_cswap(swap, &mut x2, &mut x3);
+        fe_cswap(swap, &mut z2, &mut z3);
+        swap = k_t;
+
+        let a = fe_add(&x2, &z2);
+        let aa = fe_mul(&a, &a);
+        let b = fe_sub(&x2, &z2);
+        let bb = fe_mul(&b, &b);
+        let e = fe_sub(&aa, &bb);
+        let c = fe_add(&x3, &z3);
+        let d = fe_sub(&x3, &z3);
+        let da = fe_mul(&d, &a);
+        let cb = fe_mul(&c, &b);
+        let sum = fe_add(&da, &cb);
+        x3 = fe_mul(&sum, &sum);
+        let diff = fe_sub(&da, &cb);
+        z3 = fe_mul(&x1, &fe_mul(&diff, &diff));
+        x2 = fe_mul(&aa, &bb);
+        z2 = fe_mul(&e, &fe_add(&aa, &fe_mul_small(&e, X25519_A24)));
+    }
+    fe_cswap(swap, &mut x2, &mut x3);
+    fe_cswap(swap, &mut z2, &mut z3);
+    fe_to_bytes(&fe_mul(&x2, &fe_invert(&z2)))
 }
 
-fn generate_keypair(seed: u64) -> ([u8; DHLEN], [u8; DHLEN]) {
-    let mut rng = seed;
+/// Общий секрет: X25519(privkey, pubkey). Без приватного ключа одной из сторон не вычисляется.
+pub fn dh(privkey: &[u8; DHLEN], pubkey: &[u8; DHLEN]) -> [u8; DHLEN] {
+    x25519(privkey, pubkey)
+}
+
+pub fn generate_keypair(seed: u64) -> ([u8; DHLEN], [u8; DHLEN]) {
+    generate_keypair_from(&mut XorShift64::new(seed))
+}
+
+/// Ключевая пара из внешнего FedRng; pubkey = X25519(privkey, 9)
+pub fn generate_keypair_from(rng: &mut dyn FedRng) -> ([u8; DHLEN], [u8; DHLEN]) {
     let mut privkey = [0u8; DHLEN];
     for b in &mut privkey {
-        rng ^= rng << 13; rng ^= rng >> 7; rng ^= rng << 17;
-        *b = (rng & 0xff) as u8;
+        *b = (rng.next_u64() & 0xff) as u8;
     }
     privkey[0]  &= 248;
     privkey[31] &= 127;
     privkey[31] |= 64;
-    // pubkey — детерминированно из privkey
-    let mut pubkey = [0u8; DHLEN];
-    let mut state = 0x4375_7276_6532_3535u64;
-    for (i, &b) in privkey.iter().enumerate() {
-        state ^= (b as u64).wrapping_mul(0x517cc1b727220a95);
-        state = state.rotate_left((i % 64) as u32 + 1);
-        state ^= state >> 33;
-        state = state.wrapping_mul(0xff51afd7ed558ccd);
-        pubkey[i] = (state & 0xff) as u8;
-    }
+    let pubkey = x25519(&privkey, &X25519_BASEPOINT);
     (privkey, pubkey)
 }
 
@@ -165,8 +301,6 @@ impl CipherState {
         self.nonce = 0;
     }
 
-    pub fn has_key(&self) -> bool { self.key.is_some() }
-
     fn nonce_bytes(&self) -> [u8; NONCE_SIZE] {
         let mut n = [0u8; NONCE_SIZE];
         // Noise spec: nonce — little-endian u64 в байтах 4..12
@@ -193,7 +327,6 @@ impl CipherState {
         if let Some(key) = self.key {
             if ciphertext.len() < TAG_SIZE { return Err("too short"); }
             let nonce = self.nonce_bytes();
-            self.nonce += 1;
             let (ct_bytes, tag_bytes) = ciphertext.split_at(ciphertext.len() - TAG_SIZE);
             let mut tag = [0u8; TAG_SIZE];
             tag.copy_from_slice(tag_bytes);
@@ -201,7 +334,11 @@ impl CipherState {
                 nonce, ciphertext: ct_bytes.to_vec(), tag, aad_len: ad.len()
             };
             let cipher = ChaCha20Poly1305::new(key);
-            cipher.open(&aead_ct, ad)
+            // Nonce двигаем только после успешной проверки тега:
+            // подброшенный мусор не должен рассинхронизировать сессию
+            let pt = cipher.open(&aead_ct, ad)?;
+            self.nonce += 1;
+            Ok(pt)
         } else {
             Ok(ciphertext.to_vec())
         }
@@ -358,7 +495,7 @@ impl HandshakeState {
 
     // Читаем сообщение 1 на стороне ответчика
     pub fn read_message_1(&mut self, msg: &[u8]) -> Result<Vec<u8>, &'static str> {
-        assert!(!self.is_initiator && self.message_index == 0);
+        if self.is_initiator || self.message_index != 0 { return Err("unexpected msg1"); }
         if msg.len() < DHLEN { return Err("msg1 too short"); }
         let mut re = [0u8; DHLEN];
         re.copy_from_slice(&msg[..DHLEN]);
@@ -376,21 +513,15 @@ impl HandshakeState {
         // e
         self.symmetric.mix_hash(&self.e_pub);
         msg.extend_from_slice(&self.e_pub);
-        eprintln!("W2 after mix_hash(e_pub): {:02x?}", &self.symmetric.handshake_hash[..4]);
         // ee: DH(e, re)
         let re = self.re_pub.unwrap();
         let ee = dh(&self.e_priv, &re);
-        eprintln!("W2 ee[:4]: {:02x?}", &ee[..4]);
         self.symmetric.mix_key(&ee);
-        eprintln!("W2 after mix_key(ee): hash={:02x?} ck={:02x?}", &self.symmetric.handshake_hash[..4], &self.symmetric.chaining_key[..4]);
         // s: зашифрованный static pubkey
         let enc_s = self.symmetric.encrypt_and_hash(&self.s_pub);
-        eprintln!("W2 BEFORE enc_s: nonce_will_be={} key_set={}", self.symmetric.cipher.nonce, self.symmetric.cipher.has_key());
-        eprintln!("W2 enc_s len={} cipher_has_key={} after encrypt_and_hash(s): {:02x?}", enc_s.len(), self.symmetric.cipher.has_key(), &self.symmetric.handshake_hash[..4]);
         msg.extend_from_slice(&enc_s);
         // es: DH(s, re)
         let es = dh(&self.s_priv, &re);
-        eprintln!("W2 es[:4]: {:02x?}", &es[..4]);
         self.symmetric.mix_key(&es);
         // payload
         let enc = self.symmetric.encrypt_and_hash(payload);
@@ -401,7 +532,7 @@ impl HandshakeState {
 
     // Читаем сообщение 2 на стороне инициатора
     pub fn read_message_2(&mut self, msg: &[u8]) -> Result<Vec<u8>, &'static str> {
-        assert!(self.is_initiator && self.message_index == 1);
+        if !self.is_initiator || self.message_index != 1 { return Err("unexpected msg2"); }
         let mut pos = 0;
         // re
         if msg.len() < pos + DHLEN { return Err("msg2 too short (re)"); }
@@ -410,18 +541,12 @@ impl HandshakeState {
         self.re_pub = Some(re);
         self.symmetric.mix_hash(&re);
         pos += DHLEN;
-        eprintln!("R2 after mix_hash(re): {:02x?}", &self.symmetric.handshake_hash[..4]);
         // ee: DH(e, re)
         let ee = dh(&self.e_priv, &re);
-        eprintln!("R2 ee[:4]: {:02x?}", &ee[..4]);
         self.symmetric.mix_key(&ee);
-        eprintln!("R2 after mix_key(ee): hash={:02x?} ck={:02x?}", &self.symmetric.handshake_hash[..4], &self.symmetric.chaining_key[..4]);
         // rs: расшифровываем static pubkey ответчика
         let enc_s_len = DHLEN + TAG_SIZE;
         if msg.len() < pos + enc_s_len { return Err("msg2 too short (rs)"); }
-        eprintln!("R2 trying decrypt enc_s at pos={} len={} cipher_has_key={}", pos, enc_s_len, self.symmetric.cipher.has_key());
-        eprintln!("R2 BEFORE dec_s: nonce_will_be={} key_set={}", self.symmetric.cipher.nonce, self.symmetric.cipher.has_key());
-        eprintln!("R2 hash before decrypt_enc_s: {:02x?}", &self.symmetric.handshake_hash[..4]);
         let rs_bytes = self.symmetric.decrypt_and_hash(&msg[pos..pos+enc_s_len])?;
         let mut rs = [0u8; DHLEN];
         rs.copy_from_slice(&rs_bytes[..DHLEN]);
@@ -456,7 +581,7 @@ impl HandshakeState {
 
     // Читаем сообщение 3 на стороне ответчика
     pub fn read_message_3(&mut self, msg: &[u8]) -> Result<Vec<u8>, &'static str> {
-        assert!(!self.is_initiator && self.message_index == 2);
+        if self.is_initiator || self.message_index != 2 { return Err("unexpected msg3"); }
         let mut pos = 0;
         // rs: расшифровываем static pubkey инициатора
         let enc_s_len = DHLEN + TAG_SIZE;
@@ -521,8 +646,9 @@ impl NoiseSession {
         ct
     }
 
-    pub fn recv(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
-        let pt = self.recv_cipher.decrypt_with_ad(&[], ciphertext)?;
+    pub fn recv(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, FederationError> {
+        let pt = self.recv_cipher.decrypt_with_ad(&[], ciphertext)
+            .map_err(FederationError::Cipher)?;
         self.messages_recv += 1;
         self.bytes_recv += pt.len() as u64;
         Ok(pt)
@@ -551,7 +677,7 @@ impl NoiseHandshaker {
         init_payload: &[u8],
         resp_payload: &[u8],
         final_payload: &[u8],
-    ) -> Result<(NoiseSession, NoiseSession, HandshakeLog), &'static str> {
+    ) -> Result<(NoiseSession, NoiseSession, HandshakeLog), FederationError> {
         let mut log = HandshakeLog::new();
 
         let mut init = HandshakeState::new_initiator(initiator_s_seed, initiator_e_seed);
@@ -564,30 +690,27 @@ impl NoiseHandshaker {
         // Сообщение 1: -> e
         let msg1 = init.write_message_1(init_payload);
         log.msg1_len = msg1.len();
-        eprintln!("DBG msg1 len={} hash_init={:02x?}", msg1.len(), &init.symmetric.handshake_hash[..4]);
-        let rx_payload1 = resp.read_message_1(&msg1).inspect_err(|&e| { eprintln!("DBG msg1 FAIL: {}", e); })?;
-        eprintln!("DBG msg1 OK hash_resp={:02x?}", &resp.symmetric.handshake_hash[..4]);
+        let rx_payload1 = resp.read_message_1(&msg1).inspect_err(|&e| log::debug!("noise: msg1 rejected: {}", e))
+            .map_err(FederationError::Handshake)?;
         log.msg1_payload = rx_payload1;
 
         // Сообщение 2: <- e, ee, s, es
         let msg2 = resp.write_message_2(resp_payload);
         log.msg2_len = msg2.len();
-        eprintln!("DBG msg2 len={} hash_resp={:02x?}", msg2.len(), &resp.symmetric.handshake_hash[..4]);
-        let rx_payload2 = init.read_message_2(&msg2).inspect_err(|&e| { eprintln!("DBG msg2 FAIL: {}", e); })?;
-        eprintln!("DBG msg2 OK hash_init={:02x?}", &init.symmetric.handshake_hash[..4]);
+        let rx_payload2 = init.read_message_2(&msg2).inspect_err(|&e| log::debug!("noise: msg2 rejected: {}", e))
+            .map_err(FederationError::Handshake)?;
         log.msg2_payload = rx_payload2;
 
         // Сообщение 3: -> s, se
         let msg3 = init.write_message_3(final_payload);
         log.msg3_len = msg3.len();
-        eprintln!("DBG msg3 len={} hash_init={:02x?}", msg3.len(), &init.symmetric.handshake_hash[..4]);
-        let rx_payload3 = resp.read_message_3(&msg3).inspect_err(|&e| { eprintln!("DBG msg3 FAIL: {}", e); })?;
-        eprintln!("DBG msg3 OK hash_resp={:02x?}", &resp.symmetric.handshake_hash[..4]);
+        let rx_payload3 = resp.read_message_3(&msg3).inspect_err(|&e| log::debug!("noise: msg3 rejected: {}", e))
+            .map_err(FederationError::Handshake)?;
         log.msg3_payload = rx_payload3;
 
         // Финализация
-        let init_session = init.finalize()?;
-        let resp_session = resp.finalize()?;
+        let init_session = init.finalize().map_err(FederationError::Handshake)?;
+        let resp_session = resp.finalize().map_err(FederationError::Handshake)?;
 
         // Проверяем что handshake_hash совпадает
         log.hashes_match = init_session.handshake_hash == resp_session.handshake_hash;
@@ -601,6 +724,7 @@ impl NoiseHandshaker {
 // HandshakeLog — лог хендшейка для демо
 // -----------------------------------------------------------------------------
 
+#[derive(Debug, Clone)]
 pub struct HandshakeLog {
     pub init_static_pub: [u8; DHLEN],
     pub resp_static_pub: [u8; DHLEN],
@@ -624,6 +748,26 @@ impl HandshakeLog {
             hashes_match: false, handshake_hash: [0u8; HASHLEN],
         }
     }
+
+    /// Независимая проверка записанного XX: длины сообщений должны
+    /// соответствовать токенам при известных payload'ах, хеши сторон
+    /// совпасть, статические ключи — быть настоящими
+    pub fn verify_transcript(&self) -> bool {
+        // -> e             : e открыт, payload без ключа
+        let msg1 = DHLEN + self.msg1_payload.len();
+        // <- e, ee, s, es  : после ee всё шифруется с тегом
+        let msg2 = DHLEN + (DHLEN + TAG_SIZE) + (self.msg2_payload.len() + TAG_SIZE);
+        // -> s, se
+        let msg3 = (DHLEN + TAG_SIZE) + (self.msg3_payload.len() + TAG_SIZE);
+
+        let zero = [0u8; DHLEN];
+        self.msg1_len == msg1 && self.msg2_len == msg2 && self.msg3_len == msg3
+            && self.hashes_match
+            && self.handshake_hash != [0u8; HASHLEN]
+            && self.init_static_pub != zero
+            && self.resp_static_pub != zero
+            && self.init_static_pub != self.resp_static_pub
+    }
 }
 
 // =============================================================================
@@ -662,6 +806,29 @@ mod tests {
         assert!(log.hashes_match);
     }
 
+    #[test]
+    fn test_transcript_verifies() {
+        let (_, _, log) = NoiseHandshaker::perform_xx(1, 2, 3, 4,
+            b"hello", b"node-info", b"done").unwrap();
+        assert!(log.verify_transcript());
+    }
+
+    #[test]
+    fn test_tampered_transcript_fails() {
+        let (_, _, log) = NoiseHandshaker::perform_xx(1, 2, 3, 4, b"", b"payload", b"").unwrap();
+        let mut tampered = log.clone();
+        tampered.msg2_len += 1;
+        assert!(!tampered.verify_transcript());
+
+        let mut anonymous = log.clone();
+        anonymous.resp_static_pub = [0u8; DHLEN];
+        assert!(!anonymous.verify_transcript());
+
+        let mut diverged = log;
+        diverged.hashes_match = false;
+        assert!(!diverged.verify_transcript());
+    }
+
     #[test]
     fn test_transport_bidirectional() {
         let (mut init, mut resp, _) = NoiseHandshaker::perform_xx(5, 6, 7, 8, b"", b"", b"").unwrap();
@@ -694,4 +861,25 @@ mod tests {
         ct[len - 1] ^= 0xFF;
         assert!(resp.recv(&ct).is_err());
     }
+
+    fn hex32(s: &str) -> [u8; DHLEN] {
+        let mut out = [0u8; DHLEN];
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_x25519_rfc7748_vectors() {
+        let a_priv = hex32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let b_priv = hex32("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let a_pub = x25519(&a_priv, &X25519_BASEPOINT);
+        let b_pub = x25519(&b_priv, &X25519_BASEPOINT);
+        assert_eq!(a_pub, hex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+        assert_eq!(b_pub, hex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+        let shared = hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(dh(&a_priv, &b_pub), shared);
+        assert_eq!(dh(&b_priv, &a_pub), shared);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::ethics::{EthicsAction, EthicsEvaluator, EthicsLayer, EthicsVerdict, Frozen, MODULE_ORACLE};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -281,3 +282,150 @@ impl std::fmt::Display for OracleStats {
         )
     }
 }
+
+// -----------------------------------------------------------------------------
+// OracleClient — этическая пре-проверка + кеш ответов с TTL
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub enum OracleResult {
+    Fetched(OracleResponse),
+    Cached(OracleResponse),
+    Refused(EthicsVerdict),
+}
+
+impl OracleResult {
+    pub fn response(&self) -> Option<&OracleResponse> {
+        match self {
+            OracleResult::Fetched(r) | OracleResult::Cached(r) => Some(r),
+            OracleResult::Refused(_) => None,
+        }
+    }
+
+    pub fn is_cache_hit(&self) -> bool { matches!(self, OracleResult::Cached(_)) }
+    pub fn is_refused(&self) -> bool { matches!(self, OracleResult::Refused(_)) }
+}
+
+struct CachedResponse {
+    response: OracleResponse,
+    fetched_at: i64,
+}
+
+pub struct OracleClient {
+    pub node_id: String,
+    pub ethics: EthicsEvaluator,
+    pub ttl_ms: i64,
+    cache: HashMap<String, CachedResponse>,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub refused: u64,
+}
+
+impl OracleClient {
+    pub fn new(node_id: &str) -> Self {
+        Self::with_ttl(node_id, ORACLE_TTL_SECS * 1000)
+    }
+
+    pub fn with_ttl(node_id: &str, ttl_ms: u64) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            ethics: EthicsEvaluator::new(),
+            ttl_ms: ttl_ms as i64,
+            cache: HashMap::new(),
+            cache_hits: 0, cache_misses: 0, refused: 0,
+        }
+    }
+
+    /// Запрос под KillSwitch: замороженный oracle не ходит в сеть и не
+    /// отдаёт кеш
+    pub fn request(&mut self, ethics: &EthicsLayer, url: &str,
+                   categories: &[&str]) -> Result<OracleResult, Frozen> {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+        self.request_at(ethics, url, categories, now)
+    }
+
+    /// То же что `request`, но с явным временем (мс) — для тестов и симуляций.
+    pub fn request_at(&mut self, ethics: &EthicsLayer, url: &str, categories: &[&str],
+                      now_ms: i64) -> Result<OracleResult, Frozen> {
+        ethics.guard(MODULE_ORACLE)?;
+        Ok(self.request_unguarded(url, categories, now_ms))
+    }
+
+    fn request_unguarded(&mut self, url: &str, categories: &[&str], now_ms: i64) -> OracleResult {
+        // Этика проверяется всегда, даже если ответ уже в кеше
+        let verdict = self.ethics.evaluate(&EthicsAction::OracleRequest {
+            target_url: url.to_string(),
+            is_encrypted: url.starts_with("https://"),
+            data_categories: categories.iter().map(|c| c.to_string()).collect(),
+        });
+        if !verdict.allowed {
+            self.refused += 1;
+            return OracleResult::Refused(verdict);
+        }
+
+        if let Some(entry) = self.cache.get(url) {
+            if now_ms - entry.fetched_at < self.ttl_ms {
+                self.cache_hits += 1;
+                return OracleResult::Cached(entry.response.clone());
+            }
+        }
+
+        self.cache_misses += 1;
+        let req_type = OracleRequestType::HttpGet { url: url.to_string(), json_path: String::new() };
+        let req = OracleRequest::new(&self.node_id, req_type.clone(), true);
+        let response = OracleRegistry::simulate_fetch(&req_type, &self.node_id, &req.id);
+        self.cache.insert(url.to_string(), CachedResponse { response: response.clone(), fetched_at: now_ms });
+        OracleResult::Fetched(response)
+    }
+
+    pub fn evict_expired(&mut self, now_ms: i64) -> usize {
+        let before = self.cache.len();
+        let ttl = self.ttl_ms;
+        self.cache.retain(|_, e| now_ms - e.fetched_at < ttl);
+        before - self.cache.len()
+    }
+
+    pub fn cache_size(&self) -> usize { self.cache.len() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_refuses_sensitive_categories() {
+        let mut client = OracleClient::new("node_a");
+        let ethics = EthicsLayer::new();
+        let res = client.request_at(&ethics, "https://api.example/geo",
+                                    &["personal_data", "location"], 0).unwrap();
+        assert!(res.is_refused());
+        assert_eq!(client.refused, 1);
+        assert_eq!(client.cache_size(), 0);
+    }
+
+    #[test]
+    fn test_client_cache_ttl() {
+        let mut client = OracleClient::with_ttl("node_a", 1000);
+        let ethics = EthicsLayer::new();
+        let url = "https://api.example/price";
+        let mut fetch = |now| client.request_at(&ethics, url, &["price"], now).unwrap();
+        assert!(matches!(fetch(0), OracleResult::Fetched(_)));
+        assert!(fetch(500).is_cache_hit());
+        assert!(matches!(fetch(1500), OracleResult::Fetched(_)));
+        assert_eq!((client.cache_hits, client.cache_misses), (1, 2));
+    }
+
+    #[test]
+    fn test_frozen_client_skips_cache() {
+        let mut client = OracleClient::with_ttl("node_a", 1000);
+        let mut ethics = EthicsLayer::new();
+        let url = "https://api.example/price";
+        client.request_at(&ethics, url, &["price"], 0).unwrap();
+
+        ethics.kill_switch.sovereign_freeze("DAO-3", "audit", vec![MODULE_ORACLE.into()]);
+        let err = client.request_at(&ethics, url, &["price"], 100).unwrap_err();
+        assert_eq!(err.module, MODULE_ORACLE);
+        assert_eq!((client.cache_hits, client.cache_misses), (0, 1));
+    }
+}
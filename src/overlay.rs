@@ -9,12 +9,15 @@
 //   3. NodeDiscovery    — обнаружение и обмен списком узлов
 //   4. OverlayRouter    — маршрутизация через весь стек (ZKP + DAG + Mirage)
 //   5. FederationMVP    — главный объект: запускает всё вместе
+//   6. NeighborSelector — выбор соседей по доверию и задержке
 // =============================================================================
 
 use crate::dag::FederationDag;
+use crate::ethics::EthicsLayer;
 use crate::mirage::MirageNode;
 use crate::network::NodeInfo;
 use crate::p2p::{FederationNode, NodeConfig};
+use crate::reputation::TrustGraph;
 use crate::routing::{AiRouter, UserPriorities, build_route_candidates};
 use crate::tensor::{SsauTensor, TrustRegistry};
 use crate::zkp::{OnionBuilder, NullifierSet};
@@ -40,6 +43,9 @@ pub const ROUTE_AUDIT_INTERVAL_SECS: u64 = 30;
 /// Максимальное число seed-узлов
 pub const MAX_SEED_NODES: usize = 8;
 
+/// Минимальное транзитивное доверие для узла-якоря среди соседей
+pub const ANCHOR_TRUST_THRESHOLD: f64 = 0.6;
+
 // -----------------------------------------------------------------------------
 // SeedNode — известный узел для bootstrap
 // -----------------------------------------------------------------------------
@@ -179,6 +185,8 @@ pub struct FederationMVP {
     pub dag: Arc<Mutex<FederationDag>>,
     /// AI маршрутизатор
     pub router: Arc<Mutex<AiRouter>>,
+    /// Этический слой (KillSwitch для маршрутизатора)
+    pub ethics: Arc<RwLock<EthicsLayer>>,
     /// Trust Registry
     pub trust: Arc<RwLock<TrustRegistry>>,
     /// Mirage модуль
@@ -204,6 +212,7 @@ impl FederationMVP {
             node,
             dag: Arc::new(Mutex::new(FederationDag::new())),
             router: Arc::new(Mutex::new(AiRouter::new())),
+            ethics: Arc::new(RwLock::new(EthicsLayer::new())),
             trust: Arc::new(RwLock::new(TrustRegistry::new())),
             mirage: Arc::new(Mutex::new(MirageNode::new(node_id))),
             nullifiers: Arc::new(Mutex::new(NullifierSet::new())),
@@ -340,8 +349,15 @@ impl FederationMVP {
             return None;
         }
 
+        let ethics = self.ethics.read().await;
         let mut router = self.router.lock().await;
-        let decision = router.select_route(destination, candidates, &priorities);
+        let decision = match router.select_route(&ethics, destination, candidates, &priorities) {
+            Ok(d) => d,
+            Err(e) => {
+                log::warn!("{}", e);
+                return None;
+            }
+        };
         *self.routes_computed.lock().await += 1;
 
         decision.chosen_route.map(|r| r.path)
@@ -434,3 +450,105 @@ impl FederationMVP {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// NeighborSelector — взвешенный выбор соседей оверлея
+// -----------------------------------------------------------------------------
+
+/// Оценивает кандидатов смесью транзитивного доверия (TrustGraph) и
+/// измеренной задержки. Среди выбранных всегда есть хотя бы один якорь
+/// с доверием >= ANCHOR_TRUST_THRESHOLD (если такой кандидат вообще есть).
+#[derive(Debug, Clone)]
+pub struct NeighborSelector {
+    pub node_id: String,
+    pub trust_weight: f64,
+    pub latency_weight: f64,
+}
+
+impl NeighborSelector {
+    pub fn new(node_id: &str) -> Self {
+        NeighborSelector { node_id: node_id.to_string(), trust_weight: 0.6, latency_weight: 0.4 }
+    }
+
+    pub fn with_blend(node_id: &str, trust_weight: f64, latency_weight: f64) -> Self {
+        NeighborSelector { node_id: node_id.to_string(), trust_weight, latency_weight }
+    }
+
+    /// Скоры всех кандидатов из `latency_map` (peer → ms), по убыванию.
+    pub fn score_candidates(
+        &self,
+        trust_graph: &TrustGraph,
+        latency_map: &HashMap<String, f64>,
+    ) -> Vec<(String, f64, f64)> {
+        let total = (self.trust_weight + self.latency_weight).max(1e-9);
+        let max_latency = latency_map.values().cloned().fold(0.0_f64, f64::max).max(1.0);
+        let mut scored: Vec<(String, f64, f64)> = latency_map.iter()
+            .filter(|(peer, _)| peer.as_str() != self.node_id)
+            .map(|(peer, &lat)| {
+                let trust = trust_graph.transitive_trust(&self.node_id, peer);
+                let closeness = 1.0 - (lat.max(0.0) / max_latency).min(1.0);
+                let score = (self.trust_weight * trust + self.latency_weight * closeness) / total;
+                (peer.clone(), score, trust)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0)));
+        scored
+    }
+
+    pub fn select_neighbors(
+        &self,
+        k: usize,
+        trust_graph: &TrustGraph,
+        latency_map: &HashMap<String, f64>,
+    ) -> Vec<String> {
+        if k == 0 { return vec![]; }
+        let scored = self.score_candidates(trust_graph, latency_map);
+        let mut chosen: Vec<&(String, f64, f64)> = scored.iter().take(k).collect();
+
+        let has_anchor = chosen.iter().any(|c| c.2 >= ANCHOR_TRUST_THRESHOLD);
+        if !has_anchor {
+            let anchor = scored.iter().skip(k)
+                .filter(|c| c.2 >= ANCHOR_TRUST_THRESHOLD)
+                .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+            if let Some(anchor) = anchor {
+                chosen.pop();
+                chosen.push(anchor);
+            }
+        }
+        chosen.into_iter().map(|c| c.0.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> (TrustGraph, HashMap<String, f64>) {
+        let mut graph = TrustGraph::new();
+        graph.add_edge("me", "far_trusted", 0.95);
+        graph.add_edge("me", "close_untrusted", 0.30);
+        let mut latency = HashMap::new();
+        latency.insert("far_trusted".to_string(), 300.0);
+        latency.insert("close_untrusted".to_string(), 10.0);
+        (graph, latency)
+    }
+
+    #[test]
+    fn test_blend_decides_neighbor_order() {
+        let (graph, latency) = fixture();
+        let trust_heavy = NeighborSelector::with_blend("me", 0.8, 0.2);
+        assert_eq!(trust_heavy.select_neighbors(2, &graph, &latency),
+            vec!["far_trusted", "close_untrusted"]);
+        let latency_heavy = NeighborSelector::with_blend("me", 0.2, 0.8);
+        assert_eq!(latency_heavy.select_neighbors(2, &graph, &latency),
+            vec!["close_untrusted", "far_trusted"]);
+    }
+
+    #[test]
+    fn test_high_trust_anchor_guaranteed() {
+        let (graph, latency) = fixture();
+        let latency_heavy = NeighborSelector::with_blend("me", 0.2, 0.8);
+        assert_eq!(latency_heavy.select_neighbors(1, &graph, &latency), vec!["far_trusted"]);
+    }
+}
@@ -10,9 +10,12 @@
 //   Education Pool — аренда Sentinel для обучения Mobile нейросетей
 //
 // Пополнение: TREASURY_RATE от каждого mint.rs события
+//             + страховые взносы узлов за каждый прорыв (collect_premium)
 // Управление: DAO голосование для выплат выше LARGE_PAYOUT_THRESHOLD
 // =============================================================================
 
+use crate::credits::CreditLedger;
+use crate::reputation::{ReputationRegistry, SlashResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -24,6 +27,10 @@ pub const HEALTH_UPGRADE_MIN: f64     = 50.0; // минимум для апгр
 pub const EDUCATION_HOUR_RATE: f64    = 5.0;  // credits/час аренды Sentinel
 pub const LARGE_PAYOUT_THRESHOLD: f64 = 500.0;// выше — нужно DAO
 pub const MAX_INSURANCE_PER_EVENT: f64= 200.0;// потолок выплаты
+pub const INSURANCE_PREMIUM_BASE: f64 = 0.05; // взнос за прорыв в простом регионе
+pub const PREMIUM_DIFFICULTY_SCALE: f64 = 3.0;// сложный регион — выше риск и взнос
+pub const INSURANCE_FRAUD_SLASH: f64  = 5.0;  // штраф репутации за ложную заявку
+pub const INSURANCE_POOL_ACCOUNT: &str = "INSURANCE_POOL"; // счёт страхового пула в CreditLedger
 
 // -----------------------------------------------------------------------------
 // PoolType — тип пула
@@ -207,6 +214,7 @@ pub struct SwarmTreasury {
     pub health_requests: Vec<HealthRequest>,
     pub education_sessions: Vec<EducationSession>,
     pub node_insurance_history: HashMap<String, Vec<u64>>, // node → claim_ids
+    pub node_premiums: HashMap<String, f64>,               // node → взносы
     pub counter: u64,
 }
 
@@ -220,6 +228,7 @@ impl SwarmTreasury {
             health_requests: vec![],
             education_sessions: vec![],
             node_insurance_history: HashMap::new(),
+            node_premiums: HashMap::new(),
             counter: 0,
         }
     }
@@ -237,14 +246,28 @@ impl SwarmTreasury {
         self.education.deposit(mint_amount * EDUCATION_RATE);
     }
 
+    /// Страховой взнос узла за прорыв — растёт со сложностью региона.
+    /// Списывается со счёта узла; без средств пул не пополняется
+    pub fn collect_premium(&mut self, node_id: &str, region_difficulty: f64,
+                           ledger: &mut CreditLedger) -> Result<f64, &'static str> {
+        let premium = INSURANCE_PREMIUM_BASE
+            * (1.0 + region_difficulty.clamp(0.0, 1.0) * PREMIUM_DIFFICULTY_SCALE);
+        ledger.transfer(node_id, INSURANCE_POOL_ACCOUNT, premium)?;
+        self.insurance.deposit(premium);
+        *self.node_premiums.entry(node_id.to_string()).or_insert(0.0) += premium;
+        Ok(premium)
+    }
+
     /// Подать заявку на страховку
     pub fn file_insurance_claim(&mut self, node_id: &str,
         reason: InsuranceReason, streak_lost: u32,
         credits_lost: f64) -> InsuranceClaim {
 
         self.counter += 1;
+        // Лимит выплаты сжимается вместе с платёжеспособностью пула
+        let solvency = self.insurance.solvency_ratio().clamp(0.0, 1.0);
         let payout = InsuranceClaim::compute_payout(
-            streak_lost, credits_lost, self.insurance.balance);
+            streak_lost, credits_lost, self.insurance.balance) * solvency;
 
         // Этическое нарушение — отказ
         let (approved, status) = if matches!(reason, InsuranceReason::EthicsViolation) {
@@ -255,18 +278,22 @@ impl SwarmTreasury {
             (payout, ClaimStatus::Approved)
         };
 
+        // Одобренная выплата, на которую не хватило баланса, ждёт пополнения
+        let (approved, status) = if status == ClaimStatus::Approved
+            && !self.insurance.withdraw(approved) {
+            (0.0, ClaimStatus::Pending)
+        } else {
+            (approved, status)
+        };
+
         let claim = InsuranceClaim {
             claim_id: self.counter,
             node_id: node_id.to_string(),
             reason, streak_lost, credits_lost,
             requested: payout, approved,
-            status: status.clone(), timestamp: Self::now(),
+            status, timestamp: Self::now(),
         };
 
-        if status == ClaimStatus::Approved {
-            self.insurance.withdraw(approved);
-        }
-
         self.node_insurance_history
             .entry(node_id.to_string()).or_default()
             .push(self.counter);
@@ -274,7 +301,22 @@ impl SwarmTreasury {
         claim
     }
 
-    /// Запрос на апгрейд железа
+    /// Заявка оказалась мошеннической: статус Rejected, узлу — slash.
+    /// Уже выплаченное не возвращается — пулы не держат счетов узлов
+    pub fn reject_fraudulent_claim(&mut self, claim_id: u64,
+        reputations: &mut ReputationRegistry) -> Result<SlashResult, &'static str> {
+        let claim = self.insurance_claims.iter_mut()
+            .find(|c| c.claim_id == claim_id).ok_or("Неизвестная заявка")?;
+        if claim.status == ClaimStatus::Rejected {
+            return Err("Заявка уже отклонена");
+        }
+        claim.status = ClaimStatus::Rejected;
+        Ok(reputations.slash(&claim.node_id, INSURANCE_FRAUD_SLASH,
+            &format!("pools: страховое мошенничество, claim {}", claim_id)))
+    }
+
+    /// Запрос на апгрейд железа — допустимые заявки (не более 20% пула)
+    /// встают в очередь и финансируются в process_health_queue по убыванию ROI
     pub fn request_health_upgrade(&mut self, node_id: &str,
         component: &str, description: &str, cost: f64,
         score_before: f64, score_after: f64) -> HealthRequest {
@@ -287,13 +329,9 @@ impl SwarmTreasury {
         } else if cost > LARGE_PAYOUT_THRESHOLD {
             (cost, ClaimStatus::RequiresDao)
         } else {
-            (cost, ClaimStatus::Approved)
+            (0.0, ClaimStatus::Pending)
         };
 
-        if status == ClaimStatus::Approved {
-            self.health.withdraw(approved);
-        }
-
         let req = HealthRequest {
             request_id: self.counter,
             node_id: node_id.to_string(),
@@ -308,6 +346,41 @@ impl SwarmTreasury {
         req
     }
 
+    /// Профинансировать очередь апгрейдов: сначала самый высокий ROI,
+    /// пока заявка укладывается в 20% текущего баланса. Остальные остаются
+    /// в листе ожидания (Pending)
+    pub fn process_health_queue(&mut self) -> Vec<HealthRequest> {
+        let mut queue: Vec<usize> = self.health_requests.iter().enumerate()
+            .filter(|(_, r)| r.status == ClaimStatus::Pending)
+            .map(|(i, _)| i).collect();
+        queue.sort_by(|&a, &b| {
+            let (ra, rb) = (&self.health_requests[a], &self.health_requests[b]);
+            rb.roi().partial_cmp(&ra.roi()).unwrap_or(std::cmp::Ordering::Equal)
+                .then(ra.request_id.cmp(&rb.request_id))
+        });
+
+        let mut funded = vec![];
+        for i in queue {
+            let cost = self.health_requests[i].cost_estimate;
+            if cost <= self.health.balance * 0.2 && self.health.withdraw(cost) {
+                let req = &mut self.health_requests[i];
+                req.approved_amount = cost;
+                req.status = ClaimStatus::Approved;
+                funded.push(req.clone());
+            }
+        }
+        funded
+    }
+
+    /// Лист ожидания Health пула, по убыванию ROI
+    pub fn health_waitlist(&self) -> Vec<&HealthRequest> {
+        let mut w: Vec<&HealthRequest> = self.health_requests.iter()
+            .filter(|r| r.status == ClaimStatus::Pending).collect();
+        w.sort_by(|a, b| b.roi().partial_cmp(&a.roi())
+            .unwrap_or(std::cmp::Ordering::Equal));
+        w
+    }
+
     /// Запись образовательной сессии
     pub fn schedule_education(&mut self, student: &str,
         sentinel: &str, hours: f64, modules: Vec<String>,
@@ -405,3 +478,111 @@ impl std::fmt::Display for TreasuryStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(region: &str) -> InsuranceReason {
+        InsuranceReason::CensorBlock { region: region.into(), block_rate: 0.9 }
+    }
+
+    #[test]
+    fn test_premium_scales_with_difficulty() {
+        let mut t = SwarmTreasury::new();
+        let mut ledger = CreditLedger::new();
+        ledger.balances.insert("de_node".into(), 1.0);
+        ledger.balances.insert("kp_node".into(), 1.0);
+        let easy = t.collect_premium("de_node", 0.05, &mut ledger).unwrap();
+        let hard = t.collect_premium("kp_node", 0.99, &mut ledger).unwrap();
+        assert!(hard > easy * 3.0);
+        assert!((t.insurance.balance - (easy + hard)).abs() < 1e-12);
+        assert!((t.node_premiums["kp_node"] - hard).abs() < 1e-12);
+        // Взнос списан с узла и лежит на счёте пула
+        assert!((ledger.balance("kp_node") - (1.0 - hard)).abs() < 1e-12);
+        assert!((ledger.balance(INSURANCE_POOL_ACCOUNT) - (easy + hard)).abs() < 1e-12);
+
+        // Нечем платить — ни пул, ни история взносов не растут
+        assert!(t.collect_premium("broke_node", 0.5, &mut ledger).is_err());
+        assert!((t.insurance.balance - (easy + hard)).abs() < 1e-12);
+        assert!(!t.node_premiums.contains_key("broke_node"));
+    }
+
+    #[test]
+    fn test_premiums_keep_pool_solvent_through_claim_wave() {
+        let mut t = SwarmTreasury::new();
+        t.deposit_from_mint(250.0);
+        let mut ledger = CreditLedger::new();
+        for i in 0..50 { ledger.balances.insert(format!("node_{}", i), 100.0); }
+        for i in 0..5_000 {
+            let node = format!("node_{}", i % 50);
+            t.collect_premium(&node, (i % 10) as f64 / 10.0, &mut ledger).unwrap();
+        }
+        let funded = t.insurance.total_received;
+
+        for i in 0..200 {
+            t.file_insurance_claim(&format!("node_{}", i % 50),
+                block("CN"), 20, 150.0);
+        }
+
+        assert!(t.insurance.balance > 0.0);
+        assert!(t.insurance.solvency_ratio() > 0.0);
+        assert!(t.insurance.total_paid < funded);
+        // Каждая одобренная выплата реально оплачена
+        let approved: f64 = t.insurance_claims.iter()
+            .filter(|c| c.status == ClaimStatus::Approved)
+            .map(|c| c.approved).sum();
+        assert!((approved - t.insurance.total_paid).abs() < 1e-9);
+        // Поздние заявки получают меньше — лимит следует за платёжеспособностью
+        let first = t.insurance_claims.first().unwrap().approved;
+        let last = t.insurance_claims.last().unwrap().approved;
+        assert!(last < first);
+    }
+
+    #[test]
+    fn test_health_queue_funds_highest_roi_first() {
+        let mut t = SwarmTreasury::new();
+        t.health.deposit(800.0);
+        let low = t.request_health_upgrade("ghost-pi3", "RAM", "low roi",
+            150.0, 10.0, 12.0);
+        let high = t.request_health_upgrade("work-bob", "SSD", "high roi",
+            150.0, 10.0, 30.0);
+        assert_eq!(low.status, ClaimStatus::Pending);
+        assert!(high.roi() > low.roi());
+        // Больше 20% пула — отказ сразу, в очередь не попадает
+        let greedy = t.request_health_upgrade("node-x", "GPU", "too big",
+            161.0, 10.0, 90.0);
+        assert_eq!(greedy.status, ClaimStatus::Rejected);
+
+        // После первой выплаты 150 > 20% от 650 — вторая ждёт
+        let funded = t.process_health_queue();
+        assert_eq!(funded.len(), 1);
+        assert_eq!(funded[0].request_id, high.request_id);
+        assert!((t.health.balance - 650.0).abs() < 1e-12);
+
+        let waitlist = t.health_waitlist();
+        assert_eq!(waitlist.len(), 1);
+        assert_eq!(waitlist[0].request_id, low.request_id);
+
+        // Пополнение пула — лист ожидания финансируется следующим проходом
+        t.health.deposit(100.0);
+        assert_eq!(t.process_health_queue()[0].request_id, low.request_id);
+        assert!(t.health_waitlist().is_empty());
+    }
+
+    #[test]
+    fn test_fraudulent_claim_is_rejected_and_slashed() {
+        let mut t = SwarmTreasury::new();
+        t.deposit_from_mint(1000.0);
+        let mut reps = ReputationRegistry::new();
+        for _ in 0..100 { reps.record_delivery("liar", "AikiReflection", 0.0); }
+        let before = reps.nodes["liar"].score;
+
+        let claim = t.file_insurance_claim("liar", block("CN"), 5, 40.0);
+        let r = t.reject_fraudulent_claim(claim.claim_id, &mut reps).unwrap();
+        assert!((before - r.score_after - INSURANCE_FRAUD_SLASH).abs() < 1e-9);
+        assert_eq!(t.insurance_claims[0].status, ClaimStatus::Rejected);
+        assert!(t.reject_fraudulent_claim(claim.claim_id, &mut reps).is_err());
+        assert!(t.reject_fraudulent_claim(999, &mut reps).is_err());
+    }
+}
@@ -3,6 +3,8 @@
 // PHASE 7 / STEP 11 — «Idea Laboratory — Human-AI Co-evolution»
 // =============================================================================
 
+use crate::credits::known_regions;
+use crate::noise::{hmac, HASHLEN};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -10,6 +12,7 @@ pub const SIM_ROUNDS: u32             = 1000;
 pub const MIN_BYPASS_IMPROVEMENT: f64 = 0.05;
 pub const MAX_ETHICS_DEGRADATION: f64 = 0.10;
 pub const AI_CONFIDENCE_THRESHOLD: f64= 0.75;
+pub const REGION_SPILLOVER: f64       = 0.30; // доля эффекта вне целевых регионов
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProposalDomain {
@@ -105,10 +108,47 @@ impl AiSimResult {
     }
 }
 
-pub struct AiSimulator { rng: u64 }
+pub struct AiSimulator { rng: u64, region_difficulty: HashMap<String, f64> }
 
 impl AiSimulator {
-    pub fn new() -> Self { AiSimulator { rng: 0xA150_F33D_CA7E_0000 } }
+    pub fn new() -> Self {
+        let region_difficulty = known_regions().into_iter()
+            .map(|(code, r)| (code, r.difficulty_score)).collect();
+        AiSimulator { rng: 0xA150_F33D_CA7E_0000, region_difficulty }
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut sim = Self::new();
+        sim.reseed(seed, 0);
+        sim
+    }
+
+    /// Детерминированное состояние для пары (seed, предложение):
+    /// отчёт не зависит от порядка симуляций в лаборатории
+    pub fn reseed(&mut self, seed: u64, proposal_id: u64) {
+        let mut z = seed ^ proposal_id.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        self.rng = if z == 0 { 0xA150_F33D_CA7E_0000 } else { z };
+    }
+
+    /// Вес сценария: сложность региона × log(население сети).
+    /// Помощь KP весит больше, чем помощь DE, даже при меньшем числе узлов
+    pub fn scenario_weight(&self, s: &SimScenario) -> f64 {
+        let difficulty = self.region_difficulty.get(&s.region)
+            .copied().unwrap_or(s.censor_strength);
+        difficulty * (1.0 + s.node_count as f64).ln()
+    }
+
+    /// Предложение с региональными тегами действует в полную силу
+    /// только в своих регионах. Без таких тегов — везде одинаково
+    fn targeting(p: &HumanProposal, s: &SimScenario,
+                 suite: &[SimScenario]) -> f64 {
+        let targeted = p.tags.iter()
+            .any(|t| suite.iter().any(|sc| &sc.region == t));
+        if !targeted || p.tags.contains(&s.region) { 1.0 } else { REGION_SPILLOVER }
+    }
 
     fn rand(&mut self) -> f64 {
         self.rng ^= self.rng << 13;
@@ -140,11 +180,13 @@ impl AiSimulator {
         }
     }
 
-    fn sim_scenario(&mut self, p: &HumanProposal, s: &SimScenario) -> AiSimResult {
+    fn sim_scenario(&mut self, p: &HumanProposal, s: &SimScenario,
+                    focus: f64) -> AiSimResult {
         let (mut bb,mut ba,mut eb,mut ea,mut rs) = (0.0,0.0,0.0,0.0,0.0);
         for _ in 0..SIM_ROUNDS {
             let n = (self.rand()-0.5)*0.1;
             let (be,ee,r) = self.effect(p,s);
+            let (be,ee,r) = (be*focus, ee*focus, r*focus);
             bb += s.current_bypass_rate + n;
             ba += (s.current_bypass_rate + be + n).clamp(0.0,1.0);
             let base_eth = 0.85 - s.threat_level*0.2;
@@ -170,21 +212,38 @@ impl AiSimulator {
     pub fn run(&mut self, p: &HumanProposal) -> FullSimReport {
         let scenarios = SimScenario::standard_suite();
         let results: Vec<AiSimResult> = scenarios.iter()
-            .map(|s| self.sim_scenario(p,s)).collect();
+            .map(|s| {
+                let focus = Self::targeting(p, s, &scenarios);
+                self.sim_scenario(p, s, focus)
+            }).collect();
         let n = results.len() as f64;
-        let avg_bd = results.iter().map(|r| r.bypass_delta).sum::<f64>() / n;
-        let avg_ed = results.iter().map(|r| r.ethics_delta).sum::<f64>() / n;
-        let avg_r  = results.iter().map(|r| r.risk_score).sum::<f64>() / n;
+
+        // Нормированные веса регионов
+        let raw: Vec<f64> = scenarios.iter().map(|s| self.scenario_weight(s)).collect();
+        let total_w: f64 = raw.iter().sum();
+        let weights: Vec<f64> = if total_w > 0.0 {
+            raw.iter().map(|w| w / total_w).collect()
+        } else { vec![1.0 / n; raw.len()] };
+        let weighted = |f: &dyn Fn(&AiSimResult) -> f64| -> f64 {
+            results.iter().zip(&weights).map(|(r, w)| f(r) * w).sum()
+        };
+        let avg_bd = weighted(&|r| r.bypass_delta);
+        let avg_ed = weighted(&|r| r.ethics_delta);
+        let avg_r  = weighted(&|r| r.risk_score);
         let avg_c  = results.iter().map(|r| r.confidence).sum::<f64>() / n;
+        let scenario_weights: Vec<(String, f64)> = scenarios.iter().zip(&weights)
+            .map(|(s, w)| (s.region.clone(), *w)).collect();
         let bcount = results.iter().filter(|r| r.is_beneficial()).count();
         let verdict = if bcount >= 4 && avg_bd >= MIN_BYPASS_IMPROVEMENT
                          && avg_ed >= -MAX_ETHICS_DEGRADATION { AiVerdict::Recommend }
                       else if bcount >= 2 { AiVerdict::ConditionalApprove }
                       else if avg_r > 0.7 { AiVerdict::Reject }
                       else                { AiVerdict::NeedsRevision };
-        let notes = results.iter().flat_map(|r| r.notes.clone()).collect();
+        let mut notes = vec![format!("Веса регионов: {}", scenario_weights.iter()
+            .map(|(r, w)| format!("{}={:.2}", r, w)).collect::<Vec<_>>().join(" "))];
+        notes.extend(results.iter().flat_map(|r| r.notes.clone()));
         FullSimReport { proposal_id:p.id, domain:p.domain.clone(),
-            scenario_results:results, avg_bypass_delta:avg_bd,
+            scenario_results:results, scenario_weights, avg_bypass_delta:avg_bd,
             avg_ethics_delta:avg_ed, avg_risk:avg_r, avg_confidence:avg_c,
             beneficial_scenarios:bcount, total_scenarios:5,
             ai_recommendation:verdict, notes,
@@ -200,6 +259,15 @@ pub enum AiVerdict {
 }
 
 impl AiVerdict {
+    fn code(&self) -> u8 {
+        match self {
+            AiVerdict::Recommend          => 0,
+            AiVerdict::ConditionalApprove => 1,
+            AiVerdict::NeedsRevision      => 2,
+            AiVerdict::Reject             => 3,
+        }
+    }
+
     pub fn icon(&self) -> &str {
         match self {
             AiVerdict::Recommend          => "✅ РЕКОМЕНДУЕТ",
@@ -215,6 +283,7 @@ pub struct FullSimReport {
     pub proposal_id: u64,
     pub domain: ProposalDomain,
     pub scenario_results: Vec<AiSimResult>,
+    pub scenario_weights: Vec<(String, f64)>, // регион → нормированный вес
     pub avg_bypass_delta: f64,
     pub avg_ethics_delta: f64,
     pub avg_risk: f64,
@@ -226,17 +295,65 @@ pub struct FullSimReport {
     pub rounds_simulated: u32,
 }
 
+impl FullSimReport {
+    /// Каноническое представление числовых полей отчёта для MAC
+    fn seal_bytes(&self) -> Vec<u8> {
+        let mut b = Vec::with_capacity(64 + self.scenario_results.len() * 72);
+        b.extend_from_slice(b"idealab-report-v1");
+        b.extend_from_slice(&self.proposal_id.to_le_bytes());
+        for v in [self.avg_bypass_delta, self.avg_ethics_delta,
+                  self.avg_risk, self.avg_confidence] {
+            b.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        b.extend_from_slice(&(self.beneficial_scenarios as u64).to_le_bytes());
+        b.extend_from_slice(&(self.total_scenarios as u64).to_le_bytes());
+        b.extend_from_slice(&self.rounds_simulated.to_le_bytes());
+        b.push(self.ai_recommendation.code());
+        for r in &self.scenario_results {
+            for v in [r.bypass_before, r.bypass_after, r.bypass_delta,
+                      r.ethics_before, r.ethics_after, r.ethics_delta,
+                      r.risk_score, r.confidence] {
+                b.extend_from_slice(&v.to_bits().to_le_bytes());
+            }
+        }
+        for (_, w) in &self.scenario_weights {
+            b.extend_from_slice(&w.to_bits().to_le_bytes());
+        }
+        b
+    }
+
+    /// Печать отчёта: MAC по всем числовым полям и вердикту ИИ
+    pub fn seal(&self, key: &[u8; 32]) -> [u8; HASHLEN] {
+        hmac(key, &self.seal_bytes())
+    }
+
+    /// Проверить, что отчёт не менялся после запечатывания
+    pub fn verify_seal(&self, key: &[u8; 32], seal: &[u8; HASHLEN]) -> bool {
+        let expected = self.seal(key);
+        // Сравнение без раннего выхода
+        expected.iter().zip(seal.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
 pub struct IdeaLab {
     pub proposals: Vec<HumanProposal>,
     pub reports: HashMap<u64, FullSimReport>,
     pub simulator: AiSimulator,
+    pub seed: Option<u64>,
     counter: u64,
 }
 
 impl IdeaLab {
     pub fn new() -> Self {
         IdeaLab { proposals:vec![], reports:HashMap::new(),
-            simulator:AiSimulator::new(), counter:0 }
+            simulator:AiSimulator::new(), seed:None, counter:0 }
+    }
+
+    /// Воспроизводимая лаборатория: одно предложение + один seed =
+    /// побайтно одинаковый отчёт
+    pub fn with_seed(seed: u64) -> Self {
+        IdeaLab { simulator: AiSimulator::with_seed(seed), seed: Some(seed),
+            ..Self::new() }
     }
 
     pub fn submit(&mut self, mut p: HumanProposal) -> u64 {
@@ -249,11 +366,17 @@ impl IdeaLab {
 
     pub fn simulate(&mut self, id: u64) -> Option<&FullSimReport> {
         let p = self.proposals.iter().find(|p| p.id == id)?.clone();
+        if let Some(seed) = self.seed { self.simulator.reseed(seed, id); }
         let r = self.simulator.run(&p);
         self.reports.insert(id, r);
         self.reports.get(&id)
     }
 
+    /// Проверка вердикта ИИ перед голосованием DAO
+    pub fn verify_report(&self, id: u64, key: &[u8; 32], seal: &[u8; HASHLEN]) -> bool {
+        self.reports.get(&id).is_some_and(|r| r.verify_seal(key, seal))
+    }
+
     pub fn leaderboard(&self) -> Vec<(u64, &str, f64, &AiVerdict)> {
         let mut v: Vec<_> = self.reports.iter().filter_map(|(id,r)| {
             let p = self.proposals.iter().find(|p| p.id == *id)?;
@@ -265,3 +388,62 @@ impl IdeaLab {
 }
 
 impl Default for IdeaLab { fn default() -> Self { Self::new() } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tactic_proposal() -> HumanProposal {
+        HumanProposal::new(0, "alice", 120.0, ProposalDomain::TacticMutation,
+            "Aiki v2", "усиленное отражение").with_param("intensity", 0.8)
+    }
+
+    #[test]
+    fn test_sealed_report_detects_tampering() {
+        let key = [7u8; 32];
+        let mut lab = IdeaLab::new();
+        let id = lab.submit(tactic_proposal());
+        let seal = lab.simulate(id).unwrap().seal(&key);
+        assert!(lab.verify_report(id, &key, &seal));
+        assert!(!lab.verify_report(id, &[8u8; 32], &seal));
+
+        lab.reports.get_mut(&id).unwrap().avg_bypass_delta += 0.01;
+        assert!(!lab.verify_report(id, &key, &seal));
+    }
+
+    #[test]
+    fn test_seeded_labs_reproduce_verdict() {
+        let run = |seed: u64| {
+            let mut lab = IdeaLab::with_seed(seed);
+            let id = lab.submit(tactic_proposal().with_tag("KP"));
+            let r = lab.simulate(id).unwrap();
+            (r.avg_bypass_delta, r.avg_risk, r.ai_recommendation.clone(),
+             serde_json::to_vec(r).unwrap())
+        };
+        let a = run(42);
+        let b = run(42);
+        assert_eq!(a.0.to_bits(), b.0.to_bits());
+        assert_eq!(a.1.to_bits(), b.1.to_bits());
+        assert_eq!(a.2, b.2);
+        assert_eq!(a.3, b.3);
+        assert_ne!(a.0.to_bits(), run(7).0.to_bits());
+    }
+
+    #[test]
+    fn test_high_difficulty_regions_weigh_more() {
+        let score = |region: &str| {
+            let mut lab = IdeaLab::new();
+            let id = lab.submit(tactic_proposal().with_tag(region));
+            let r = lab.simulate(id).unwrap();
+            assert!(r.notes[0].starts_with("Веса регионов"));
+            r.avg_bypass_delta
+        };
+        assert!(score("KP") > score("DE"));
+
+        let lab = AiSimulator::new();
+        let suite = SimScenario::standard_suite();
+        let kp = suite.iter().find(|s| s.region == "KP").unwrap();
+        let de = suite.iter().find(|s| s.region == "DE").unwrap();
+        assert!(lab.scenario_weight(kp) > lab.scenario_weight(de));
+    }
+}
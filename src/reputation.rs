@@ -7,6 +7,8 @@
 // Формула накопления:
 //   rep += delivery_weight * region_difficulty * tactic_multiplier
 //   rep -= betrayal_penalty (необратимо при помощи цензору)
+//   rep -= slash(amount, reason) — общий штраф рынка, пулов и этики;
+//          накопленные штрафы эскалируют в предательство
 //
 // Уровни: Ghost → Newcomer → Reliable → Trusted → Veteran → Legend
 // DAO вес = reputation_score ^ DAO_WEIGHT_EXPONENT
@@ -21,6 +23,9 @@ pub const REP_STRIKE_MULT: f64      = 2.0;   // множитель CumulativeStr
 pub const REP_DECOY_MULT: f64       = 1.5;   // множитель StandoffDecoy
 pub const REP_BETRAYAL_SLASH: f64   = 0.50;  // -50% репутации за предательство
 pub const REP_FAILURE_DECAY: f64    = 0.02;  // -2% за каждый провал
+pub const REP_SLASH_ESCALATION: f64 = 10.0;  // каждые 10 очков штрафов — предательство
+pub const REP_REGION_SCALE: f64     = 3.0;   // KP (1.0) даёт ×4 к награде
+pub const REP_DEFAULT_DIFFICULTY: f64 = 0.0; // регион не указан — без бонуса
 pub const DAO_WEIGHT_EXPONENT: f64  = 0.7;   // сглаживание для DAO
 pub const LEGEND_THRESHOLD: f64     = 500.0;
 pub const VETERAN_THRESHOLD: f64    = 100.0;
@@ -32,7 +37,8 @@ pub const NEWCOMER_THRESHOLD: f64   = 1.0;
 // ReputationTier — уровень репутации
 // -----------------------------------------------------------------------------
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+// Порядок вариантов = порядок уровней (Ghost < ... < Legend)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ReputationTier {
     Ghost,      // < 1.0   — неизвестный
     Newcomer,   // 1-10    — новичок
@@ -43,15 +49,6 @@ pub enum ReputationTier {
 }
 
 impl ReputationTier {
-    pub fn from_score(score: f64) -> Self {
-        if score >= LEGEND_THRESHOLD  { ReputationTier::Legend   }
-        else if score >= VETERAN_THRESHOLD  { ReputationTier::Veteran  }
-        else if score >= TRUSTED_THRESHOLD  { ReputationTier::Trusted  }
-        else if score >= RELIABLE_THRESHOLD { ReputationTier::Reliable }
-        else if score >= NEWCOMER_THRESHOLD { ReputationTier::Newcomer }
-        else                                { ReputationTier::Ghost    }
-    }
-
     pub fn name(&self) -> &str {
         match self {
             ReputationTier::Ghost    => "👻 Ghost",
@@ -75,6 +72,92 @@ impl ReputationTier {
     }
 }
 
+// -----------------------------------------------------------------------------
+// TierSchedule — настраиваемая таблица уровней
+// -----------------------------------------------------------------------------
+
+/// DAO вес уровня: score^exponent + bonus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaoWeightFormula {
+    pub exponent: f64,
+    pub bonus: f64,
+}
+
+impl DaoWeightFormula {
+    pub fn apply(&self, score: f64) -> f64 {
+        score.max(0.0).powf(self.exponent) + self.bonus
+    }
+}
+
+/// Ступень таблицы. class — базовый уровень, к которому относится
+/// ступень (для фильтров и статистики); name — отображаемое имя
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierLevel {
+    pub min_score: f64,
+    pub name: String,
+    pub class: ReputationTier,
+    pub dao_weight: DaoWeightFormula,
+}
+
+impl TierLevel {
+    pub fn new(min_score: f64, name: &str, class: ReputationTier,
+               dao_weight: DaoWeightFormula) -> Self {
+        TierLevel { min_score, name: name.to_string(), class, dao_weight }
+    }
+
+    fn standard(min_score: f64, class: ReputationTier) -> Self {
+        let dao_weight = DaoWeightFormula {
+            exponent: DAO_WEIGHT_EXPONENT, bonus: class.dao_weight_bonus() };
+        TierLevel::new(min_score, class.name(), class.clone(), dao_weight)
+    }
+}
+
+impl Default for TierLevel {
+    fn default() -> Self { TierLevel::standard(0.0, ReputationTier::Ghost) }
+}
+
+/// Таблица уровней, отсортированная по убыванию min_score.
+/// Узел получает первую ступень, чей порог он достиг
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TierSchedule {
+    levels: Vec<TierLevel>,
+}
+
+impl TierSchedule {
+    pub fn new(levels: Vec<TierLevel>) -> Self {
+        let mut schedule = TierSchedule { levels: vec![] };
+        for level in levels { schedule.insert(level); }
+        schedule
+    }
+
+    /// Добавить ступень; ступень с тем же порогом заменяется
+    pub fn insert(&mut self, level: TierLevel) {
+        self.levels.retain(|l| l.min_score != level.min_score);
+        let pos = self.levels.iter()
+            .position(|l| l.min_score < level.min_score)
+            .unwrap_or(self.levels.len());
+        self.levels.insert(pos, level);
+    }
+
+    /// Ступень для score; ниже всех порогов — Ghost
+    pub fn classify(&self, score: f64) -> TierLevel {
+        self.levels.iter().find(|l| score >= l.min_score)
+            .cloned().unwrap_or_default()
+    }
+}
+
+impl Default for TierSchedule {
+    fn default() -> Self {
+        TierSchedule::new(vec![
+            TierLevel::standard(LEGEND_THRESHOLD,   ReputationTier::Legend),
+            TierLevel::standard(VETERAN_THRESHOLD,  ReputationTier::Veteran),
+            TierLevel::standard(TRUSTED_THRESHOLD,  ReputationTier::Trusted),
+            TierLevel::standard(RELIABLE_THRESHOLD, ReputationTier::Reliable),
+            TierLevel::standard(NEWCOMER_THRESHOLD, ReputationTier::Newcomer),
+        ])
+    }
+}
+
 // -----------------------------------------------------------------------------
 // ReputationEvent — одно событие влияющее на репутацию
 // -----------------------------------------------------------------------------
@@ -83,11 +166,13 @@ impl ReputationTier {
 pub enum ReputationEventKind {
     SuccessfulDelivery { tactic: String, region_difficulty: f64 },
     FailedDelivery     { region: String },
-    AikiVictory        { censor_cpu_drained: f64 },
+    AikiVictory        { censor_cpu_drained: f64,
+                         #[serde(default)] region_difficulty: f64 },
     EthicsViolation    { violation: String, severity: f64 },
     Betrayal           { evidence_hash: String },
     DaoParticipation   { proposal_id: String },
     LongTermUptime     { days: u32 },
+    Slashed            { reason: String, amount: f64 },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,11 +188,16 @@ pub struct ReputationEvent {
 // NodeReputation — профиль репутации узла
 // -----------------------------------------------------------------------------
 
+/// level и tier не доверяются сохранённым данным: после десериализации
+/// они пересчитываются из score по стандартной TierSchedule
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct NodeReputation {
     pub node_id: String,
     pub score: f64,
     pub tier: ReputationTier,
+    #[serde(default)]
+    pub level: TierLevel,         // ступень из TierSchedule
     pub total_deliveries: u64,
     pub successful_deliveries: u64,
     pub aiki_victories: u64,
@@ -118,6 +208,8 @@ pub struct NodeReputation {
     pub history: Vec<ReputationEvent>,
     pub is_blacklisted: bool,     // после 3 предательств
     pub stake: f64,               // репутационный залог
+    #[serde(default)]
+    pub slashed_total: f64,       // сумма всех штрафов — для эскалации
 }
 
 impl NodeReputation {
@@ -126,6 +218,7 @@ impl NodeReputation {
             node_id: node_id.to_string(),
             score: 0.0,
             tier: ReputationTier::Ghost,
+            level: TierLevel::default(),
             total_deliveries: 0,
             successful_deliveries: 0,
             aiki_victories: 0,
@@ -136,6 +229,7 @@ impl NodeReputation {
             history: vec![],
             is_blacklisted: false,
             stake: 0.0,
+            slashed_total: 0.0,
         }
     }
 
@@ -146,17 +240,42 @@ impl NodeReputation {
 
     pub fn dao_voting_weight(&self) -> f64 {
         if self.is_blacklisted { return 0.0; }
-        // DAO вес = score^0.7 + tier_bonus
+        // DAO вес = score^exponent + bonus по ступени (по умолчанию ^0.7)
         // Нельзя купить — только заработать
-        let base = self.score.powf(DAO_WEIGHT_EXPONENT);
-        base + self.tier.dao_weight_bonus()
+        self.level.dao_weight.apply(self.score)
+    }
+
+    pub fn update_tier(&mut self, schedule: &TierSchedule) {
+        self.level = schedule.classify(self.score);
+        self.tier = self.level.class.clone();
     }
+}
 
-    pub fn update_tier(&mut self) {
-        self.tier = ReputationTier::from_score(self.score);
+impl Serialize for NodeReputation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
     }
 }
 
+impl<'de> Deserialize<'de> for NodeReputation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut node = Self::deserialize(deserializer)?;
+        node.update_tier(&TierSchedule::default());
+        Ok(node)
+    }
+}
+
+/// Итог ReputationRegistry::slash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashResult {
+    pub node_id: String,
+    pub applied: f64,         // фактическое снижение score
+    pub score_after: f64,
+    pub cumulative: f64,      // slashed_total после штрафа
+    pub escalations: u32,     // предательств засчитано этим штрафом
+    pub blacklisted: bool,
+}
+
 // -----------------------------------------------------------------------------
 // ReputationRegistry — реестр репутаций
 // -----------------------------------------------------------------------------
@@ -166,15 +285,41 @@ pub struct ReputationRegistry {
     pub total_events: u64,
     pub total_slashes: u64,
     pub blacklisted_count: u32,
+    tier_schedule: TierSchedule,
 }
 
 impl ReputationRegistry {
     pub fn new() -> Self {
+        Self::with_tier_schedule(TierSchedule::default())
+    }
+
+    pub fn with_tier_schedule(tier_schedule: TierSchedule) -> Self {
         ReputationRegistry {
             nodes: HashMap::new(),
             total_events: 0,
             total_slashes: 0,
             blacklisted_count: 0,
+            tier_schedule,
+        }
+    }
+
+    pub fn tier_schedule(&self) -> &TierSchedule { &self.tier_schedule }
+
+    /// Заменить таблицу уровней и переклассифицировать все узлы
+    pub fn set_tier_schedule(&mut self, schedule: TierSchedule) {
+        self.tier_schedule = schedule;
+        self.recompute_tiers();
+    }
+
+    pub fn recompute_tiers(&mut self) {
+        for node in self.nodes.values_mut() {
+            node.update_tier(&self.tier_schedule);
+        }
+    }
+
+    fn recompute_tier(&mut self, node_id: &str) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.update_tier(&self.tier_schedule);
         }
     }
 
@@ -183,6 +328,11 @@ impl ReputationRegistry {
             .or_insert_with(|| NodeReputation::new(node_id))
     }
 
+    /// Множитель награды за сложность региона: 1.0 при 0, ×4 при 1.0
+    pub fn region_multiplier(region_difficulty: f64) -> f64 {
+        1.0 + region_difficulty.clamp(0.0, 1.0) * REP_REGION_SCALE
+    }
+
     fn now() -> i64 {
         use std::time::{SystemTime, UNIX_EPOCH};
         SystemTime::now().duration_since(UNIX_EPOCH)
@@ -199,7 +349,7 @@ impl ReputationRegistry {
             "Hybrid"           => (REP_AIKI_MULT + REP_STRIKE_MULT) / 2.0,
             _                  => 1.0,
         };
-        let delta = REP_BASE_DELIVERY * (1.0 + region_difficulty * 3.0) * tactic_mult;
+        let delta = REP_BASE_DELIVERY * Self::region_multiplier(region_difficulty) * tactic_mult;
 
         let node = self.get_or_create(node_id);
         if node.is_blacklisted { return 0.0; }
@@ -207,7 +357,6 @@ impl ReputationRegistry {
         node.stake += delta * 0.1;
         node.total_deliveries += 1;
         node.successful_deliveries += 1;
-        node.update_tier();
 
         let event = ReputationEvent {
             node_id: node_id.to_string(),
@@ -218,6 +367,7 @@ impl ReputationRegistry {
             rep_delta: delta, timestamp: Self::now(), is_slash: false,
         };
         node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         delta
     }
@@ -229,7 +379,6 @@ impl ReputationRegistry {
         node.score = (node.score + delta).max(0.0);
         node.total_deliveries += 1;
         node.stake = (node.stake + delta * 0.05).max(0.0);
-        node.update_tier();
         let event = ReputationEvent {
             node_id: node_id.to_string(),
             kind: ReputationEventKind::FailedDelivery {
@@ -237,48 +386,92 @@ impl ReputationRegistry {
             rep_delta: delta, timestamp: Self::now(), is_slash: false,
         };
         node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         delta
     }
 
-    /// Победа Айкидо — особая награда
+    /// Победа Айкидо — особая награда (регион не указан)
     pub fn record_aiki_victory(&mut self, node_id: &str,
                                 cpu_drained: f64) -> f64 {
-        let delta = cpu_drained * REP_AIKI_MULT * 2.0;
+        self.record_aiki_victory_in(node_id, cpu_drained, REP_DEFAULT_DIFFICULTY)
+    }
+
+    /// Победа Айкидо в регионе: против KP весит больше, чем против DE
+    pub fn record_aiki_victory_in(&mut self, node_id: &str, cpu_drained: f64,
+                                   region_difficulty: f64) -> f64 {
+        let delta = cpu_drained * REP_AIKI_MULT * 2.0
+            * Self::region_multiplier(region_difficulty);
         let node = self.get_or_create(node_id);
         if node.is_blacklisted { return 0.0; }
         node.score += delta;
         node.stake += delta * 0.2;
         node.aiki_victories += 1;
-        node.update_tier();
         let event = ReputationEvent {
             node_id: node_id.to_string(),
-            kind: ReputationEventKind::AikiVictory { censor_cpu_drained: cpu_drained },
+            kind: ReputationEventKind::AikiVictory {
+                censor_cpu_drained: cpu_drained, region_difficulty },
             rep_delta: delta, timestamp: Self::now(), is_slash: false,
         };
         node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         delta
     }
 
-    /// Нарушение этики
+    /// Нарушение этики — штраф через общий slash
     pub fn record_ethics_violation(&mut self, node_id: &str,
                                     violation: &str, severity: f64) -> f64 {
         let node = self.get_or_create(node_id);
-        let delta = -(node.score * severity * 0.3).max(0.1);
-        node.score = (node.score + delta).max(0.0);
+        let amount = (node.score * severity * 0.3).max(0.1);
         node.ethics_violations += 1;
-        node.update_tier();
-        let event = ReputationEvent {
-            node_id: node_id.to_string(),
-            kind: ReputationEventKind::EthicsViolation {
-                violation: violation.to_string(), severity },
-            rep_delta: delta, timestamp: Self::now(), is_slash: true,
+        let kind = ReputationEventKind::EthicsViolation {
+            violation: violation.to_string(), severity };
+        -self.apply_slash(node_id, amount, kind, violation).applied
+    }
+
+    /// Единая точка штрафа для рынка, пулов и этики: score падает на
+    /// amount (не ниже 0), причина пишется в историю. Каждые
+    /// REP_SLASH_ESCALATION очков накопленных штрафов — предательство,
+    /// третье ведёт в блэклист. Один штраф — не больше одного предательства:
+    /// крупный штраф высокорепутационного узла не отправляет его в блэклист разом
+    pub fn slash(&mut self, node_id: &str, amount: f64, reason: &str) -> SlashResult {
+        let amount = if amount.is_finite() { amount.max(0.0) } else { 0.0 };
+        let kind = ReputationEventKind::Slashed { reason: reason.to_string(), amount };
+        self.apply_slash(node_id, amount, kind, reason)
+    }
+
+    fn apply_slash(&mut self, node_id: &str, amount: f64, kind: ReputationEventKind,
+                   reason: &str) -> SlashResult {
+        let (applied, escalations) = {
+            let node = self.get_or_create(node_id);
+            let applied = amount.min(node.score);
+            node.score -= applied;
+            let before = (node.slashed_total / REP_SLASH_ESCALATION).floor();
+            node.slashed_total += amount;
+            let after = (node.slashed_total / REP_SLASH_ESCALATION).floor();
+            node.history.push(ReputationEvent {
+                node_id: node_id.to_string(), kind,
+                rep_delta: -applied, timestamp: Self::now(), is_slash: true,
+            });
+            let escalations = if node.is_blacklisted || after <= before { 0 } else { 1 };
+            (applied, escalations)
         };
-        node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         self.total_slashes += 1;
-        delta
+        for _ in 0..escalations {
+            self.record_betrayal(node_id, &format!("slash: {}", reason));
+        }
+        let node = &self.nodes[node_id];
+        SlashResult {
+            node_id: node_id.to_string(),
+            applied,
+            score_after: node.score,
+            cumulative: node.slashed_total,
+            escalations,
+            blacklisted: node.is_blacklisted,
+        }
     }
 
     /// ПРЕДАТЕЛЬСТВО — помощь цензору
@@ -291,12 +484,12 @@ impl ReputationRegistry {
             node.score -= slash;
             node.stake = 0.0;
             node.betrayals += 1;
-            let newly_blacklisted = if node.betrayals >= 3 {
+            let newly_blacklisted = if node.betrayals >= 3 && !node.is_blacklisted {
                 node.is_blacklisted = true;
                 node.score = 0.0;
                 true
             } else { false };
-            node.update_tier();
+            if node.is_blacklisted { node.score = 0.0; }
             let event = ReputationEvent {
                 node_id: node_id.to_string(),
                 kind: ReputationEventKind::Betrayal {
@@ -308,6 +501,7 @@ impl ReputationRegistry {
         };
         // Теперь borrow закрыт — можно обновить счётчики
         if newly_blacklisted { self.blacklisted_count += 1; }
+        self.recompute_tier(node_id);
         self.total_events += 1;
         self.total_slashes += 1;
         -slash
@@ -320,7 +514,6 @@ impl ReputationRegistry {
         let delta = 0.05 * node.tier.dao_weight_bonus().max(0.1);
         node.score += delta;
         node.dao_participations += 1;
-        node.update_tier();
         let event = ReputationEvent {
             node_id: node_id.to_string(),
             kind: ReputationEventKind::DaoParticipation {
@@ -328,6 +521,7 @@ impl ReputationRegistry {
             rep_delta: delta, timestamp: Self::now(), is_slash: false,
         };
         node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         delta
     }
@@ -338,22 +532,35 @@ impl ReputationRegistry {
         let delta = (days as f64).sqrt() * 0.5;
         node.score += delta;
         node.uptime_days += days;
-        node.update_tier();
         let event = ReputationEvent {
             node_id: node_id.to_string(),
             kind: ReputationEventKind::LongTermUptime { days },
             rep_delta: delta, timestamp: Self::now(), is_slash: false,
         };
         node.history.push(event);
+        self.recompute_tier(node_id);
         self.total_events += 1;
         delta
     }
 
     pub fn leaderboard(&self, n: usize) -> Vec<(&NodeReputation, usize)> {
+        self.leaderboard_filtered(0, n, ReputationTier::Ghost, false)
+    }
+
+    /// Страница рейтинга: узлы не ниже min_tier, по убыванию score
+    /// (при равенстве — по node_id). Ранг 1-based и сквозной между
+    /// страницами: offset=10 начинается с ранга 11
+    pub fn leaderboard_filtered(&self, offset: usize, limit: usize,
+                                min_tier: ReputationTier,
+                                include_blacklisted: bool) -> Vec<(&NodeReputation, usize)> {
         let mut v: Vec<&NodeReputation> = self.nodes.values()
-            .filter(|n| !n.is_blacklisted).collect();
-        v.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        v.into_iter().take(n).enumerate()
+            .filter(|n| include_blacklisted || !n.is_blacklisted)
+            .filter(|n| n.tier >= min_tier)
+            .collect();
+        v.sort_by(|a, b| b.score.partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.node_id.cmp(&b.node_id)));
+        v.into_iter().enumerate().skip(offset).take(limit)
             .map(|(i, n)| (n, i + 1)).collect()
     }
 
@@ -429,6 +636,8 @@ pub const TRUST_DECAY: f64         = 0.15; // затухание по хопам
 pub const PAGERANK_DAMPING: f64    = 0.85; // классический PageRank d
 pub const PAGERANK_ITERATIONS: u32 = 20;   // итераций сходимости
 pub const MIN_TRUST_EDGE: f64      = 0.10; // минимальный вес ребра
+pub const INCREMENTAL_EPSILON: f64 = 1e-9; // меньшие поправки не распространяем
+pub const INCREMENTAL_MAX_SHARE: f64 = 0.25; // задело больше 25% узлов — полный пересчёт
 
 // -----------------------------------------------------------------------------
 // TrustEdge — направленное ребро доверия
@@ -478,11 +687,14 @@ pub struct TrustGraph {
     pub edges: Vec<TrustEdge>,
     pub trust_ranks: HashMap<String, f64>,
     pub iterations_run: u32,
+    rank_history: HashMap<String, Vec<f64>>,         // ранги по итерациям, до нормализации
+    out_shares: HashMap<String, Vec<(String, f64)>>, // доли исходящих рёбер при расчёте
 }
 
 impl TrustGraph {
     pub fn new() -> Self {
-        TrustGraph { edges: vec![], trust_ranks: HashMap::new(), iterations_run: 0 }
+        TrustGraph { edges: vec![], trust_ranks: HashMap::new(), iterations_run: 0,
+            rank_history: HashMap::new(), out_shares: HashMap::new() }
     }
 
     pub fn add_edge(&mut self, from: &str, to: &str, weight: f64) {
@@ -525,6 +737,21 @@ impl TrustGraph {
         self.edges.iter().filter(|e| e.from == node).collect()
     }
 
+    // Доли, в которых узел раздаёт свой ранг по исходящим рёбрам
+    fn shares(&self, node: &str) -> Vec<(String, f64)> {
+        let outgoing = self.outgoing(node);
+        let total_weight: f64 = outgoing.iter()
+            .map(|e| e.effective_weight()).sum::<f64>().max(1e-9);
+        outgoing.iter().map(|e| (e.to.clone(), e.effective_weight() / total_weight)).collect()
+    }
+
+    fn normalize_ranks(&mut self) {
+        let raw = |h: &Vec<f64>| h.last().copied().unwrap_or(0.0);
+        let max_rank = self.rank_history.values().map(raw).fold(0.0f64, f64::max).max(1e-9);
+        self.trust_ranks = self.rank_history.iter()
+            .map(|(k, h)| (k.clone(), raw(h) / max_rank)).collect();
+    }
+
     // PageRank-подобный алгоритм для доверия
     pub fn compute_trust_rank(&mut self, seed_reputations: &HashMap<String, f64>) {
         let nodes = self.all_nodes();
@@ -536,6 +763,8 @@ impl TrustGraph {
             let base = seed_reputations.get(node).copied().unwrap_or(1.0);
             (node.clone(), base / total_rep)
         }).collect();
+        let mut history: HashMap<String, Vec<f64>> = ranks.iter()
+            .map(|(k, v)| (k.clone(), vec![*v])).collect();
 
         // PageRank итерации
         for _ in 0..PAGERANK_ITERATIONS {
@@ -554,16 +783,90 @@ impl TrustGraph {
                     *new_ranks.entry(edge.to.clone()).or_insert(0.0) += contribution;
                 }
             }
+            for (node, v) in &new_ranks {
+                history.entry(node.clone()).or_default().push(*v);
+            }
             ranks = new_ranks;
         }
 
         // Нормализуем 0..1
-        let max_rank = ranks.values().cloned().fold(0.0f64, f64::max).max(1e-9);
-        self.trust_ranks = ranks.into_iter()
-            .map(|(k,v)| (k, v / max_rank)).collect();
+        self.out_shares = nodes.iter().map(|nd| (nd.clone(), self.shares(nd))).collect();
+        self.rank_history = history;
+        self.normalize_ranks();
         self.iterations_run = PAGERANK_ITERATIONS;
     }
 
+    /// Локальное обновление TrustRank после изменения исходящих рёбер
+    /// affected_node (betray/vouch/add_edge от его имени).
+    ///
+    /// Изменилась только одна колонка матрицы переходов, поэтому разница
+    /// с полным расчётом подчиняется e(j+1) = d·M'·e(j) + d·Δ·x_a(j), где
+    /// Δ — разница долей affected_node, x_a(j) — его ранг на итерации j.
+    /// Поправку считаем разреженно, отбрасывая значения меньше
+    /// INCREMENTAL_EPSILON. Если она задела больше INCREMENTAL_MAX_SHARE
+    /// узлов, узел новый или расчёта ещё не было — полный
+    /// compute_trust_rank. Возвращает true, если хватило локального.
+    pub fn update_after_edge_change(&mut self, affected_node: &str,
+                                    reputations: &HashMap<String, f64>) -> bool {
+        let (old_shares, history_a) = match (self.out_shares.get(affected_node),
+                                             self.rank_history.get(affected_node)) {
+            (Some(s), Some(h)) => (s.clone(), h.clone()),
+            _ => { self.compute_trust_rank(reputations); return false; }
+        };
+        let new_shares = self.shares(affected_node);
+        if new_shares.iter().any(|(to, _)| !self.rank_history.contains_key(to)) {
+            self.compute_trust_rank(reputations);
+            return false;
+        }
+
+        let mut diff: HashMap<String, f64> = HashMap::new();
+        for (to, share) in &new_shares {
+            *diff.entry(to.clone()).or_insert(0.0) += PAGERANK_DAMPING * share;
+        }
+        for (to, share) in &old_shares {
+            *diff.entry(to.clone()).or_insert(0.0) -= PAGERANK_DAMPING * share;
+        }
+
+        let limit = ((self.rank_history.len() as f64 * INCREMENTAL_MAX_SHARE).ceil() as usize).max(1);
+        let mut shares_cache: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        let mut touched = std::collections::HashSet::new();
+        // Поправки по итерациям: steps[j] — разреженный e(j+1)
+        let mut steps: Vec<HashMap<String, f64>> = Vec::with_capacity(history_a.len());
+        let mut err: HashMap<String, f64> = HashMap::new();
+        for x_a in history_a.iter().take(history_a.len().saturating_sub(1)) {
+            let mut next: HashMap<String, f64> = HashMap::new();
+            for (node, v) in &err {
+                let shares = shares_cache.entry(node.clone())
+                    .or_insert_with(|| self.shares(node));
+                for (to, share) in shares.iter() {
+                    *next.entry(to.clone()).or_insert(0.0) += PAGERANK_DAMPING * v * share;
+                }
+            }
+            for (to, d) in &diff {
+                *next.entry(to.clone()).or_insert(0.0) += d * x_a;
+            }
+            next.retain(|_, v| v.abs() >= INCREMENTAL_EPSILON);
+            touched.extend(next.keys().cloned());
+            if touched.len() > limit {
+                self.compute_trust_rank(reputations);
+                return false;
+            }
+            steps.push(next.clone());
+            err = next;
+        }
+
+        for (j, step) in steps.into_iter().enumerate() {
+            for (node, v) in step {
+                if let Some(h) = self.rank_history.get_mut(&node) {
+                    if let Some(slot) = h.get_mut(j + 1) { *slot += v; }
+                }
+            }
+        }
+        self.out_shares.insert(affected_node.to_string(), new_shares);
+        self.normalize_ranks();
+        true
+    }
+
     pub fn trust_rank_of(&self, node: &str) -> f64 {
         self.trust_ranks.get(node).copied().unwrap_or(0.0)
     }
@@ -591,6 +894,43 @@ impl TrustGraph {
         best
     }
 
+    /// Путь максимального доверия (произведение весов рёбер) и его узлы.
+    /// Dijkstra по стоимости −ln(w): максимум произведения = минимум суммы.
+    /// None — to недостижим из from по рёбрам с ненулевым весом
+    pub fn trust_path(&self, from: &str, to: &str) -> Option<(f64, Vec<String>)> {
+        use std::collections::BinaryHeap;
+        if from == to { return Some((1.0, vec![from.to_string()])); }
+
+        let mut cost: HashMap<String, f64> = HashMap::new();
+        let mut prev: HashMap<String, String> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        cost.insert(from.to_string(), 0.0);
+        heap.push(PathCost(0.0, from.to_string()));
+
+        while let Some(PathCost(c, node)) = heap.pop() {
+            if node == to { break; }
+            if c > cost.get(&node).copied().unwrap_or(f64::INFINITY) { continue; }
+            for edge in self.outgoing(&node) {
+                let w = edge.effective_weight();
+                if w <= 0.0 { continue; }
+                let next = c - w.ln();
+                if next < cost.get(&edge.to).copied().unwrap_or(f64::INFINITY) {
+                    cost.insert(edge.to.clone(), next);
+                    prev.insert(edge.to.clone(), node.clone());
+                    heap.push(PathCost(next, edge.to.clone()));
+                }
+            }
+        }
+
+        let total = *cost.get(to)?;
+        let mut path = vec![to.to_string()];
+        while let Some(p) = prev.get(path.last().unwrap()) {
+            path.push(p.clone());
+        }
+        path.reverse();
+        Some(((-total).exp(), path))
+    }
+
     pub fn top_trusted(&self, n: usize) -> Vec<(&str, f64)> {
         let mut v: Vec<(&str, f64)> = self.trust_ranks.iter()
             .map(|(k,v)| (k.as_str(), *v)).collect();
@@ -605,3 +945,252 @@ impl TrustGraph {
 }
 
 impl Default for TrustGraph { fn default() -> Self { Self::new() } }
+
+// Элемент очереди Dijkstra: меньшая стоимость — выше приоритет
+struct PathCost(f64, String);
+
+impl PartialEq for PathCost {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == std::cmp::Ordering::Equal }
+}
+impl Eq for PathCost {}
+impl PartialOrd for PathCost {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+impl Ord for PathCost {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.0.total_cmp(&self.0).then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credits::known_regions;
+
+    fn region_difficulty(code: &str) -> f64 {
+        known_regions()[code].difficulty_score
+    }
+
+    fn demo_registry() -> ReputationRegistry {
+        let mut reg = ReputationRegistry::new();
+        for _ in 0..80 { reg.record_delivery("node_tokyo",   "AikiReflection",   0.85); }
+        for _ in 0..50 { reg.record_delivery("node_tokyo",   "CumulativeStrike",  0.99); }
+        for _ in 0..60 { reg.record_delivery("node_berlin",  "AikiReflection",   0.60); }
+        for _ in 0..45 { reg.record_delivery("node_nairobi", "StandoffDecoy",    0.70); }
+        for _ in 0..30 { reg.record_delivery("node_toronto", "Passive",          0.05); }
+        for hash in ["hash_001", "hash_002", "hash_003"] {
+            reg.record_betrayal("node_evil", hash);
+        }
+        reg
+    }
+
+    #[test]
+    fn test_trust_path_prefers_strongest_route() {
+        let mut g = TrustGraph::new();
+        g.add_edge("nexus", "berlin", 0.9);
+        g.add_edge("berlin", "carol", 0.9);
+        g.add_edge("nexus", "carol", 0.95);
+        g.add_edge("island", "lonely", 0.8);
+
+        // Прямое ребро сильнее 0.81 — идём напрямую
+        let (t, path) = g.trust_path("nexus", "carol").unwrap();
+        assert!((t - 0.95).abs() < 1e-9);
+        assert_eq!(path, vec!["nexus", "carol"]);
+
+        // Прямое ребро ослабло — выгоднее через berlin
+        g.add_edge("nexus", "carol", 0.3);
+        let (t, path) = g.trust_path("nexus", "carol").unwrap();
+        assert!((t - 0.81).abs() < 1e-9);
+        assert_eq!(path, vec!["nexus", "berlin", "carol"]);
+
+        // Без прямого ребра — только многоходовый путь
+        g.edges.retain(|e| !(e.from == "nexus" && e.to == "carol"));
+        assert_eq!(g.trust_path("nexus", "carol").unwrap().1.len(), 3);
+
+        assert!(g.trust_path("nexus", "lonely").is_none());
+        assert!(g.trust_path("carol", "nexus").is_none());
+    }
+
+    // Граф: 200 узлов по кольцу с хордами, репутация убывает по номеру
+    fn ring_graph() -> (TrustGraph, HashMap<String, f64>) {
+        let mut g = TrustGraph::new();
+        let mut reps = HashMap::new();
+        for i in 0..200 {
+            let id = format!("n{:03}", i);
+            g.add_edge(&id, &format!("n{:03}", (i + 1) % 200), 0.9);
+            if i % 5 == 0 { g.add_edge(&id, &format!("n{:03}", (i + 7) % 200), 0.6); }
+            reps.insert(id, 300.0 - i as f64);
+        }
+        (g, reps)
+    }
+
+    #[test]
+    fn test_incremental_trust_rank_matches_full_recompute() {
+        let (mut incremental, reps) = ring_graph();
+        let (mut full, _) = ring_graph();
+        incremental.compute_trust_rank(&reps);
+        full.compute_trust_rank(&reps);
+
+        incremental.betray("n010", "n017");
+        full.betray("n010", "n017");
+        assert!(incremental.update_after_edge_change("n010", &reps));
+        full.compute_trust_rank(&reps);
+
+        let mut max_err = 0.0f64;
+        for (node, rank) in &full.trust_ranks {
+            max_err = max_err.max((rank - incremental.trust_rank_of(node)).abs());
+        }
+        assert!(max_err < 1e-3, "расхождение {}", max_err);
+    }
+
+    #[test]
+    fn test_inserted_tier_reclassifies_mid_score_node() {
+        let mut reg = ReputationRegistry::new();
+        reg.record_uptime("node_mid", 14_400); // score = 60
+        reg.record_uptime("node_low", 400);    // score = 10
+        let before = reg.nodes["node_mid"].dao_voting_weight();
+        assert_eq!(reg.nodes["node_mid"].tier, ReputationTier::Trusted);
+
+        let mut schedule = reg.tier_schedule().clone();
+        schedule.insert(TierLevel::new(50.0, "🛡 Guardian", ReputationTier::Trusted,
+            DaoWeightFormula { exponent: DAO_WEIGHT_EXPONENT, bonus: 1.75 }));
+        reg.set_tier_schedule(schedule);
+
+        let mid = &reg.nodes["node_mid"];
+        assert_eq!(mid.level.name, "🛡 Guardian");
+        assert_eq!(mid.tier, ReputationTier::Trusted);
+        assert!((mid.dao_voting_weight() - before - 0.25).abs() < 1e-9);
+        assert_eq!(reg.nodes["node_low"].level.name, ReputationTier::Reliable.name());
+    }
+
+    #[test]
+    fn test_deserialized_level_follows_score() {
+        let mut reg = ReputationRegistry::new();
+        reg.record_uptime("node_mid", 14_400); // score = 60
+        let mut json = serde_json::to_value(&reg.nodes["node_mid"]).unwrap();
+        json.as_object_mut().unwrap().remove("level");
+        json["tier"] = serde_json::json!("Legend");
+
+        let node: NodeReputation = serde_json::from_value(json).unwrap();
+        assert_eq!(node.tier, ReputationTier::Trusted);
+        assert_eq!(node.level, reg.nodes["node_mid"].level);
+    }
+
+    #[test]
+    fn test_leaderboard_pagination() {
+        let reg = demo_registry();
+        let full = reg.leaderboard(10);
+        assert_eq!(full.len(), 4);
+
+        let mut paged = vec![];
+        for page in 0..3 {
+            paged.extend(reg.leaderboard_filtered(page * 2, 2, ReputationTier::Ghost, false));
+        }
+        assert_eq!(paged.len(), 4);
+        for ((a, ra), (b, rb)) in paged.iter().zip(full.iter()) {
+            assert_eq!(a.node_id, b.node_id);
+            assert_eq!(ra, rb);
+        }
+        assert_eq!(paged.iter().map(|(_, r)| *r).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert!(reg.leaderboard_filtered(10, 5, ReputationTier::Ghost, true).is_empty());
+
+        // Фильтр по уровню
+        let top = reg.leaderboard_filtered(0, 10, ReputationTier::Veteran, false);
+        assert!(top.iter().all(|(n, _)| n.tier >= ReputationTier::Veteran));
+        assert!(top.len() < full.len());
+    }
+
+    #[test]
+    fn test_leaderboard_blacklist_filter() {
+        let reg = demo_registry();
+        let clean = reg.leaderboard_filtered(0, 10, ReputationTier::Ghost, false);
+        assert!(clean.iter().all(|(n, _)| n.node_id != "node_evil"));
+        let all = reg.leaderboard_filtered(0, 10, ReputationTier::Ghost, true);
+        assert_eq!(all.len(), clean.len() + 1);
+        let (evil, rank) = all.iter().find(|(n, _)| n.node_id == "node_evil").unwrap();
+        assert!(evil.is_blacklisted);
+        assert_eq!(*rank, all.len());
+    }
+
+    #[test]
+    fn test_region_difficulty_ranks_dangerous_nodes_higher() {
+        let mut reg = ReputationRegistry::new();
+        for _ in 0..20 {
+            reg.record_delivery("node_kp", "StandoffDecoy", region_difficulty("KP"));
+            reg.record_delivery("node_de", "StandoffDecoy", region_difficulty("DE"));
+        }
+        let kp = &reg.nodes["node_kp"];
+        let de = &reg.nodes["node_de"];
+        assert_eq!(kp.successful_deliveries, de.successful_deliveries);
+        assert!(kp.score > de.score);
+        let board = reg.leaderboard(2);
+        assert_eq!(board[0].0.node_id, "node_kp");
+        assert_eq!(board[1].0.node_id, "node_de");
+
+        // Айкидо: без региона — прежняя награда, в KP — больше
+        let plain = reg.record_aiki_victory("node_a", 0.5);
+        assert!((plain - 0.5 * REP_AIKI_MULT * 2.0).abs() < 1e-12);
+        assert!(reg.record_aiki_victory_in("node_b", 0.5, region_difficulty("KP")) > plain);
+    }
+
+    #[test]
+    fn test_slash_reduces_score_and_dao_weight() {
+        let mut reg = ReputationRegistry::new();
+        for _ in 0..100 { reg.record_delivery("node_a", "AikiReflection", 0.0); }
+        let (score, weight) = (reg.nodes["node_a"].score, reg.nodes["node_a"].dao_voting_weight());
+
+        let r = reg.slash("node_a", 2.0, "market: провал доставки");
+        assert!((r.applied - 2.0).abs() < 1e-12);
+        assert!((r.score_after - (score - 2.0)).abs() < 1e-9);
+        assert_eq!((r.escalations, r.blacklisted), (0, false));
+        assert!(reg.nodes["node_a"].dao_voting_weight() < weight);
+        assert!(matches!(&reg.nodes["node_a"].history.last().unwrap().kind,
+            ReputationEventKind::Slashed { reason, .. } if reason == "market: провал доставки"));
+        assert_eq!(reg.nodes["node_a"].betrayals, 0);
+    }
+
+    #[test]
+    fn test_repeated_slashes_escalate_to_blacklist() {
+        let mut reg = ReputationRegistry::new();
+        for _ in 0..200 { reg.record_delivery("node_b", "AikiReflection", 0.0); }
+        let reasons = ["market: провал", "pools: страховое мошенничество", "ethics: privacy"];
+        let mut last = None;
+        for i in 0..12 {
+            let r = reg.slash("node_b", 2.5, reasons[i % 3]);
+            if r.escalations > 0 { assert!((r.cumulative / REP_SLASH_ESCALATION).fract() < 1e-9); }
+            last = Some(r);
+        }
+        let last = last.unwrap();
+        assert!(last.blacklisted && last.score_after == 0.0);
+        let node = &reg.nodes["node_b"];
+        assert_eq!(node.betrayals, 3);
+        assert_eq!(node.dao_voting_weight(), 0.0);
+        assert_eq!(reg.blacklisted_count, 1);
+        let recorded: Vec<&str> = node.history.iter().filter_map(|e| match &e.kind {
+            ReputationEventKind::Slashed { reason, .. } => Some(reason.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(recorded.len(), 12);
+        assert!(reasons.iter().all(|r| recorded.contains(r)));
+        assert!(node.history.iter().any(|e| matches!(&e.kind,
+            ReputationEventKind::Betrayal { evidence_hash } if evidence_hash.starts_with("slash: "))));
+    }
+
+    #[test]
+    fn test_single_ethics_violation_does_not_blacklist_veteran() {
+        let mut reg = ReputationRegistry::new();
+        reg.record_delivery("node_v", "AikiReflection", 0.0);
+        reg.nodes.get_mut("node_v").unwrap().score = 300.0;
+
+        reg.record_ethics_violation("node_v", "privacy", 1.0);
+        let node = &reg.nodes["node_v"];
+        assert_eq!(node.betrayals, 1);
+        assert!(!node.is_blacklisted && node.score > 0.0);
+        assert_eq!(reg.blacklisted_count, 0);
+
+        // Повторные предательства уже заблокированного узла счётчик не раздувают
+        for i in 0..5 { reg.record_betrayal("node_v", &format!("h{}", i)); }
+        assert!(reg.nodes["node_v"].is_blacklisted);
+        assert_eq!(reg.blacklisted_count, 1);
+    }
+}
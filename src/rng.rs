@@ -0,0 +1,133 @@
+// =============================================================================
+// FEDERATION CORE — rng.rs
+// «Единый источник случайности»
+// =============================================================================
+//
+// До этого каждый модуль держал свой `rng ^= rng << 13` с локальным seed.
+// FedRng — общий интерфейс: конструкторы принимают `&mut dyn FedRng`,
+// поэтому тест может передать засеянный генератор и получить
+// воспроизводимый результат во всех компонентах сразу.
+//
+// XorShift64 — реализация по умолчанию: тот же xorshift (13, 7, 17),
+// что и раньше, так что засеянные конструкторы выдают прежние значения.
+//
+// FedRng надстроен над rand::RngCore: любой генератор из rand (StdRng,
+// ChaCha) подходит как источник, а XorShift64 можно отдать в API rand.
+// =============================================================================
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use rand::RngCore;
+
+/// Источник псевдослучайных чисел Федерации
+pub trait FedRng: RngCore {
+    /// Равномерно в [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<R: RngCore + ?Sized> FedRng for R {}
+
+/// xorshift64 — быстрый, детерминированный, НЕ криптостойкий
+#[derive(Debug, Clone)]
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> Self {
+        // Нулевое состояние у xorshift неподвижно
+        XorShift64 { state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed } }
+    }
+
+    /// Seed из системного времени — для непредсказуемого боевого режима
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+            .unwrap().as_nanos() as u64;
+        Self::new(nanos)
+    }
+}
+
+impl RngCore for XorShift64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirage::{AnomalyScore, MimicryMatrix, MirageGenerator};
+    use crate::neural_node::NeuralWeights;
+    use crate::tensor::SsauTensor;
+    use crate::transport::MicroClock;
+
+    #[test]
+    fn test_seeded_components_are_reproducible() {
+        let real = SsauTensor::new("A", "B", 20.0, 100.0);
+        let score = AnomalyScore {
+            threat_level: 0.5, anomalies: vec![], suspected_attacker: None,
+            activate_mirage: true, description: String::new(),
+        };
+        let build = |seed: u64| {
+            let mut rng = XorShift64::new(seed);
+            let weights = NeuralWeights::from_rng(4, 3, &mut rng);
+            let mut clock = MicroClock::with_rng(&mut rng);
+            let mut gen = MirageGenerator::with_rng(MimicryMatrix::maze(), &mut rng);
+            let fake = gen.generate_fake_tensor(&real, 0.5, &score);
+            let (privkey, _) = crate::noise::generate_keypair_from(&mut rng);
+            (weights.weights, clock.jitter_us(100, 50_000),
+             fake.fake_latency_ms.to_bits(), privkey)
+        };
+        assert_eq!(build(42), build(42));
+        assert_ne!(build(42).0, build(43).0);
+    }
+
+    #[test]
+    fn test_seeded_constructor_matches_legacy_xorshift() {
+        // NeuralWeights::new(seed) — обёртка над from_rng(XorShift64::new(seed))
+        let legacy = NeuralWeights::new(5, 2, 0xABCD);
+        let threaded = NeuralWeights::from_rng(5, 2, &mut XorShift64::new(0xABCD));
+        assert_eq!(legacy.weights, threaded.weights);
+
+        let mut rng = XorShift64::new(7);
+        let mut buf = [0u8; 13];
+        rng.fill_bytes(&mut buf);
+        assert!(buf.iter().any(|&b| b != 0));
+        assert!((0.0..1.0).contains(&rng.next_f64()));
+    }
+
+    #[test]
+    fn test_rand_generators_are_fed_rngs() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut std_rng = StdRng::seed_from_u64(42);
+        let a = NeuralWeights::from_rng(3, 2, &mut std_rng);
+        let b = NeuralWeights::from_rng(3, 2, &mut StdRng::seed_from_u64(42));
+        assert_eq!(a.weights, b.weights);
+
+        // И наоборот: XorShift64 работает с API rand
+        use rand::Rng;
+        let roll: u8 = XorShift64::new(9).gen_range(1..=6);
+        assert!((1..=6).contains(&roll));
+    }
+}
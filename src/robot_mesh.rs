@@ -14,13 +14,18 @@
 // =============================================================================
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 pub const BT_MAX_PAYLOAD: usize    = 512;   // Bluetooth ATT MTU
 pub const ZIGBEE_MAX_PAYLOAD: usize= 84;    // Zigbee frame payload
 pub const MESH_HOP_TTL: u8         = 7;     // максимум хопов
 pub const STEALTH_INTERVAL_SECS: u64 = 60;  // раз в минуту в служебном трафике
 pub const BASTION_SCAN_RADIUS_M: u32 = 30;  // радиус BT сканирования
+pub const SEEN_CACHE_SIZE: usize   = 256;   // недавние packet id на бастион
+pub const BATTERY_AC: u8           = 255;   // battery_pct сетевого питания
+pub const RELAY_BATTERY_RESERVE: u8 = 20;   // ниже — дроид не ретранслирует
+pub const BATTERY_PCT_MJ: f64      = 10.0;  // мДж на 1% батареи дроида
+pub const STEALTH_MIN_PLAUSIBILITY: f64 = 0.5; // ниже — cover выглядит аномально
 
 // -----------------------------------------------------------------------------
 // RadioProtocol — беспроводной протокол дроида
@@ -60,6 +65,54 @@ impl RadioProtocol {
             RadioProtocol::WiFiDirect  => 200,
         }
     }
+    pub fn throughput_kbps(&self) -> u32 {
+        match self {
+            RadioProtocol::Bluetooth5  => 2_000,
+            RadioProtocol::BluetoothLE => 1_000,
+            RadioProtocol::Zigbee      => 250,
+            RadioProtocol::ZWave       => 100,
+            RadioProtocol::Matter      => 1_000,
+            RadioProtocol::Thread      => 250,
+            RadioProtocol::WiFiDirect  => 250_000,
+        }
+    }
+    /// Протоколы с собственной маршрутизацией — устойчивы на многих хопах
+    pub fn is_mesh_friendly(&self) -> bool {
+        matches!(self, RadioProtocol::Thread | RadioProtocol::Zigbee)
+    }
+    pub fn hop_latency_ms(&self) -> u32 {
+        match self {
+            RadioProtocol::Bluetooth5  => 5,
+            RadioProtocol::BluetoothLE => 15,
+            RadioProtocol::Zigbee      => 30,
+            RadioProtocol::Thread      => 20,
+            RadioProtocol::WiFiDirect  => 2,
+            _                          => 25,
+        }
+    }
+    /// Энергия передачи, мДж на КБ: медленное радио дольше в эфире
+    pub fn energy_mj_per_kb(&self) -> f64 {
+        match self {
+            RadioProtocol::Bluetooth5  => 25.0,
+            RadioProtocol::BluetoothLE => 15.0,
+            RadioProtocol::Zigbee      => 60.0,
+            RadioProtocol::ZWave       => 80.0,
+            RadioProtocol::Matter      => 40.0,
+            RadioProtocol::Thread      => 35.0,
+            RadioProtocol::WiFiDirect  => 120.0,
+        }
+    }
+    pub fn relay_energy_mj(&self, bytes: usize) -> f64 {
+        bytes as f64 / 1024.0 * self.energy_mj_per_kb()
+    }
+    /// Ключ выбора при согласовании: меш (для multi-hop) > пропускная
+    /// способность > радиус > порядок PROTOCOL_PRIORITY
+    fn preference(&self, multi_hop: bool) -> (bool, u32, u32, usize) {
+        let rank = PROTOCOL_PRIORITY.iter().position(|p| p == self)
+            .unwrap_or(PROTOCOL_PRIORITY.len());
+        (multi_hop && self.is_mesh_friendly(), self.throughput_kbps(),
+         self.range_m(), PROTOCOL_PRIORITY.len() - rank)
+    }
     pub fn name(&self) -> &str {
         match self {
             RadioProtocol::Bluetooth5  => "BT5",
@@ -73,6 +126,17 @@ impl RadioProtocol {
     }
 }
 
+// Приоритет: WiFiDirect > Thread > Matter > BT5 > BLE > Zigbee > ZWave
+const PROTOCOL_PRIORITY: [RadioProtocol; 7] = [
+    RadioProtocol::WiFiDirect,
+    RadioProtocol::Thread,
+    RadioProtocol::Matter,
+    RadioProtocol::Bluetooth5,
+    RadioProtocol::BluetoothLE,
+    RadioProtocol::Zigbee,
+    RadioProtocol::ZWave,
+];
+
 // -----------------------------------------------------------------------------
 // DroidType — тип домашнего устройства
 // -----------------------------------------------------------------------------
@@ -114,6 +178,19 @@ impl DroidType {
             DroidType::WashingMachine => 0.10,
         }
     }
+    /// Типичный размер служебного сообщения устройства (мин, макс), байт
+    pub fn typical_message_bytes(&self) -> (usize, usize) {
+        match self {
+            DroidType::Vacuum         => (128, 1024),
+            DroidType::Speaker        => (64, 512),
+            DroidType::Fridge         => (32, 256),
+            DroidType::Thermostat     => (16, 64),
+            DroidType::DoorLock       => (16, 64),
+            DroidType::Hub            => (32, 256),
+            DroidType::TV             => (256, 4096),
+            DroidType::WashingMachine => (32, 128),
+        }
+    }
     pub fn stealth_cover(&self) -> &str {
         match self {
             DroidType::Vacuum    => "маршрутные данные уборки",
@@ -150,26 +227,44 @@ pub struct DroidNode {
 
 impl DroidNode {
     pub fn best_protocol(&self) -> Option<&RadioProtocol> {
-        // Приоритет: WiFiDirect > Thread > Matter > BT5 > BLE > Zigbee > ZWave
-        let priority = [
-            RadioProtocol::WiFiDirect,
-            RadioProtocol::Thread,
-            RadioProtocol::Matter,
-            RadioProtocol::Bluetooth5,
-            RadioProtocol::BluetoothLE,
-            RadioProtocol::Zigbee,
-            RadioProtocol::ZWave,
-        ];
-        for p in &priority {
+        for p in &PROTOCOL_PRIORITY {
             if self.protocols.contains(p) { return Some(
                 self.protocols.iter().find(|x| *x == p).unwrap()); }
         }
         None
     }
 
+    /// Общий протокол с соседом для прямой передачи —
+    /// самый быстрый из поддерживаемых обоими
+    pub fn negotiate_protocol(&self, other: &DroidNode) -> Option<RadioProtocol> {
+        self.negotiate(other, false)
+    }
+
+    /// То же для многохопового маршрута — Thread/Zigbee в приоритете
+    pub fn negotiate_mesh_protocol(&self, other: &DroidNode) -> Option<RadioProtocol> {
+        self.negotiate(other, true)
+    }
+
+    fn negotiate(&self, other: &DroidNode, multi_hop: bool) -> Option<RadioProtocol> {
+        self.protocols.iter()
+            .filter(|p| other.protocols.contains(p))
+            .max_by_key(|p| p.preference(multi_hop))
+            .cloned()
+    }
+
+    pub fn is_battery_powered(&self) -> bool { self.battery_pct != BATTERY_AC }
+
+    /// Хватит ли заряда на передачу, не уходя ниже резерва
+    pub fn has_energy_for(&self, bytes: usize, proto: &RadioProtocol) -> bool {
+        !self.is_battery_powered()
+            || self.battery_pct as f64 - proto.relay_energy_mj(bytes) / BATTERY_PCT_MJ
+                >= RELAY_BATTERY_RESERVE as f64
+    }
+
     pub fn can_relay(&self, payload_size: usize) -> bool {
         self.mesh_enabled && self.firmware_patched &&
-        self.protocols.iter().any(|p| p.max_payload() >= payload_size)
+        self.protocols.iter().any(|p| p.max_payload() >= payload_size
+            && self.has_energy_for(payload_size, p))
     }
 
     pub fn signal_strength_to(&self, other: &DroidNode) -> f32 {
@@ -198,6 +293,8 @@ pub struct StealthPacket {
     pub hop_ttl: u8,
     pub next_hop: Option<String>,
     pub checksum: u32,
+    pub cover_droid: DroidType,  // чей трафик изображаем
+    pub protocol: RadioProtocol,
 }
 
 impl StealthPacket {
@@ -208,10 +305,19 @@ impl StealthPacket {
             return None; // данные не влезают
         }
 
+        // Размер cover — типичный для устройства, но payload должен
+        // поместиться после offset = треть cover
+        let (typ_min, typ_max) = droid.droid_type.typical_message_bytes();
+        let needed = (federation_data.len() * 3).div_ceil(2);
+        let cover_size = 128usize.clamp(typ_min, typ_max).max(needed);
+        if cover_size > proto.max_payload()
+            || Self::plausibility(&droid.droid_type, proto, cover_size) < STEALTH_MIN_PLAUSIBILITY {
+            return None; // такое сообщение от этого дроида подозрительно
+        }
+
         *rng ^= *rng << 13; *rng ^= *rng >> 7; *rng ^= *rng << 17;
 
         // Генерируем правдоподобные служебные данные
-        let cover_size = proto.max_payload().min(128);
         let mut cover_data: Vec<u8> = (0..cover_size)
             .map(|_| { *rng ^= *rng << 17; (*rng & 0xff) as u8 }).collect();
 
@@ -230,9 +336,25 @@ impl StealthPacket {
             cover_data, hidden_payload: federation_data.to_vec(),
             hidden_offset: offset, hop_ttl: MESH_HOP_TTL,
             next_hop: None, checksum,
+            cover_droid: droid.droid_type.clone(), protocol: proto.clone(),
         })
     }
 
+    // 1.0 — размер в типичном диапазоне устройства, дальше падает
+    // пропорционально отклонению; больше кадра протокола — 0
+    fn plausibility(droid: &DroidType, proto: &RadioProtocol, size: usize) -> f64 {
+        if size > proto.max_payload() || size == 0 { return 0.0; }
+        let (typ_min, typ_max) = droid.typical_message_bytes();
+        if size > typ_max { typ_max as f64 / size as f64 }
+        else if size < typ_min { size as f64 / typ_min as f64 }
+        else { 1.0 }
+    }
+
+    /// Насколько cover похож на обычный трафик этого дроида, 0..1
+    pub fn plausibility_score(&self) -> f64 {
+        Self::plausibility(&self.cover_droid, &self.protocol, self.cover_data.len())
+    }
+
     pub fn extract(&self) -> Vec<u8> {
         self.hidden_payload.clone()
     }
@@ -255,6 +377,10 @@ pub struct HomeBastion {
     pub packets_relayed: u64,
     pub bytes_relayed: u64,
     pub neighbors: Vec<String>,  // соседние квартиры
+    pub duplicates_dropped: u64,
+    pub energy_spent_mj: f64,
+    seen_packets: VecDeque<u64>, // недавно ретранслированные packet id
+    battery_residue: HashMap<String, f64>, // дробный остаток расхода, %
     rng: u64,
 }
 
@@ -269,10 +395,43 @@ impl HomeBastion {
             packets_relayed: 0,
             bytes_relayed: 0,
             neighbors: vec![],
+            duplicates_dropped: 0,
+            energy_spent_mj: 0.0,
+            seen_packets: VecDeque::new(),
+            battery_residue: HashMap::new(),
             rng: 0xBA57_F33D_CAFE_0000,
         }
     }
 
+    /// Отмечает пакет как увиденный. false — дубликат, пакет отброшен
+    pub fn accept_packet(&mut self, packet_id: u64) -> bool {
+        if self.seen_packets.contains(&packet_id) {
+            self.duplicates_dropped += 1;
+            return false;
+        }
+        if self.seen_packets.len() >= SEEN_CACHE_SIZE {
+            self.seen_packets.pop_front();
+        }
+        self.seen_packets.push_back(packet_id);
+        true
+    }
+
+    /// Списывает энергию передачи с дроида. Батарейный теряет заряд
+    /// пропорционально байтам и цене протокола, сетевой — только учёт
+    pub fn spend_relay_energy(&mut self, droid_id: &str, proto: &RadioProtocol, bytes: usize) {
+        let Some(droid) = self.droids.get_mut(droid_id) else { return };
+        let mj = proto.relay_energy_mj(bytes);
+        self.energy_spent_mj += mj;
+        droid.relay_count += 1;
+        droid.bytes_relayed += bytes as u64;
+        if !droid.is_battery_powered() { return; }
+        let residue = self.battery_residue.entry(droid_id.to_string()).or_insert(0.0);
+        *residue += mj / BATTERY_PCT_MJ;
+        let whole = residue.floor();
+        *residue -= whole;
+        droid.battery_pct = droid.battery_pct.saturating_sub(whole as u8);
+    }
+
     pub fn add_droid(&mut self, droid: DroidNode) {
         if droid.mesh_enabled && droid.firmware_patched {
             self.mesh_active = true;
@@ -280,34 +439,23 @@ impl HomeBastion {
         self.droids.insert(droid.droid_id.clone(), droid);
     }
 
-    pub fn best_relay(&self, payload_size: usize) -> Option<&DroidNode> {
-        self.droids.values()
-            .filter(|d| d.can_relay(payload_size))
-            .max_by(|a, b| {
-                let score_a = a.droid_type.uptime_pct()
-                    * a.best_protocol().map(|p| p.max_payload() as f64).unwrap_or(0.0);
-                let score_b = b.droid_type.uptime_pct()
-                    * b.best_protocol().map(|p| p.max_payload() as f64).unwrap_or(0.0);
-                score_a.partial_cmp(&score_b).unwrap()
-            })
-    }
-
-    pub fn relay_packet(&mut self, data: &[u8]) -> RelayResult {
-        // Собираем данные без borrow на self
-        let relay_info = self.best_relay(data.len()).map(|droid| {
-            let proto = droid.best_protocol().unwrap();
-            let latency = match proto {
-                RadioProtocol::Bluetooth5  => 5,
-                RadioProtocol::BluetoothLE => 15,
-                RadioProtocol::Zigbee      => 30,
-                RadioProtocol::Thread      => 20,
-                RadioProtocol::WiFiDirect  => 2,
-                _                          => 25,
-            };
-            (droid.droid_id.clone(), proto.name().to_string(),
-             latency, droid.droid_type.stealth_cover().to_string())
-        });
+    /// Передача соседскому дроиду — протокол согласуется с ним,
+    /// а не выбирается по возможностям только нашего дроида
+    pub fn relay_packet(&mut self, data: &[u8], peer: &DroidNode) -> RelayResult {
+        let relay_info = self.droids.values()
+            .filter(|d| d.mesh_enabled && d.firmware_patched)
+            .filter_map(|d| d.negotiate_protocol(peer)
+                .filter(|p| p.max_payload() >= data.len() && d.has_energy_for(data.len(), p))
+                .map(|p| (d, p)))
+            .max_by(|(da, pa), (db, pb)| pa.preference(false).cmp(&pb.preference(false))
+                .then(da.droid_id.cmp(&db.droid_id).reverse()))
+            .map(|(droid, proto)| (droid.droid_id.clone(), proto,
+                droid.droid_type.stealth_cover().to_string()));
+        self.finish_relay(data, relay_info)
+    }
 
+    fn finish_relay(&mut self, data: &[u8],
+                    relay_info: Option<(String, RadioProtocol, String)>) -> RelayResult {
         match relay_info {
             None => RelayResult {
                 success: false, droid_id: "none".into(),
@@ -315,12 +463,13 @@ impl HomeBastion {
                 stealth_cover: "none".into(),
                 reason: "нет подходящего дроида".into(),
             },
-            Some((droid_id, protocol, latency_ms, stealth_cover)) => {
+            Some((droid_id, proto, stealth_cover)) => {
                 self.packets_relayed += 1;
                 self.bytes_relayed += data.len() as u64;
+                self.spend_relay_energy(&droid_id, &proto, data.len());
                 RelayResult {
-                    success: true, droid_id, protocol,
-                    latency_ms, stealth_cover, reason: "OK".into(),
+                    success: true, droid_id, protocol: proto.name().to_string(),
+                    latency_ms: proto.hop_latency_ms(), stealth_cover, reason: "OK".into(),
                 }
             }
         }
@@ -384,13 +533,14 @@ pub struct CityMesh {
     pub bastions: HashMap<String, HomeBastion>,
     pub total_relayed: u64,
     pub active_routes: Vec<(String, String, Vec<String>)>, // from→to via droids
+    next_packet_id: u64,
 }
 
 impl CityMesh {
     pub fn new(city: &str) -> Self {
         CityMesh { city: city.to_string(),
             bastions: HashMap::new(),
-            total_relayed: 0, active_routes: vec![] }
+            total_relayed: 0, active_routes: vec![], next_packet_id: 1 }
     }
 
     pub fn add_bastion(&mut self, bastion: HomeBastion) {
@@ -406,40 +556,129 @@ impl CityMesh {
         }
     }
 
+    /// Протокол связи между двумя бастионами: лучший из согласованных
+    /// между их активными дроидами. None — соседи друг друга не слышат
+    pub fn link_protocol(&self, apt_a: &str, apt_b: &str) -> Option<RadioProtocol> {
+        self.link(apt_a, apt_b, 0).map(|(_, proto)| proto)
+    }
+
+    // Дроид-отправитель в apt_a и протокол звена для bytes байт.
+    // Батарейные дроиды у резерва в звенья не берутся
+    fn link(&self, apt_a: &str, apt_b: &str, bytes: usize) -> Option<(String, RadioProtocol)> {
+        let a = self.bastions.get(apt_a)?;
+        let b = self.bastions.get(apt_b)?;
+        let active = |bastion: &HomeBastion| bastion.droids.values()
+            .filter(|d| d.mesh_enabled && d.firmware_patched)
+            .cloned().collect::<Vec<_>>();
+        let (da, db) = (active(a), active(b));
+        da.iter()
+            .flat_map(|x| db.iter().filter_map(move |y| x.negotiate_mesh_protocol(y)
+                .filter(|p| x.has_energy_for(bytes, p))
+                .map(|p| (x.droid_id.clone(), p))))
+            .max_by(|(ia, pa), (ib, pb)| pa.preference(true).cmp(&pb.preference(true))
+                .then(ia.cmp(ib).reverse()))
+    }
+
+    // Передача по звену: энергия списывается с дроида-отправителя
+    fn transmit(&mut self, apt_a: &str, apt_b: &str, bytes: usize) -> Option<RadioProtocol> {
+        let (droid_id, proto) = self.link(apt_a, apt_b, bytes)?;
+        if let Some(b) = self.bastions.get_mut(apt_a) {
+            b.spend_relay_energy(&droid_id, &proto, bytes);
+        }
+        Some(proto)
+    }
+
     pub fn route_through_mesh(&mut self, from: &str, to: &str,
-                               _data: &[u8]) -> MeshRouteResult {
-        // BFS по бастионам
-        let mut visited = std::collections::HashSet::new();
-        let mut queue = std::collections::VecDeque::new();
-        queue.push_back((from.to_string(), vec![from.to_string()]));
-        visited.insert(from.to_string());
-
-        while let Some((current, path)) = queue.pop_front() {
+                               data: &[u8]) -> MeshRouteResult {
+        // Флуд по бастионам — только по звеньям с общим протоколом.
+        // Каждый бастион пересылает packet id один раз, повторы отбрасывает
+        let packet_id = self.next_packet_id;
+        self.next_packet_id += 1;
+        if let Some(b) = self.bastions.get_mut(from) { b.accept_packet(packet_id); }
+
+        let mut queue = VecDeque::new();
+        queue.push_back((from.to_string(), vec![from.to_string()], 0u32));
+        let mut delivered: Option<(Vec<String>, u32)> = None;
+
+        while let Some((current, path, latency)) = queue.pop_front() {
             if current == to {
+                // Получатель дальше не пересылает
+                if delivered.is_none() { delivered = Some((path, latency)); }
+                continue;
+            }
+            if path.len() >= MESH_HOP_TTL as usize { continue; }
+
+            let sender = path.len().checked_sub(2).map(|i| path[i].clone());
+            let neighbors = self.bastions.get(&current)
+                .map(|b| b.neighbors.clone()).unwrap_or_default();
+            for neighbor in neighbors {
+                if sender.as_ref() == Some(&neighbor) { continue; }
+                let Some(proto) = self.transmit(&current, &neighbor, data.len()) else { continue };
+                let fresh = self.bastions.get_mut(&neighbor)
+                    .map(|b| b.accept_packet(packet_id)).unwrap_or(false);
+                if !fresh { continue; }
+                let mut new_path = path.clone();
+                new_path.push(neighbor.clone());
+                queue.push_back((neighbor, new_path, latency + proto.hop_latency_ms()));
+            }
+        }
+
+        match delivered {
+            Some((path, latency)) => {
                 self.total_relayed += 1;
-                return MeshRouteResult {
+                MeshRouteResult {
                     success: true, hops: path.len() as u8 - 1,
-                    path: path.clone(), latency_ms: path.len() as u32 * 15,
+                    path, latency_ms: latency,
                     reason: "route_found".into(),
-                };
+                }
             }
-            if path.len() >= MESH_HOP_TTL as usize { continue; }
+            None => MeshRouteResult {
+                success: false, hops: 0, path: vec![],
+                latency_ms: 0, reason: "no_route".into(),
+            },
+        }
+    }
 
+    /// Флуд Pulse всем достижимым бастионам. Каждая передача по звену —
+    /// одна ретрансляция дроидом; повторный приём отбрасывается по packet id
+    pub fn broadcast_pulse(&mut self, from: &str, pulse_bytes: &[u8]) -> BroadcastResult {
+        let packet_id = self.next_packet_id;
+        self.next_packet_id += 1;
+        let mut result = BroadcastResult { packet_id, origin: from.to_string(),
+            reached: Vec::new(), hops: HashMap::new(), droid_relays: 0, coverage: 0.0 };
+        match self.bastions.get_mut(from) {
+            Some(b) => { b.accept_packet(packet_id); }
+            None => return result,
+        }
+
+        let mut queue = VecDeque::new();
+        queue.push_back((from.to_string(), None::<String>, 0u8));
+        while let Some((current, sender, hops)) = queue.pop_front() {
+            if hops >= MESH_HOP_TTL { continue; }
             let neighbors = self.bastions.get(&current)
                 .map(|b| b.neighbors.clone()).unwrap_or_default();
             for neighbor in neighbors {
-                if !visited.contains(&neighbor) {
-                    visited.insert(neighbor.clone());
-                    let mut new_path = path.clone();
-                    new_path.push(neighbor.clone());
-                    queue.push_back((neighbor, new_path));
+                if sender.as_ref() == Some(&neighbor) { continue; }
+                if self.transmit(&current, &neighbor, pulse_bytes.len()).is_none() { continue; }
+                result.droid_relays += 1;
+                if let Some(b) = self.bastions.get_mut(&current) {
+                    b.packets_relayed += 1;
+                    b.bytes_relayed += pulse_bytes.len() as u64;
                 }
+                let fresh = self.bastions.get_mut(&neighbor)
+                    .map(|b| b.accept_packet(packet_id)).unwrap_or(false);
+                if !fresh { continue; }
+                result.reached.push(neighbor.clone());
+                result.hops.insert(neighbor.clone(), hops + 1);
+                queue.push_back((neighbor, Some(current.clone()), hops + 1));
             }
         }
-        MeshRouteResult {
-            success: false, hops: 0, path: vec![],
-            latency_ms: 0, reason: "no_route".into(),
-        }
+
+        result.reached.sort();
+        result.coverage = result.reached.len() as f64
+            / self.bastions.len().saturating_sub(1).max(1) as f64;
+        if !result.reached.is_empty() { self.total_relayed += 1; }
+        result
     }
 
     pub fn city_stats(&self) -> CityStats {
@@ -452,6 +691,10 @@ impl CityMesh {
             total_bastions: self.bastions.len(),
             active_bastions, total_droids,
             total_relayed: self.total_relayed,
+            duplicates_dropped: self.bastions.values()
+                .map(|b| b.duplicates_dropped).sum(),
+            energy_spent_mj: self.bastions.values()
+                .map(|b| b.energy_spent_mj).sum(),
         }
     }
 }
@@ -463,6 +706,16 @@ pub struct MeshRouteResult {
     pub reason: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BroadcastResult {
+    pub packet_id: u64,
+    pub origin: String,
+    pub reached: Vec<String>,          // бастионы, получившие Pulse (без origin)
+    pub hops: HashMap<String, u8>,     // хопов до каждого из reached
+    pub droid_relays: u64,             // передач по звеньям, включая дубликаты
+    pub coverage: f64,                 // доля остальных бастионов города
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CityStats {
     pub city: String,
@@ -470,4 +723,170 @@ pub struct CityStats {
     pub active_bastions: usize,
     pub total_droids: usize,
     pub total_relayed: u64,
+    pub duplicates_dropped: u64,
+    pub energy_spent_mj: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn droid(id: &str, apt: &str, protocols: Vec<RadioProtocol>) -> DroidNode {
+        DroidNode {
+            droid_id: id.into(), droid_type: DroidType::Hub, protocols,
+            apartment_id: apt.into(), floor: 1, position_x: 0.0, position_y: 0.0,
+            battery_pct: 255, firmware_patched: true, mesh_enabled: true,
+            relay_count: 0, bytes_relayed: 0,
+        }
+    }
+
+    #[test]
+    fn test_negotiate_protocol_shared_pick() {
+        let speaker = droid("speaker", "a", vec![RadioProtocol::BluetoothLE, RadioProtocol::Zigbee]);
+        let lock = droid("lock", "b", vec![RadioProtocol::Zigbee, RadioProtocol::BluetoothLE,
+            RadioProtocol::Bluetooth5]);
+        // Прямая связь — быстрее BLE, меш — Zigbee
+        assert_eq!(speaker.negotiate_protocol(&lock), Some(RadioProtocol::BluetoothLE));
+        assert_eq!(lock.negotiate_protocol(&speaker), Some(RadioProtocol::BluetoothLE));
+        assert_eq!(speaker.negotiate_mesh_protocol(&lock), Some(RadioProtocol::Zigbee));
+
+        let mut home = HomeBastion::new("a", "node", 1);
+        home.add_droid(speaker.clone());
+        let r = home.relay_packet(b"PULSE", &lock);
+        assert!(r.success);
+        assert_eq!(r.protocol, "BLE");
+    }
+
+    #[test]
+    fn test_negotiate_protocol_no_common() {
+        let fridge = droid("fridge", "a", vec![RadioProtocol::WiFiDirect]);
+        let hub = droid("hub", "b", vec![RadioProtocol::Thread]);
+        assert_eq!(fridge.negotiate_protocol(&hub), None);
+
+        let mut city = CityMesh::new("test");
+        for (apt, d) in [("a", fridge), ("b", hub)] {
+            let mut b = HomeBastion::new(apt, "node", 1);
+            b.add_droid(d);
+            city.add_bastion(b);
+        }
+        city.connect_neighbors("a", "b");
+        assert!(city.link_protocol("a", "b").is_none());
+        assert!(!city.route_through_mesh("a", "b", b"x").success);
+    }
+
+    // Топология демо: кольцо 042-087-103-156 с хвостом до 201
+    fn moscow() -> CityMesh {
+        let mut city = CityMesh::new("test");
+        for apt in ["apt_042", "apt_087", "apt_103", "apt_156", "apt_201"] {
+            let mut b = HomeBastion::new(apt, "node", 1);
+            b.add_droid(droid(&format!("hub_{}", apt), apt, vec![RadioProtocol::Thread]));
+            city.add_bastion(b);
+        }
+        city.connect_neighbors("apt_042", "apt_087");
+        city.connect_neighbors("apt_087", "apt_103");
+        city.connect_neighbors("apt_103", "apt_156");
+        city.connect_neighbors("apt_156", "apt_201");
+        city.connect_neighbors("apt_042", "apt_156"); // петля
+        city
+    }
+
+    #[test]
+    fn test_broadcast_reaches_connected_bastions_only() {
+        let mut city = moscow();
+        // Изолированный бастион: сосед есть, но общего протокола нет
+        let mut lonely = HomeBastion::new("apt_300", "node", 3);
+        lonely.add_droid(droid("wifi_fridge", "apt_300", vec![RadioProtocol::WiFiDirect]));
+        city.add_bastion(lonely);
+        city.connect_neighbors("apt_201", "apt_300");
+
+        let r = city.broadcast_pulse("apt_042", b"PULSE");
+        assert_eq!(r.reached, vec!["apt_087", "apt_103", "apt_156", "apt_201"]);
+        assert!(!r.reached.contains(&"apt_300".to_string()));
+        assert_eq!(r.hops["apt_087"], 1);
+        assert_eq!(r.hops["apt_201"], 2);
+        assert_eq!(r.hops["apt_103"], 2);
+        assert!((r.coverage - 0.8).abs() < 1e-9);
+        // Кольцо: 103 слышит пакет дважды — лишняя передача учтена, приём нет
+        assert_eq!(r.droid_relays, 6);
+        assert!(city.city_stats().duplicates_dropped > 0);
+    }
+
+    #[test]
+    fn test_battery_droid_drains_and_fails_over_to_ac() {
+        let mut lock = droid("lock", "a", vec![RadioProtocol::Bluetooth5]);
+        lock.battery_pct = 30;
+        let mut home = HomeBastion::new("a", "node", 1);
+        home.add_droid(lock);
+        home.add_droid(droid("hub", "a", vec![RadioProtocol::Zigbee]));
+        let peer = droid("peer", "b", vec![RadioProtocol::Bluetooth5, RadioProtocol::Zigbee]);
+        assert_eq!(home.relay_packet(&[0u8; 80], &peer).droid_id, "lock");
+        let mut failed_over = false;
+        for _ in 0..200 {
+            if home.relay_packet(&[0u8; 80], &peer).droid_id == "hub" { failed_over = true; break; }
+        }
+        assert!(failed_over);
+        assert!(home.droids["lock"].battery_pct >= RELAY_BATTERY_RESERVE);
+        assert!(!home.droids["lock"].can_relay(80));
+        assert_eq!(home.droids["hub"].battery_pct, BATTERY_AC);
+
+        // В меше: Thread-звено батарейного дроида сменяется Zigbee сетевого
+        let mut city = CityMesh::new("test");
+        let mut sensor = droid("sensor", "a", vec![RadioProtocol::Thread]);
+        sensor.battery_pct = 25;
+        let mut a = HomeBastion::new("a", "node", 1);
+        a.add_droid(sensor);
+        a.add_droid(droid("hub", "a", vec![RadioProtocol::Zigbee]));
+        let mut b = HomeBastion::new("b", "node", 1);
+        b.add_droid(droid("hub_b", "b", vec![RadioProtocol::Thread, RadioProtocol::Zigbee]));
+        city.add_bastion(a);
+        city.add_bastion(b);
+        city.connect_neighbors("a", "b");
+        assert_eq!(city.link_protocol("a", "b"), Some(RadioProtocol::Thread));
+        for _ in 0..200 { assert!(city.route_through_mesh("a", "b", &[0u8; 80]).success); }
+        let r = city.route_through_mesh("a", "b", &[0u8; 80]);
+        assert_eq!(r.latency_ms, RadioProtocol::Zigbee.hop_latency_ms());
+        assert!(city.bastions["a"].droids["sensor"].battery_pct >= RELAY_BATTERY_RESERVE);
+        assert!(city.bastions["a"].droids["hub"].relay_count > 0);
+        assert!(city.city_stats().energy_spent_mj > 0.0);
+    }
+
+    #[test]
+    fn test_stealth_plausibility_by_cover_size() {
+        let mut fridge = droid("fridge", "a", vec![RadioProtocol::WiFiDirect]);
+        fridge.droid_type = DroidType::Fridge;
+        let mut rng = 0x5EED_u64;
+        let pkt = StealthPacket::embed(b"PULSE:id=42", &fridge, &mut rng).unwrap();
+        assert_eq!(pkt.cover_type, "температурные логи");
+        assert_eq!(pkt.plausibility_score(), 1.0);
+
+        // 2 КБ «температурного лога» — аномалия
+        let mut bloated = pkt.clone();
+        bloated.cover_data = vec![0u8; 2048];
+        assert!(bloated.plausibility_score() < STEALTH_MIN_PLAUSIBILITY);
+        assert!(StealthPacket::embed(&[0u8; 1400], &fridge, &mut rng).is_none());
+
+        // Тот же объём от телевизора — обычный трафик
+        let mut tv = fridge.clone();
+        tv.droid_type = DroidType::TV;
+        let big = StealthPacket::embed(&[0u8; 1400], &tv, &mut rng).unwrap();
+        assert!(big.plausibility_score() > 0.9);
+        assert_eq!(big.extract().len(), 1400);
+    }
+
+    #[test]
+    fn test_mesh_loop_drops_duplicates() {
+        let mut city = moscow();
+
+        let r = city.route_through_mesh("apt_042", "apt_201", b"PULSE");
+        assert!(r.success);
+        assert_eq!(r.path, vec!["apt_042", "apt_156", "apt_201"]);
+        let stats = city.city_stats();
+        assert_eq!(stats.total_relayed, 1);
+        // Петля вернула пакет в уже пройденные бастионы — повторы отброшены
+        assert!(stats.duplicates_dropped > 0);
+
+        // Новый пакет получает новый id и проходит заново
+        assert!(city.route_through_mesh("apt_042", "apt_201", b"PULSE").success);
+        assert_eq!(city.city_stats().total_relayed, 2);
+    }
 }
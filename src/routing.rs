@@ -1,3 +1,7 @@
+use crate::ethics::{EthicsLayer, Frozen, MODULE_AI_ROUTER};
+use crate::chacha::{AeadCiphertext, ChaCha20Poly1305, KEY_SIZE, NONCE_SIZE, TAG_SIZE};
+use crate::noise::{dh, generate_keypair_from, hash, DHLEN};
+use crate::rng::{RngCore, XorShift64};
 use crate::tensor::{shannon_entropy, SsauTensor, TrustRegistry};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -140,7 +144,19 @@ impl AiRouter {
         AiRouter { route_cache: HashMap::new(), active_entropy: HashMap::new() }
     }
 
+    /// Выбор маршрута под KillSwitch: замороженный ai_router не выбирает маршрут
     pub fn select_route(
+        &mut self,
+        ethics: &EthicsLayer,
+        destination: &str,
+        candidates: Vec<RouteCandidate>,
+        priorities: &UserPriorities,
+    ) -> Result<RoutingDecision, Frozen> {
+        ethics.guard(MODULE_AI_ROUTER)?;
+        Ok(self.select_route_unguarded(destination, candidates, priorities))
+    }
+
+    fn select_route_unguarded(
         &mut self,
         destination: &str,
         mut candidates: Vec<RouteCandidate>,
@@ -273,3 +289,188 @@ fn dfs_paths(
     }
     visited.remove(current);
 }
+
+// Onion-маршрутизация: каждый хоп снимает ровно один слой и видит только следующий хоп.
+// Ключ слоя = hash(X25519(ephemeral, hop_static) || label), свежий ephemeral и случайный
+// nonce на каждый слой; без приватного ключа хопа слой не вскрыть.
+
+const ONION_KEY_LABEL: &[u8] = b"federation-onion-layer";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnionPacket {
+    pub ephemeral_pub: [u8; DHLEN],
+    pub nonce: [u8; NONCE_SIZE],
+    pub ciphertext: Vec<u8>,
+    pub tag: [u8; TAG_SIZE],
+}
+
+impl OnionPacket {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(DHLEN + NONCE_SIZE + self.ciphertext.len() + TAG_SIZE);
+        out.extend_from_slice(&self.ephemeral_pub);
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(&self.ciphertext);
+        out.extend_from_slice(&self.tag);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() < DHLEN + NONCE_SIZE + TAG_SIZE { return Err("onion packet too short"); }
+        let mut ephemeral_pub = [0u8; DHLEN];
+        ephemeral_pub.copy_from_slice(&bytes[..DHLEN]);
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(&bytes[DHLEN..DHLEN + NONCE_SIZE]);
+        let mut tag = [0u8; TAG_SIZE];
+        tag.copy_from_slice(&bytes[bytes.len() - TAG_SIZE..]);
+        Ok(OnionPacket {
+            ephemeral_pub,
+            nonce,
+            ciphertext: bytes[DHLEN + NONCE_SIZE..bytes.len() - TAG_SIZE].to_vec(),
+            tag,
+        })
+    }
+}
+
+fn onion_layer_key(shared: &[u8; DHLEN]) -> [u8; KEY_SIZE] {
+    let mut input = shared.to_vec();
+    input.extend_from_slice(ONION_KEY_LABEL);
+    hash(&input)
+}
+
+pub struct OnionBuilder {
+    rng: XorShift64,
+}
+
+impl OnionBuilder {
+    pub fn new(seed: u64) -> Self {
+        OnionBuilder { rng: XorShift64::new(seed) }
+    }
+
+    /// Оборачивает `payload` для маршрута `hops` = [(node_id, static_pub), ...] по порядку.
+    /// Слой i содержит id хопа i+1; последний слой — пустой id (выход).
+    pub fn wrap(&mut self, payload: &[u8], hops: &[(String, [u8; DHLEN])]) -> Option<OnionPacket> {
+        let mut inner = payload.to_vec();
+        let mut packet = None;
+        for i in (0..hops.len()).rev() {
+            let next_hop = hops.get(i + 1).map(|(id, _)| id.as_str()).unwrap_or("");
+            if next_hop.len() > u16::MAX as usize { return None; }
+            let mut layer = Vec::with_capacity(2 + next_hop.len() + inner.len());
+            layer.extend_from_slice(&(next_hop.len() as u16).to_le_bytes());
+            layer.extend_from_slice(next_hop.as_bytes());
+            layer.extend_from_slice(&inner);
+
+            let (e_priv, e_pub) = generate_keypair_from(&mut self.rng);
+            let key = onion_layer_key(&dh(&e_priv, &hops[i].1));
+            let mut nonce = [0u8; NONCE_SIZE];
+            for b in &mut nonce {
+                *b = (self.rng.next_u64() & 0xff) as u8;
+            }
+            let ct = ChaCha20Poly1305::new(key).seal(&layer, &e_pub, &nonce);
+            let p = OnionPacket { ephemeral_pub: e_pub, nonce, ciphertext: ct.ciphertext, tag: ct.tag };
+            inner = p.to_bytes();
+            packet = Some(p);
+        }
+        packet
+    }
+}
+
+pub struct OnionPeeler;
+
+impl OnionPeeler {
+    /// Снимает один слой. Для релея — `(Some(next_hop), байты_внутреннего_пакета)`,
+    /// для выходного узла — `(None, plaintext)`.
+    pub fn peel(packet: &OnionPacket, my_priv: &[u8; DHLEN])
+        -> Result<(Option<String>, Vec<u8>), &'static str> {
+        let key = onion_layer_key(&dh(my_priv, &packet.ephemeral_pub));
+        let ct = AeadCiphertext {
+            nonce: packet.nonce,
+            ciphertext: packet.ciphertext.clone(),
+            tag: packet.tag,
+            aad_len: DHLEN,
+        };
+        let layer = ChaCha20Poly1305::new(key).open(&ct, &packet.ephemeral_pub)?;
+        if layer.len() < 2 { return Err("onion layer too short"); }
+        let id_len = u16::from_le_bytes([layer[0], layer[1]]) as usize;
+        if layer.len() < 2 + id_len { return Err("onion layer truncated"); }
+        let inner = layer[2 + id_len..].to_vec();
+        if id_len == 0 { return Ok((None, inner)); }
+        let next_hop = String::from_utf8(layer[2..2 + id_len].to_vec())
+            .map_err(|_| "onion next hop is not utf-8")?;
+        Ok((Some(next_hop), inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise::generate_keypair;
+
+    #[test]
+    fn test_frozen_router_refuses_until_thaw() {
+        let mut ethics = EthicsLayer::new();
+        let mut router = AiRouter::new();
+        let route = || vec![RouteCandidate::from_tensors(vec![], vec!["a".into(), "b".into()])];
+        let prio = UserPriorities::balanced();
+        assert!(router.select_route(&ethics, "b", route(), &prio).is_ok());
+
+        ethics.kill_switch.sovereign_freeze("DAO-7", "audit", vec![MODULE_AI_ROUTER.into()]);
+        let err = router.select_route(&ethics, "c", route(), &prio).unwrap_err();
+        assert_eq!(err.module, MODULE_AI_ROUTER);
+        assert!(err.reason.contains("DAO:DAO-7"));
+        assert!(!router.route_cache.contains_key("c"));
+
+        assert!(ethics.kill_switch.thaw("DAO-8"));
+        let decision = router.select_route(&ethics, "b", route(), &prio).unwrap();
+        assert!(decision.chosen_route.is_some());
+    }
+
+    #[test]
+    fn test_onion_three_hops() {
+        let keys: Vec<([u8; DHLEN], [u8; DHLEN])> = (1..=3).map(|s| generate_keypair(s * 7919)).collect();
+        let hops: Vec<(String, [u8; DHLEN])> = keys.iter().enumerate()
+            .map(|(i, (_, pubkey))| (format!("node_{}", i), *pubkey)).collect();
+        let packet = OnionBuilder::new(42).wrap(b"secret pulse", &hops).unwrap();
+
+        let (next, inner) = OnionPeeler::peel(&packet, &keys[0].0).unwrap();
+        assert_eq!(next.as_deref(), Some("node_1"));
+        assert!(!inner.windows(6).any(|w| w == b"node_2"));
+        assert!(!inner.windows(6).any(|w| w == b"secret"));
+
+        let packet = OnionPacket::from_bytes(&inner).unwrap();
+        let (next, inner) = OnionPeeler::peel(&packet, &keys[1].0).unwrap();
+        assert_eq!(next.as_deref(), Some("node_2"));
+        assert!(!inner.windows(6).any(|w| w == b"secret"));
+
+        let packet = OnionPacket::from_bytes(&inner).unwrap();
+        let (next, inner) = OnionPeeler::peel(&packet, &keys[2].0).unwrap();
+        assert_eq!(next, None);
+        assert_eq!(inner, b"secret pulse");
+    }
+
+    #[test]
+    fn test_onion_wrong_key_fails() {
+        let (_, pub_a) = generate_keypair(11);
+        let (priv_b, _) = generate_keypair(12);
+        let packet = OnionBuilder::new(7).wrap(b"x", &[("a".to_string(), pub_a)]).unwrap();
+        assert!(OnionPeeler::peel(&packet, &priv_b).is_err());
+    }
+
+    #[test]
+    fn test_onion_layer_needs_hop_private_key() {
+        let (priv_a, pub_a) = generate_keypair(21);
+        let (_, pub_b) = generate_keypair(22);
+        let hops = vec![("a".to_string(), pub_a), ("b".to_string(), pub_b)];
+        let packet = OnionBuilder::new(5).wrap(b"payload", &hops).unwrap();
+
+        // Наблюдатель знает оба публичных ключа — этого недостаточно для ключа слоя
+        let observer_key = onion_layer_key(&dh(&packet.ephemeral_pub, &pub_a));
+        let ct = AeadCiphertext { nonce: packet.nonce, ciphertext: packet.ciphertext.clone(),
+            tag: packet.tag, aad_len: DHLEN };
+        assert!(ChaCha20Poly1305::new(observer_key).open(&ct, &packet.ephemeral_pub).is_err());
+
+        let (_, inner) = OnionPeeler::peel(&packet, &priv_a).unwrap();
+        let inner = OnionPacket::from_bytes(&inner).unwrap();
+        assert_ne!(inner.nonce, packet.nonce);
+        assert_ne!(inner.ephemeral_pub, packet.ephemeral_pub);
+    }
+}
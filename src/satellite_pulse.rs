@@ -17,14 +17,27 @@
 //   5. BlackoutMode   — режим выживания при полном блэкауте
 // =============================================================================
 
+use crate::error::FederationError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 pub const PULSE_MAX_BYTES: usize      = 256;   // максимум байт на Pulse
+pub const PULSE_VERSION: u8           = 1;     // версия бинарного формата
+pub const PULSE_FIXED_BYTES: usize    = 87;    // версия + поля до sender_node
+pub const PULSE_NODE_BYTES: usize     = 16;    // sender_node усечён до 16 байт
+pub const PULSE_ENCODED_MAX: usize    = (PULSE_FIXED_BYTES + PULSE_NODE_BYTES).div_ceil(8) * 8;
 pub const RADIO_FRAME_OVERHEAD: usize = 32;    // заголовок RadioFrame
 pub const SAT_LATENCY_MS: u64         = 600;   // задержка Starlink ~600мс
 pub const SAT_BANDWIDTH_BPS: u64      = 9_600; // 9.6 kbps — Iridium минимум
 pub const PULSE_INTERVAL_SECS: u64    = 300;   // пульс каждые 5 минут
 pub const BLACKOUT_THRESHOLD: f64     = 0.95;  // >95% узлов недоступны
+pub const STRATEGY_MARGIN: f64        = 0.02;  // запас за границей стратегии
+pub const STRATEGY_DWELL_UPDATES: u32 = 3;     // замеров подряд до смены
+pub const PULSE_REPLAY_WINDOW: u64    = 32;    // окно переупорядочивания pulse_id
+pub const PULSE_MAX_SENDERS: usize    = 256;   // окон повтора на станцию, старые вытесняются
+
+// Границы доли недоступных узлов: Normal | Reduced | Satellite | Ghost | LastResort
+const STRATEGY_BOUNDS: [f64; 4] = [0.50, 0.80, BLACKOUT_THRESHOLD, 0.99];
 
 // -----------------------------------------------------------------------------
 // SatelliteProvider — тип спутниковой связи
@@ -39,15 +52,31 @@ pub enum SatelliteProvider {
     Proprietary(String),
 }
 
+/// Параметры канала провайдера для симуляции SatelliteLink
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelModel {
+    pub base_latency_ms: u64,
+    pub jitter_ms: u64,        // случайная добавка 0..jitter к задержке
+    pub loss_prob: f64,
+    pub capacity_bytes: usize, // максимальный кадр (payload + заголовок)
+    pub duty_cycle: f64,       // доля эфирного времени; 1.0 = без ограничений
+}
+
 impl SatelliteProvider {
+    pub fn channel_model(&self) -> ChannelModel {
+        let (base_latency_ms, jitter_ms, loss_prob, capacity_bytes, duty_cycle) = match self {
+            SatelliteProvider::Starlink       => ( 600,  50, 0.05, 65536, 1.0),
+            SatelliteProvider::Iridium        => (1500, 200, 0.15,   256, 1.0),
+            // Геостационар: большой канал, но долгий путь и разброс
+            SatelliteProvider::Viasat         => ( 800, 400, 0.08, 32768, 1.0),
+            // Любительское радио: крошечный кадр, всплески потерь, 10% эфира
+            SatelliteProvider::Amateur        => (2000, 800, 0.30,    64, 0.10),
+            SatelliteProvider::Proprietary(_) => (1000, 100, 0.08,  4096, 1.0),
+        };
+        ChannelModel { base_latency_ms, jitter_ms, loss_prob, capacity_bytes, duty_cycle }
+    }
     pub fn latency_ms(&self) -> u64 {
-        match self {
-            SatelliteProvider::Starlink     => 600,
-            SatelliteProvider::Iridium      => 1500,
-            SatelliteProvider::Viasat       => 800,
-            SatelliteProvider::Amateur      => 2000,
-            SatelliteProvider::Proprietary(_) => 1000,
-        }
+        self.channel_model().base_latency_ms
     }
     pub fn bandwidth_bps(&self) -> u64 {
         match self {
@@ -69,16 +98,87 @@ impl SatelliteProvider {
     }
     pub fn max_pulse_bytes(&self) -> usize {
         // Iridium и Amateur — только минимальный Pulse
+        self.channel_model().capacity_bytes
+    }
+
+    /// Окна видимости над станцией. LEO проходят пролётами,
+    /// геостационар и собственные каналы видны всегда
+    pub fn visibility(&self) -> VisibilitySchedule {
+        const MIN: u64 = 60_000;
         match self {
-            SatelliteProvider::Starlink   => 65536,
-            SatelliteProvider::Iridium    => 256,
-            SatelliteProvider::Viasat     => 32768,
-            SatelliteProvider::Amateur    => 64,
-            SatelliteProvider::Proprietary(_) => 4096,
+            SatelliteProvider::Starlink       => VisibilitySchedule::passes(15 * MIN, 13 * MIN, 0),
+            SatelliteProvider::Iridium        => VisibilitySchedule::passes(10 * MIN, 8 * MIN, 0),
+            SatelliteProvider::Amateur        => VisibilitySchedule::passes(95 * MIN, 12 * MIN, 0),
+            SatelliteProvider::Viasat
+            | SatelliteProvider::Proprietary(_) => VisibilitySchedule::always(),
         }
     }
 }
 
+/// Периодические пролёты: спутник в зоне [offset + k·period, + window)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisibilitySchedule {
+    pub period_ms: u64,
+    pub window_ms: u64,
+    pub offset_ms: u64,
+}
+
+impl VisibilitySchedule {
+    pub fn passes(period_ms: u64, window_ms: u64, offset_ms: u64) -> Self {
+        VisibilitySchedule { period_ms: period_ms.max(1), window_ms, offset_ms }
+    }
+
+    pub fn always() -> Self {
+        VisibilitySchedule { period_ms: 1, window_ms: 1, offset_ms: 0 }
+    }
+
+    pub fn is_visible(&self, now_ms: u64) -> bool {
+        if self.window_ms >= self.period_ms { return true; }
+        if now_ms < self.offset_ms { return false; }
+        (now_ms - self.offset_ms) % self.period_ms < self.window_ms
+    }
+
+    /// Через сколько мс откроется следующее окно (0 — уже открыто)
+    pub fn wait_ms(&self, now_ms: u64) -> u64 {
+        if self.is_visible(now_ms) { return 0; }
+        if now_ms < self.offset_ms { return self.offset_ms - now_ms; }
+        self.period_ms - (now_ms - self.offset_ms) % self.period_ms
+    }
+}
+
+// -----------------------------------------------------------------------------
+// PulseDecodeError — почему принятые байты не стали Pulse
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PulseDecodeError {
+    TooShort { len: usize, min: usize },
+    BadVersion(u8),
+    LengthMismatch(usize),  // длина больше формата или без выравнивания на 8
+    BadSignature,
+    BadChecksum,            // контрольная сумма RadioFrame не сошлась
+    Replayed { pulse_id: u64, highest: u64 }, // уже принят или старше окна
+}
+
+impl std::fmt::Display for PulseDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PulseDecodeError::TooShort { len, min } => write!(f,
+                "Pulse обрезан: {} < {} байт", len, min),
+            PulseDecodeError::BadVersion(v) => write!(f,
+                "Неизвестная версия Pulse: {} (ожидалась {})", v, PULSE_VERSION),
+            PulseDecodeError::LengthMismatch(len) => write!(f,
+                "Неверная длина Pulse: {} байт", len),
+            PulseDecodeError::BadSignature => write!(f, "Подпись Pulse не сошлась"),
+            PulseDecodeError::BadChecksum => write!(f, "Checksum RadioFrame не сошёлся"),
+            PulseDecodeError::Replayed { pulse_id, highest } => write!(f,
+                "Повтор Pulse #{} (последний принятый #{})", pulse_id, highest),
+        }
+    }
+}
+
+impl std::error::Error for PulseDecodeError {}
+
 // -----------------------------------------------------------------------------
 // FederationPulse — сверхсжатый снимок состояния
 // -----------------------------------------------------------------------------
@@ -116,6 +216,9 @@ impl FederationPulse {
         // Упакованная бинарная сериализация — минимум байт
         let mut buf = Vec::with_capacity(PULSE_MAX_BYTES);
 
+        // Версия формата (1 байт)
+        buf.push(PULSE_VERSION);
+
         // Header (16 байт)
         buf.extend_from_slice(&self.pulse_id.to_le_bytes());
         buf.extend_from_slice(&(self.timestamp as u64).to_le_bytes());
@@ -123,8 +226,9 @@ impl FederationPulse {
         // Model digest (8 байт)
         buf.extend_from_slice(&self.model_digest);
 
-        // Rep digest (5 * 6 = 30 байт)
-        for (hash, score) in self.rep_digest.iter().take(5) {
+        // Rep digest (5 * 6 = 30 байт) — пустые слоты нулями, длина фиксирована
+        for i in 0..5 {
+            let (hash, score) = self.rep_digest.get(i).copied().unwrap_or((0, 0));
             buf.extend_from_slice(&hash.to_le_bytes());
             buf.extend_from_slice(&score.to_le_bytes());
         }
@@ -145,49 +249,77 @@ impl FederationPulse {
         buf.extend_from_slice(&self.signature.to_le_bytes());
 
         // Node ID — сжато до 16 байт
-        let node_bytes: Vec<u8> = self.sender_node.bytes().take(16).collect();
+        let node_bytes: Vec<u8> = self.sender_node.bytes().take(PULSE_NODE_BYTES).collect();
         buf.extend_from_slice(&node_bytes);
         while buf.len() % 8 != 0 { buf.push(0); }
 
         buf
     }
 
-    pub fn decode(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < 86 { return None; }
-        let mut pos = 0;
-        let pulse_id = u64::from_le_bytes(bytes[pos..pos+8].try_into().ok()?); pos+=8;
-        let timestamp = u64::from_le_bytes(bytes[pos..pos+8].try_into().ok()?) as i64; pos+=8;
-        let model_digest: [u8; 8] = bytes[pos..pos+8].try_into().ok()?; pos+=8;
+    pub fn decode(bytes: &[u8]) -> Result<Self, PulseDecodeError> {
+        if bytes.len() < PULSE_FIXED_BYTES {
+            return Err(PulseDecodeError::TooShort {
+                len: bytes.len(), min: PULSE_FIXED_BYTES });
+        }
+        if bytes[0] != PULSE_VERSION {
+            return Err(PulseDecodeError::BadVersion(bytes[0]));
+        }
+        if bytes.len() > PULSE_ENCODED_MAX || !bytes.len().is_multiple_of(8) {
+            return Err(PulseDecodeError::LengthMismatch(bytes.len()));
+        }
+
+        // Длина проверена — дальше все срезы в границах
+        let mut pos = 1;
+        let mut take = |n: usize| { let s = &bytes[pos..pos + n]; pos += n; s };
+        let u64_le = |b: &[u8]| u64::from_le_bytes(b.try_into().unwrap());
+        let pulse_id = u64_le(take(8));
+        let timestamp = u64_le(take(8)) as i64;
+        let model_digest: [u8; 8] = take(8).try_into().unwrap();
         let mut rep_digest = vec![];
         for _ in 0..5 {
-            let h = u32::from_le_bytes(bytes[pos..pos+4].try_into().ok()?); pos+=4;
-            let s = u16::from_le_bytes(bytes[pos..pos+2].try_into().ok()?); pos+=2;
-            rep_digest.push((h, s));
-        }
-        let mint_block = u64::from_le_bytes(bytes[pos..pos+8].try_into().ok()?); pos+=8;
-        let total_supply = u32::from_le_bytes(bytes[pos..pos+4].try_into().ok()?); pos+=4;
-        let dag_head = u64::from_le_bytes(bytes[pos..pos+8].try_into().ok()?); pos+=8;
-        let active_tactic = bytes[pos]; pos+=1;
-        let threat_level = bytes[pos]; pos+=1;
-        let connected_nodes = u16::from_le_bytes(bytes[pos..pos+2].try_into().ok()?); pos+=2;
-        let signature = u64::from_le_bytes(bytes[pos..pos+8].try_into().ok()?); pos+=8;
-        let sender_node = String::from_utf8_lossy(
-            &bytes[pos..bytes.len().min(pos+16)]).trim_end_matches('\0').to_string();
-
-        Some(FederationPulse {
+            let h = u32::from_le_bytes(take(4).try_into().unwrap());
+            let s = u16::from_le_bytes(take(2).try_into().unwrap());
+            if (h, s) != (0, 0) { rep_digest.push((h, s)); }
+        }
+        let mint_block = u64_le(take(8));
+        let total_supply = u32::from_le_bytes(take(4).try_into().unwrap());
+        let dag_head = u64_le(take(8));
+        let active_tactic = take(1)[0];
+        let threat_level = take(1)[0];
+        let connected_nodes = u16::from_le_bytes(take(2).try_into().unwrap());
+        let signature = u64_le(take(8));
+        let sender_node = String::from_utf8_lossy(&bytes[PULSE_FIXED_BYTES..])
+            .trim_end_matches('\0').to_string();
+
+        let pulse = FederationPulse {
             pulse_id, timestamp, sender_node, model_digest,
             rep_digest, mint_block, total_supply, dag_head,
             active_tactic, threat_level, connected_nodes, signature,
-        })
+        };
+        if !pulse.verify_signature() {
+            return Err(PulseDecodeError::BadSignature);
+        }
+        Ok(pulse)
     }
 
     pub fn size_bytes(&self) -> usize { self.encode().len() }
 
+    /// Подписываемые поля: pulse_id, дайджест модели и sender_node в том
+    /// виде, в каком он уходит в эфир (первые PULSE_NODE_BYTES байт).
+    /// Упрощённая схема — в prod заменить на Ed25519
+    fn checksum(&self) -> u64 {
+        self.model_digest.iter()
+            .chain(self.sender_node.as_bytes().iter().take(PULSE_NODE_BYTES))
+            .fold(self.pulse_id, |a, &b| a.rotate_left(7).wrapping_add(b as u64))
+            ^ FEDERATION_KEY
+    }
+
+    pub fn sign(&mut self) {
+        self.signature = self.checksum();
+    }
+
     pub fn verify_signature(&self) -> bool {
-        // Упрощённая проверка — в prod заменить на Ed25519
-        let checksum: u64 = self.model_digest.iter()
-            .fold(self.pulse_id, |a, &b| a.wrapping_add(b as u64));
-        self.signature == checksum ^ 0xFEDE_0001_0000_C0DE
+        self.signature == self.checksum()
     }
 
     pub fn tactic_name(&self) -> &str {
@@ -213,8 +345,10 @@ pub struct RadioFrame {
     pub checksum: u32,
     pub hop_count: u8,
     pub priority: u8,              // 0=low 255=emergency
-    pub compression_ratio: f64,
+    pub compression_ratio: f64,    // всегда ≥ 1.0
     pub original_size: usize,
+    #[serde(default)]
+    pub compressed: bool,          // false — payload хранится как есть
 }
 
 impl RadioFrame {
@@ -224,38 +358,61 @@ impl RadioFrame {
         let encoded = pulse.encode();
         let original_size = encoded.len();
 
-        // Применяем лёгкое RLE сжатие для нулей
-        let compressed = Self::rle_compress(&encoded);
-        let ratio = original_size as f64 / compressed.len() as f64;
-        let checksum = compressed.iter().fold(0u32,
+        // Применяем лёгкое RLE сжатие для нулей; высокоэнтропийный
+        // (например, зашифрованный) Pulse RLE только раздует — тогда as-is
+        let rle = Self::rle_compress(&encoded);
+        let compressed = rle.len() < original_size;
+        let payload = if compressed { rle } else { encoded };
+        let ratio = original_size as f64 / payload.len().max(1) as f64;
+        let checksum = payload.iter().fold(0u32,
             |a, &b| a.wrapping_add(b as u32));
 
         RadioFrame {
             frame_id: *rng,
-            provider, payload: compressed,
+            provider, payload,
             checksum, hop_count: 0,
             priority: if pulse.threat_level > 200 { 255 } else { 128 },
             compression_ratio: ratio,
             original_size,
+            compressed,
         }
     }
 
+    // Серия нулей → [0x00, длина]; остальные байты как есть
     fn rle_compress(data: &[u8]) -> Vec<u8> {
-        // Pulse уже компактен (104б < 256б лимита) — передаём as-is
-        data.to_vec()
+        let mut out = Vec::with_capacity(data.len());
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] != 0 { out.push(data[i]); i += 1; continue; }
+            let run = data[i..].iter().take(255).take_while(|&&b| b == 0).count();
+            out.push(0);
+            out.push(run as u8);
+            i += run;
+        }
+        out
     }
 
     fn rle_decompress(data: &[u8]) -> Vec<u8> {
-        data.to_vec()
+        let mut out = Vec::with_capacity(PULSE_ENCODED_MAX);
+        let mut bytes = data.iter();
+        while let Some(&b) = bytes.next() {
+            if b != 0 { out.push(b); continue; }
+            // Обрыв после 0x00 — остаток отбросится по длине в decode
+            let run = bytes.next().copied().unwrap_or(0);
+            out.extend(std::iter::repeat_n(0u8, run as usize));
+            // Длиннее формата decode всё равно отвергнет — не раздуваем мусор
+            if out.len() > PULSE_ENCODED_MAX { break; }
+        }
+        out
     }
 
-    pub fn unwrap(&self) -> Option<FederationPulse> {
+    pub fn unwrap(&self) -> Result<FederationPulse, PulseDecodeError> {
         // Проверка checksum
         let actual = self.payload.iter().fold(0u32,
             |a, &b| a.wrapping_add(b as u32));
-        if actual != self.checksum { return None; }
-        let decompressed = Self::rle_decompress(&self.payload);
-        FederationPulse::decode(&decompressed)
+        if actual != self.checksum { return Err(PulseDecodeError::BadChecksum); }
+        if !self.compressed { return FederationPulse::decode(&self.payload); }
+        FederationPulse::decode(&Self::rle_decompress(&self.payload))
     }
 
     pub fn fits_channel(&self, provider: &SatelliteProvider) -> bool {
@@ -263,9 +420,46 @@ impl RadioFrame {
     }
 
     pub fn transmission_time_ms(&self, provider: &SatelliteProvider) -> u64 {
+        provider.latency_ms() + self.airtime_ms(provider)
+    }
+
+    /// Время занятия эфира — без задержки распространения
+    pub fn airtime_ms(&self, provider: &SatelliteProvider) -> u64 {
         let bits = (self.payload.len() + RADIO_FRAME_OVERHEAD) as u64 * 8;
-        let transfer_ms = bits * 1000 / provider.bandwidth_bps().max(1);
-        provider.latency_ms() + transfer_ms
+        bits * 1000 / provider.bandwidth_bps().max(1)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ReplayWindow — защита от повтора записанных Pulse
+// -----------------------------------------------------------------------------
+
+/// Скользящее окно по pulse_id одного отправителя: highest — старший
+/// принятый id, бит i в seen — принят ли highest − i
+#[derive(Debug, Clone, Default)]
+struct ReplayWindow {
+    highest: u64,
+    seen: u64,
+    last_accept: u64, // номер приёма последнего принятого Pulse — для вытеснения
+}
+
+impl ReplayWindow {
+    fn accept(&mut self, pulse_id: u64) -> Result<(), PulseDecodeError> {
+        let replayed = PulseDecodeError::Replayed { pulse_id, highest: self.highest };
+        if self.seen == 0 || pulse_id > self.highest {
+            let shift = pulse_id.saturating_sub(self.highest);
+            self.seen = if self.seen == 0 || shift >= PULSE_REPLAY_WINDOW { 0 }
+                        else { self.seen << shift };
+            self.seen |= 1;
+            self.highest = pulse_id;
+            return Ok(());
+        }
+        let age = self.highest - pulse_id;
+        if age >= PULSE_REPLAY_WINDOW || self.seen & (1 << age) != 0 {
+            return Err(replayed);
+        }
+        self.seen |= 1 << age;
+        Ok(())
     }
 }
 
@@ -280,19 +474,52 @@ pub struct SatelliteLink {
     pub frames_lost: u64,
     pub bytes_transmitted: u64,
     pub is_blackout: bool,
+    pub duty_rejections: u64,
+    pub out_of_view: u64,           // попытки вне окна видимости
+    pub schedule: VisibilitySchedule,
+    clock_ms: u64,        // виртуальное время канала
+    quiet_until_ms: u64,  // до этого момента эфир закрыт (duty cycle)
+    pub replays_rejected: u64,
+    replay_windows: HashMap<String, ReplayWindow>, // по sender_node, ≤ PULSE_MAX_SENDERS
+    accepted: u64,
     rng: u64,
 }
 
 impl SatelliteLink {
     pub fn new(provider: SatelliteProvider, station: &str) -> Self {
         SatelliteLink {
-            provider, ground_station: station.to_string(),
             frames_sent: 0, frames_lost: 0,
             bytes_transmitted: 0, is_blackout: false,
+            duty_rejections: 0, out_of_view: 0,
+            schedule: provider.visibility(),
+            clock_ms: 0, quiet_until_ms: 0,
+            provider, ground_station: station.to_string(),
+            replays_rejected: 0, replay_windows: HashMap::new(), accepted: 0,
             rng: 0x5A71_1337_FEED_0000,
         }
     }
 
+    pub fn with_schedule(mut self, schedule: VisibilitySchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Перевести часы канала на now_ms (назад не ходят).
+    /// true — спутник в зоне, передавать можно
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        self.clock_ms = self.clock_ms.max(now_ms);
+        self.in_view()
+    }
+
+    pub fn in_view(&self) -> bool {
+        self.schedule.is_visible(self.clock_ms)
+    }
+
+    /// Продвинуть виртуальное время канала (простой между передачами)
+    pub fn advance(&mut self, ms: u64) {
+        self.clock_ms += ms;
+    }
+
     fn next_rng(&mut self) -> f64 {
         self.rng ^= self.rng << 13; self.rng ^= self.rng >> 7;
         self.rng ^= self.rng << 17;
@@ -308,22 +535,35 @@ impl SatelliteLink {
                 frame.payload.len(), self.provider.max_pulse_bytes());
         }
 
-        // Симуляция потерь пакетов (5% для Starlink, 15% для Iridium)
-        let loss_rate = match self.provider {
-            SatelliteProvider::Starlink => 0.05,
-            SatelliteProvider::Iridium  => 0.15,
-            SatelliteProvider::Amateur  => 0.30,
-            _                           => 0.08,
-        };
+        if !self.in_view() {
+            self.out_of_view += 1;
+            return TransmitResult::no_satellite(self.schedule.wait_ms(self.clock_ms));
+        }
+
+        let model = self.provider.channel_model();
+        if self.clock_ms < self.quiet_until_ms {
+            self.duty_rejections += 1;
+            return TransmitResult::duty_cycle(self.quiet_until_ms - self.clock_ms);
+        }
+
+        // Эфир занят даже если кадр потом потеряется:
+        // после airtime T канал молчит T·(1/duty − 1)
+        let airtime = frame.airtime_ms(&self.provider);
+        self.clock_ms += airtime;
+        if model.duty_cycle < 1.0 {
+            let quiet = airtime as f64 * (1.0 / model.duty_cycle.max(1e-6) - 1.0);
+            self.quiet_until_ms = self.clock_ms + quiet.ceil() as u64;
+        }
 
         self.frames_sent += 1;
-        if self.next_rng() < loss_rate {
+        if self.next_rng() < model.loss_prob {
             self.frames_lost += 1;
             return TransmitResult::lost(frame.frame_id);
         }
 
         self.bytes_transmitted += frame.payload.len() as u64;
-        let tx_time = frame.transmission_time_ms(&self.provider);
+        let jitter = (self.next_rng() * model.jitter_ms as f64) as u64;
+        let tx_time = frame.transmission_time_ms(&self.provider) + jitter;
 
         TransmitResult {
             success: true, frame_id: frame.frame_id,
@@ -333,6 +573,30 @@ impl SatelliteLink {
         }
     }
 
+    /// Приём кадра наземной станцией: распаковка, проверка подписи и
+    /// защита от повтора. Pulse с id не новее принятого отвергается,
+    /// кроме ещё не виденных id в пределах PULSE_REPLAY_WINDOW. Окон не
+    /// больше PULSE_MAX_SENDERS: новый отправитель вытесняет самого давно
+    /// молчавшего
+    pub fn receive(&mut self, frame: &RadioFrame) -> Result<FederationPulse, FederationError> {
+        let pulse = frame.unwrap()?;
+        if !self.replay_windows.contains_key(&pulse.sender_node)
+            && self.replay_windows.len() >= PULSE_MAX_SENDERS {
+            let stalest = self.replay_windows.iter()
+                .min_by_key(|(_, w)| w.last_accept)
+                .map(|(sender, _)| sender.clone());
+            if let Some(sender) = stalest { self.replay_windows.remove(&sender); }
+        }
+        let window = self.replay_windows.entry(pulse.sender_node.clone()).or_default();
+        if let Err(e) = window.accept(pulse.pulse_id) {
+            self.replays_rejected += 1;
+            return Err(e.into());
+        }
+        self.accepted += 1;
+        window.last_accept = self.accepted;
+        Ok(pulse)
+    }
+
     pub fn link_stats(&self) -> LinkStats {
         let reliability = if self.frames_sent > 0 {
             1.0 - self.frames_lost as f64 / self.frames_sent as f64
@@ -345,6 +609,8 @@ impl SatelliteLink {
             reliability,
             bytes_transmitted: self.bytes_transmitted,
             latency_ms: self.provider.latency_ms(),
+            replays_rejected: self.replays_rejected,
+            out_of_view: self.out_of_view,
         }
     }
 }
@@ -370,6 +636,16 @@ impl TransmitResult {
             bytes:0, provider:"ERR".into(),
             reason: format!("too_large: {}>{}", size, max) }
     }
+    pub fn no_satellite(wait_ms: u64) -> Self {
+        TransmitResult { success:false, frame_id:0, latency_ms:0,
+            bytes:0, provider:"NONE".into(),
+            reason: format!("no satellite in view: next pass in {}ms", wait_ms) }
+    }
+    pub fn duty_cycle(wait_ms: u64) -> Self {
+        TransmitResult { success:false, frame_id:0, latency_ms:0,
+            bytes:0, provider:"ERR".into(),
+            reason: format!("duty_cycle: wait {}ms", wait_ms) }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -377,7 +653,8 @@ pub struct LinkStats {
     pub provider: String, pub station: String,
     pub frames_sent: u64, pub frames_lost: u64,
     pub reliability: f64, pub bytes_transmitted: u64,
-    pub latency_ms: u64,
+    pub latency_ms: u64, pub replays_rejected: u64,
+    pub out_of_view: u64,
 }
 
 // -----------------------------------------------------------------------------
@@ -390,10 +667,11 @@ pub struct BlackoutMode {
     pub total_nodes: u32,
     pub last_pulse: Option<FederationPulse>,
     pub pulses_missed: u32,
-    pub survival_strategy: SurvivalStrategy,
+    pub survival_strategy: SurvivalStrategy, // стабильная стратегия
+    pending: Option<(SurvivalStrategy, u32)>, // кандидат и сколько замеров держится
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SurvivalStrategy {
     Normal,
     ReducedPulse,   // пульс реже, только критичные данные
@@ -402,30 +680,70 @@ pub enum SurvivalStrategy {
     LastResort,     // ham radio + физические носители
 }
 
+impl SurvivalStrategy {
+    fn level(&self) -> usize {
+        match self {
+            SurvivalStrategy::Normal        => 0,
+            SurvivalStrategy::ReducedPulse  => 1,
+            SurvivalStrategy::SatelliteOnly => 2,
+            SurvivalStrategy::GhostMesh     => 3,
+            SurvivalStrategy::LastResort    => 4,
+        }
+    }
+    fn from_level(level: usize) -> Self {
+        match level {
+            0 => SurvivalStrategy::Normal,
+            1 => SurvivalStrategy::ReducedPulse,
+            2 => SurvivalStrategy::SatelliteOnly,
+            3 => SurvivalStrategy::GhostMesh,
+            _ => SurvivalStrategy::LastResort,
+        }
+    }
+}
+
 impl BlackoutMode {
     pub fn new(total_nodes: u32) -> Self {
         BlackoutMode {
             is_active: false, online_nodes: total_nodes,
             total_nodes, last_pulse: None, pulses_missed: 0,
             survival_strategy: SurvivalStrategy::Normal,
+            pending: None,
         }
     }
 
+    /// Гистерезис: стратегия меняется только если доля недоступных узлов
+    /// ушла за границу на STRATEGY_MARGIN и держится там
+    /// STRATEGY_DWELL_UPDATES замеров подряд
     pub fn update_connectivity(&mut self, online: u32) {
         self.online_nodes = online;
         let ratio = 1.0 - online as f64 / self.total_nodes as f64;
-        self.is_active = ratio >= BLACKOUT_THRESHOLD;
-        self.survival_strategy = if ratio >= 0.99 {
-            SurvivalStrategy::LastResort
-        } else if ratio >= 0.95 {
-            SurvivalStrategy::GhostMesh
-        } else if ratio >= 0.80 {
-            SurvivalStrategy::SatelliteOnly
-        } else if ratio >= 0.50 {
-            SurvivalStrategy::ReducedPulse
+        let current = self.survival_strategy.level();
+        // У верхней границы запас не выходит за 100% — иначе LastResort недостижим
+        let up = STRATEGY_BOUNDS.iter()
+            .filter(|&&b| ratio >= (b + STRATEGY_MARGIN).min((b + 1.0) / 2.0)).count();
+        let down = STRATEGY_BOUNDS.iter().filter(|&&b| ratio >= b - STRATEGY_MARGIN).count();
+        let target = if up > current { up } else if down < current { down } else { current };
+
+        if target == current {
+            self.pending = None;
         } else {
-            SurvivalStrategy::Normal
-        };
+            let candidate = SurvivalStrategy::from_level(target);
+            let streak = match &self.pending {
+                Some((s, n)) if *s == candidate => n + 1,
+                _ => 1,
+            };
+            if streak >= STRATEGY_DWELL_UPDATES {
+                self.survival_strategy = candidate;
+                self.pending = None;
+            } else {
+                self.pending = Some((candidate, streak));
+            }
+        }
+        self.is_active = self.survival_strategy.level() >= SurvivalStrategy::GhostMesh.level();
+    }
+
+    pub fn stable_strategy(&self) -> &SurvivalStrategy {
+        &self.survival_strategy
     }
 
     pub fn connectivity_pct(&self) -> f64 {
@@ -442,3 +760,249 @@ impl BlackoutMode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(provider: SatelliteProvider, payload_len: usize) -> RadioFrame {
+        let payload = vec![0xA5u8; payload_len];
+        let checksum = payload.iter().fold(0u32, |a, &b| a.wrapping_add(b as u32));
+        RadioFrame {
+            frame_id: 1, provider, payload, checksum, hop_count: 0,
+            priority: 128, compression_ratio: 1.0, original_size: payload_len,
+            compressed: false,
+        }
+    }
+
+    #[test]
+    fn test_amateur_rejects_large_frames_and_enforces_duty_cycle() {
+        let mut link = SatelliteLink::new(SatelliteProvider::Amateur, "ham");
+        let r = link.transmit(&frame(SatelliteProvider::Amateur, 100));
+        assert!(!r.success);
+        assert!(r.reason.starts_with("too_large"));
+        assert_eq!(link.frames_sent, 0);
+
+        // Маленький кадр проходит в эфир, следующий сразу — упирается в duty cycle
+        let small = frame(SatelliteProvider::Amateur, 16);
+        assert!(!link.transmit(&small).reason.starts_with("duty_cycle"));
+        assert!(link.transmit(&small).reason.starts_with("duty_cycle"));
+        assert_eq!(link.duty_rejections, 1);
+        assert_eq!(link.frames_sent, 1);
+
+        // Помолчали положенное — эфир снова открыт
+        let airtime = small.airtime_ms(&SatelliteProvider::Amateur);
+        link.advance(airtime * 9);
+        assert!(!link.transmit(&small).reason.starts_with("duty_cycle"));
+    }
+
+    #[test]
+    fn test_transmit_only_inside_visibility_window() {
+        let mut link = SatelliteLink::new(SatelliteProvider::Starlink, "leo")
+            .with_schedule(VisibilitySchedule::passes(10_000, 3_000, 1_000));
+        let f = frame(SatelliteProvider::Starlink, 64);
+        assert!(!link.tick(0));
+        assert!(link.transmit(&f).reason.starts_with("no satellite in view"));
+
+        assert!(link.tick(1_000));
+        let in_window: Vec<TransmitResult> = (0..40).map(|_| link.transmit(&f)).collect();
+        assert!(in_window.iter().any(|r| r.success));
+        assert!(in_window.iter().all(|r| !r.reason.starts_with("no satellite")));
+        let sent = link.frames_sent;
+
+        // Между пролётами — отказ, в статистику надёжности не идёт
+        assert!(!link.tick(5_000));
+        for _ in 0..10 {
+            let r = link.transmit(&f);
+            assert!(r.reason.starts_with("no satellite in view: next pass in 6000ms"));
+        }
+        assert_eq!(link.frames_sent, sent);
+        let stats = link.link_stats();
+        assert_eq!(stats.out_of_view, 11);
+        let lost = in_window.iter().filter(|r| r.reason == "packet_loss").count() as f64;
+        assert!((stats.reliability - (1.0 - lost / 40.0)).abs() < 1e-9);
+
+        // Следующий пролёт
+        assert!(link.tick(11_500));
+        assert!(!link.transmit(&f).reason.starts_with("no satellite"));
+    }
+
+    #[test]
+    fn test_viasat_accepts_large_frames_with_high_latency() {
+        let mut link = SatelliteLink::new(SatelliteProvider::Viasat, "geo");
+        let big = frame(SatelliteProvider::Viasat, 20_000);
+        let ok = (0..10).map(|_| link.transmit(&big)).find(|r| r.success)
+            .expect("Viasat должен принять крупный кадр");
+        assert_eq!(ok.bytes, 20_000);
+        let model = SatelliteProvider::Viasat.channel_model();
+        assert!(ok.latency_ms >= model.base_latency_ms);
+        assert!(ok.latency_ms > SatelliteProvider::Starlink.channel_model().base_latency_ms
+            + SatelliteProvider::Starlink.channel_model().jitter_ms);
+        // Узкие каналы такой кадр не пропустят
+        assert!(!big.fits_channel(&SatelliteProvider::Iridium));
+    }
+
+    fn signed_pulse() -> FederationPulse { signed_pulse_with_id(7) }
+
+    fn signed_pulse_with_id(pulse_id: u64) -> FederationPulse {
+        let mut pulse = FederationPulse {
+            pulse_id, timestamp: 1_739_000_000, sender_node: "node-a".into(),
+            model_digest: [1u8, 2, 3, 4, 5, 6, 7, 8], rep_digest: vec![(0xAB, 100), (0xCD, 50)],
+            mint_block: 1, total_supply: 410, dag_head: 0xDA6,
+            active_tactic: 1, threat_level: 10, connected_nodes: 5,
+            signature: 0,
+        };
+        pulse.sign();
+        pulse
+    }
+
+    #[test]
+    fn test_pulse_decode_roundtrip_and_errors() {
+        let pulse = signed_pulse();
+        let bytes = pulse.encode();
+        let decoded = FederationPulse::decode(&bytes).unwrap();
+        assert_eq!(decoded.pulse_id, pulse.pulse_id);
+        assert_eq!(decoded.sender_node, "node-a");
+        assert_eq!(decoded.rep_digest, pulse.rep_digest);
+
+        assert_eq!(FederationPulse::decode(&bytes[..40]).unwrap_err(),
+            PulseDecodeError::TooShort { len: 40, min: PULSE_FIXED_BYTES });
+
+        let mut long = bytes.clone();
+        long.extend_from_slice(&[0u8; 16]);
+        assert_eq!(FederationPulse::decode(&long).unwrap_err(),
+            PulseDecodeError::LengthMismatch(long.len()));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = PULSE_VERSION + 1;
+        assert_eq!(FederationPulse::decode(&wrong_version).unwrap_err(),
+            PulseDecodeError::BadVersion(PULSE_VERSION + 1));
+
+        let mut forged = bytes.clone();
+        forged[1] ^= 0xFF; // pulse_id изменён, подпись старая
+        assert_eq!(FederationPulse::decode(&forged).unwrap_err(),
+            PulseDecodeError::BadSignature);
+
+        // Чужой sender_node под старой подписью — новое окно повтора не открыть
+        let mut spoofed = bytes.clone();
+        spoofed[PULSE_FIXED_BYTES + 5] = b'b';
+        assert_eq!(FederationPulse::decode(&spoofed).unwrap_err(),
+            PulseDecodeError::BadSignature);
+    }
+
+    #[test]
+    fn test_radio_frame_unwrap_reports_checksum() {
+        let mut rng = 0x1234_u64;
+        let mut frame = RadioFrame::wrap(&signed_pulse(), SatelliteProvider::Iridium, &mut rng);
+        assert!(frame.unwrap().is_ok());
+        frame.payload[3] ^= 0x01;
+        assert_eq!(frame.unwrap().unwrap_err(), PulseDecodeError::BadChecksum);
+    }
+
+    #[test]
+    fn test_high_entropy_pulse_stored_uncompressed() {
+        use crate::rng::{RngCore, XorShift64};
+        let mut r = XorShift64::new(0x00C1_F3E5);
+        let mut pulse = signed_pulse_with_id(r.next_u64());
+        r.fill_bytes(&mut pulse.model_digest);
+        pulse.rep_digest = (0..5).map(|_| (r.next_u64() as u32, r.next_u64() as u16)).collect();
+        pulse.timestamp = r.next_u64() as i64;
+        pulse.mint_block = r.next_u64();
+        pulse.total_supply = r.next_u64() as u32;
+        pulse.dag_head = r.next_u64();
+        pulse.connected_nodes = r.next_u64() as u16 | 0x0101;
+        pulse.sender_node = format!("{:016x}", r.next_u64());
+        pulse.sign();
+
+        let mut rng = 0x1234_u64;
+        let frame = RadioFrame::wrap(&pulse, SatelliteProvider::Iridium, &mut rng);
+        assert!(!frame.compressed);
+        assert_eq!(frame.compression_ratio, 1.0);
+        assert_eq!(frame.payload, pulse.encode());
+        assert_eq!(frame.unwrap().unwrap().dag_head, pulse.dag_head);
+
+        // Обычный Pulse с пустыми слотами сжимается и тоже восстанавливается
+        let sparse = RadioFrame::wrap(&signed_pulse(), SatelliteProvider::Iridium, &mut rng);
+        assert!(sparse.compressed);
+        assert!(sparse.compression_ratio > 1.0);
+        assert_eq!(sparse.unwrap().unwrap().rep_digest, signed_pulse().rep_digest);
+    }
+
+    #[test]
+    fn test_receive_rejects_replayed_pulse() {
+        let mut rng = 0x1234_u64;
+        let mut link = SatelliteLink::new(SatelliteProvider::Iridium, "ground");
+        let mut frame = |id| RadioFrame::wrap(&signed_pulse_with_id(id),
+            SatelliteProvider::Iridium, &mut rng);
+        let (f10, f12, f11) = (frame(10), frame(12), frame(11));
+
+        assert_eq!(link.receive(&f10).unwrap().pulse_id, 10);
+        assert_eq!(link.receive(&f10).unwrap_err(),
+            FederationError::Pulse(PulseDecodeError::Replayed { pulse_id: 10, highest: 10 }));
+
+        // 12 пришёл раньше 11 — 11 ещё не видели и он в окне
+        assert!(link.receive(&f12).is_ok());
+        assert!(link.receive(&f11).is_ok());
+        assert!(link.receive(&f11).is_err());
+
+        // Старше окна — отвергается, даже если не видели
+        let far = frame(12 + PULSE_REPLAY_WINDOW);
+        assert!(link.receive(&far).is_ok());
+        assert!(link.receive(&frame(9)).is_err());
+        assert_eq!(link.link_stats().replays_rejected, 3);
+    }
+
+    #[test]
+    fn test_replay_windows_bounded_by_sender_cap() {
+        let mut rng = 0x1234_u64;
+        let mut link = SatelliteLink::new(SatelliteProvider::Iridium, "ground");
+        let mut frame = |sender: &str, id| {
+            let mut pulse = signed_pulse_with_id(id);
+            pulse.sender_node = sender.to_string();
+            pulse.sign();
+            RadioFrame::wrap(&pulse, SatelliteProvider::Iridium, &mut rng)
+        };
+        let first = frame("node-a", 1);
+        assert!(link.receive(&first).is_ok());
+        for i in 0..PULSE_MAX_SENDERS {
+            assert!(link.receive(&frame(&format!("flood-{}", i), 1)).is_ok());
+        }
+        assert_eq!(link.replay_windows.len(), PULSE_MAX_SENDERS);
+        // Вытеснен самый давно молчавший — node-a
+        assert!(!link.replay_windows.contains_key("node-a"));
+        assert!(link.replay_windows.contains_key("flood-0"));
+    }
+
+    #[test]
+    fn test_blackout_strategy_hysteresis() {
+        let mut mode = BlackoutMode::new(10_000);
+        // Стоим в SatelliteOnly
+        for _ in 0..STRATEGY_DWELL_UPDATES { mode.update_connectivity(1_500); }
+        assert_eq!(mode.stable_strategy(), &SurvivalStrategy::SatelliteOnly);
+
+        // Дребезг вокруг границы 80%: 19.9% онлайн ↔ 20.1% онлайн — внутри запаса
+        for i in 0..10 {
+            mode.update_connectivity(if i % 2 == 0 { 2_010 } else { 1_990 });
+            assert_eq!(mode.stable_strategy(), &SurvivalStrategy::SatelliteOnly);
+        }
+
+        // За запасом, но с перебоями — счётчик сбрасывается
+        mode.update_connectivity(2_500);
+        mode.update_connectivity(2_500);
+        mode.update_connectivity(1_990);
+        mode.update_connectivity(2_500);
+        assert_eq!(mode.stable_strategy(), &SurvivalStrategy::SatelliteOnly);
+
+        // Запас + STRATEGY_DWELL_UPDATES замеров подряд — переключаемся
+        mode.update_connectivity(2_500);
+        assert_eq!(mode.stable_strategy(), &SurvivalStrategy::SatelliteOnly);
+        mode.update_connectivity(2_500);
+        assert_eq!(mode.stable_strategy(), &SurvivalStrategy::ReducedPulse);
+        assert!(!mode.is_active);
+
+        // Почти полный блэкаут всё ещё доводит до LastResort
+        for _ in 0..STRATEGY_DWELL_UPDATES { mode.update_connectivity(10); }
+        assert_eq!(mode.stable_strategy(), &SurvivalStrategy::LastResort);
+        assert!(mode.is_active);
+    }
+}
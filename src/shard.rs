@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub const MIN_SHARD_SIZE: usize = 3;
 pub const MAX_SHARD_SIZE: usize = 50;
@@ -7,6 +7,7 @@ pub const SPLIT_THRESHOLD: f64 = 0.4;
 pub const MERGE_THRESHOLD: f64 = 0.7;
 pub const RENDEZVOUS_TTL: u8 = 5;
 pub const BRIDGE_ELECTION_QUORUM: usize = 2;
+pub const RING_VIRTUAL_NODES: usize = 64;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[derive(Default)]
@@ -401,3 +402,168 @@ pub enum ShardingAction {
     Monitor { assessment: ThreatAssessment },
     NoAction,
 }
+
+// Консистентное хеширование: ключ принадлежит первой виртуальной точке по часовой стрелке.
+// Добавление/удаление узла перемещает только соседние дуги (~1/N ключей).
+
+fn ring_hash(data: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for &b in data {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    // FNV плохо разносит похожие строки ("node#1", "node#2") — добавляем финальный микс
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51afd7ed558ccd);
+    h ^= h >> 33;
+    h
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ShardRing {
+    points: BTreeMap<u64, String>,
+    pub nodes: Vec<String>,
+    pub virtual_nodes: usize,
+}
+
+impl ShardRing {
+    pub fn new() -> Self { Self::with_virtual_nodes(RING_VIRTUAL_NODES) }
+
+    pub fn with_virtual_nodes(virtual_nodes: usize) -> Self {
+        ShardRing { points: BTreeMap::new(), nodes: vec![], virtual_nodes: virtual_nodes.max(1) }
+    }
+
+    pub fn add_node(&mut self, node_id: &str) {
+        if self.nodes.iter().any(|n| n == node_id) { return; }
+        for v in 0..self.virtual_nodes {
+            let point = ring_hash(format!("{}#{}", node_id, v).as_bytes());
+            self.points.insert(point, node_id.to_string());
+        }
+        self.nodes.push(node_id.to_string());
+    }
+
+    pub fn remove_node(&mut self, node_id: &str) {
+        self.points.retain(|_, owner| owner != node_id);
+        self.nodes.retain(|n| n != node_id);
+    }
+
+    pub fn key_hash(key: &str) -> u64 { ring_hash(key.as_bytes()) }
+
+    pub fn owner_of_hash(&self, h: u64) -> Option<&str> {
+        self.points.range(h..).next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, node)| node.as_str())
+    }
+
+    pub fn owner(&self, key: &str) -> Option<&str> {
+        self.owner_of_hash(Self::key_hash(key))
+    }
+
+    pub fn len(&self) -> usize { self.nodes.len() }
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+}
+
+/// Дуга кольца (start, end]. Если start >= end — дуга проходит через 0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl KeyRange {
+    pub fn contains(&self, h: u64) -> bool {
+        if self.start < self.end { h > self.start && h <= self.end }
+        else { h > self.start || h <= self.end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeMove {
+    pub range: KeyRange,
+    pub from: String,
+    pub to: String,
+}
+
+/// Только те дуги, у которых сменился владелец между old и new.
+pub fn rebalance_plan(old_ring: &ShardRing, new_ring: &ShardRing) -> Vec<RangeMove> {
+    if old_ring.is_empty() || new_ring.is_empty() { return vec![]; }
+    let mut bounds: Vec<u64> = old_ring.points.keys()
+        .chain(new_ring.points.keys()).cloned().collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut moves: Vec<RangeMove> = vec![];
+    for (i, &end) in bounds.iter().enumerate() {
+        let start = if i == 0 { bounds[bounds.len() - 1] } else { bounds[i - 1] };
+        // Внутри дуги (start, end] владелец в обоих кольцах постоянен
+        let from = old_ring.owner_of_hash(end).unwrap_or_default();
+        let to = new_ring.owner_of_hash(end).unwrap_or_default();
+        if from == to { continue; }
+        if let Some(last) = moves.last_mut() {
+            if last.range.end == start && last.from == from && last.to == to {
+                last.range.end = end;
+                continue;
+            }
+        }
+        moves.push(RangeMove {
+            range: KeyRange { start, end },
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_of(n: usize) -> ShardRing {
+        let mut ring = ShardRing::new();
+        for i in 0..n { ring.add_node(&format!("node_{}", i)); }
+        ring
+    }
+
+    #[test]
+    fn test_ring_add_node_moves_only_to_new_node() {
+        let old = ring_of(4);
+        let mut new = old.clone();
+        new.add_node("node_4");
+        let keys: Vec<String> = (0..2000).map(|i| format!("key_{}", i)).collect();
+        let mut moved = 0;
+        for k in &keys {
+            let (a, b) = (old.owner(k).unwrap(), new.owner(k).unwrap());
+            if a != b {
+                assert_eq!(b, "node_4");
+                moved += 1;
+            }
+        }
+        let frac = moved as f64 / keys.len() as f64;
+        assert!(frac > 0.05 && frac < 0.40, "moved fraction {}", frac);
+
+        let plan = rebalance_plan(&old, &new);
+        assert!(plan.iter().all(|m| m.to == "node_4"));
+        for k in &keys {
+            let h = ShardRing::key_hash(k);
+            let changed = old.owner(k) != new.owner(k);
+            assert_eq!(changed, plan.iter().any(|m| m.range.contains(h)));
+        }
+    }
+
+    #[test]
+    fn test_ring_remove_node_reassigns_only_its_keys() {
+        let old = ring_of(5);
+        let mut new = old.clone();
+        new.remove_node("node_2");
+        for i in 0..2000 {
+            let k = format!("key_{}", i);
+            let before = old.owner(&k).unwrap();
+            if before == "node_2" {
+                assert_ne!(new.owner(&k).unwrap(), "node_2");
+            } else {
+                assert_eq!(new.owner(&k).unwrap(), before);
+            }
+        }
+        assert!(rebalance_plan(&old, &new).iter().all(|m| m.from == "node_2"));
+    }
+}
@@ -4,7 +4,8 @@
 // 1000 узлов vs SuperCensor
 // =============================================================================
 
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 pub const WAR_NODES: usize        = 1000;
 pub const WAR_TICKS: usize        = 50;
@@ -16,8 +17,9 @@ pub const DPI_BLOCK_RATE: f64     = 0.65;
 pub const AIKI_EXHAUST_RATE: f64  = 0.12;
 pub const MESH_SPREAD_RATE: f64   = 0.08;
 pub const RECOVERY_THRESHOLD: f64 = 0.50;
+pub const WAR_DEFAULT_SEED: u64   = 0xFEDE_0000_0000_0000;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NodeClass {
     Sentinel, Citadel, Workstation, Ghost, Mobile, Droid,
 }
@@ -68,12 +70,79 @@ impl NodeClass {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WarTactic {
     AikiReflection, CityMesh, SatelliteFallback, Dormant, Captured,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl WarTactic {
+    // Строка в EffectivenessMatrix; Dormant/Captured не прорываются
+    fn matrix_row(&self) -> Option<usize> {
+        match self {
+            WarTactic::AikiReflection    => Some(0),
+            WarTactic::CityMesh          => Some(1),
+            WarTactic::SatelliteFallback => Some(2),
+            _                            => None,
+        }
+    }
+}
+
+// Adaptive — цензор перераспределяет ресурсы: по мере истощения
+// ослабляет DPI и покрытие отключений. Static — держит ставки
+// до конца, истощение только копится
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CensorStrategy { Static, Adaptive }
+
+impl CensorStrategy {
+    fn index(&self) -> usize {
+        match self { CensorStrategy::Static => 0, CensorStrategy::Adaptive => 1 }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// EffectivenessMatrix — эффективность тактики против стратегии цензора
+// -----------------------------------------------------------------------------
+
+/// Множитель прироста bypass_rate для [тактика][стратегия цензора].
+/// 1.0 — базовая эффективность, 0 — тактика бесполезна
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectivenessMatrix {
+    cells: [[f64; 2]; 3],
+}
+
+impl EffectivenessMatrix {
+    pub fn get(&self, tactic: &WarTactic, censor: &CensorStrategy) -> f64 {
+        tactic.matrix_row().map(|r| self.cells[r][censor.index()]).unwrap_or(0.0)
+    }
+
+    pub fn set(&mut self, tactic: &WarTactic, censor: &CensorStrategy, effectiveness: f64) {
+        if let Some(r) = tactic.matrix_row() {
+            self.cells[r][censor.index()] = effectiveness.max(0.0);
+        }
+    }
+}
+
+impl Default for EffectivenessMatrix {
+    fn default() -> Self { EffectivenessMatrix { cells: [[1.0; 2]; 3] } }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarConfig {
+    pub seed: u64,
+    pub censor_strategy: CensorStrategy,
+    pub matrix: EffectivenessMatrix,
+    // Узлы, чьё состояние пишется каждый тик (node_trace); пусто — без трассы
+    pub trace_nodes: HashSet<usize>,
+}
+
+impl Default for WarConfig {
+    fn default() -> Self {
+        WarConfig { seed: WAR_DEFAULT_SEED, censor_strategy: CensorStrategy::Adaptive,
+            matrix: EffectivenessMatrix::default(), trace_nodes: HashSet::new() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WarRegion { CN, KP, RU, IR, FREE }
 
 impl WarRegion {
@@ -101,7 +170,7 @@ impl WarRegion {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WarNode {
     pub id: usize,
     pub class: NodeClass,
@@ -125,7 +194,7 @@ impl WarNode {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CensorState {
     pub active: bool,
     pub inet_kill_coverage: f64,
@@ -147,17 +216,18 @@ impl CensorState {
         self.dpi_effectiveness  = DPI_BLOCK_RATE;
         self.satellite_jam      = SATELLITE_JAM;
     }
-    pub fn apply_aiki_exhaust(&mut self, aiki_nodes: usize) {
+    pub fn apply_aiki_exhaust(&mut self, aiki_nodes: usize, strategy: &CensorStrategy) {
         let exhaust = aiki_nodes as f64 * AIKI_EXHAUST_RATE * 0.01;
         self.exhaustion = (self.exhaustion + exhaust).min(1.0);
         self.resources  = (self.resources  - exhaust * 0.5).max(0.0);
+        if *strategy == CensorStrategy::Static { return; }
         let factor = 1.0 - self.exhaustion * 0.7;
         self.dpi_effectiveness  = (DPI_BLOCK_RATE * factor).max(0.0);
         self.inet_kill_coverage = (INET_KILL_RATE * (1.0 - self.exhaustion * 0.3)).max(0.0);
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WarPhase {
     Peace, Strike, Crisis, Adaptation, Recovery, Victory,
 }
@@ -185,7 +255,7 @@ impl WarPhase {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TickStats {
     pub tick: usize,
     pub alive_nodes: usize,
@@ -201,6 +271,18 @@ pub struct TickStats {
     pub phase: WarPhase,
 }
 
+/// Состояние одного узла на конце тика — для разбора, почему он погиб
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeTickState {
+    pub tick: usize,
+    pub alive: bool,
+    pub inet_connected: bool,
+    pub mesh_connected: bool,
+    pub tactic: WarTactic,
+    pub bypass_rate: f64,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct WarSimulator {
     pub nodes: Vec<WarNode>,
     pub censor: CensorState,
@@ -209,15 +291,32 @@ pub struct WarSimulator {
     rng: u64,
     pub time_to_recover: Option<usize>,
     pub time_to_victory: Option<usize>,
+    pub config: WarConfig,
+    traces: HashMap<usize, Vec<NodeTickState>>,
 }
 
 impl WarSimulator {
     pub fn new() -> Self {
+        Self::with_config(WarConfig::default())
+    }
+
+    pub fn with_config(config: WarConfig) -> Self {
         let nodes = (0..WAR_NODES).map(WarNode::new).collect();
         WarSimulator { nodes, censor: CensorState::new(),
             tick: 0, history: Vec::new(),
-            rng: 0xFEDE_0000_0000_0000u64,
-            time_to_recover: None, time_to_victory: None }
+            rng: if config.seed == 0 { WAR_DEFAULT_SEED } else { config.seed },
+            time_to_recover: None, time_to_victory: None, config,
+            traces: HashMap::new() }
+    }
+
+    /// Трасса узла по тикам. Пусто, если узел не в config.trace_nodes
+    pub fn node_trace(&self, node_id: usize) -> Vec<NodeTickState> {
+        self.traces.get(&node_id).cloned().unwrap_or_default()
+    }
+
+    // Эффективность тактики против текущей стратегии цензора
+    fn effectiveness(&self, tactic: &WarTactic) -> f64 {
+        self.config.matrix.get(tactic, &self.config.censor_strategy)
     }
 
     fn rand(&mut self) -> f64 {
@@ -264,22 +363,24 @@ impl WarSimulator {
             }
             // AikiReflection
             let mut aiki_count = 0usize;
+            let aiki_eff = self.effectiveness(&WarTactic::AikiReflection);
             for i in 0..self.nodes.len() {
                 if !self.nodes[i].alive || !self.nodes[i].inet_connected { continue; }
                 if self.nodes[i].bypass_rate > 0.3 {
                     self.nodes[i].aiki_active = true;
                     self.nodes[i].tactic = WarTactic::AikiReflection;
                     let rf = 1.0 - self.nodes[i].region.censor_strength() * 0.5;
-                    self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.08 * rf).min(0.95);
+                    self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.08 * rf * aiki_eff).min(0.95);
                     aiki_count += 1;
                 }
             }
-            self.censor.apply_aiki_exhaust(aiki_count);
+            self.censor.apply_aiki_exhaust(aiki_count, &self.config.censor_strategy);
 
             // CityMesh
             let mesh_count = self.nodes.iter()
                 .filter(|n| n.alive && n.class.mesh_capable() && n.inet_connected)
                 .count();
+            let mesh_eff = self.effectiveness(&WarTactic::CityMesh);
             for i in 0..self.nodes.len() {
                 if !self.nodes[i].alive || self.nodes[i].inet_connected { continue; }
                 if self.nodes[i].class.mesh_capable() {
@@ -287,18 +388,19 @@ impl WarSimulator {
                     if self.rand() < chance {
                         self.nodes[i].mesh_connected = true;
                         self.nodes[i].tactic = WarTactic::CityMesh;
-                        self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.05).min(0.70);
+                        self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.05 * mesh_eff).min(0.70);
                     }
                 }
             }
             // Спутниковый fallback
             let sat = 1.0 - self.censor.satellite_jam;
+            let sat_eff = self.effectiveness(&WarTactic::SatelliteFallback);
             for i in 0..self.nodes.len() {
                 if !self.nodes[i].alive || self.nodes[i].inet_connected || self.nodes[i].mesh_connected { continue; }
                 if matches!(self.nodes[i].class, NodeClass::Sentinel | NodeClass::Ghost)
                     && self.rand() < sat * 0.3 {
                         self.nodes[i].tactic = WarTactic::SatelliteFallback;
-                        self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.03).min(0.50);
+                        self.nodes[i].bypass_rate = (self.nodes[i].bypass_rate + 0.03 * sat_eff).min(0.50);
                     }
             }
             // Dormant
@@ -345,6 +447,14 @@ impl WarSimulator {
             else if bypass_avg < 0.60 { WarPhase::Recovery }
             else { WarPhase::Victory };
 
+        for &id in &self.config.trace_nodes {
+            let Some(node) = self.nodes.get(id) else { continue };
+            self.traces.entry(id).or_default().push(NodeTickState {
+                tick: self.tick, alive: node.alive,
+                inet_connected: node.inet_connected, mesh_connected: node.mesh_connected,
+                tactic: node.tactic.clone(), bypass_rate: node.bypass_rate });
+        }
+
         self.history.push(TickStats { tick: self.tick, alive_nodes: alive,
             inet_connected: inet_conn, mesh_connected: mesh_conn,
             aiki_active, satellite_active: sat_active, captured_nodes: captured,
@@ -353,8 +463,19 @@ impl WarSimulator {
             censor_resources: self.censor.resources, phase });
     }
 
+    // Продолжает с текущего тика — после resume доигрывает остаток
     pub fn run_full(&mut self) {
-        for _ in 0..WAR_TICKS { self.step(); }
+        while self.tick < WAR_TICKS { self.step(); }
+    }
+
+    /// Полный снимок состояния, включая RNG: resume продолжит
+    /// ровно так, как шёл бы непрерванный прогон
+    pub fn checkpoint(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    pub fn resume(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
     }
 
     pub fn class_breakdown(&self) -> Vec<(String, usize, usize, f64)> {
@@ -400,3 +521,68 @@ impl WarSimulator {
         v
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn final_bypass(config: WarConfig) -> f64 {
+        let mut sim = WarSimulator::with_config(config);
+        sim.run_full();
+        sim.history.last().unwrap().bypass_rate_avg
+    }
+
+    #[test]
+    fn test_effectiveness_matrix_changes_outcome() {
+        let base = final_bypass(WarConfig::default());
+        assert_eq!(base, final_bypass(WarConfig::default()));
+
+        let mut boosted = WarConfig::default();
+        boosted.matrix.set(&WarTactic::AikiReflection, &CensorStrategy::Adaptive, 2.0);
+        assert!(final_bypass(boosted) > base + 0.01);
+
+        // Против Static буст Aiki не действует — стратегия Adaptive не задета
+        let mut other = WarConfig::default();
+        other.matrix.set(&WarTactic::AikiReflection, &CensorStrategy::Static, 2.0);
+        assert_eq!(final_bypass(other), base);
+    }
+
+    #[test]
+    fn test_node_trace_matches_aggregate_death() {
+        let ticks = ATTACK_TICK + 5;
+        let mut probe = WarSimulator::new();
+        for _ in 0..ticks { probe.step(); }
+        let victim = probe.nodes.iter().position(|n| !n.alive).unwrap();
+
+        let mut config = WarConfig::default();
+        config.trace_nodes.insert(victim);
+        let mut sim = WarSimulator::with_config(config);
+        for _ in 0..ticks { sim.step(); }
+
+        let trace = sim.node_trace(victim);
+        assert_eq!(trace.len(), ticks);
+        assert!(sim.node_trace(victim + 1).is_empty());
+        let death = trace.iter().find(|s| !s.alive).unwrap();
+        assert_eq!(death.tactic, WarTactic::Captured);
+        let i = sim.history.iter().position(|h| h.tick == death.tick).unwrap();
+        assert!(sim.history[i].alive_nodes < sim.history[i - 1].alive_nodes);
+    }
+
+    #[test]
+    fn test_checkpoint_resume_matches_straight_run() {
+        let mut straight = WarSimulator::new();
+        straight.run_full();
+
+        let mut first = WarSimulator::new();
+        for _ in 0..ATTACK_TICK + 7 { first.step(); }
+        let bytes = first.checkpoint().unwrap();
+        drop(first);
+        let mut resumed = WarSimulator::resume(&bytes).unwrap();
+        resumed.run_full();
+
+        assert_eq!(resumed.tick, WAR_TICKS);
+        assert_eq!(resumed.history, straight.history);
+        assert_eq!(resumed.time_to_recover, straight.time_to_recover);
+        assert!(WarSimulator::resume(&bytes[..bytes.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+// =============================================================================
+// FEDERATION CORE — snapshot.rs
+// «Снимок Федерации» — стабильный JSON для внешних дашбордов
+// =============================================================================
+//
+// Внутренние структуры меняются от патча к патчу, внешним инструментам
+// нужна неподвижная схема. FederationSnapshot собирается из живых
+// компонентов и хранит только плоские значения: строки, числа, списки.
+//
+// Схема v1 (SNAPSHOT_SCHEMA_VERSION):
+//   schema_version  u32
+//   taken_at        i64, unix-секунды
+//   nodes[]         { id, role?, layer?, reputation, tier, betrayals, blacklisted }
+//                   role/tier — имена вариантов без эмодзи: "Sentinel", "Veteran"
+//   roles           { role → число устройств }
+//   supply          { total_supply, max_supply, pct_issued, total_burned,
+//                     treasury, total_bypasses, halving_epoch }
+//   active_tactics  { tactic → успешных доставок }
+//   ethics          { state: "active" | "partial_freeze" | "full_freeze",
+//                     frozen_modules[], violation_score }
+//   health          общая оценка FederationHealth, 0..1
+//   bypass_rate     прорывов в секунду за последнюю минуту
+//
+// Новые поля добавляются только с #[serde(default)]; удаление или смена
+// смысла поля — новая версия схемы.
+// =============================================================================
+
+use crate::ethics::{EthicsLayer, KillSwitchState};
+use crate::health::FederationHealth;
+use crate::inventory::{DeviceRole, FederationInventory};
+use crate::mint::{MintEngine, MAX_SUPPLY};
+use crate::reputation::{ReputationEventKind, ReputationRegistry, ReputationTier};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+pub const SNAPSHOT_RATE_WINDOW_SECS: u64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotNode {
+    pub id: String,
+    pub role: Option<String>,   // нет в инвентаре — None
+    pub layer: Option<u8>,
+    pub reputation: f64,
+    pub tier: String,
+    pub betrayals: u32,
+    pub blacklisted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotSupply {
+    pub total_supply: f64,
+    pub max_supply: f64,
+    pub pct_issued: f64,
+    pub total_burned: f64,
+    pub treasury: f64,
+    pub total_bypasses: u64,
+    pub halving_epoch: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEthics {
+    pub state: String,
+    pub frozen_modules: Vec<String>,
+    pub violation_score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FederationSnapshot {
+    pub schema_version: u32,
+    pub taken_at: i64,
+    pub nodes: Vec<SnapshotNode>,            // по id
+    pub roles: BTreeMap<String, u32>,
+    pub supply: SnapshotSupply,
+    pub active_tactics: BTreeMap<String, u64>,
+    pub ethics: SnapshotEthics,
+    pub health: f64,
+    #[serde(default)]
+    pub bypass_rate: f64,
+}
+
+// Имена в схеме заданы явно: переименование варианта или новый Debug
+// не должны молча менять JSON
+fn role_name(role: &DeviceRole) -> &'static str {
+    match role {
+        DeviceRole::Sentinel    => "Sentinel",
+        DeviceRole::Citadel     => "Citadel",
+        DeviceRole::Workstation => "Workstation",
+        DeviceRole::Mobile      => "Mobile",
+        DeviceRole::Ghost       => "Ghost",
+        DeviceRole::Droid       => "Droid",
+    }
+}
+
+fn tier_name(tier: &ReputationTier) -> &'static str {
+    match tier {
+        ReputationTier::Ghost    => "Ghost",
+        ReputationTier::Newcomer => "Newcomer",
+        ReputationTier::Reliable => "Reliable",
+        ReputationTier::Trusted  => "Trusted",
+        ReputationTier::Veteran  => "Veteran",
+        ReputationTier::Legend   => "Legend",
+    }
+}
+
+impl FederationSnapshot {
+    pub fn capture(reputation: &ReputationRegistry, inventory: &FederationInventory,
+                   mint: &MintEngine, ethics: &EthicsLayer) -> Self {
+        Self::capture_at(reputation, inventory, mint, ethics, chrono::Utc::now().timestamp())
+    }
+
+    pub fn capture_at(reputation: &ReputationRegistry, inventory: &FederationInventory,
+                      mint: &MintEngine, ethics: &EthicsLayer, now: i64) -> Self {
+        let ids: BTreeSet<&String> = reputation.nodes.keys()
+            .chain(inventory.capacities.keys()).collect();
+        let nodes = ids.into_iter().map(|id| {
+            let rep = reputation.nodes.get(id);
+            let cap = inventory.capacities.get(id);
+            SnapshotNode {
+                id: id.clone(),
+                role: cap.map(|c| role_name(&c.role).to_string()),
+                layer: cap.map(|c| c.layer),
+                reputation: rep.map(|r| r.score).unwrap_or(0.0),
+                tier: tier_name(rep.map_or(&ReputationTier::Ghost, |r| &r.tier)).to_string(),
+                betrayals: rep.map(|r| r.betrayals).unwrap_or(0),
+                blacklisted: rep.map(|r| r.is_blacklisted).unwrap_or(false),
+            }
+        }).collect();
+
+        let mut roles = BTreeMap::new();
+        for cap in inventory.capacities.values() {
+            *roles.entry(role_name(&cap.role).to_string()).or_insert(0) += 1;
+        }
+
+        let mut active_tactics = BTreeMap::new();
+        for event in reputation.nodes.values().flat_map(|n| &n.history) {
+            if let ReputationEventKind::SuccessfulDelivery { tactic, .. } = &event.kind {
+                *active_tactics.entry(tactic.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let stats = mint.supply_stats();
+        let (state, frozen_modules) = match &ethics.kill_switch.state {
+            KillSwitchState::Active => ("active", vec![]),
+            KillSwitchState::PartialFreeze { frozen_modules } =>
+                ("partial_freeze", frozen_modules.clone()),
+            KillSwitchState::FullFreeze { .. } => ("full_freeze", vec![]),
+        };
+
+        FederationSnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            taken_at: now,
+            nodes,
+            roles,
+            supply: SnapshotSupply {
+                total_supply: stats.total_supply,
+                max_supply: MAX_SUPPLY,
+                pct_issued: stats.pct_issued,
+                total_burned: stats.total_burned,
+                treasury: stats.treasury,
+                total_bypasses: stats.total_bypasses,
+                halving_epoch: stats.halving_epoch,
+            },
+            active_tactics,
+            ethics: SnapshotEthics {
+                state: state.to_string(),
+                frozen_modules,
+                violation_score: ethics.evaluator.system_violation_score,
+            },
+            health: FederationHealth::compute(reputation, inventory, mint, ethics).overall,
+            bypass_rate: mint.bypass_rate(SNAPSHOT_RATE_WINDOW_SECS),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Снимок из будущей схемы не читается: смысл полей мог измениться
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let snapshot: FederationSnapshot = serde_json::from_str(json)
+            .map_err(|e| format!("снимок не разобран: {}", e))?;
+        if snapshot.schema_version > SNAPSHOT_SCHEMA_VERSION {
+            return Err(format!("неизвестная версия схемы: {}", snapshot.schema_version));
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethics::MODULE_MINT;
+    use crate::health::demo_federation;
+
+    // Состояние демо health после трёх предательств и заморозки mint
+    fn fixtures() -> (ReputationRegistry, FederationInventory, MintEngine, EthicsLayer) {
+        let (mut reg, inv, mint, mut ethics) = demo_federation();
+        for i in 0..3 { reg.record_betrayal("node_berlin", &format!("hash_{}", i)); }
+        ethics.kill_switch.state = KillSwitchState::PartialFreeze {
+            frozen_modules: vec![MODULE_MINT.to_string()] };
+        (reg, inv, mint, ethics)
+    }
+
+    #[test]
+    fn test_snapshot_roundtrips_through_json() {
+        let (reg, inv, mint, ethics) = fixtures();
+        let snapshot = FederationSnapshot::capture_at(&reg, &inv, &mint, &ethics, 1_700_000_000);
+        let json = snapshot.to_json().unwrap();
+        let restored = FederationSnapshot::from_json(&json).unwrap();
+        assert_eq!(restored, snapshot);
+
+        assert_eq!(restored.schema_version, SNAPSHOT_SCHEMA_VERSION);
+        let ids: Vec<&str> = restored.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["hub-berlin", "hub-nairobi", "hub-nexus", "hub-tokyo", "hub-toronto",
+                         "node_berlin", "node_tokyo"]);
+        let berlin = &restored.nodes[5];
+        assert!(berlin.blacklisted && berlin.role.is_none() && berlin.betrayals == 3);
+        assert_eq!(restored.nodes[0].tier, "Ghost");
+        assert_eq!(restored.nodes[2].role.as_deref(), Some("Sentinel"));
+        assert_eq!(restored.roles.values().sum::<u32>(), 5);
+        assert_eq!(restored.active_tactics["AikiReflection"], 40);
+        assert_eq!(restored.active_tactics["StandoffDecoy"], 30);
+        assert_eq!(restored.supply.total_bypasses, 500);
+        assert_eq!(restored.supply.total_supply, mint.total_supply);
+        assert_eq!(restored.ethics.state, "partial_freeze");
+        assert_eq!(restored.ethics.frozen_modules, [MODULE_MINT]);
+        // Схема не зависит от внутренних имён: эмодзи в JSON нет
+        assert!(!json.contains("🏰") && json.contains("\"schema_version\": 1"));
+
+        let future = json.replace("\"schema_version\": 1", "\"schema_version\": 99");
+        assert!(FederationSnapshot::from_json(&future).is_err());
+    }
+}
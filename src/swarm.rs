@@ -300,3 +300,146 @@ impl SwarmLearner {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// SwarmRegister — реплицированное состояние с кворумами чтения/записи
+// Конфликты: last-writer-wins по (version, node_id).
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedValue<T> {
+    pub value: T,
+    pub version: u64,
+    pub writer: String,
+}
+
+impl<T> VersionedValue<T> {
+    fn newer_than(&self, other: &VersionedValue<T>) -> bool {
+        (self.version, &self.writer) > (other.version, &other.writer)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisterReplica<T> {
+    pub node_id: String,
+    pub reachable: bool,
+    pub stored: Option<VersionedValue<T>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwarmRegister<T: Clone> {
+    pub node_id: String,
+    pub replicas: Vec<RegisterReplica<T>>,
+    pub write_quorum: usize,
+    pub read_quorum: usize,
+    pub repairs: u64,
+}
+
+impl<T: Clone> SwarmRegister<T> {
+    pub fn new(node_id: &str, replica_ids: &[&str], write_quorum: usize, read_quorum: usize) -> Self {
+        SwarmRegister {
+            node_id: node_id.to_string(),
+            replicas: replica_ids.iter().map(|id| RegisterReplica {
+                node_id: id.to_string(), reachable: true, stored: None,
+            }).collect(),
+            write_quorum: write_quorum.max(1),
+            read_quorum: read_quorum.max(1),
+            repairs: 0,
+        }
+    }
+
+    pub fn set_reachable(&mut self, replica_id: &str, reachable: bool) {
+        if let Some(r) = self.replicas.iter_mut().find(|r| r.node_id == replica_id) {
+            r.reachable = reachable;
+        }
+    }
+
+    pub fn reachable_count(&self) -> usize {
+        self.replicas.iter().filter(|r| r.reachable).count()
+    }
+
+    pub fn write(&mut self, value: T, version: u64) -> Result<usize, &'static str> {
+        let writer = self.node_id.clone();
+        self.write_from(&writer, value, version)
+    }
+
+    /// Запись от имени `writer`. Возвращает число подтвердивших реплик.
+    pub fn write_from(&mut self, writer: &str, value: T, version: u64) -> Result<usize, &'static str> {
+        if self.reachable_count() < self.write_quorum { return Err("write quorum unreachable"); }
+        let incoming = VersionedValue { value, version, writer: writer.to_string() };
+        let mut acks = 0;
+        for r in self.replicas.iter_mut().filter(|r| r.reachable) {
+            // Реплика с более новой версией отказывает — это тоже ответ, но не ack
+            let accept = r.stored.as_ref().map(|cur| incoming.newer_than(cur)).unwrap_or(true);
+            if accept {
+                r.stored = Some(incoming.clone());
+                acks += 1;
+            }
+        }
+        if acks < self.write_quorum { return Err("write rejected: stale version"); }
+        Ok(acks)
+    }
+
+    /// Читает у кворума, выбирает самую новую версию и чинит отставшие реплики.
+    pub fn read(&mut self) -> Result<Option<VersionedValue<T>>, &'static str> {
+        if self.reachable_count() < self.read_quorum { return Err("read quorum unreachable"); }
+        let mut latest: Option<VersionedValue<T>> = None;
+        for r in self.replicas.iter().filter(|r| r.reachable) {
+            if let Some(v) = &r.stored {
+                if latest.as_ref().map(|l| v.newer_than(l)).unwrap_or(true) {
+                    latest = Some(v.clone());
+                }
+            }
+        }
+        if let Some(ref l) = latest {
+            for r in self.replicas.iter_mut().filter(|r| r.reachable) {
+                let stale = r.stored.as_ref().map(|v| l.newer_than(v)).unwrap_or(true);
+                if stale {
+                    r.stored = Some(l.clone());
+                    self.repairs += 1;
+                }
+            }
+        }
+        Ok(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_quorum_write_read() {
+        let mut reg = SwarmRegister::new("n0", &["r1", "r2", "r3", "r4", "r5"], 3, 3);
+        reg.set_reachable("r4", false);
+        reg.set_reachable("r5", false);
+        assert_eq!(reg.write(100u64, 1).unwrap(), 3);
+        assert_eq!(reg.read().unwrap().unwrap().value, 100);
+
+        reg.set_reachable("r3", false);
+        assert!(reg.write(200, 2).is_err());
+        assert!(reg.read().is_err());
+    }
+
+    #[test]
+    fn test_register_reconciles_divergent_replicas() {
+        let mut reg = SwarmRegister::new("n0", &["r1", "r2", "r3", "r4", "r5"], 3, 3);
+        reg.write("old".to_string(), 1).unwrap();
+        reg.set_reachable("r1", false);
+        reg.set_reachable("r2", false);
+        reg.write_from("n1", "new".to_string(), 2).unwrap();
+        // Тот же version, но больший node_id побеждает
+        reg.write_from("n9", "newest".to_string(), 2).unwrap();
+
+        reg.set_reachable("r1", true);
+        reg.set_reachable("r2", true);
+        reg.set_reachable("r4", false);
+        reg.set_reachable("r5", false);
+        let v = reg.read().unwrap().unwrap();
+        assert_eq!(v.value, "newest");
+        assert_eq!(v.writer, "n9");
+        assert_eq!(reg.repairs, 2);
+        assert!(reg.replicas.iter().filter(|r| r.reachable)
+            .all(|r| r.stored.as_ref().unwrap().value == "newest"));
+    }
+}
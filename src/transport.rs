@@ -10,10 +10,17 @@
 //   3. SyncBarrier        — барьер синхронизации для CumulativeStrike
 //   4. TransportChannel   — канал с мутацией и jitter
 //   5. TransportScheduler — планировщик синхронных ударов
+//   6. PacketSink         — куда уходят байты фреймов (UDP/TCP/loopback)
+//   7. DeliveryReport     — исход доставки (ack/таймаут) для NeuralRouter
+//   8. WireFrame          — байты на проводе без служебных полей канала
 // =============================================================================
 
+use crate::neural_node::NeuralInput;
+use crate::rng::{FedRng, RngCore, XorShift64};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex};
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 pub const FRAME_VERSION: u8 = 1;
@@ -21,6 +28,11 @@ pub const MAX_FRAME_SIZE: usize = 65535;
 pub const MIN_JITTER_US: u64 = 100;    // минимальный джиттер 100 мкс
 pub const MAX_JITTER_US: u64 = 50_000; // максимальный 50 мс
 pub const SYNC_WINDOW_US: u64 = 1_000; // окно синхронизации 1 мс
+pub const DECOY_SIZE_HISTORY: usize = 32; // размеров реальных пакетов для формы приманок
+pub const MIN_ADAPTIVE_DECOYS: usize = 1;  // чистый канал
+pub const MAX_ADAPTIVE_DECOYS: usize = 12; // Mirage видит атаку
+pub const ACK_TIMEOUT_US: u64 = 2_000_000;  // без ack за 2 с — доставка провалена
+pub const MAX_IN_FLIGHT: usize = 1024;      // фреймов без ack на канал, дальше вытесняются старые
 
 // -----------------------------------------------------------------------------
 // MicroClock — микросекундный таймер
@@ -28,7 +40,7 @@ pub const SYNC_WINDOW_US: u64 = 1_000; // окно синхронизации 1
 
 pub struct MicroClock {
     pub epoch: Instant,
-    pub rng: u64,
+    pub rng: XorShift64,
 }
 
 impl MicroClock {
@@ -36,10 +48,15 @@ impl MicroClock {
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
         MicroClock {
             epoch: Instant::now(),
-            rng: now.as_nanos() as u64 ^ 0xcafe_babe_dead_beef,
+            rng: XorShift64::new(now.as_nanos() as u64 ^ 0xcafe_babe_dead_beef),
         }
     }
 
+    /// Часы с джиттером из внешнего FedRng
+    pub fn with_rng(rng: &mut dyn FedRng) -> Self {
+        MicroClock { epoch: Instant::now(), rng: XorShift64::new(rng.next_u64()) }
+    }
+
     /// Текущее время в микросекундах от запуска
     pub fn now_us(&self) -> u64 {
         self.epoch.elapsed().as_micros() as u64
@@ -52,10 +69,7 @@ impl MicroClock {
 
     /// Случайный джиттер в мкс
     pub fn jitter_us(&mut self, min: u64, max: u64) -> u64 {
-        self.rng ^= self.rng << 13;
-        self.rng ^= self.rng >> 7;
-        self.rng ^= self.rng << 17;
-        min + (self.rng % (max - min + 1))
+        min + (self.rng.next_u64() % (max - min + 1))
     }
 
     /// Синхронная метка — округление до ближайшего окна
@@ -88,6 +102,8 @@ pub struct TransportFrame {
     pub hop_count: u8,
     pub ttl: u8,
     pub checksum: u32,
+    #[serde(default)]
+    pub max_delay_us: Option<u64>, // бюджет задержки от created_us
 }
 
 impl TransportFrame {
@@ -110,6 +126,7 @@ impl TransportFrame {
             jitter_us: 0,
             hop_count: 0,
             ttl: 16,
+            max_delay_us: None,
         };
         f.checksum = f.compute_checksum();
         f
@@ -142,6 +159,132 @@ impl TransportFrame {
     pub fn latency_us(&self, clock: &MicroClock) -> u64 {
         clock.now_us().saturating_sub(self.created_us)
     }
+
+    /// Байты фрейма на проводе — только WireFrame. Признак приманки,
+    /// адреса, группа удара и расписание в них не попадают
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        WireFrame {
+            version: self.version,
+            frame_id: self.frame_id,
+            hop_count: self.hop_count,
+            ttl: self.ttl,
+            mask_type: self.mask_type.clone(),
+            payload: self.payload.clone(),
+        }.to_bytes()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// WireFrame — то, что видит наблюдатель на проводе
+// -----------------------------------------------------------------------------
+
+/// Формат: version u8 | frame_id u64 | hop_count u8 | ttl u8 |
+/// mask_len u8 | mask | payload_len u32 | payload | fnv1a u32 (всё выше, BE).
+/// Приманка и реальный пакет одного размера неотличимы по структуре.
+/// Кодируется только то, что from_bytes примет обратно
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireFrame {
+    pub version: u8,
+    pub frame_id: u64,
+    pub hop_count: u8,
+    pub ttl: u8,
+    pub mask_type: String,
+    pub payload: Vec<u8>,
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5u32, |h, &b| (h ^ b as u32).wrapping_mul(0x01000193))
+}
+
+impl WireFrame {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        let mask = self.mask_type.as_bytes();
+        if mask.len() > u8::MAX as usize { return Err("mask_type длиннее 255 байт"); }
+        if self.payload.len() > MAX_FRAME_SIZE { return Err("payload больше MAX_FRAME_SIZE"); }
+        let mut out = Vec::with_capacity(20 + mask.len() + self.payload.len());
+        out.push(self.version);
+        out.extend_from_slice(&self.frame_id.to_be_bytes());
+        out.push(self.hop_count);
+        out.push(self.ttl);
+        out.push(mask.len() as u8);
+        out.extend_from_slice(mask);
+        out.extend_from_slice(&(self.payload.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.payload);
+        out.extend_from_slice(&fnv1a(&out).to_be_bytes());
+        Ok(out)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() < 20 { return Err("фрейм короче заголовка"); }
+        let (body, sum) = bytes.split_at(bytes.len() - 4);
+        if fnv1a(body).to_be_bytes() != sum { return Err("контрольная сумма не сошлась"); }
+        let mask_len = body[11] as usize;
+        let mask_end = 12 + mask_len;
+        if body.len() < mask_end + 4 { return Err("обрезанный mask_type"); }
+        let payload_len = u32::from_be_bytes(body[mask_end..mask_end + 4].try_into().unwrap());
+        let payload = &body[mask_end + 4..];
+        if payload.len() != payload_len as usize { return Err("длина payload не сходится"); }
+        if payload.len() > MAX_FRAME_SIZE { return Err("payload больше MAX_FRAME_SIZE"); }
+        Ok(WireFrame {
+            version: body[0],
+            frame_id: u64::from_be_bytes(body[1..9].try_into().unwrap()),
+            hop_count: body[9],
+            ttl: body[10],
+            mask_type: String::from_utf8(body[12..mask_end].to_vec())
+                .map_err(|_| "mask_type не UTF-8")?,
+            payload: payload.to_vec(),
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// PacketSink — физический выход канала
+// -----------------------------------------------------------------------------
+
+/// Куда TransportChannel отдаёт готовые фреймы. Мутация, jitter и
+/// приманки остаются в канале — реализация только двигает байты
+pub trait PacketSink: Send {
+    fn send(&mut self, frame_bytes: &[u8]) -> io::Result<()>;
+}
+
+/// Sink без выхода: фреймы отбрасываются. Выход по умолчанию — ничего
+/// не копит, сколько бы канал ни работал
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiscardSink;
+
+impl PacketSink for DiscardSink {
+    fn send(&mut self, _frame_bytes: &[u8]) -> io::Result<()> { Ok(()) }
+}
+
+/// Sink в памяти: клоны делят один буфер — один конец отдаётся
+/// каналу, другим читают отправленное
+#[derive(Debug, Clone, Default)]
+pub struct LoopbackSink {
+    frames: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+impl LoopbackSink {
+    pub fn new() -> Self { Self::default() }
+
+    /// Забрать следующий отправленный фрейм
+    pub fn recv(&self) -> Option<Vec<u8>> {
+        self.frames.lock().ok()?.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.lock().map(|f| f.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl PacketSink for LoopbackSink {
+    fn send(&mut self, frame_bytes: &[u8]) -> io::Result<()> {
+        self.frames.lock()
+            .map_err(|_| io::Error::other("loopback отравлен"))?
+            .push_back(frame_bytes.to_vec());
+        Ok(())
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -217,6 +360,18 @@ pub struct TransportChannel {
     pub avg_latency_us: f64,
     pub queue: Vec<TransportFrame>,
     pub jitter_history: Vec<u64>,
+    pub send_errors: u64,
+    pub real_enqueued: u64,
+    pub decoys_enqueued: u64,
+    pub real_sizes: VecDeque<usize>,  // последние размеры реальных пакетов
+    pub min_decoys: usize,            // границы send_adaptive
+    pub max_decoys: usize,
+    pub adaptive_history: Vec<(f64, usize)>,  // (threat_level, приманок)
+    pub ack_timeout_us: u64,
+    route_input: Option<NeuralInput>,            // вход, на котором выбран dst
+    in_flight: HashMap<u64, (u64, NeuralInput)>, // frame_id → (отправлен, вход)
+    sink: Box<dyn PacketSink>,
+    rng: XorShift64,                             // размеры и байты приманок
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -230,7 +385,13 @@ pub struct SendResult {
 }
 
 impl TransportChannel {
+    /// Канал в DiscardSink — для демо и тестов
     pub fn new(src: &str, dst: &str) -> Self {
+        Self::with_sink(src, dst, Box::new(DiscardSink))
+    }
+
+    /// Канал поверх любого PacketSink (UDP, TCP, ...)
+    pub fn with_sink(src: &str, dst: &str, sink: Box<dyn PacketSink>) -> Self {
         TransportChannel {
             channel_id: format!("{}->{}", src, dst),
             src: src.to_string(),
@@ -242,18 +403,44 @@ impl TransportChannel {
             avg_latency_us: 0.0,
             queue: vec![],
             jitter_history: vec![],
+            send_errors: 0,
+            real_enqueued: 0,
+            decoys_enqueued: 0,
+            real_sizes: VecDeque::new(),
+            min_decoys: MIN_ADAPTIVE_DECOYS,
+            max_decoys: MAX_ADAPTIVE_DECOYS,
+            adaptive_history: vec![],
+            ack_timeout_us: ACK_TIMEOUT_US,
+            route_input: None,
+            in_flight: HashMap::new(),
+            sink,
+            rng: XorShift64::from_time(),
         }
     }
 
     /// Применить мутацию и поставить в очередь
     pub fn enqueue(&mut self, payload: &[u8], mask_type: &str,
                    is_decoy: bool, strike_group: Option<u64>) -> SendResult {
-        let jitter = self.clock.jitter_us(MIN_JITTER_US, MAX_JITTER_US);
+        self.enqueue_within(payload, mask_type, is_decoy, strike_group, None)
+    }
+
+    /// enqueue с бюджетом задержки: джиттер урезается так, чтобы
+    /// scheduled_us ≤ created_us + max_delay_us; бюджет исчерпан — без джиттера
+    pub fn enqueue_within(&mut self, payload: &[u8], mask_type: &str,
+                          is_decoy: bool, strike_group: Option<u64>,
+                          max_delay_us: Option<u64>) -> SendResult {
+        let sampled = self.clock.jitter_us(MIN_JITTER_US, MAX_JITTER_US);
         let now = self.clock.now_us();
         let sync = self.clock.sync_mark(SYNC_WINDOW_US);
 
         let mut frame = TransportFrame::new(&self.src, &self.dst,
             payload.to_vec(), &self.clock);
+        let jitter = match max_delay_us {
+            Some(budget) => sampled.min(frame.created_us.saturating_add(budget)
+                .saturating_sub(now.max(frame.created_us))),
+            None => sampled,
+        };
+        frame.max_delay_us = max_delay_us;
         frame.mask_type = mask_type.to_string();
         frame.is_decoy = is_decoy;
         frame.strike_group = strike_group;
@@ -271,6 +458,7 @@ impl TransportChannel {
         };
 
         self.queue.push(frame);
+        if is_decoy { self.decoys_enqueued += 1; } else { self.real_enqueued += 1; }
         self.jitter_history.push(jitter);
         if self.jitter_history.len() > 100 {
             self.jitter_history.remove(0);
@@ -278,55 +466,185 @@ impl TransportChannel {
         result
     }
 
+    /// Размеры приманок берутся из истории реальных пакетов канала.
+    /// Хотя бы одна приманка совпадает с текущим реальным пакетом —
+    /// по размеру он не выделяется
+    fn decoy_sizes(&mut self, real_len: usize, count: usize) -> Vec<usize> {
+        self.real_sizes.push_back(real_len);
+        if self.real_sizes.len() > DECOY_SIZE_HISTORY { self.real_sizes.pop_front(); }
+        let mut sizes: Vec<usize> = (0..count).map(|_| {
+            let i = (self.rng.next_u64() % self.real_sizes.len() as u64) as usize;
+            self.real_sizes[i]
+        }).collect();
+        if count > 0 && !sizes.contains(&real_len) {
+            let i = (self.rng.next_u64() % count as u64) as usize;
+            sizes[i] = real_len;
+        }
+        sizes
+    }
+
+    fn decoy_payload(&mut self, len: usize) -> Vec<u8> {
+        let mut payload = vec![0u8; len];
+        self.rng.fill_bytes(&mut payload);
+        payload
+    }
+
     /// Применить StandoffDecoy — обернуть реальный пакет в ложные
     pub fn send_with_decoys(&mut self, payload: &[u8], mask_type: &str,
                              decoy_count: usize) -> Vec<SendResult> {
+        self.send_with_decoys_within(payload, mask_type, decoy_count, None)
+    }
+
+    /// StandoffDecoy с бюджетом задержки: приманки получают тот же
+    /// бюджет, чтобы не отличаться от реального пакета по времени.
+    /// Место реального пакета среди приманок случайно — по позиции в
+    /// пачке его тоже не вычислить
+    pub fn send_with_decoys_within(&mut self, payload: &[u8], mask_type: &str,
+                             decoy_count: usize, max_delay_us: Option<u64>)
+                             -> Vec<SendResult> {
+        let sizes = self.decoy_sizes(payload.len(), decoy_count);
+        let real_at = (self.rng.next_u64() % (decoy_count as u64 + 1)) as usize;
         let mut results = vec![];
-        // Ложные пакеты ДО
-        for _ in 0..decoy_count/2 {
-            let decoy_payload: Vec<u8> = (0..64).map(|i| {
-                self.clock.rng ^= self.clock.rng << 13;
-                (self.clock.rng as u8).wrapping_add(i)
-            }).collect();
-            results.push(self.enqueue(&decoy_payload, mask_type, true, None));
-        }
-        // Реальный пакет
-        results.push(self.enqueue(payload, mask_type, false, None));
-        // Ложные пакеты ПОСЛЕ
-        for _ in decoy_count/2..decoy_count {
-            let decoy_payload: Vec<u8> = (0..64).map(|i| {
-                self.clock.rng ^= self.clock.rng << 17;
-                (self.clock.rng as u8).wrapping_add(i)
-            }).collect();
-            results.push(self.enqueue(&decoy_payload, mask_type, true, None));
+        for (i, &len) in sizes.iter().enumerate() {
+            if i == real_at {
+                results.push(self.enqueue_within(payload, mask_type, false, None, max_delay_us));
+            }
+            let decoy_payload = self.decoy_payload(len);
+            results.push(self.enqueue_within(&decoy_payload, mask_type, true, None,
+                max_delay_us));
+        }
+        if real_at == decoy_count {
+            results.push(self.enqueue_within(payload, mask_type, false, None, max_delay_us));
         }
         results
     }
 
+    /// Границы числа приманок для send_adaptive; min > max меняются местами
+    pub fn set_decoy_range(&mut self, min: usize, max: usize) {
+        self.min_decoys = min.min(max);
+        self.max_decoys = min.max(max);
+    }
+
+    /// Угроза 0..1 линейно → приманок от min_decoys до max_decoys
+    pub fn decoys_for_threat(&self, threat_level: f64) -> usize {
+        let t = if threat_level.is_nan() { 1.0 } else { threat_level.clamp(0.0, 1.0) };
+        let span = (self.max_decoys - self.min_decoys) as f64;
+        self.min_decoys + (span * t).round() as usize
+    }
+
+    /// StandoffDecoy с числом приманок по угрозе Mirage: под атакой —
+    /// больше шума, на чистом канале — экономия полосы
+    pub fn send_adaptive(&mut self, payload: &[u8], mask_type: &str,
+                         threat_level: f64) -> Vec<SendResult> {
+        let count = self.decoys_for_threat(threat_level);
+        self.adaptive_history.push((threat_level, count));
+        if self.adaptive_history.len() > 100 {
+            self.adaptive_history.remove(0);
+        }
+        self.send_with_decoys(payload, mask_type, count)
+    }
+
     /// Применить CumulativeStrike — синхронизированная отправка
     pub fn send_strike(&mut self, payload: &[u8], mask_type: &str,
                         group_id: u64) -> SendResult {
         self.enqueue(payload, mask_type, false, Some(group_id))
     }
 
-    /// Сфлашить очередь (симуляция отправки)
+    /// Отправить в sink все фреймы, чьё время пришло
     pub fn flush(&mut self) -> Vec<TransportFrame> {
         let now = self.clock.now_us();
+        self.flush_at(now)
+    }
+
+    /// flush на момент now_us (мкс от запуска часов канала). Фреймы уходят
+    /// в sink в порядке очереди; при ошибке sink остаток возвращается в
+    /// голову очереди и ждёт следующего flush. Фрейм, который нельзя
+    /// закодировать, не уйдёт никогда — он отбрасывается
+    pub fn flush_at(&mut self, now_us: u64) -> Vec<TransportFrame> {
         // Разделяем — готовые и ещё не время
         let (ready, pending): (Vec<_>, Vec<_>) = self.queue.drain(..)
-            .partition(|f| f.scheduled_us <= now + 1000);
+            .partition(|f| f.scheduled_us <= now_us.saturating_add(1000));
         self.queue = pending;
 
-        for f in &ready {
+        let mut sent = Vec::with_capacity(ready.len());
+        let mut ready = ready.into_iter();
+        for f in ready.by_ref() {
+            let bytes = match f.to_bytes() {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::warn!("[{}] фрейм {} отброшен: {}", self.channel_id, f.frame_id, e);
+                    self.send_errors += 1;
+                    continue;
+                }
+            };
+            if let Err(e) = self.sink.send(&bytes) {
+                log::warn!("[{}] sink: {}", self.channel_id, e);
+                self.send_errors += 1;
+                let mut rest = vec![f];
+                rest.extend(ready.by_ref());
+                rest.append(&mut self.queue);
+                self.queue = rest;
+                break;
+            }
             self.frames_sent += 1;
             self.bytes_sent += f.payload.len() as u64;
             if f.is_decoy { self.decoys_sent += 1; }
+            else if let Some(input) = self.route_input.clone() {
+                self.track_in_flight(f.frame_id, now_us, input);
+            }
             let lat = f.latency_us(&self.clock) as f64;
             self.avg_latency_us = self.avg_latency_us * 0.9 + lat * 0.1;
+            sent.push(f);
         }
-        ready
+        sent
+    }
+
+    /// Фрейм ждёт ack. Если expire_at давно не вызывали и карта заполнена,
+    /// вытесняется самый старый фрейм — его отчёт о провале теряется
+    fn track_in_flight(&mut self, frame_id: u64, now_us: u64, input: NeuralInput) {
+        if self.in_flight.len() >= MAX_IN_FLIGHT {
+            let oldest = self.in_flight.iter()
+                .min_by_key(|(&id, (sent_us, _))| (*sent_us, id))
+                .map(|(&id, _)| id);
+            if let Some(id) = oldest {
+                log::debug!("[{}] фрейм {} вытеснен из ожидания ack", self.channel_id, id);
+                self.in_flight.remove(&id);
+            }
+        }
+        self.in_flight.insert(frame_id, (now_us, input));
+    }
+
+    /// Вход NeuralRouter, по которому выбран dst. Реальные фреймы после
+    /// flush ждут ack и дают DeliveryReport; без входа — не отслеживаются
+    pub fn set_route_input(&mut self, input: NeuralInput) {
+        self.route_input = Some(input);
+    }
+
+    /// Ack от dst на момент now_us. Качество падает с RTT: мгновенный ack — 1,
+    /// на границе таймаута — 0. Ack позже таймаута — провал
+    pub fn ack(&mut self, frame_id: u64, now_us: u64) -> Option<DeliveryReport> {
+        let (sent_us, input) = self.in_flight.remove(&frame_id)?;
+        let rtt = now_us.saturating_sub(sent_us);
+        let success = rtt <= self.ack_timeout_us;
+        let quality = if success {
+            1.0 - rtt as f64 / self.ack_timeout_us.max(1) as f64
+        } else { 0.0 };
+        Some(DeliveryReport { neighbor: self.dst.clone(), input, success, quality })
+    }
+
+    /// Фреймы без ack дольше ack_timeout_us — отчёты о провале, по frame_id
+    pub fn expire_at(&mut self, now_us: u64) -> Vec<DeliveryReport> {
+        let mut expired: Vec<u64> = self.in_flight.iter()
+            .filter(|(_, (sent_us, _))| now_us.saturating_sub(*sent_us) > self.ack_timeout_us)
+            .map(|(&id, _)| id).collect();
+        expired.sort_unstable();
+        expired.into_iter().filter_map(|id| self.in_flight.remove(&id)).map(|(_, input)| {
+            DeliveryReport { neighbor: self.dst.clone(), input, success: false, quality: 0.0 }
+        }).collect()
     }
 
+    pub fn in_flight(&self) -> usize { self.in_flight.len() }
+
     pub fn jitter_entropy(&self) -> f64 {
         if self.jitter_history.len() < 2 { return 0.0; }
         let mean = self.jitter_history.iter().sum::<u64>() as f64
@@ -349,10 +667,22 @@ impl TransportChannel {
             decoy_ratio: if self.frames_sent > 0 {
                 self.decoys_sent as f64 / self.frames_sent as f64
             } else { 0.0 },
+            decoys_per_real: if self.real_enqueued > 0 {
+                self.decoys_enqueued as f64 / self.real_enqueued as f64
+            } else { 0.0 },
         }
     }
 }
 
+/// Исход доставки реального фрейма: соседу neighbor на входе input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    pub neighbor: String,
+    pub input: NeuralInput,
+    pub success: bool,
+    pub quality: f64,   // 0..1, из RTT
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelStats {
     pub channel_id: String,
@@ -362,7 +692,8 @@ pub struct ChannelStats {
     pub queue_depth: usize,
     pub avg_latency_us: f64,
     pub jitter_entropy: f64,
-    pub decoy_ratio: f64,
+    pub decoy_ratio: f64,      // доля приманок среди отправленных
+    pub decoys_per_real: f64,  // приманок на реальный пакет (накопительно)
 }
 
 impl std::fmt::Display for ChannelStats {
@@ -371,11 +702,11 @@ impl std::fmt::Display for ChannelStats {
             "Channel [{}]\n  \
              Фреймов: {:>6}  Байт: {:>8}  Коробочек: {:>5}\n  \
              Очередь: {:>3}  Задержка: {:>8.1}мкс  \
-             Джиттер: {:>6.4}  Decoy%: {:.1}%",
+             Джиттер: {:>6.4}  Decoy%: {:.1}%  Приманок/реальный: {:.1}",
             self.channel_id,
             self.frames_sent, self.bytes_sent, self.decoys_sent,
             self.queue_depth, self.avg_latency_us,
-            self.jitter_entropy, self.decoy_ratio * 100.0,
+            self.jitter_entropy, self.decoy_ratio * 100.0, self.decoys_per_real,
         )
     }
 }
@@ -403,8 +734,13 @@ impl TransportScheduler {
         }
     }
 
+    /// Канал в DiscardSink
     pub fn add_channel(&mut self, src: &str, dst: &str) {
-        let ch = TransportChannel::new(src, dst);
+        self.add_channel_with_sink(src, dst, Box::new(DiscardSink));
+    }
+
+    pub fn add_channel_with_sink(&mut self, src: &str, dst: &str, sink: Box<dyn PacketSink>) {
+        let ch = TransportChannel::with_sink(src, dst, sink);
         self.channels.insert(ch.channel_id.clone(), ch);
     }
 
@@ -683,3 +1019,193 @@ impl HierarchicalRouter {
 }
 
 impl Default for HierarchicalRouter { fn default() -> Self { Self::new() } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // Sink, который отказывает после limit фреймов
+    struct FlakySink { inner: LoopbackSink, limit: usize }
+
+    impl PacketSink for FlakySink {
+        fn send(&mut self, frame_bytes: &[u8]) -> io::Result<()> {
+            if self.inner.len() >= self.limit {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "буфер полон"));
+            }
+            self.inner.send(frame_bytes)
+        }
+    }
+
+    #[test]
+    fn test_loopback_receives_real_and_decoys_in_order() {
+        let sink = LoopbackSink::new();
+        let mut ch = TransportChannel::with_sink("a", "b", Box::new(sink.clone()));
+        let results = ch.send_with_decoys(b"REAL", "HttpsRequest", 4);
+        let sent = ch.flush_at(ch.clock.now_us() + MAX_JITTER_US);
+        assert_eq!(sent.len(), 5);
+        assert!(ch.queue.is_empty());
+
+        let delivered: Vec<WireFrame> = std::iter::from_fn(|| sink.recv())
+            .map(|b| WireFrame::from_bytes(&b).unwrap()).collect();
+        let real: Vec<usize> = (0..results.len()).filter(|&i| !results[i].is_decoy).collect();
+        assert_eq!(real.len(), 1);
+        assert_eq!(delivered[real[0]].payload, b"REAL");
+        for ((frame, r), f) in delivered.iter().zip(&results).zip(&sent) {
+            assert_eq!(frame.frame_id, r.frame_id);
+            assert_eq!((frame.version, frame.ttl), (FRAME_VERSION, f.ttl));
+        }
+        assert_eq!((ch.frames_sent, ch.decoys_sent), (5, 4));
+    }
+
+    #[test]
+    fn test_decoy_and_real_share_wire_structure() {
+        let clock = MicroClock::new();
+        let real = TransportFrame::new("a", "b", b"REAL".to_vec(), &clock);
+        let decoy = TransportFrame {
+            src_node: "ghost".into(), dst_node: "nowhere".into(), is_decoy: true,
+            strike_group: Some(7), scheduled_us: real.scheduled_us + 5_000,
+            jitter_us: 5_000, max_delay_us: Some(10_000), payload: b"FAKE".to_vec(),
+            ..real.clone()
+        };
+        let (real_bytes, decoy_bytes) = (real.to_bytes().unwrap(), decoy.to_bytes().unwrap());
+        // Отличаются только payload и контрольная сумма
+        assert_eq!(real_bytes.len(), decoy_bytes.len());
+        let differ: Vec<usize> = (0..real_bytes.len())
+            .filter(|&i| real_bytes[i] != decoy_bytes[i]).collect();
+        let tail = real_bytes.len() - 8;
+        assert!(differ.iter().all(|&i| i >= tail), "{:?}", differ);
+        for bytes in [&real_bytes, &decoy_bytes] {
+            let text = String::from_utf8_lossy(bytes);
+            assert!(!text.contains("ghost") && !text.contains("nowhere"));
+        }
+
+        let mut corrupted = real_bytes.clone();
+        corrupted[12] ^= 1;
+        assert!(WireFrame::from_bytes(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_unencodable_frames_dropped_not_truncated() {
+        let sink = LoopbackSink::new();
+        let mut ch = TransportChannel::with_sink("a", "b", Box::new(sink.clone()));
+        // 254 байта ASCII + «ж»: обрезка до 255 разрезала бы символ пополам
+        let long_mask = format!("{}ж", "m".repeat(254));
+        ch.enqueue(b"x", &long_mask, false, None);
+        ch.enqueue(&vec![0u8; MAX_FRAME_SIZE + 1], "HttpsRequest", false, None);
+        ch.enqueue(b"ok", "HttpsRequest", false, None);
+
+        let sent = ch.flush_at(ch.clock.now_us() + MAX_JITTER_US);
+        assert_eq!(sent.len(), 1);
+        assert_eq!((ch.send_errors, ch.queue.len()), (2, 0));
+        assert_eq!(WireFrame::from_bytes(&sink.recv().unwrap()).unwrap().payload, b"ok");
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_in_flight_capped_oldest_evicted() {
+        let mut ch = TransportChannel::new("a", "b");
+        ch.set_route_input(NeuralInput { latency: 0.3, bandwidth: 0.8,
+            reliability: 0.9, trust: 0.7, ethics_score: 1.0 });
+        let mut ids = vec![];
+        for i in 0..MAX_IN_FLIGHT as u64 + 3 {
+            ids.push(ch.enqueue(b"x", "HttpsRequest", false, None).frame_id);
+            ch.flush_at(ch.clock.now_us() + MAX_JITTER_US + i);
+        }
+        assert_eq!(ch.in_flight(), MAX_IN_FLIGHT);
+        assert!(ids[..3].iter().all(|&id| ch.ack(id, 0).is_none()));
+        assert!(ch.ack(ids[3], 0).is_some());
+    }
+
+    #[test]
+    fn test_real_frame_hides_among_decoys_by_size() {
+        let mut ch = TransportChannel::new("a", "b");
+        ch.clock = MicroClock::with_rng(&mut XorShift64::new(7));
+        ch.rng = XorShift64::new(7);
+        let mut positions = HashSet::new();
+        for i in 0..200usize {
+            let payload = vec![0xAB; 20 + (i * 37) % 400];
+            let results = ch.send_with_decoys(&payload, "HttpsRequest", 6);
+            assert_eq!(results.len(), 7);
+            let frames = &ch.queue[ch.queue.len() - 7..];
+            assert_eq!(frames.iter().filter(|f| !f.is_decoy).count(), 1);
+            positions.insert(frames.iter().position(|f| !f.is_decoy).unwrap());
+            let real = frames.iter().find(|f| !f.is_decoy).unwrap();
+            // Размер реального пакета встречается и среди приманок
+            assert!(frames.iter().any(|f| f.is_decoy && f.payload.len() == real.payload.len()));
+            let sizes: HashSet<usize> = frames.iter().map(|f| f.payload.len()).collect();
+            if i >= DECOY_SIZE_HISTORY { assert!(sizes.len() > 1, "приманки повторяют историю"); }
+        }
+        // Реальный пакет побывал на каждом месте пачки, включая края
+        assert_eq!(positions.len(), 7);
+        ch.flush_at(ch.clock.now_us() + MAX_JITTER_US);
+        let stats = ch.stats();
+        assert_eq!(stats.decoys_per_real, 6.0);
+        assert!((stats.decoy_ratio - 6.0 / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_adaptive_decoys_follow_threat_level() {
+        let mut ch = TransportChannel::new("a", "b");
+        ch.set_decoy_range(2, 10);
+        assert_eq!(ch.send_adaptive(b"x", "HttpsRequest", 0.0).len(), 1 + 2);
+        assert_eq!(ch.send_adaptive(b"x", "HttpsRequest", 1.0).len(), 1 + 10);
+        assert_eq!(ch.adaptive_history, [(0.0, 2), (1.0, 10)]);
+
+        let mut prev = ch.decoys_for_threat(0.0);
+        for step in 1..=100 {
+            let n = ch.decoys_for_threat(step as f64 / 100.0);
+            assert!(n >= prev);
+            prev = n;
+        }
+        // Вне диапазона и NaN — к границам, NaN считается угрозой
+        assert_eq!(ch.decoys_for_threat(-3.0), 2);
+        assert_eq!(ch.decoys_for_threat(7.0), 10);
+        assert_eq!(ch.decoys_for_threat(f64::NAN), 10);
+    }
+
+    #[test]
+    fn test_delay_budget_caps_jitter() {
+        let channel = || {
+            let mut ch = TransportChannel::new("a", "b");
+            ch.clock = MicroClock::with_rng(&mut XorShift64::new(11));
+            ch
+        };
+        let (mut free, mut tight, mut loose) = (channel(), channel(), channel());
+        for _ in 0..50 {
+            let sampled = free.enqueue(b"pulse", "raw", false, None).jitter_applied_us;
+            let capped = tight.enqueue_within(b"pulse", "raw", false, None, Some(500));
+            let full = loose.enqueue_within(b"pulse", "raw", false, None, Some(1_000_000));
+            assert!(capped.jitter_applied_us <= sampled.min(500));
+            assert_eq!(full.jitter_applied_us, sampled);
+        }
+        // Бюджет у границы u64 не переполняет дедлайн
+        let unbounded = loose.enqueue_within(b"pulse", "raw", false, None, Some(u64::MAX));
+        assert!(unbounded.jitter_applied_us <= MAX_JITTER_US);
+        for f in &tight.queue {
+            assert!(f.scheduled_us <= f.created_us + 500);
+        }
+        assert!(tight.queue.iter().any(|f| f.jitter_us == 500), "джиттер упёрся в бюджет");
+
+        // Бюджет исчерпан — джиттера нет; приманки несут тот же бюджет
+        let results = tight.send_with_decoys_within(b"pulse", "raw", 4, Some(0));
+        assert!(results.iter().all(|r| r.jitter_applied_us == 0));
+        assert!(tight.queue.iter().rev().take(5).all(|f| f.max_delay_us == Some(0)));
+    }
+
+    #[test]
+    fn test_sink_error_keeps_unsent_frames_queued() {
+        let inner = LoopbackSink::new();
+        let mut ch = TransportChannel::with_sink("a", "b",
+            Box::new(FlakySink { inner: inner.clone(), limit: 2 }));
+        let results = ch.send_with_decoys(b"REAL", "HttpsRequest", 4);
+        let late = ch.clock.now_us() + MAX_JITTER_US;
+        assert_eq!(ch.flush_at(late).len(), 2);
+        assert_eq!((ch.queue.len(), ch.send_errors, ch.frames_sent), (3, 1, 2));
+        // Неотправленные ждут в прежнем порядке
+        let queued: Vec<u64> = ch.queue.iter().map(|f| f.frame_id).collect();
+        let unsent: Vec<u64> = results[2..].iter().map(|r| r.frame_id).collect();
+        assert_eq!(queued, unsent);
+        assert_eq!(inner.len(), 2);
+    }
+}
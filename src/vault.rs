@@ -11,6 +11,7 @@
 // Ghost узлы: хранят осколок не зная что это и чей он.
 // =============================================================================
 
+use crate::rng::{FedRng, RngCore, XorShift64};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -20,6 +21,7 @@ pub const DEFAULT_SHARDS: usize    = 5;        // N осколков
 pub const DEFAULT_THRESHOLD: usize = 3;        // K для восстановления
 pub const GHOST_MEMORY_KB: usize   = 4;        // размер осколка в памяти Ghost
 pub const ZK_PROOF_SIZE: usize     = 32;       // байт ZK-доказательства
+pub const HOT_TTL_MS: i64          = 3_600_000; // срок жизни Hot записи — 1 час
 
 // -----------------------------------------------------------------------------
 // VaultTier — уровень хранилища
@@ -49,6 +51,32 @@ impl VaultTier {
     }
 }
 
+// -----------------------------------------------------------------------------
+// VaultError — причина отказа в доступе
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VaultError {
+    Expired,                               // ZK proof просрочен
+    RepTooLow { required: f64, actual: f64 },
+    CommitmentMismatch,                    // proof выдан не для этого ключа
+    NotFound,
+}
+
+impl std::fmt::Display for VaultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultError::Expired => write!(f, "ZK proof истёк"),
+            VaultError::RepTooLow { required, actual } => write!(f,
+                "Недостаточная репутация: {:.1} < {:.1}", actual, required),
+            VaultError::CommitmentMismatch => write!(f, "Commitment не совпадает"),
+            VaultError::NotFound => write!(f, "Ключ не найден"),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
 // -----------------------------------------------------------------------------
 // ZkProof — нулевое знание доступа
 // -----------------------------------------------------------------------------
@@ -63,31 +91,17 @@ pub struct ZkProof {
 }
 
 impl ZkProof {
-    pub fn generate(owner_id: &str, secret: &[u8], rng: &mut u64) -> Self {
+    /// Доказательство с явным сроком действия
+    pub fn generate_at(owner_id: &str, secret: &[u8], rng: &mut dyn FedRng,
+                       expires_at: i64) -> Self {
         // Упрощённый ZK — в production заменить на настоящий ZKP
-        *rng ^= *rng << 13; *rng ^= *rng >> 7; *rng ^= *rng << 17;
-        let commitment = format!("commit_{:016x}", *rng ^ owner_id.len() as u64);
-        *rng ^= *rng << 13; *rng ^= *rng >> 7;
-        let nullifier = format!("null_{:016x}", *rng);
+        let commitment = format!("commit_{:016x}", rng.next_u64() ^ owner_id.len() as u64);
+        let nullifier = format!("null_{:016x}", rng.next_u64());
         let proof_hash = format!("zkp_{:08x}{:08x}",
             secret.iter().fold(0u32, |a, &b| a.wrapping_add(b as u32)),
             owner_id.bytes().fold(0u32, |a, b| a.wrapping_mul(31).wrapping_add(b as u32)));
 
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)
-            .unwrap().as_millis() as i64;
-
-        ZkProof {
-            proof_hash, commitment, nullifier,
-            valid: true, expires_at: now + 3_600_000, // 1 час
-        }
-    }
-
-    pub fn verify(&self) -> bool {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)
-            .unwrap().as_millis() as i64;
-        self.valid && now < self.expires_at
+        ZkProof { proof_hash, commitment, nullifier, valid: true, expires_at }
     }
 }
 
@@ -106,6 +120,7 @@ pub struct VaultEntry {
     pub accessed_at: i64,
     pub access_count: u32,
     pub reputation_required: f64,    // минимальная репутация для доступа
+    pub expires_at: i64,             // после — запись недоступна
 }
 
 impl VaultEntry {
@@ -131,9 +146,35 @@ pub struct KeyShard {
 }
 
 impl KeyShard {
+    /// Commitment конкретного осколка: общий commitment ключа, посоленный
+    /// номером осколка. Реальные осколки разных Ghost не связываются между
+    /// собой, а владелец по commitment ключа находит каждый свой осколок
+    pub fn salted_commitment(commitment: &str, shard_id: u8) -> String {
+        let prefix = commitment.split('_').next().unwrap_or("commit");
+        let mut h: u64 = 0xcbf29ce484222325;
+        for b in commitment.bytes().chain([shard_id]) {
+            h ^= b as u64; h = h.wrapping_mul(0x100000001b3);
+        }
+        format!("{}_{:016x}", prefix, h)
+    }
+
     pub fn memory_kb(&self) -> f64 {
         self.shard_data.len() as f64 / 1024.0
     }
+
+    /// Инвариант формы: реальный и ложный осколок обязаны выглядеть одинаково
+    pub fn is_well_formed(&self) -> bool {
+        fn hex_tagged(s: &str, prefix: &str) -> bool {
+            s.strip_prefix(prefix).is_some_and(|h| h.len() == 16
+                && h.bytes().all(|b| b.is_ascii_hexdigit()))
+        }
+        self.threshold >= 1
+            && self.threshold <= self.total_shards
+            && self.shard_id >= 1 && self.shard_id <= self.total_shards
+            && !self.shard_data.is_empty()
+            && hex_tagged(&self.key_commitment, "commit_")
+            && hex_tagged(&self.owner_commitment, "owner_")
+    }
 }
 
 
@@ -173,15 +214,12 @@ pub struct ShamirScheme;
 impl ShamirScheme {
     /// Разделить секрет на N осколков, K достаточно для восстановления
     pub fn split(secret: &[u8], n: usize, k: usize,
-                 rng: &mut u64) -> Vec<Vec<u8>> {
+                 rng: &mut dyn FedRng) -> Vec<Vec<u8>> {
         let mut shards = vec![vec![0u8; secret.len()]; n];
 
         // Генерируем k-1 случайных полиномов
         let coeffs: Vec<Vec<u8>> = (0..k-1).map(|_| {
-            (0..secret.len()).map(|_| {
-                *rng ^= *rng << 13; *rng ^= *rng >> 7; *rng ^= *rng << 17;
-                (*rng & 0xff) as u8
-            }).collect()
+            (0..secret.len()).map(|_| (rng.next_u64() & 0xff) as u8).collect()
         }).collect();
 
         for i in 0..n {
@@ -233,6 +271,7 @@ pub struct GhostNetwork {
     pub nodes: HashMap<String, Vec<KeyShard>>,  // ghost_id → осколки
     pub total_shards: u64,
     pub total_decoys: u64,
+    pub key_counts: HashMap<String, (u64, u64)>, // key_id → (реальных, ложных)
 }
 
 impl GhostNetwork {
@@ -241,9 +280,25 @@ impl GhostNetwork {
             nodes: HashMap::new(),
             total_shards: 0,
             total_decoys: 0,
+            key_counts: HashMap::new(),
         }
     }
 
+    /// Сохранить осколок с учётом, к какому ключу он относится
+    pub fn store_key_shard(&mut self, key_id: &str, ghost_id: &str, shard: KeyShard) {
+        let counts = self.key_counts.entry(key_id.to_string()).or_insert((0, 0));
+        if shard.is_decoy { counts.1 += 1; } else { counts.0 += 1; }
+        self.store_shard(ghost_id, shard);
+    }
+
+    /// Доля ложных осколков среди всех осколков ключа
+    pub fn decoy_ratio(&self, key_id: &str) -> Option<f64> {
+        let (real, decoys) = *self.key_counts.get(key_id)?;
+        let total = real + decoys;
+        if total == 0 { return None; }
+        Some(decoys as f64 / total as f64)
+    }
+
     pub fn register_ghost(&mut self, ghost_id: &str) {
         self.nodes.entry(ghost_id.to_string()).or_default();
     }
@@ -277,6 +332,9 @@ impl Default for GhostNetwork { fn default() -> Self { Self::new() } }
 // CryptoVault — главное хранилище
 // -----------------------------------------------------------------------------
 
+/// Источник времени в мс — подменяется в тестах
+pub type VaultClock = Box<dyn Fn() -> i64 + Send + Sync>;
+
 pub struct CryptoVault {
     pub hot: HashMap<String, VaultEntry>,
     pub cold: HashMap<String, VaultEntry>,
@@ -284,7 +342,8 @@ pub struct CryptoVault {
     pub shard_index: HashMap<String, Vec<(u8, String)>>, // key_id → [(shard_id, ghost_id)]
     pub total_entries: u64,
     pub total_zk_proofs: u64,
-    rng: u64,
+    rng: XorShift64,
+    clock: VaultClock,
 }
 
 impl CryptoVault {
@@ -292,37 +351,55 @@ impl CryptoVault {
         use std::time::{SystemTime, UNIX_EPOCH};
         let seed = SystemTime::now().duration_since(UNIX_EPOCH)
             .unwrap().as_nanos() as u64;
+        Self::with_rng(&mut XorShift64::new(seed ^ 0xdeadbeef_cafebabe))
+    }
+
+    /// Хранилище с seed из внешнего FedRng — коммитменты и decoy воспроизводимы
+    pub fn with_rng(rng: &mut dyn FedRng) -> Self {
+        let seed = rng.next_u64();
         CryptoVault {
             hot: HashMap::new(), cold: HashMap::new(),
             ghost_network: GhostNetwork::new(),
             shard_index: HashMap::new(),
             total_entries: 0, total_zk_proofs: 0,
-            rng: seed ^ 0xdeadbeef_cafebabe,
+            rng: XorShift64::new(seed),
+            clock: Box::new(|| {
+                use std::time::{SystemTime, UNIX_EPOCH};
+                SystemTime::now().duration_since(UNIX_EPOCH)
+                    .unwrap().as_millis() as i64
+            }),
         }
     }
 
+    /// Хранилище с внешним источником времени
+    pub fn with_clock(clock: impl Fn() -> i64 + Send + Sync + 'static) -> Self {
+        CryptoVault { clock: Box::new(clock), ..Self::new() }
+    }
+
     fn encrypt(&mut self, data: &[u8]) -> Vec<u8> {
         // XOR stream cipher (в prod — ChaCha20)
-        self.rng ^= self.rng << 13;
-        self.rng ^= self.rng >> 7;
-        self.rng ^= self.rng << 17;
-        let key = self.rng.to_le_bytes();
+        let key = self.rng.next_u64().to_le_bytes();
         data.iter().enumerate()
             .map(|(i, &b)| b ^ key[i % 8]).collect()
     }
 
-    fn now() -> i64 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        SystemTime::now().duration_since(UNIX_EPOCH)
-            .unwrap().as_millis() as i64
+    fn now(&self) -> i64 {
+        (self.clock)()
     }
 
     /// Положить в Hot vault
     pub fn store_hot(&mut self, key_id: &str, owner_id: &str,
                      payload: &[u8], rep_required: f64) -> ZkProof {
+        self.store_hot_with_ttl(key_id, owner_id, payload, rep_required, HOT_TTL_MS)
+    }
+
+    /// Положить в Hot vault с заданным сроком жизни
+    pub fn store_hot_with_ttl(&mut self, key_id: &str, owner_id: &str,
+                              payload: &[u8], rep_required: f64, ttl_ms: i64) -> ZkProof {
         let encrypted = self.encrypt(payload);
-        let proof = ZkProof::generate(owner_id, payload, &mut self.rng);
-        let now = Self::now();
+        let now = self.now();
+        let expires_at = now + ttl_ms;
+        let proof = ZkProof::generate_at(owner_id, payload, &mut self.rng, expires_at);
         let entry = VaultEntry {
             key_id: key_id.to_string(),
             owner_id: owner_id.to_string(),
@@ -331,6 +408,7 @@ impl CryptoVault {
             proof: proof.clone(),
             created_at: now, accessed_at: now,
             access_count: 0, reputation_required: rep_required,
+            expires_at,
         };
         if self.hot.len() < HOT_VAULT_LIMIT {
             self.hot.insert(key_id.to_string(), entry);
@@ -344,8 +422,8 @@ impl CryptoVault {
     pub fn store_cold(&mut self, key_id: &str, owner_id: &str,
                       payload: &[u8], rep_required: f64) -> ZkProof {
         let encrypted = self.encrypt(payload);
-        let proof = ZkProof::generate(owner_id, payload, &mut self.rng);
-        let now = Self::now();
+        let now = self.now();
+        let proof = ZkProof::generate_at(owner_id, payload, &mut self.rng, now + HOT_TTL_MS);
         let entry = VaultEntry {
             key_id: key_id.to_string(),
             owner_id: owner_id.to_string(),
@@ -354,6 +432,7 @@ impl CryptoVault {
             proof: proof.clone(),
             created_at: now, accessed_at: now,
             access_count: 0, reputation_required: rep_required,
+            expires_at: i64::MAX, // Cold хранится бессрочно
         };
         if self.cold.len() < COLD_VAULT_LIMIT {
             self.cold.insert(key_id.to_string(), entry);
@@ -369,9 +448,9 @@ impl CryptoVault {
                             n: usize, k: usize) -> ShardingResult {
         let shards = ShamirScheme::split(payload, n, k, &mut self.rng);
 
-        self.rng ^= self.rng << 13;
-        let commitment = format!("commit_{:016x}", self.rng ^ payload.len() as u64);
-        let owner_commit = format!("owner_{:016x}", self.rng
+        let seed = self.rng.next_u64();
+        let commitment = format!("commit_{:016x}", seed ^ payload.len() as u64);
+        let owner_commit = format!("owner_{:016x}", seed
             ^ owner_id.bytes().fold(0u64, |a, b| a.wrapping_add(b as u64)));
 
         let mut shard_map = vec![];
@@ -380,35 +459,41 @@ impl CryptoVault {
             let ghost_id = ghost_ids[i % ghost_ids.len()];
 
             // Реальный осколок
-            let shard = KeyShard {
+            let mut batch = vec![KeyShard {
                 shard_id: (i + 1) as u8,
                 total_shards: n as u8,
                 threshold: k as u8,
                 ghost_node_id: ghost_id.to_string(),
                 shard_data: shard_data.clone(),
-                key_commitment: commitment.clone(),
-                owner_commitment: owner_commit.clone(),
+                key_commitment: KeyShard::salted_commitment(&commitment, (i + 1) as u8),
+                owner_commitment: KeyShard::salted_commitment(&owner_commit, (i + 1) as u8),
                 is_decoy: false,
-            };
-            self.ghost_network.store_shard(ghost_id, shard);
+            }];
             shard_map.push((i as u8 + 1, ghost_id.to_string()));
 
-            // Ложные осколки для маскировки
+            // Ложные осколки для маскировки — той же длины и того же
+            // формата commitment, что и реальный
             for _ in 0..2 {
-                self.rng ^= self.rng << 13; self.rng ^= self.rng >> 7;
-                let decoy_data: Vec<u8> = (0..shard_data.len())
-                    .map(|_| { self.rng ^= self.rng << 17; (self.rng & 0xff) as u8 }).collect();
-                let decoy = KeyShard {
+                let mut decoy_data = vec![0u8; shard_data.len()];
+                self.rng.fill_bytes(&mut decoy_data);
+                let key_c = format!("commit_{:016x}", self.rng.next_u64());
+                batch.push(KeyShard {
                     shard_id: (i + 1) as u8,
                     total_shards: n as u8,
                     threshold: k as u8,
                     ghost_node_id: ghost_id.to_string(),
                     shard_data: decoy_data,
-                    key_commitment: format!("decoy_{:08x}", self.rng),
-                    owner_commitment: format!("decoy_owner_{:08x}", self.rng),
+                    key_commitment: key_c,
+                    owner_commitment: format!("owner_{:016x}", self.rng.next_u64()),
                     is_decoy: true,
-                };
-                self.ghost_network.store_shard(ghost_id, decoy);
+                });
+            }
+
+            // Позиция реального осколка в памяти Ghost тоже случайна
+            let offset = (self.rng.next_u64() % batch.len() as u64) as usize;
+            batch.rotate_left(offset);
+            for shard in batch {
+                self.ghost_network.store_key_shard(key_id, ghost_id, shard);
             }
         }
 
@@ -427,25 +512,23 @@ impl CryptoVault {
 
     /// Получить из Hot vault с ZK проверкой
     pub fn retrieve_hot(&mut self, key_id: &str,
-                         proof: &ZkProof, owner_rep: f64) -> VaultResult {
-        if !proof.verify() {
-            return VaultResult::denied("ZK proof истёк");
+                         proof: &ZkProof, owner_rep: f64) -> Result<Vec<u8>, VaultError> {
+        let now = self.now();
+        let entry = self.hot.get_mut(key_id).ok_or(VaultError::NotFound)?;
+        if !proof.valid || proof.commitment != entry.proof.commitment
+            || proof.nullifier != entry.proof.nullifier {
+            return Err(VaultError::CommitmentMismatch);
         }
-        match self.hot.get_mut(key_id) {
-            None => VaultResult::denied("Ключ не найден в Hot vault"),
-            Some(entry) => {
-                if owner_rep < entry.reputation_required {
-                    return VaultResult::denied(&format!(
-                        "Недостаточная репутация: {:.1} < {:.1}",
-                        owner_rep, entry.reputation_required));
-                }
-                entry.access_count += 1;
-                entry.accessed_at = Self::now();
-                VaultResult::success(
-                    entry.encrypted_payload.clone(),
-                    VaultTier::Hot, entry.access_count)
-            }
+        if now >= proof.expires_at || now >= entry.expires_at {
+            return Err(VaultError::Expired);
         }
+        if owner_rep < entry.reputation_required {
+            return Err(VaultError::RepTooLow {
+                required: entry.reputation_required, actual: owner_rep });
+        }
+        entry.access_count += 1;
+        entry.accessed_at = now;
+        Ok(entry.encrypted_payload.clone())
     }
 
     pub fn vault_stats(&self) -> VaultStats {
@@ -464,29 +547,9 @@ impl CryptoVault {
 impl Default for CryptoVault { fn default() -> Self { Self::new() } }
 
 // -----------------------------------------------------------------------------
-// VaultResult / ShardingResult / VaultStats
+// ShardingResult / VaultStats
 // -----------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VaultResult {
-    pub success: bool,
-    pub payload: Option<Vec<u8>>,
-    pub tier: Option<VaultTier>,
-    pub access_count: u32,
-    pub reason: String,
-}
-
-impl VaultResult {
-    pub fn success(payload: Vec<u8>, tier: VaultTier, count: u32) -> Self {
-        VaultResult { success: true, payload: Some(payload),
-            tier: Some(tier), access_count: count, reason: "OK".into() }
-    }
-    pub fn denied(reason: &str) -> Self {
-        VaultResult { success: false, payload: None,
-            tier: None, access_count: 0, reason: reason.to_string() }
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ShardingResult {
     pub key_id: String,
@@ -524,3 +587,96 @@ impl std::fmt::Display for VaultStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn vault_with_key() -> (CryptoVault, ZkProof) {
+        let mut vault = CryptoVault::new();
+        let proof = vault.store_hot("key_001", "node_tokyo", b"MASTER_KEY", 30.0);
+        (vault, proof)
+    }
+
+    #[test]
+    fn test_retrieve_hot_ok() {
+        let (mut vault, proof) = vault_with_key();
+        let payload = vault.retrieve_hot("key_001", &proof, 50.0).unwrap();
+        assert_eq!(payload.len(), b"MASTER_KEY".len());
+        assert_eq!(vault.hot["key_001"].access_count, 1);
+    }
+
+    #[test]
+    fn test_retrieve_hot_error_variants() {
+        let (mut vault, proof) = vault_with_key();
+        assert_eq!(vault.retrieve_hot("missing", &proof, 50.0), Err(VaultError::NotFound));
+        assert_eq!(vault.retrieve_hot("key_001", &proof, 10.0),
+            Err(VaultError::RepTooLow { required: 30.0, actual: 10.0 }));
+
+        let other = vault.store_hot("key_002", "node_berlin", b"OTHER", 0.0);
+        assert_eq!(vault.retrieve_hot("key_001", &other, 50.0),
+            Err(VaultError::CommitmentMismatch));
+
+        let mut stale = proof.clone();
+        stale.expires_at = 0;
+        assert_eq!(vault.retrieve_hot("key_001", &stale, 50.0), Err(VaultError::Expired));
+        assert_eq!(vault.hot["key_001"].access_count, 0);
+    }
+
+    #[test]
+    fn test_decoy_shards_indistinguishable() {
+        let mut vault = CryptoVault::new();
+        let ghosts = ["g1", "g2", "g3", "g4", "g5"];
+        let result = vault.shard_to_ghosts("vet_key", "veteran",
+            b"VETERAN_SIGNING_KEY_32bytes_long", &ghosts, 5, 3);
+
+        let ratio = vault.ghost_network.decoy_ratio("vet_key").unwrap();
+        assert!((ratio - 2.0 / 3.0).abs() < 1e-12);
+        assert!(vault.ghost_network.decoy_ratio("unknown").is_none());
+
+        for g in ghosts {
+            let shards = &vault.ghost_network.nodes[g];
+            let real = shards.iter().find(|s| !s.is_decoy).unwrap();
+            for s in shards {
+                assert!(s.is_well_formed());
+                // Всё, что видит захвативший Ghost, совпадает по форме
+                assert_eq!(s.shard_data.len(), real.shard_data.len());
+                assert_eq!(s.key_commitment.len(), real.key_commitment.len());
+                assert_eq!(s.owner_commitment.len(), real.owner_commitment.len());
+                assert_eq!((s.shard_id, s.total_shards, s.threshold),
+                    (real.shard_id, real.total_shards, real.threshold));
+                assert_eq!(s.ghost_node_id, real.ghost_node_id);
+            }
+        }
+
+        // Реальные осколки не делят commitment — по нему их не связать
+        let reals: Vec<&KeyShard> = ghosts.iter()
+            .flat_map(|g| vault.ghost_network.nodes[*g].iter().filter(|s| !s.is_decoy))
+            .collect();
+        let keys: HashSet<&str> = reals.iter().map(|s| s.key_commitment.as_str()).collect();
+        let owners: HashSet<&str> = reals.iter().map(|s| s.owner_commitment.as_str()).collect();
+        assert_eq!((keys.len(), owners.len()), (reals.len(), reals.len()));
+        // Владелец находит свой осколок по commitment ключа
+        let (id, ghost) = &vault.shard_index["vet_key"][0];
+        let salted = KeyShard::salted_commitment(&result.commitment, *id);
+        assert_eq!(vault.ghost_network.retrieve_shard(ghost, &salted).unwrap().shard_id, *id);
+    }
+
+    #[test]
+    fn test_hot_entry_expires_with_injected_clock() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        use std::sync::Arc;
+        let t = Arc::new(AtomicI64::new(1_000));
+        let clock = t.clone();
+        let mut vault = CryptoVault::with_clock(move || clock.load(Ordering::SeqCst));
+
+        let proof = vault.store_hot_with_ttl("session", "node_tokyo", b"K", 0.0, 500);
+        assert_eq!(proof.expires_at, 1_500);
+        t.store(1_499, Ordering::SeqCst);
+        assert!(vault.retrieve_hot("session", &proof, 0.0).is_ok());
+        t.store(1_500, Ordering::SeqCst);
+        assert_eq!(vault.retrieve_hot("session", &proof, 0.0), Err(VaultError::Expired));
+        assert_eq!(vault.hot["session"].accessed_at, 1_499);
+    }
+}
@@ -23,6 +23,10 @@ pub struct SuperCensor {
 
 impl SuperCensor {
     pub fn new(name: &str) -> Self {
+        Self::with_seed(name, 0xdeadbeef_cafebabe)
+    }
+
+    pub fn with_seed(name: &str, seed: u64) -> Self {
         SuperCensor {
             name: name.to_string(),
             block_rate: 0.0,
@@ -35,7 +39,7 @@ impl SuperCensor {
             ],
             confused: false,
             exhausted: false,
-            rng: 0xdeadbeef_cafebabe,
+            rng: seed.max(1),
         }
     }
 
@@ -159,6 +163,57 @@ pub struct FinalVerdict {
     pub censor_cpu_final: f64,
     pub passed: bool,
     pub grade: String,
+    pub seed: u64,
+    /// Порог, определивший исход: pass_delivery_rate при провале,
+    /// нижняя граница полученной оценки при успехе
+    pub decisive_threshold: f64,
+    pub decisive_rule: String,
+}
+
+impl FinalVerdict {
+    /// Однострочная сводка — одинакова для одинаковых config + seed
+    pub fn summary(&self) -> String {
+        format!("{} seed={:#x} packets={} delivery={:.4} peak_block={:.4} grade=[{}] passed={} decisive={} ({:.2})",
+            self.test_name, self.seed, self.total_packets, self.final_delivery_rate,
+            self.peak_block_rate, self.grade, self.passed, self.decisive_rule, self.decisive_threshold)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// VeilBreakerConfig — пороги оценки и seed
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeBand {
+    pub min_delivery_rate: f64,
+    pub grade: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VeilBreakerConfig {
+    pub pass_delivery_rate: f64,
+    /// Оценки по убыванию min_delivery_rate; первая подходящая побеждает
+    pub grade_bands: Vec<GradeBand>,
+    pub seed: u64,
+}
+
+// Смешиваем с seed цензора так, чтобы seed по умолчанию давал исторический прогон
+const CENSOR_SEED_MIX: u64 = 0x1337_c0de_feed_face ^ 0xdeadbeef_cafebabe;
+
+impl Default for VeilBreakerConfig {
+    fn default() -> Self {
+        let band = |min: f64, grade: &str| GradeBand { min_delivery_rate: min, grade: grade.into() };
+        VeilBreakerConfig {
+            pass_delivery_rate: 0.80,
+            grade_bands: vec![
+                band(0.95, "S — ЛЕГЕНДАРНЫЙ"),
+                band(0.90, "A — ОТЛИЧНЫЙ"),
+                band(0.80, "B — ХОРОШИЙ"),
+                band(0.0,  "F — ПРОВАЛ"),
+            ],
+            seed: 0x1337_c0de_feed_face,
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -170,11 +225,16 @@ pub struct VeilBreakerTest {
     pub censor: SuperCensor,
     pub phase_results: Vec<PhaseResult>,
     pub total_packets: u64,
+    pub config: VeilBreakerConfig,
     rng: u64,
 }
 
 impl VeilBreakerTest {
     pub fn new() -> Self {
+        Self::new_with_config(VeilBreakerConfig::default())
+    }
+
+    pub fn new_with_config(config: VeilBreakerConfig) -> Self {
         let nodes = vec![
             NetworkNode::new("node_tokyo",   "JP"),
             NetworkNode::new("node_berlin",  "DE"),
@@ -184,9 +244,11 @@ impl VeilBreakerTest {
             NetworkNode::new("node_saopaulo","BR"),
         ];
         VeilBreakerTest {
-            nodes, censor: SuperCensor::new("SuperCensor_AI_v4"),
+            nodes,
+            censor: SuperCensor::with_seed("SuperCensor_AI_v4", config.seed ^ CENSOR_SEED_MIX),
             phase_results: vec![], total_packets: 0,
-            rng: 0x1337_c0de_feed_face,
+            rng: config.seed.max(1),
+            config,
         }
     }
 
@@ -374,18 +436,56 @@ impl VeilBreakerTest {
         let last = self.phase_results.last().unwrap();
         let peak_block = self.phase_results.iter()
             .map(|r| 1.0 - r.delivery_rate).fold(0.0f64, f64::max);
+        let rate = last.delivery_rate;
+        let passed = rate >= self.config.pass_delivery_rate;
+        let band = self.config.grade_bands.iter().find(|b| rate >= b.min_delivery_rate);
+        let grade = band.map(|b| b.grade.clone()).unwrap_or_else(|| "F — ПРОВАЛ".into());
+        let (decisive_threshold, decisive_rule) = match band {
+            Some(b) if passed => (b.min_delivery_rate, format!("grade_band[{}]", b.grade)),
+            _ => (self.config.pass_delivery_rate, "pass_delivery_rate".to_string()),
+        };
         FinalVerdict {
             test_name: "THE VEIL-BREAKER TEST".into(),
             total_packets: self.total_packets,
             peak_block_rate: peak_block,
-            final_delivery_rate: last.delivery_rate,
+            final_delivery_rate: rate,
             censor_status: self.censor.status().into(),
             censor_cpu_final: self.censor.cpu_load,
-            passed: last.delivery_rate >= 0.80,
-            grade: if last.delivery_rate >= 0.95      { "S — ЛЕГЕНДАРНЫЙ" }
-                   else if last.delivery_rate >= 0.90  { "A — ОТЛИЧНЫЙ"   }
-                   else if last.delivery_rate >= 0.80  { "B — ХОРОШИЙ"    }
-                   else                                { "F — ПРОВАЛ"     }.into(),
+            passed,
+            grade,
+            seed: self.config.seed,
+            decisive_threshold,
+            decisive_rule,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_verdict() {
+        let mut a = VeilBreakerTest::new_with_config(VeilBreakerConfig { seed: 42, ..Default::default() });
+        let mut b = VeilBreakerTest::new_with_config(VeilBreakerConfig { seed: 42, ..Default::default() });
+        a.run();
+        b.run();
+        assert_eq!(a.final_verdict().summary(), b.final_verdict().summary());
+    }
+
+    #[test]
+    fn test_raised_pass_rate_fails() {
+        let mut baseline = VeilBreakerTest::new();
+        baseline.run();
+        let achieved = baseline.final_verdict().final_delivery_rate;
+
+        let strict = VeilBreakerConfig { pass_delivery_rate: achieved + 0.01, ..Default::default() };
+        let mut test = VeilBreakerTest::new_with_config(strict);
+        test.run();
+        let v = test.final_verdict();
+        assert_eq!(v.final_delivery_rate, achieved);
+        assert!(!v.passed);
+        assert_eq!(v.decisive_rule, "pass_delivery_rate");
+        assert_eq!(v.decisive_threshold, achieved + 0.01);
+    }
+}